@@ -17,7 +17,7 @@
 use std::ffi::CStr;
 use std::os::raw::{c_char, c_void};
 use std::{str, mem};
-use maxgraph_store::db::api::{GraphBackup, GraphStorage};
+use maxgraph_store::db::api::{GraphBackup, GraphStorage, BackupRetention};
 use maxgraph_store::db::graph::store::{GraphStore, GraphBackupEngine};
 use crate::store::graph::GraphHandle;
 use crate::store::jna_response::JnaResponse;
@@ -133,4 +133,108 @@ pub extern fn getBackupList(handle: GraphBackupHandle) -> Box<JnaResponse> {
         }
         response
     }
-}
\ No newline at end of file
+}
+
+#[no_mangle]
+pub extern fn createNewBackupAt(handle: GraphBackupHandle, si: i64) -> Box<JnaResponse> {
+    unsafe {
+        let graph_be = &mut *(handle as *mut GraphBackupEngine);
+        match graph_be.create_new_backup_at(si) {
+            Ok(id) => {
+                let mut response = JnaResponse::new_success();
+                if let Err(e) = response.data(id.to_ne_bytes().to_vec()) {
+                    response.success(false);
+                    let msg = format!("{:?}", e);
+                    response.err_msg(&msg);
+                }
+                response
+            }
+            Err(e) => {
+                let msg = format!("{:?}", e);
+                JnaResponse::new_error(&msg)
+            }
+        }
+    }
+}
+
+/// Each backup takes 32 bytes in native byte order: backup id (i32), number of files (u32), snapshot
+/// id (i64, -1 if unknown), timestamp (i64) and size (u64).
+#[no_mangle]
+pub extern fn getBackupInfoList(handle: GraphBackupHandle) -> Box<JnaResponse> {
+    unsafe {
+        let graph_be = &*(handle as *const GraphBackupEngine);
+        let mut data = Vec::new();
+        for info in graph_be.get_backup_info_list() {
+            data.extend_from_slice(&info.backup_id.to_ne_bytes());
+            data.extend_from_slice(&info.num_files.to_ne_bytes());
+            data.extend_from_slice(&info.snapshot_id.unwrap_or(-1).to_ne_bytes());
+            data.extend_from_slice(&info.timestamp.to_ne_bytes());
+            data.extend_from_slice(&info.size.to_ne_bytes());
+        }
+        let mut response = JnaResponse::new_success();
+        if let Err(e) = response.data(data) {
+            response.success(false);
+            let msg = format!("{:?}", e);
+            response.err_msg(&msg);
+        }
+        response
+    }
+}
+
+#[no_mangle]
+pub extern fn purgeBackupsKeepLast(handle: GraphBackupHandle, num_backups_to_keep: i32) -> Box<JnaResponse> {
+    purge_backups(handle, BackupRetention::KeepLast(num_backups_to_keep.max(0) as usize))
+}
+
+#[no_mangle]
+pub extern fn purgeBackupsKeepNewerThan(handle: GraphBackupHandle, si: i64) -> Box<JnaResponse> {
+    purge_backups(handle, BackupRetention::KeepNewerThan(si))
+}
+
+/// The response data is ids (i32) of the purged backups in native byte order.
+fn purge_backups(handle: GraphBackupHandle, retention: BackupRetention) -> Box<JnaResponse> {
+    unsafe {
+        let graph_be = &mut *(handle as *mut GraphBackupEngine);
+        match graph_be.purge_backups(retention) {
+            Ok(ids) => {
+                let data = ids.iter().flat_map(|id| id.to_ne_bytes().to_vec()).collect();
+                let mut response = JnaResponse::new_success();
+                if let Err(e) = response.data(data) {
+                    response.success(false);
+                    let msg = format!("{:?}", e);
+                    response.err_msg(&msg);
+                }
+                response
+            }
+            Err(e) => {
+                let msg = format!("{:?}", e);
+                JnaResponse::new_error(&msg)
+            }
+        }
+    }
+}
+
+/// The response data is the id (i32) of the backup restored from.
+#[no_mangle]
+pub extern fn restoreAtSnapshot(handle: GraphBackupHandle, restore_path: *const c_char, si: i64) -> Box<JnaResponse> {
+    unsafe {
+        let graph_be = &mut *(handle as *mut GraphBackupEngine);
+        let slice = CStr::from_ptr(restore_path).to_bytes();
+        let restore_path_str = str::from_utf8(slice).unwrap();
+        match graph_be.restore_at_snapshot(restore_path_str, si) {
+            Ok(id) => {
+                let mut response = JnaResponse::new_success();
+                if let Err(e) = response.data(id.to_ne_bytes().to_vec()) {
+                    response.success(false);
+                    let msg = format!("{:?}", e);
+                    response.err_msg(&msg);
+                }
+                response
+            }
+            Err(e) => {
+                let msg = format!("{:?}", e);
+                JnaResponse::new_error(&msg)
+            }
+        }
+    }
+}
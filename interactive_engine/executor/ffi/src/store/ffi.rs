@@ -36,6 +36,7 @@ pub type EdgeHandle = *const c_void;
 pub type EdgeIteratorHandle = *const c_void;
 pub type PropertyHandle = *const c_void;
 pub type PropertyIteratorHandle = *const c_void;
+pub type ConditionHandle = *const c_void;
 
 // pub type FfiMultiVersionGraph = WrapperPartitionGraph<GraphStorageWrapper<GraphStore>>;
 pub type FfiSnapshot = WrapperPartitionSnapshot<GraphStorageWrapper<GraphStore>>;
@@ -46,6 +47,16 @@ pub type FfiEdgeIterator = IntoIter<GraphResult<FfiEdge>>;
 pub type FfiProperty = WrapperProperty;
 pub type FfiPropertyIterator = IntoIter<GraphResult<FfiProperty>>;
 
+#[repr(C)]
+pub enum CmpOperator {
+    LessThan = 0,
+    LessEqual = 1,
+    GreaterThan = 2,
+    GreaterEqual = 3,
+    Equal = 4,
+    NotEqual = 5,
+}
+
 #[repr(C)]
 pub struct StringSlice {
     data: *const u8,
@@ -140,11 +151,14 @@ pub extern fn GetEdge(partition_snapshot: PartitionSnapshotHandle,
 #[no_mangle]
 pub extern fn ScanVertex(partition_snapshot: PartitionSnapshotHandle,
                          label_id: LabelId,
+                         condition: ConditionHandle,
+                         property_ids: *const PropertyId, property_count: usize,
                          error: *mut ErrorHandle)
                          -> VertexIteratorHandle {
     unsafe {
         let handler = &*(partition_snapshot as *const FfiSnapshot);
-        match handler.scan_vertex(label_option(label_id), None, None) {
+        let property_ids = property_ids_option(property_ids, property_count);
+        match handler.scan_vertex(label_option(label_id), condition_option(condition), property_ids.as_ref()) {
             Ok(data) => {
                 Box::into_raw(data) as VertexIteratorHandle
             }
@@ -180,11 +194,15 @@ pub extern fn ScanEdge(partition_snapshot: PartitionSnapshotHandle,
 #[no_mangle]
 pub extern fn GetOutEdges(partition_snapshot: PartitionSnapshotHandle,
                           vertex_id: VertexId, edge_label_id: LabelId,
+                          condition: ConditionHandle,
+                          property_ids: *const PropertyId, property_count: usize,
                           error: *mut ErrorHandle)
                           -> EdgeIteratorHandle {
     unsafe {
         let handler = &*(partition_snapshot as *const FfiSnapshot);
-        match handler.get_out_edges(vertex_id, label_option(edge_label_id), None, None) {
+        let property_ids = property_ids_option(property_ids, property_count);
+        match handler.get_out_edges(vertex_id, label_option(edge_label_id), condition_option(condition),
+                                    property_ids.as_ref()) {
             Ok(data) => {
                 Box::into_raw(data) as EdgeIteratorHandle
             }
@@ -200,11 +218,15 @@ pub extern fn GetOutEdges(partition_snapshot: PartitionSnapshotHandle,
 #[no_mangle]
 pub extern fn GetInEdges(partition_snapshot: PartitionSnapshotHandle,
                          vertex_id: VertexId, edge_label_id: LabelId,
+                         condition: ConditionHandle,
+                         property_ids: *const PropertyId, property_count: usize,
                          error: *mut ErrorHandle)
                          -> EdgeIteratorHandle {
     unsafe {
         let handler = &*(partition_snapshot as *const FfiSnapshot);
-        match handler.get_in_edges(vertex_id, label_option(edge_label_id), None, None) {
+        let property_ids = property_ids_option(property_ids, property_count);
+        match handler.get_in_edges(vertex_id, label_option(edge_label_id), condition_option(condition),
+                                   property_ids.as_ref()) {
             Ok(data) => {
                 Box::into_raw(data) as EdgeIteratorHandle
             }
@@ -580,6 +602,66 @@ pub extern fn GetPropertyAsString(property_handle: PropertyHandle, error: *mut E
     }
 }
 
+/// Condition FFIs
+
+#[no_mangle]
+pub extern fn CreateInt32Condition(op: CmpOperator, property_id: PropertyId, value: i32) -> ConditionHandle {
+    new_predicate(op, property_id, PropertyValue::Int(value))
+}
+
+#[no_mangle]
+pub extern fn CreateInt64Condition(op: CmpOperator, property_id: PropertyId, value: i64) -> ConditionHandle {
+    new_predicate(op, property_id, PropertyValue::Long(value))
+}
+
+#[no_mangle]
+pub extern fn CreateFloatCondition(op: CmpOperator, property_id: PropertyId, value: f32) -> ConditionHandle {
+    new_predicate(op, property_id, PropertyValue::Float(value))
+}
+
+#[no_mangle]
+pub extern fn CreateDoubleCondition(op: CmpOperator, property_id: PropertyId, value: f64) -> ConditionHandle {
+    new_predicate(op, property_id, PropertyValue::Double(value))
+}
+
+#[no_mangle]
+pub extern fn CreateStringCondition(op: CmpOperator, property_id: PropertyId, value: *const c_char) -> ConditionHandle {
+    unsafe {
+        let slice = CStr::from_ptr(value).to_bytes();
+        let value = String::from_utf8_lossy(slice).into_owned();
+        new_predicate(op, property_id, PropertyValue::String(value))
+    }
+}
+
+/// `left` and `right` are taken over by the returned condition and must not be released any more.
+#[no_mangle]
+pub extern fn CreateAndCondition(left: ConditionHandle, right: ConditionHandle) -> ConditionHandle {
+    unsafe {
+        let left = Box::from_raw(left as *mut Condition);
+        let right = Box::from_raw(right as *mut Condition);
+        Box::into_raw(Box::new(Condition::And(left, right))) as ConditionHandle
+    }
+}
+
+/// `left` and `right` are taken over by the returned condition and must not be released any more.
+#[no_mangle]
+pub extern fn CreateOrCondition(left: ConditionHandle, right: ConditionHandle) -> ConditionHandle {
+    unsafe {
+        let left = Box::from_raw(left as *mut Condition);
+        let right = Box::from_raw(right as *mut Condition);
+        Box::into_raw(Box::new(Condition::Or(left, right))) as ConditionHandle
+    }
+}
+
+/// `condition` is taken over by the returned condition and must not be released any more.
+#[no_mangle]
+pub extern fn CreateNotCondition(condition: ConditionHandle) -> ConditionHandle {
+    unsafe {
+        let condition = Box::from_raw(condition as *mut Condition);
+        Box::into_raw(Box::new(Condition::Not(condition))) as ConditionHandle
+    }
+}
+
 /// Error FFIs
 
 #[no_mangle]
@@ -665,6 +747,14 @@ pub extern fn ReleasePropertyIteratorHandle(ptr: PropertyIteratorHandle) {
     }
 }
 
+#[no_mangle]
+pub extern fn ReleaseConditionHandle(ptr: ConditionHandle) {
+    let handler = ptr as *mut Condition;
+    unsafe {
+        Box::from_raw(handler);
+    }
+}
+
 /// Internal functions
 
 fn label_option(label_id : LabelId) -> Option<LabelId> {
@@ -682,3 +772,32 @@ fn edge_relation_option(edge_relation: &EdgeRelation) -> Option<&EdgeRelation> {
         None
     }
 }
+
+fn condition_option<'a>(condition: ConditionHandle) -> Option<&'a Condition> {
+    if condition.is_null() {
+        None
+    } else {
+        unsafe { Some(&*(condition as *const Condition)) }
+    }
+}
+
+/// A null `property_ids` means all properties are required
+fn property_ids_option(property_ids: *const PropertyId, property_count: usize) -> Option<Vec<PropertyId>> {
+    if property_ids.is_null() {
+        None
+    } else {
+        unsafe { Some(std::slice::from_raw_parts(property_ids, property_count).to_vec()) }
+    }
+}
+
+fn new_predicate(op: CmpOperator, property_id: PropertyId, value: PropertyValue) -> ConditionHandle {
+    let condition = match op {
+        CmpOperator::LessThan => Condition::LessThan(property_id, value),
+        CmpOperator::LessEqual => Condition::LessEqual(property_id, value),
+        CmpOperator::GreaterThan => Condition::GreaterThan(property_id, value),
+        CmpOperator::GreaterEqual => Condition::GreaterEqual(property_id, value),
+        CmpOperator::Equal => Condition::Equal(property_id, value),
+        CmpOperator::NotEqual => Condition::NotEqual(property_id, value),
+    };
+    Box::into_raw(Box::new(condition)) as ConditionHandle
+}
@@ -0,0 +1,70 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+//! Load csv files into a graph store whose schema already contains the target type, e.g.
+//!
+//! csv_loader --store /path/to/store --config person.json --si 10 person_0.csv person_1.csv
+
+use std::fs;
+use std::process;
+
+use structopt::StructOpt;
+use maxgraph_store::db::api::*;
+use maxgraph_store::db::graph::loader::CsvLoader;
+use maxgraph_store::db::graph::store::GraphStore;
+
+#[derive(Debug, StructOpt)]
+struct Args {
+    /// path of the graph store
+    #[structopt(long = "store")]
+    store: String,
+
+    /// path of the json `CSVLoadConfig`
+    #[structopt(long = "config")]
+    config: String,
+
+    /// snapshot id at which data is loaded
+    #[structopt(long = "si")]
+    si: SnapshotId,
+
+    /// path of the temporary sst file, default to `<store>/load.sst`
+    #[structopt(long = "sst")]
+    sst: Option<String>,
+
+    /// csv files to load
+    files: Vec<String>,
+}
+
+fn main() {
+    let args = Args::from_args();
+    if let Err(e) = run(&args) {
+        eprintln!("load {:?} failed: {}", args, e);
+        process::exit(1);
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let mut builder = GraphConfigBuilder::new();
+    builder.set_storage_engine("rocksdb");
+    let graph = GraphStore::open(&builder.build(), &args.store).map_err(|e| format!("{:?}", e))?;
+    let text = fs::read_to_string(&args.config).map_err(|e| e.to_string())?;
+    let loader = CsvLoader::new(&graph);
+    let config = loader.parse_config(&text).map_err(|e| format!("{:?}", e))?;
+    let sst = args.sst.clone().unwrap_or_else(|| format!("{}/load.sst", args.store));
+    let files: Vec<&str> = args.files.iter().map(|f| f.as_str()).collect();
+    let count = loader.load(args.si, &config, &files, &sst).map_err(|e| format!("{:?}", e))?;
+    println!("loaded {} rows from {} files", count, files.len());
+    Ok(())
+}
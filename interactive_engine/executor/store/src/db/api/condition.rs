@@ -7,6 +7,10 @@ pub enum Condition {
     Or(OrCondition),
     Not(NotCondition),
     Predicate(PredicateCondition),
+    In(SetCondition),
+    NotIn(SetCondition),
+    Exists(ExistsCondition),
+    NotExists(ExistsCondition),
 }
 
 pub struct AndCondition {
@@ -65,6 +69,7 @@ impl PredicateCondition {
     }
 }
 
+/// A property which is absent never satisfies a predicate, whatever the operator is.
 pub enum ComparisonOp {
     LessThan,
     LessEqual,
@@ -72,6 +77,42 @@ pub enum ComparisonOp {
     GreaterEqual,
     Equal,
     NotEqual,
+    /// the string property starts with the string value
+    StartsWith,
+    /// the string property ends with the string value
+    EndsWith,
+    /// the string property contains the string value
+    Contains,
+    /// some element of the list property equals to the value
+    ListContains,
+}
+
+/// `In` matches if the property equals to any of `values`, and `NotIn` matches if the property
+/// exists and equals to none of them.
+pub struct SetCondition {
+    pub prop: PropId,
+    pub values: Vec<Value>,
+}
+
+impl SetCondition {
+    pub fn new(prop: PropId, values: Vec<Value>) -> Self {
+        SetCondition {
+            prop,
+            values,
+        }
+    }
+}
+
+pub struct ExistsCondition {
+    pub prop: PropId,
+}
+
+impl ExistsCondition {
+    pub fn new(prop: PropId) -> Self {
+        ExistsCondition {
+            prop,
+        }
+    }
 }
 
 pub struct ConditionBuilder {
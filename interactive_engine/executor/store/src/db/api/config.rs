@@ -32,6 +32,8 @@ impl GraphConfigBuilder {
         }
     }
 
+    /// "rocksdb" keeps data at the path the store is opened with, and "memory" keeps data in memory
+    /// only, which is lost once the store is dropped.
     pub fn set_storage_engine(&mut self, engine: &str) -> &mut Self {
         self.config.storage_engine = engine.to_owned();
         self
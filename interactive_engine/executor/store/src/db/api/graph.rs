@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use std::marker::PhantomData;
 use super::schema::*;
-use super::{VertexId, SnapshotId, LabelId, BackupId, GraphResult, EdgeId,
+use super::{VertexId, SnapshotId, LabelId, BackupId, BackupInfo, BackupRetention, GraphResult, EdgeId,
             Vertex, Edge, EdgeKind, PropId, PropertyMap, Condition,
             PropertiesRef, ValueRef};
 use crate::db::api::DataLoadTarget;
@@ -62,6 +62,36 @@ pub trait GraphStorage {
     /// decoding properties error or other errors, `GraphError` will be returned.
     fn query_edges<'a>(&'a self, si: SnapshotId, label: Option<LabelId>, condition: Option<Arc<Condition>>) -> GraphResult<Box<dyn EdgeResultIter<E=Self::E> + 'a>>;
 
+    /// Return the number of out edges of `edge_kind` of vertex with id=`src_id` at `si`. This interface is thread safe.
+    ///
+    /// If edge kind of `edge_kind` is not found, something error when query storage or other errors,
+    /// `GraphError` will be returned.
+    fn get_out_degree(&self, si: SnapshotId, src_id: VertexId, edge_kind: &EdgeKind) -> GraphResult<usize>;
+
+    /// Return the number of in edges of `edge_kind` of vertex with id=`dst_id` at `si`. This interface is thread safe.
+    ///
+    /// If edge kind of `edge_kind` is not found, something error when query storage or other errors,
+    /// `GraphError` will be returned.
+    fn get_in_degree(&self, si: SnapshotId, dst_id: VertexId, edge_kind: &EdgeKind) -> GraphResult<usize>;
+
+    /// Return the `k`-th out edge of `edge_kind` of vertex with id=`src_id` at `si`, or None if `k` is not
+    /// less than the out degree. This interface is thread safe.
+    ///
+    /// The order of edges is stable within a snapshot, but it's not the order of `get_out_edges`.
+    ///
+    /// If edge kind of `edge_kind` is not found, something error when query storage, error in meta or
+    /// other errors, `GraphError` will be returned.
+    fn get_kth_out_edge(&self, si: SnapshotId, src_id: VertexId, edge_kind: &EdgeKind, k: usize) -> GraphResult<Option<EdgeWrapper<Self::E>>>;
+
+    /// Return the `k`-th in edge of `edge_kind` of vertex with id=`dst_id` at `si`, or None if `k` is not
+    /// less than the in degree. This interface is thread safe.
+    ///
+    /// The order of edges is stable within a snapshot, but it's not the order of `get_in_edges`.
+    ///
+    /// If edge kind of `edge_kind` is not found, something error when query storage, error in meta or
+    /// other errors, `GraphError` will be returned.
+    fn get_kth_in_edge(&self, si: SnapshotId, dst_id: VertexId, edge_kind: &EdgeKind, k: usize) -> GraphResult<Option<EdgeWrapper<Self::E>>>;
+
     /// Create a new vertex type with `label` and `type_def` at `si` and `schema_version`. This interface is thread safe.
     ///
     /// If vertex type already exists, `si` is smaller than last operation, get lock error, storage error
@@ -76,6 +106,27 @@ pub trait GraphStorage {
     /// Returns true if schema_version changed, false otherwise.
     fn create_edge_type(&self, si: SnapshotId, schema_version: i64, label: LabelId, type_def: &TypeDef) -> GraphResult<bool>;
 
+    /// Alter the vertex type of `label` to `type_def` at `si` and `schema_version`. Only adding and dropping
+    /// properties are supported: properties in both definitions must have the same inner id and value type,
+    /// and an added property must have an inner id never used by the type. The codec version of the type is
+    /// bumped, so vertices written before `si` are still decoded with the old layout: at any si smaller than
+    /// `si` they are read as before, and after that dropped properties are hidden and added properties
+    /// return their default values. This interface is thread safe.
+    ///
+    /// If vertex type of `label` not found, a property is changed or still indexed when dropped, `si` is
+    /// smaller than last operation, get lock error, storage error or other errors, `GraphError` will be returned.
+    /// Returns true if schema_version changed, false otherwise.
+    fn alter_vertex_type(&self, si: SnapshotId, schema_version: i64, label: LabelId, type_def: &TypeDef) -> GraphResult<bool>;
+
+    /// Alter the edge type of `label` to `type_def` at `si` and `schema_version`. The new definition applies
+    /// to all edge kinds of `label` and has the same constraints as `alter_vertex_type`. This interface is
+    /// thread safe.
+    ///
+    /// If edge type of `label` not found, a property is changed, `si` is smaller than last operation, get lock
+    /// error, storage error or other errors, `GraphError` will be returned.
+    /// Returns true if schema_version changed, false otherwise.
+    fn alter_edge_type(&self, si: SnapshotId, schema_version: i64, label: LabelId, type_def: &TypeDef) -> GraphResult<bool>;
+
     /// Add a new edge kind of `kind` to edge type with `kind.label` at `si` and `schema_version`. This interface is thread safe.
     ///
     /// If edge type with `kind.label` not exists, edge kind `kind` already exists, `si` is smaller
@@ -107,6 +158,45 @@ pub trait GraphStorage {
     /// Returns true if schema_version changed, false otherwise.
     fn remove_edge_kind(&self, si: SnapshotId, schema_version: i64, edge_kind: &EdgeKind) -> GraphResult<bool>;
 
+    /// Create a secondary index on property `prop_id` of vertex type `label_id` at `si` and `schema_version`.
+    /// Existing vertices are indexed at once, and `query_vertices` at any si not less than `si` uses the
+    /// index when the condition has a predicate on `prop_id`. This interface is thread safe.
+    ///
+    /// If vertex type of `label_id` not found, the index already exists, `si` is smaller than last operation,
+    /// get lock error, storage error or other errors, `GraphError` will be returned.
+    ///
+    /// Returns true if schema_version changed, false otherwise.
+    fn create_vertex_index(&self, si: SnapshotId, schema_version: i64, label_id: LabelId, prop_id: PropId) -> GraphResult<bool>;
+
+    /// Drop the secondary index on property `prop_id` of vertex type `label_id` at `si` and `schema_version`.
+    /// This interface is thread safe.
+    ///
+    /// If the index not found, `si` is smaller than last operation, get lock error, storage error or other
+    /// errors, `GraphError` will be returned.
+    ///
+    /// Returns true if schema_version changed, false otherwise.
+    fn drop_vertex_index(&self, si: SnapshotId, schema_version: i64, label_id: LabelId, prop_id: PropId) -> GraphResult<bool>;
+
+    /// Create a secondary index on property `prop_id` of edge type `label_id` at `si` and `schema_version`.
+    /// The index covers all edge kinds of the type. Existing forward edges are indexed at once, and
+    /// `query_edges` at any si not less than `si` uses the index when the condition has a predicate on
+    /// `prop_id`. This interface is thread safe.
+    ///
+    /// If edge type of `label_id` not found, the index already exists, `si` is smaller than last operation,
+    /// get lock error, storage error or other errors, `GraphError` will be returned.
+    ///
+    /// Returns true if schema_version changed, false otherwise.
+    fn create_edge_index(&self, si: SnapshotId, schema_version: i64, label_id: LabelId, prop_id: PropId) -> GraphResult<bool>;
+
+    /// Drop the secondary index on property `prop_id` of edge type `label_id` at `si` and `schema_version`.
+    /// This interface is thread safe.
+    ///
+    /// If the index not found, `si` is smaller than last operation, get lock error, storage error or other
+    /// errors, `GraphError` will be returned.
+    ///
+    /// Returns true if schema_version changed, false otherwise.
+    fn drop_edge_index(&self, si: SnapshotId, schema_version: i64, label_id: LabelId, prop_id: PropId) -> GraphResult<bool>;
+
     /// realtime write interfaces
     /// These realtime write interfaces should be thread safe and user should ensure all data are in
     /// ascending order by si, or error will be return. The distribute protocol ensure si of all data
@@ -196,6 +286,37 @@ pub trait GraphBackup {
     ///
     /// Returns the available backup id vector(may be empty)。
     fn get_backup_list(&self) -> Vec<BackupId>;
+
+    /// Create a new backup of graph store which represents snapshot `si`, so all writes at or before
+    /// `si` must have been finished. Backups are incremental only in storage: files shared with the
+    /// former backups are not copied again, but every backup is a full copy of the store to restore
+    /// from, and no delta between backups is recorded. This interface is thread safe.
+    ///
+    /// If `si` is older than the snapshot of the latest backup, something error when creating or other
+    /// errors, `GraphError` will be returned.
+    fn create_new_backup_at(&mut self, si: SnapshotId) -> GraphResult<BackupId>;
+
+    /// Get the information of all available backups in ascending order of backup id. This interface
+    /// is thread safe.
+    fn get_backup_info_list(&self) -> Vec<BackupInfo>;
+
+    /// Delete backups which are not kept by `retention`. Files shared with other backups are kept
+    /// until no backup uses them. This interface is thread safe.
+    ///
+    /// Returns ids of the deleted backups if successful, `GraphError` otherwise.
+    fn purge_backups(&mut self, retention: BackupRetention) -> GraphResult<Vec<BackupId>>;
+
+    /// Restore the graph store at `restore_path` from the oldest backup whose snapshot is not older
+    /// than `si`, and remove everything written after `si`, so the restored store comes up at exactly
+    /// snapshot `si`. Backups created after gc has run at a snapshot newer than `si` are skipped, as
+    /// data visible at `si` may be gone in them. Removing the later writes scans the whole restored
+    /// store, so the cost of a restore grows with the size of the store rather than the writes after
+    /// `si`. This interface is thread safe.
+    ///
+    /// Returns the id of the backup restored from if successful. If no backup covers `si` without
+    /// having collected its data, something error when restoring or other errors, `GraphError` will
+    /// be returned.
+    fn restore_at_snapshot(&mut self, restore_path: &str, si: SnapshotId) -> GraphResult<BackupId>;
 }
 
 pub trait VertexResultIter {
@@ -139,3 +139,24 @@ impl DataLoadTarget {
         pb
     }
 }
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackupInfo {
+    pub backup_id: BackupId,
+    /// the snapshot the backup represents, None if it's not created by `create_new_backup_at`
+    pub snapshot_id: Option<SnapshotId>,
+    /// seconds since the epoch when the backup is created
+    pub timestamp: i64,
+    /// total size of the files of the backup, including files shared with other backups
+    pub size: u64,
+    pub num_files: u32,
+}
+
+/// Which backups survive a purge
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BackupRetention {
+    /// keep the latest `n` backups
+    KeepLast(usize),
+    /// keep backups of snapshots newer than `si`
+    KeepNewerThan(SnapshotId),
+}
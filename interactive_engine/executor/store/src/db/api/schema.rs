@@ -6,7 +6,7 @@ use crate::db::api::property::Value;
 use super::{GraphResult, PropId};
 use super::property::ValueType;
 use super::error::*;
-use crate::db::proto::model::{TypeDefPb, PropertyDefPb, GraphDefPb, TypeEnumPb, VertexTableIdEntry, EdgeTableIdEntry};
+use crate::db::proto::model::{TypeDefPb, PropertyDefPb, GraphDefPb, TypeEnumPb, VertexTableIdEntry, EdgeTableIdEntry, IndexDefPb};
 use protobuf::{ProtobufEnum, Message};
 use crate::db::api::{LabelId, EdgeKind};
 
@@ -21,6 +21,8 @@ pub struct GraphDef {
     vertex_table_ids: HashMap<LabelId, i64>,
     edge_table_ids: HashMap<EdgeKind, i64>,
     table_idx: i64,
+    // label id to ids of its indexed properties
+    indexes: HashMap<LabelId, HashSet<PropId>>,
 }
 
 impl GraphDef {
@@ -44,6 +46,7 @@ impl GraphDef {
             vertex_table_ids,
             edge_table_ids,
             table_idx,
+            indexes: HashMap::new(),
         }
     }
 
@@ -62,6 +65,40 @@ impl GraphDef {
         Ok(())
     }
 
+    /// Replace the definition of an existing type with `type_def`, e.g. after properties are added to
+    /// or dropped from the type.
+    pub fn update_type(&mut self, label: LabelId, type_def: TypeDef) -> GraphResult<()> {
+        if !self.label_to_types.contains_key(&label) {
+            let msg = format!("labelId {}", label);
+            return Err(GraphError::new(GraphErrorCode::TypeNotFound, msg));
+        }
+        for property in type_def.get_prop_defs() {
+            if property.id > self.property_idx {
+                self.property_idx = property.id
+            }
+            self.property_name_to_id.insert(property.name.clone(), property.id);
+        }
+        self.label_to_types.insert(label, type_def);
+        let mut current_property_names = HashSet::new();
+        for t in self.label_to_types.values() {
+            for p in t.get_prop_defs() {
+                current_property_names.insert(&p.name);
+            }
+        }
+        self.property_name_to_id.retain(|k, _v| {
+            current_property_names.contains(k)
+        });
+        Ok(())
+    }
+
+    pub fn get_vertex_table_ids(&self) -> &HashMap<LabelId, i64> {
+        &self.vertex_table_ids
+    }
+
+    pub fn get_edge_table_ids(&self) -> &HashMap<EdgeKind, i64> {
+        &self.edge_table_ids
+    }
+
     pub fn put_vertex_table_id(&mut self, label: LabelId, table_id: i64) {
         self.vertex_table_ids.insert(label, table_id);
     }
@@ -78,9 +115,35 @@ impl GraphDef {
                 current_property_names.contains(k)
             });
             self.vertex_table_ids.remove(label_id);
+            self.indexes.remove(label_id);
+        }
+    }
+
+    pub fn add_index(&mut self, label_id: LabelId, prop_id: PropId) -> GraphResult<()> {
+        if !self.label_to_types.contains_key(&label_id) {
+            let msg = format!("labelId {}", label_id);
+            return Err(GraphError::new(GraphErrorCode::TypeNotFound, msg));
+        }
+        if !self.indexes.entry(label_id).or_insert_with(HashSet::new).insert(prop_id) {
+            let msg = format!("index on property#{} of label#{} already exists", prop_id, label_id);
+            return Err(GraphError::new(GraphErrorCode::InvalidOperation, msg));
+        }
+        Ok(())
+    }
+
+    pub fn remove_index(&mut self, label_id: LabelId, prop_id: PropId) {
+        if let Some(props) = self.indexes.get_mut(&label_id) {
+            props.remove(&prop_id);
+            if props.is_empty() {
+                self.indexes.remove(&label_id);
+            }
         }
     }
 
+    pub fn get_indexes(&self, label_id: LabelId) -> Option<&HashSet<PropId>> {
+        self.indexes.get(&label_id)
+    }
+
     pub fn add_edge_kind(&mut self, edge_kind: EdgeKind) {
         self.edge_kinds.insert(edge_kind);
     }
@@ -110,6 +173,10 @@ impl GraphDef {
         self.table_idx = table_idx;
     }
 
+    pub fn get_table_idx(&self) -> i64 {
+        self.table_idx
+    }
+
     pub fn increase_version(&mut self) {
         self.version = self.version + 1;
     }
@@ -145,6 +212,14 @@ impl GraphDef {
             pb.mut_edgeTableIds().push(edge_table_id_entry);
         }
         pb.set_tableIdx(self.table_idx);
+        for (label_id, props) in &self.indexes {
+            for prop_id in props {
+                let mut index_def = IndexDefPb::new();
+                index_def.mut_labelId().set_id(*label_id);
+                index_def.set_propertyId(*prop_id);
+                pb.mut_indexDefs().push(index_def);
+            }
+        }
         Ok(pb)
     }
 }
@@ -156,6 +231,8 @@ pub struct TypeDef {
     label_id: LabelId,
     properties: HashMap<PropId, PropDef>,
     type_enum: TypeEnumPb,
+    // records of this type expire after `ttl` milliseconds since they are written
+    ttl: Option<i64>,
 }
 
 impl TypeDef {
@@ -163,6 +240,10 @@ impl TypeDef {
         self.version
     }
 
+    pub fn set_version(&mut self, version: i32) {
+        self.version = version;
+    }
+
     pub fn get_prop_defs(&self) -> impl Iterator<Item=&PropDef> {
         self.properties.values()
     }
@@ -179,6 +260,14 @@ impl TypeDef {
         return self.label_id;
     }
 
+    pub fn get_type_enum(&self) -> TypeEnumPb {
+        self.type_enum
+    }
+
+    pub fn get_ttl(&self) -> Option<i64> {
+        self.ttl
+    }
+
     pub fn from_proto(proto: &TypeDefPb) -> GraphResult<Self> {
         let version_id = proto.get_versionId();
         let label = proto.get_label();
@@ -189,7 +278,8 @@ impl TypeDef {
             properties.insert(property_def.id, property_def);
         }
         let type_enum = proto.get_typeEnum();
-        Ok(Self::new(version_id, label.to_string(), label_id, properties, type_enum))
+        let ttl = Some(proto.get_ttl()).filter(|ttl| *ttl > 0);
+        Ok(Self::new(version_id, label.to_string(), label_id, properties, type_enum, ttl))
     }
 
     pub fn to_proto(&self) -> GraphResult<TypeDefPb> {
@@ -201,6 +291,7 @@ impl TypeDef {
             typedef_pb.mut_props().push(property_def.to_proto()?);
         }
         typedef_pb.set_typeEnum(self.type_enum);
+        typedef_pb.set_ttl(self.ttl.unwrap_or(0));
         Ok(typedef_pb)
     }
 
@@ -220,13 +311,14 @@ impl TypeDef {
         }
     }
 
-    fn new(version: i32, label: String, label_id: LabelId, properties: HashMap<PropId, PropDef>, type_enum: TypeEnumPb) -> Self {
+    fn new(version: i32, label: String, label_id: LabelId, properties: HashMap<PropId, PropDef>, type_enum: TypeEnumPb, ttl: Option<i64>) -> Self {
         TypeDef {
             version,
             label,
             label_id,
             properties,
             type_enum,
+            ttl,
         }
     }
 
@@ -273,6 +365,22 @@ impl TypeDefBuilder {
         self
     }
 
+    pub fn set_label(&mut self, label: &str) -> &mut Self {
+        self.type_def.label = label.to_owned();
+        self
+    }
+
+    pub fn set_type_enum(&mut self, type_enum: TypeEnumPb) -> &mut Self {
+        self.type_def.type_enum = type_enum;
+        self
+    }
+
+    /// Records of the type become invisible `ttl_ms` milliseconds after they are written.
+    pub fn set_ttl(&mut self, ttl_ms: i64) -> &mut Self {
+        self.type_def.ttl = Some(ttl_ms).filter(|ttl| *ttl > 0);
+        self
+    }
+
     pub fn build(self) -> TypeDef {
         self.type_def
     }
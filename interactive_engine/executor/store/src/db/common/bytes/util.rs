@@ -78,6 +78,22 @@ pub fn parse_pb<M: Message>(buf: &[u8]) -> GraphResult<M> {
         .map_err(|e| GraphError::new(InvalidData, format!("{:?}", e)))
 }
 
+/// Return the smallest byte string which is greater than all byte strings prefixed with `bytes`,
+/// or `None` if there's no such one (e.g. all bytes are 0xff).
+pub fn bytes_upper_bound(bytes: &[u8]) -> Option<Vec<u8>> {
+    for i in (0..bytes.len()).rev() {
+        if bytes[i] != u8::MAX {
+            let mut ret = bytes.to_vec();
+            ret[i] += 1;
+            for j in i+1..bytes.len() {
+                ret[j] = 0;
+            }
+            return Some(ret);
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,207 @@
+//! Adjacency index of edge tables, which makes degree and k-th edge queries cheap.
+//!
+//! For every vertex and direction an edge table keeps three kinds of multi-version records:
+//! the degree, the slots and the positions. Live edges always occupy slots [0, degree), so the
+//! k-th edge is a point lookup of slot k. When an edge is removed, the edge in the last slot is
+//! moved into its slot, and the position records map each edge to its slot to make this possible.
+//! As a result the order of slots is not the order of edges in a scan.
+//!
+//! Data ingested from outside has no index, so if no degree record is visible the answers come from
+//! scanning the edges, and the index of the vertex is built on its first modification.
+//!
+//! Edges of types with ttl expire without any modification, so such types have no index at all and
+//! their degrees and k-th edges always come from scanning the unexpired edges.
+//!
+//! The records of a vertex are updated by read-modify-write, so writers of the same vertex must be
+//! serialized by `AdjacencyLocks`.
+use crate::db::api::*;
+use crate::db::storage::ExternalStorage;
+use crate::db::common::bytes::transform;
+use crate::db::common::bytes::util::UnsafeBytesReader;
+use crate::db::util::lock::{GraphMutexLock, GraphMutexLockGuard};
+use super::table_manager::TableId;
+use super::bin::*;
+
+const LOCK_STRIPES: usize = 64;
+
+/// Striped locks of the adjacency index. A writer must hold the lock of the vertex from checking
+/// whether the edge exists until the edge itself is written, otherwise concurrent writers of the
+/// vertex lose updates of its degree and slots.
+pub struct AdjacencyLocks {
+    stripes: Vec<GraphMutexLock<()>>,
+}
+
+impl AdjacencyLocks {
+    pub fn new() -> Self {
+        let stripes = (0..LOCK_STRIPES).map(|_| GraphMutexLock::new(())).collect();
+        AdjacencyLocks { stripes }
+    }
+
+    /// lock the index of the vertex `id` is attached to in `direction`
+    pub fn lock(&self, table_id: TableId, id: &EdgeId, direction: EdgeDirection) -> GraphResult<GraphMutexLockGuard<()>> {
+        let vertex_id = get_vertex_id(id, direction);
+        let hash = (table_id as u64).wrapping_mul(31).wrapping_add(vertex_id as u64);
+        self.stripes[(hash % LOCK_STRIPES as u64) as usize].lock()
+    }
+}
+
+/// Return the number of edges of `vertex_id` in `direction` visible at `ts`.
+pub fn get_degree(storage: &dyn ExternalStorage, table_id: TableId, vertex_id: VertexId, direction: EdgeDirection, ts: SnapshotId) -> GraphResult<usize> {
+    match get_indexed_degree(storage, table_id, vertex_id, direction, ts)? {
+        Some(degree) => Ok(degree as usize),
+        None => Ok(scan_edge_ids(storage, table_id, vertex_id, direction, ts)?.len()),
+    }
+}
+
+/// Return the id of the `k`-th edge of `vertex_id` in `direction` visible at `ts`.
+pub fn get_kth_edge_id(storage: &dyn ExternalStorage, table_id: TableId, vertex_id: VertexId, direction: EdgeDirection, ts: SnapshotId, k: usize) -> GraphResult<Option<EdgeId>> {
+    match get_indexed_degree(storage, table_id, vertex_id, direction, ts)? {
+        Some(degree) => {
+            if k as i64 >= degree {
+                return Ok(None);
+            }
+            let key = slot_key(table_id, vertex_id, direction, k as i64, ts);
+            let res = get_latest(storage, &key).and_then(|v| {
+                match v {
+                    Some(data) => Ok(Some(parse_slot(vertex_id, direction, &data))),
+                    None => {
+                        let msg = format!("slot {} of vertex#{} is missing, degree is {}", k, vertex_id, degree);
+                        let err = gen_graph_err!(GraphErrorCode::InvalidData, msg, get_kth_edge_id);
+                        Err(err)
+                    }
+                }
+            });
+            res_unwrap!(res, get_kth_edge_id, table_id, vertex_id, direction, ts, k)
+        }
+        None => {
+            let ids = scan_edge_ids(storage, table_id, vertex_id, direction, ts)?;
+            Ok(ids.into_iter().nth(k))
+        }
+    }
+}
+
+/// Put edge `id` into the index at `ts`. The edge must not be visible at `ts` before, and the caller
+/// must hold the lock of the vertex in `AdjacencyLocks`.
+pub fn add_edge(storage: &dyn ExternalStorage, table_id: TableId, id: EdgeId, direction: EdgeDirection, ts: SnapshotId) -> GraphResult<()> {
+    let vertex_id = get_vertex_id(&id, direction);
+    let degree = res_unwrap!(load_or_build(storage, table_id, vertex_id, direction, ts), add_edge, table_id, id, direction, ts)?;
+    put_slot(storage, table_id, vertex_id, direction, degree, &id, ts)?;
+    put_degree(storage, table_id, vertex_id, direction, degree + 1, ts)
+}
+
+/// Remove edge `id` from the index at `ts`. The edge must be visible at `ts` before, and the caller
+/// must hold the lock of the vertex in `AdjacencyLocks`.
+pub fn remove_edge(storage: &dyn ExternalStorage, table_id: TableId, id: EdgeId, direction: EdgeDirection, ts: SnapshotId) -> GraphResult<()> {
+    let vertex_id = get_vertex_id(&id, direction);
+    let degree = res_unwrap!(load_or_build(storage, table_id, vertex_id, direction, ts), remove_edge, table_id, id, direction, ts)?;
+    let pos_key = position_key(table_id, id, direction, ts);
+    let slot = match get_latest(storage, &pos_key)? {
+        Some(data) => transform::bytes_to_i64(&data)?.to_be(),
+        None => {
+            let msg = format!("{:?} is not in adjacency index", id);
+            let err = gen_graph_err!(GraphErrorCode::InvalidData, msg, remove_edge, table_id, id, direction, ts);
+            return Err(err);
+        }
+    };
+    let last = degree - 1;
+    if slot != last {
+        let last_key = slot_key(table_id, vertex_id, direction, last, ts);
+        let last_id = match get_latest(storage, &last_key)? {
+            Some(data) => parse_slot(vertex_id, direction, &data),
+            None => {
+                let msg = format!("slot {} of vertex#{} is missing, degree is {}", last, vertex_id, degree);
+                let err = gen_graph_err!(GraphErrorCode::InvalidData, msg, remove_edge, table_id, id, direction, ts);
+                return Err(err);
+            }
+        };
+        put_slot(storage, table_id, vertex_id, direction, slot, &last_id, ts)?;
+    }
+    storage.put(&slot_key(table_id, vertex_id, direction, last, ts), &[])?;
+    storage.put(&pos_key, &[])?;
+    put_degree(storage, table_id, vertex_id, direction, last, ts)
+}
+
+fn get_vertex_id(id: &EdgeId, direction: EdgeDirection) -> VertexId {
+    match direction {
+        EdgeDirection::Out => id.src_id,
+        EdgeDirection::In => id.dst_id,
+        EdgeDirection::Both => unreachable!(),
+    }
+}
+
+fn get_indexed_degree(storage: &dyn ExternalStorage, table_id: TableId, vertex_id: VertexId, direction: EdgeDirection, ts: SnapshotId) -> GraphResult<Option<i64>> {
+    let key = degree_key(table_id, vertex_id, direction, ts);
+    match get_latest(storage, &key)? {
+        Some(data) => Ok(Some(transform::bytes_to_i64(&data)?.to_be())),
+        None => Ok(None),
+    }
+}
+
+/// return the degree at `ts`, and build the index from the visible edges if there's no one
+fn load_or_build(storage: &dyn ExternalStorage, table_id: TableId, vertex_id: VertexId, direction: EdgeDirection, ts: SnapshotId) -> GraphResult<i64> {
+    if let Some(degree) = get_indexed_degree(storage, table_id, vertex_id, direction, ts)? {
+        return Ok(degree);
+    }
+    let ids = scan_edge_ids(storage, table_id, vertex_id, direction, ts)?;
+    for (slot, id) in ids.iter().enumerate() {
+        put_slot(storage, table_id, vertex_id, direction, slot as i64, id, ts)?;
+    }
+    Ok(ids.len() as i64)
+}
+
+fn put_degree(storage: &dyn ExternalStorage, table_id: TableId, vertex_id: VertexId, direction: EdgeDirection, degree: i64, ts: SnapshotId) -> GraphResult<()> {
+    let key = degree_key(table_id, vertex_id, direction, ts);
+    storage.put(&key, &transform::i64_to_arr(degree.to_be()))
+}
+
+fn put_slot(storage: &dyn ExternalStorage, table_id: TableId, vertex_id: VertexId, direction: EdgeDirection, slot: i64, id: &EdgeId, ts: SnapshotId) -> GraphResult<()> {
+    let other_id = match direction {
+        EdgeDirection::Out => id.dst_id,
+        _ => id.src_id,
+    };
+    let mut val = transform::i64_to_vec(other_id.to_be());
+    val.extend_from_slice(&transform::i64_to_arr(id.inner_id.to_be()));
+    storage.put(&slot_key(table_id, vertex_id, direction, slot, ts), &val)?;
+    storage.put(&position_key(table_id, *id, direction, ts), &transform::i64_to_arr(slot.to_be()))
+}
+
+fn parse_slot(vertex_id: VertexId, direction: EdgeDirection, data: &[u8]) -> EdgeId {
+    let reader = UnsafeBytesReader::new(data);
+    let other_id = reader.read_i64(0).to_be();
+    let inner_id = reader.read_i64(8).to_be();
+    match direction {
+        EdgeDirection::Out => EdgeId::new(vertex_id, other_id, inner_id),
+        _ => EdgeId::new(other_id, vertex_id, inner_id),
+    }
+}
+
+/// return the value of the newest version of `key` which is not newer than the ts in `key`,
+/// or None if there's no such version or it's a tombstone
+fn get_latest(storage: &dyn ExternalStorage, key: &[u8]) -> GraphResult<Option<Vec<u8>>> {
+    let id_len = key.len() - 8;
+    let mut iter = storage.scan_from(key)?;
+    if let Some((k, v)) = iter.next() {
+        if k.len() == key.len() && k[..id_len] == key[..id_len] && !v.is_empty() {
+            return Ok(Some(v.to_vec()));
+        }
+    }
+    Ok(None)
+}
+
+fn scan_edge_ids(storage: &dyn ExternalStorage, table_id: TableId, vertex_id: VertexId, direction: EdgeDirection, ts: SnapshotId) -> GraphResult<Vec<EdgeId>> {
+    let prefix = edge_prefix(table_id, vertex_id, direction);
+    let mut iter = storage.scan_prefix(&prefix)?;
+    let mut ret = Vec::new();
+    let mut last_id = None;
+    while let Some((k, v)) = iter.next() {
+        let (id, edge_ts) = parse_edge_key(k);
+        if edge_ts > ts || last_id == Some(id) {
+            continue;
+        }
+        last_id = Some(id);
+        if v.len() >= 4 {
+            ret.push(id);
+        }
+    }
+    Ok(ret)
+}
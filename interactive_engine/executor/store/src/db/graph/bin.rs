@@ -1,7 +1,8 @@
-use crate::db::common::bytes::util::{UnsafeBytesWriter, UnsafeBytesReader};
+use crate::db::common::bytes::util::{UnsafeBytesWriter, UnsafeBytesReader, bytes_upper_bound};
 use crate::db::common::bytes::transform;
 use crate::db::api::*;
 use super::table_manager::TableId;
+use super::meta::META_TABLE_ID;
 
 pub fn vertex_key(table_id: TableId, id: VertexId, ts: SnapshotId) -> [u8; 24] {
     let mut ret = [0; 24];
@@ -98,3 +99,179 @@ pub fn edge_prefix(id: TableId, vertex_id: VertexId, direction: EdgeDirection) -
     writer.write_i64(8, vertex_id.to_be());
     ret
 }
+
+/// return [start, end) which covers all versions of all vertices in the table
+pub fn vertex_table_range(table_id: TableId) -> (Vec<u8>, Vec<u8>) {
+    let start = vertex_table_prefix_key(table_id);
+    let end = prefix_upper_bound(&start);
+    (start.to_vec(), end)
+}
+
+/// return [start, end) which covers all versions of both forward and backward edges in the table
+pub fn edge_table_range(table_id: TableId) -> (Vec<u8>, Vec<u8>) {
+    let start = edge_table_prefix_key(table_id, EdgeDirection::Out);
+    let end = prefix_upper_bound(&edge_table_prefix_key(table_id, EdgeDirection::In));
+    (start.to_vec(), end)
+}
+
+/// Indexes of all tables live under this reserved prefix. Table ids start from i64::MIN / 2 + 1 and
+/// i64::MIN is the prefix of meta, so no vertex or edge table can use it. The next 8 bytes are the
+/// prefix of the indexed table, which is unique among vertex tables and both directions of edge tables.
+///
+/// For an edge table it's followed by the adjacency index: `vertex_id | tag | ...`, where tag tells
+/// the degree record, slot records and position records apart. For a vertex table it's followed by
+/// property indexes: `prop_id | index_si | encoded value | vertex_id`.
+///
+/// Property indexes of edge tables can't share the space of the adjacency index, so they live under
+/// `EDGE_PROPERTY_INDEX_TAG`, which is less than the prefix of any table, followed by the prefix of
+/// forward edges of the table and `prop_id | index_si | encoded value | src_id | dst_id | inner_id`.
+const INDEX_PREFIX: i64 = i64::min_value() + 1;
+const EDGE_PROPERTY_INDEX_TAG: i64 = i64::min_value();
+const DEGREE_TAG: i64 = 0;
+const SLOT_TAG: i64 = 1;
+const POSITION_TAG: i64 = 2;
+
+fn write_adjacency_key_prefix(writer: &mut UnsafeBytesWriter, table_id: TableId, vertex_id: VertexId,
+                              direction: EdgeDirection, tag: i64) {
+    writer.write_i64(0, INDEX_PREFIX.to_be());
+    writer.write_i64(8, edge_table_prefix(table_id, direction).to_be());
+    writer.write_i64(16, vertex_id.to_be());
+    writer.write_i64(24, tag.to_be());
+}
+
+/// key of the degree of `vertex_id` in `direction`
+pub fn degree_key(table_id: TableId, vertex_id: VertexId, direction: EdgeDirection, ts: SnapshotId) -> [u8; 40] {
+    let mut ret = [0u8; 40];
+    let mut writer = UnsafeBytesWriter::new(&mut ret);
+    write_adjacency_key_prefix(&mut writer, table_id, vertex_id, direction, DEGREE_TAG);
+    writer.write_i64(32, (!ts).to_be());
+    ret
+}
+
+/// key of the `slot`-th edge of `vertex_id` in `direction`
+pub fn slot_key(table_id: TableId, vertex_id: VertexId, direction: EdgeDirection, slot: i64, ts: SnapshotId) -> [u8; 48] {
+    let mut ret = [0u8; 48];
+    let mut writer = UnsafeBytesWriter::new(&mut ret);
+    write_adjacency_key_prefix(&mut writer, table_id, vertex_id, direction, SLOT_TAG);
+    writer.write_i64(32, slot.to_be());
+    writer.write_i64(40, (!ts).to_be());
+    ret
+}
+
+/// key of the slot of edge `id` in `direction`
+pub fn position_key(table_id: TableId, id: EdgeId, direction: EdgeDirection, ts: SnapshotId) -> [u8; 56] {
+    let mut ret = [0u8; 56];
+    let mut writer = UnsafeBytesWriter::new(&mut ret);
+    let (vertex_id, other_id) = match direction {
+        EdgeDirection::Out => (id.src_id, id.dst_id),
+        EdgeDirection::In => (id.dst_id, id.src_id),
+        EdgeDirection::Both => unreachable!(),
+    };
+    write_adjacency_key_prefix(&mut writer, table_id, vertex_id, direction, POSITION_TAG);
+    writer.write_i64(32, other_id.to_be());
+    writer.write_i64(40, id.inner_id.to_be());
+    writer.write_i64(48, (!ts).to_be());
+    ret
+}
+
+/// return [start, end) which covers all adjacency index records of the edge table
+pub fn adjacency_table_range(table_id: TableId) -> (Vec<u8>, Vec<u8>) {
+    let mut start = transform::i64_to_vec(INDEX_PREFIX.to_be());
+    start.extend_from_slice(&edge_table_prefix_key(table_id, EdgeDirection::Out));
+    let mut end = transform::i64_to_vec(INDEX_PREFIX.to_be());
+    end.extend_from_slice(&edge_table_prefix_key(table_id, EdgeDirection::In));
+    (start, prefix_upper_bound(&end))
+}
+
+/// prefix of all entries of the index on `prop_id` created at `index_si` in the vertex table
+pub fn property_index_prefix(table_id: TableId, prop_id: PropId, index_si: SnapshotId) -> Vec<u8> {
+    let mut ret = vec![0u8; 28];
+    let mut writer = UnsafeBytesWriter::new(&mut ret);
+    writer.write_i64(0, INDEX_PREFIX.to_be());
+    writer.write_i64(8, vertex_table_prefix(table_id).to_be());
+    writer.write_i32(16, prop_id.to_be());
+    writer.write_i64(20, index_si.to_be());
+    ret
+}
+
+/// prefix of all entries of the index on `prop_id` created at `index_si` in the edge table
+pub fn edge_property_index_prefix(table_id: TableId, prop_id: PropId, index_si: SnapshotId) -> Vec<u8> {
+    let mut ret = vec![0u8; 36];
+    let mut writer = UnsafeBytesWriter::new(&mut ret);
+    writer.write_i64(0, INDEX_PREFIX.to_be());
+    writer.write_i64(8, EDGE_PROPERTY_INDEX_TAG.to_be());
+    writer.write_i64(16, edge_table_prefix(table_id, EdgeDirection::Out).to_be());
+    writer.write_i32(24, prop_id.to_be());
+    writer.write_i64(28, index_si.to_be());
+    ret
+}
+
+/// `value` must be encoded in an order preserving and prefix free way and `id` must be of a fixed
+/// length, so that all versions of an entry are adjacent and sorted by ts in descending order
+pub fn property_index_key(prefix: &[u8], value: &[u8], id: &[u8], ts: SnapshotId) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(prefix.len() + value.len() + id.len() + 8);
+    ret.extend_from_slice(prefix);
+    ret.extend_from_slice(value);
+    ret.extend_from_slice(id);
+    ret.extend_from_slice(&transform::i64_to_arr((!ts).to_be()));
+    ret
+}
+
+/// return [start, end) which covers all property index entries of the vertex table
+pub fn property_index_table_range(table_id: TableId) -> (Vec<u8>, Vec<u8>) {
+    let mut start = transform::i64_to_vec(INDEX_PREFIX.to_be());
+    start.extend_from_slice(&vertex_table_prefix_key(table_id));
+    let end = prefix_upper_bound(&start);
+    (start, end)
+}
+
+/// return [start, end) which covers all property index entries of the edge table
+pub fn edge_property_index_table_range(table_id: TableId) -> (Vec<u8>, Vec<u8>) {
+    let mut start = transform::i64_to_vec(INDEX_PREFIX.to_be());
+    start.extend_from_slice(&transform::i64_to_arr(EDGE_PROPERTY_INDEX_TAG.to_be()));
+    start.extend_from_slice(&edge_table_prefix_key(table_id, EdgeDirection::Out));
+    let end = prefix_upper_bound(&start);
+    (start, end)
+}
+
+/// all keys end with 8 bytes of `!ts`, no matter it's a vertex key or an edge key
+pub fn parse_key_ts(key: &[u8]) -> SnapshotId {
+    let reader = UnsafeBytesReader::new(key);
+    !reader.read_i64(key.len() - 8).to_be()
+}
+
+/// return the id of the table which `key` belongs to, whether it's a key of data or of indexes, or
+/// None if it's a meta key
+pub fn parse_table_id(key: &[u8]) -> Option<TableId> {
+    let reader = UnsafeBytesReader::new(key);
+    let mut prefix = reader.read_i64(0).to_be();
+    if prefix == META_TABLE_ID {
+        return None;
+    }
+    if prefix == INDEX_PREFIX {
+        prefix = reader.read_i64(8).to_be();
+        if prefix == EDGE_PROPERTY_INDEX_TAG {
+            prefix = reader.read_i64(16).to_be();
+        }
+    }
+    // both directions of an edge table and the vertex table share the same id
+    Some(prefix >> 1)
+}
+
+/// return the id of the table if `key` is a key of vertex or edge data, or None if it's a key of
+/// meta or of indexes
+pub fn parse_data_table_id(key: &[u8]) -> Option<TableId> {
+    let reader = UnsafeBytesReader::new(key);
+    let prefix = reader.read_i64(0).to_be();
+    if prefix == META_TABLE_ID || prefix == INDEX_PREFIX {
+        return None;
+    }
+    Some(prefix >> 1)
+}
+
+/// the upper bound of all keys starting with `prefix`. Keys are at most 56 bytes except property
+/// index keys, which never start with 0xff, so if there's no upper bound of the prefix a byte
+/// string of 57 0xff is greater than all of them.
+pub fn prefix_upper_bound(prefix: &[u8]) -> Vec<u8> {
+    bytes_upper_bound(prefix).unwrap_or_else(|| vec![u8::MAX; 57])
+}
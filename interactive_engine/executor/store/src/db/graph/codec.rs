@@ -3,11 +3,12 @@ use ::crossbeam_epoch::{Atomic, Owned, Guard};
 
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::db::api::*;
 use crate::db::common::bytes::util::{UnsafeBytesReader, UnsafeBytesWriter};
 use crate::db::util::lock::GraphMutexLock;
+use crate::db::util::time::current_time_millis;
 use super::version::*;
 use std::fmt;
 
@@ -26,6 +27,9 @@ pub type CodecVersion = i32;
 /// | var len prop1 | var len prop2 .... | ... | var len propM |
 /// +---------------+--------------------+-----+---------------+
 /// ↑ this is `var_len_prop_start_offset`
+///
+/// If the type has a ttl, an 8B big-endian write time in milliseconds is appended after the var len
+/// props, and the record expires when `write time + ttl <= now`.
 #[derive(Clone, Debug)]
 pub struct Codec {
     version: CodecVersion,
@@ -39,6 +43,7 @@ pub struct Codec {
     fixed_len_prop_count: usize,
     var_len_prop_start_offset: usize,
     null_bytes: Vec<u8>,
+    ttl: Option<i64>,
 }
 
 impl Codec {
@@ -93,6 +98,7 @@ impl Codec {
             fixed_len_prop_count,
             var_len_prop_start_offset,
             null_bytes,
+            ttl: type_def.get_ttl(),
         }
     }
 }
@@ -101,8 +107,9 @@ impl Codec {
 /// be return. For example, target codec contains prop#1, prop#2 and prop#3, src codec contains prop#1,
 /// prop#3 and prop#4. So the binary data has prop#1, prop#3 and prop#4. User will get prop#1, prop#2,
 /// and prop#3 because current schema user can see has these properties. When user gets prop#1 or prop#3
-/// and it's in binary data, so just return it. When user get prop#2 but it's not in binary data,
-/// so return None. And prop#4 in data will never be get because user don't know it.
+/// and it's in binary data, so just return it. When user get prop#2 but it's not in binary data, it
+/// was added to the type after the data was written, so return its default value in target codec or
+/// None if it has no default value. And prop#4 in data will never be get because user don't know it.
 pub struct Decoder {
     target: &'static Codec,
     src: &'static Codec,
@@ -147,15 +154,45 @@ impl Decoder {
             return self.decode_property_at(&reader, idx);
         }
         let internal_id = self.target.props[idx].inner_id;
-        let idx = *self.src.inner_id_map.get(&internal_id)?;
-        self.decode_property_at(&reader, idx)
+        match self.src.inner_id_map.get(&internal_id) {
+            Some(src_idx) => self.decode_property_at(&reader, *src_idx),
+            None => self.default_value_at(idx),
+        }
     }
 
-    fn decode_property_at<'a>(&self, reader: &UnsafeBytesReader<'a>, idx: usize) -> Option<ValueRef<'a>> {
+    /// Check the null bit of `prop_id` without decoding its value.
+    pub fn has_property(&self, data: &[u8], prop_id: PropId) -> bool {
+        let reader = UnsafeBytesReader::new(data);
+        let idx = match self.target.id_map.get(&prop_id) {
+            Some(idx) => *idx,
+            None => return false,
+        };
+        if self.fast_mode() {
+            return !Self::is_null_at(&reader, idx);
+        }
+        let internal_id = self.target.props[idx].inner_id;
+        match self.src.inner_id_map.get(&internal_id) {
+            Some(idx) => !Self::is_null_at(&reader, *idx),
+            None => self.target.props[idx].default_value.is_some(),
+        }
+    }
+
+    /// Default value of the `idx`-th property in target codec, used for properties which are not in src codec
+    fn default_value_at<'a>(&self, idx: usize) -> Option<ValueRef<'a>> {
+        let target: &'static Codec = self.target;
+        let info = &target.props[idx];
+        info.default_value.as_ref().map(|v| ValueRef::new(info.r#type, v))
+    }
+
+    fn is_null_at(reader: &UnsafeBytesReader, idx: usize) -> bool {
         let null_byte_off = std::mem::size_of::<CodecVersion>() + idx / 8;
         let null_byte = reader.read_u8(null_byte_off);
         let null_mask = 1 << (7 - (idx % 8) as u8);
-        if (null_byte & null_mask) == 0 {
+        (null_byte & null_mask) != 0
+    }
+
+    fn decode_property_at<'a>(&self, reader: &UnsafeBytesReader<'a>, idx: usize) -> Option<ValueRef<'a>> {
+        if !Self::is_null_at(reader, idx) {
             if idx < self.src.fixed_len_prop_count {
                 self.decode_fixed_len_property_at(reader, idx)
             } else {
@@ -202,6 +239,11 @@ impl Decoder {
         Some(ret)
     }
 
+    /// Whether the record in `data` has outlived the ttl of its type at `now` (in milliseconds).
+    pub fn is_expired(&self, data: &[u8], now: i64) -> bool {
+        self.src.ttl.map_or(false, |ttl| get_write_time(data) + ttl <= now)
+    }
+
     fn fast_mode(&self) -> bool {
         self.target.version == self.src.version
     }
@@ -261,8 +303,10 @@ impl<'a> IterDecoder<'a> {
         let info = &self.decoder.target.props[self.cur];
         let prop_id = info.prop_id;
         let internal_id = info.inner_id;
-        let idx = *self.decoder.src.inner_id_map.get(&internal_id)?;
-        let v = self.decoder.decode_property_at(&self.reader, idx)?;
+        let v = match self.decoder.src.inner_id_map.get(&internal_id) {
+            Some(idx) => self.decoder.decode_property_at(&self.reader, *idx)?,
+            None => self.decoder.default_value_at(self.cur)?,
+        };
         Some((prop_id, v))
     }
 }
@@ -288,10 +332,17 @@ impl Encoder {
         }
     }
 
+    pub fn get_ttl(&self) -> Option<i64> {
+        self.codec.ttl
+    }
+
     pub fn encode(&self, props: &dyn PropertyMap, buf: &mut Vec<u8>) -> GraphResult<()> {
         // the vector pass to encoder may be not filled with zeros, so encoder should make sure
         // every bit is written by itself and set the vector's len to real length.
-        let size = res_unwrap!(self.check_and_cal_size(props), encode)?;
+        let mut size = res_unwrap!(self.check_and_cal_size(props), encode)?;
+        if self.codec.ttl.is_some() {
+            size += std::mem::size_of::<i64>();
+        }
         if buf.capacity() < size {
             buf.reserve(size - buf.capacity());
         }
@@ -302,6 +353,9 @@ impl Encoder {
         // write fixed len property
         self.encode_fix_len_properties(&mut writer, props, &mut null_byte)?;
         self.encode_var_len_properties(&mut writer, props, &mut null_byte)?;
+        if self.codec.ttl.is_some() {
+            writer.write_i64(size - std::mem::size_of::<i64>(), current_time_millis().to_be());
+        }
         Ok(())
     }
 
@@ -398,6 +452,16 @@ pub fn get_codec_version(data: &[u8]) -> CodecVersion {
     reader.read_i32(0).to_be()
 }
 
+/// Write time of a record whose type has a ttl, it's the last 8 bytes of the data.
+pub fn get_write_time(data: &[u8]) -> i64 {
+    let len = std::mem::size_of::<i64>();
+    if data.len() < len {
+        return 0;
+    }
+    let reader = UnsafeBytesReader::new(data);
+    reader.read_i64(data.len() - len).to_be()
+}
+
 #[derive(Clone, Debug, PartialEq)]
 struct PropInfo {
     prop_id: PropId,
@@ -566,7 +630,6 @@ impl CodecManager {
         Err(err)
     }
 
-    #[allow(dead_code)]
     pub fn drop_codec(&self, version: CodecVersion) -> GraphResult<()> {
         let _lock = res_unwrap!(self.lock.lock(), drop_codec, version)?;
         let guard = epoch::pin();
@@ -578,11 +641,29 @@ impl CodecManager {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn gc(&self, si: SnapshotId) -> GraphResult<()> {
         res_unwrap!(self.versions.gc(si).map(|_| ()), gc, si)
     }
 
+    /// Return codec versions which are no longer visible at any snapshot. Data encoded by them may
+    /// still exist in storage, so it's up to the caller to decide whether they can be dropped.
+    pub fn get_obsolete_versions(&self) -> Vec<CodecVersion> {
+        let alive: HashSet<CodecVersion> = self.versions.get_all().into_iter()
+            .map(|v| v.data as CodecVersion)
+            .collect();
+        let guard = epoch::pin();
+        let map = self.get_map(&guard);
+        map.keys().filter(|v| !alive.contains(*v)).cloned().collect()
+    }
+
+    /// Return inner ids of properties in all codecs, including the obsolete ones which are not dropped,
+    /// i.e. inner ids which may appear in the stored data.
+    pub fn get_all_inner_ids(&self) -> HashSet<PropId> {
+        let guard = epoch::pin();
+        let map = self.get_map(&guard);
+        map.values().flat_map(|codec| codec.inner_id_map.keys().cloned()).collect()
+    }
+
     fn get_map(&self, guard: &Guard) -> &'static CodecMap {
         unsafe { &*self.codec_map.load(Ordering::Relaxed, &guard).as_raw() }
     }
@@ -740,6 +821,89 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_decode_with_older_codec() {
+        let mut builder = TypeDefBuilder::new();
+        builder.version(1);
+        builder.add_property(1, 1, "1".to_string(), ValueType::Long, None, false, "cmt".to_string());
+        builder.add_property(2, 2, "2".to_string(), ValueType::String, None, false, "cmt".to_string());
+        builder.add_property(3, 3, "3".to_string(), ValueType::Int, None, false, "cmt".to_string());
+        let src = Codec::from(&builder.build());
+        // prop#2 is dropped, prop#4 and prop#5 are added and prop#3 is dropped and added again
+        let mut builder = TypeDefBuilder::new();
+        builder.version(2);
+        builder.add_property(1, 1, "1".to_string(), ValueType::Long, None, false, "cmt".to_string());
+        builder.add_property(3, 6, "3".to_string(), ValueType::Int, None, false, "cmt".to_string());
+        builder.add_property(4, 4, "4".to_string(), ValueType::String, Some(Value::string("x")), false, "cmt".to_string());
+        builder.add_property(5, 5, "5".to_string(), ValueType::Double, None, false, "cmt".to_string());
+        let target = Codec::from(&builder.build());
+
+        let mut properties = HashMap::new();
+        properties.insert(1, Value::long(10));
+        properties.insert(2, Value::string("abc"));
+        properties.insert(3, Value::int(20));
+        let mut buf = Vec::new();
+        create_encoder(&src).encode(&properties, &mut buf).unwrap();
+        let src_ref = unsafe { std::mem::transmute(&src) };
+        let target_ref = unsafe { std::mem::transmute(&target) };
+        let decoder = Decoder::new(target_ref, src_ref, epoch::pin());
+        let mut ans = HashMap::new();
+        ans.insert(1, Value::long(10));
+        ans.insert(4, Value::string("x"));
+        check_properties(decoder.clone(), &buf, ans);
+        for prop_id in vec![2, 3, 5] {
+            assert!(decoder.decode_property(&buf, prop_id).is_none());
+            assert!(!decoder.has_property(&buf, prop_id));
+        }
+        assert!(decoder.has_property(&buf, 4));
+    }
+
+    #[test]
+    fn test_encode_with_ttl() {
+        let mut builder = TypeDefBuilder::new();
+        builder.version(1).set_ttl(1000);
+        for (prop_id, inner_id, r#type) in test_prop_list() {
+            builder.add_property(prop_id, inner_id, prop_id.to_string(), r#type, None, false, "cmt".to_string());
+        }
+        let codec = Codec::from(&builder.build());
+        let before = current_time_millis();
+        let mut buf = vec![255; 1000];
+        create_encoder(&codec).encode(&test_data(), &mut buf).unwrap();
+        let write_time = get_write_time(&buf);
+        assert!(write_time >= before && write_time <= current_time_millis());
+        let decoder = create_decoder(&codec);
+        check_properties(decoder.clone(), &buf, test_data());
+        assert!(!decoder.is_expired(&buf, write_time + 999));
+        assert!(decoder.is_expired(&buf, write_time + 1000));
+
+        let codec = create_test_codec();
+        create_encoder(&codec).encode(&test_data(), &mut buf).unwrap();
+        assert!(!create_decoder(&codec).is_expired(&buf, i64::max_value()));
+    }
+
+    #[test]
+    fn test_codec_manager_gc() {
+        let manager = CodecManager::new();
+        for version in 1..=5 {
+            let mut builder = TypeDefBuilder::new();
+            builder.version(version);
+            builder.add_property(1, 1, "1".to_string(), ValueType::Long, None, false, "cmt".to_string());
+            let codec = Codec::from(&builder.build());
+            manager.add_codec(version as SnapshotId * 10, codec).unwrap();
+        }
+        assert!(manager.get_obsolete_versions().is_empty());
+        manager.gc(35).unwrap();
+        let mut obsolete = manager.get_obsolete_versions();
+        obsolete.sort();
+        assert_eq!(obsolete, vec![1, 2]);
+        manager.drop_codec(1).unwrap();
+        assert_eq!(manager.get_obsolete_versions(), vec![2]);
+        // codec 2 is still kept, so data encoded by it can be decoded with the codec visible at si
+        assert!(manager.get_decoder(35, 2).is_ok());
+        assert!(manager.get_decoder(35, 1).is_err());
+        assert!(manager.get_encoder(35).is_ok());
+    }
+
     fn create_encoder(codec: &Codec) -> Encoder {
         let codec_ref = unsafe { std::mem::transmute(codec) };
         Encoder::new(codec_ref, epoch::pin())
@@ -3,11 +3,14 @@ use std::sync::Arc;
 use crate::db::api::*;
 use crate::db::storage::{StorageIter, ExternalStorage};
 use crate::db::common::unsafe_util;
+use crate::db::util::time::current_time_millis;
 use super::property::*;
 use super::codec::*;
 use super::types::*;
 use super::bin::*;
 use super::query;
+use super::index;
+use super::table_manager::{Table, TableId};
 
 pub struct EdgeImpl {
     id: EdgeId,
@@ -69,7 +72,7 @@ impl std::fmt::Debug for EdgeImpl {
 }
 
 pub struct SingleLabelEdgeIter<'a> {
-    inner: EdgeResultIterList<SingleTypeEdgeIter<'a>>,
+    inner: EdgeResultIterList<Box<dyn EdgeResultIter<E=EdgeImpl> + 'a>>,
 }
 
 impl<'a> SingleLabelEdgeIter<'a> {
@@ -83,7 +86,7 @@ impl<'a> SingleLabelEdgeIter<'a> {
         let mut info_iter = info.into_iter();
         let mut iters = Vec::new();
         while let Some(type_info) = info_iter.next() {
-            let res = SingleTypeEdgeIter::create(si, id, direction, type_info, storage, condition.clone());
+            let res = create_edge_kind_iter(si, id, direction, type_info, storage, condition.clone());
             match res_unwrap!(res, create)? {
                 Some(iter) => iters.push(iter),
                 None => {},
@@ -109,6 +112,29 @@ impl<'a> EdgeResultIter for SingleLabelEdgeIter<'a> {
     }
 }
 
+/// Scan an index instead of the whole table if all edges of the kind are queried and some index can
+/// answer a part of `condition`. Only forward edges are indexed, which are what such a query scans.
+fn create_edge_kind_iter<'a>(si: SnapshotId,
+                             id: VertexId,
+                             direction: EdgeDirection,
+                             info: EdgeKindInfoRef,
+                             storage: &'a dyn ExternalStorage,
+                             condition: Option<Arc<Condition>>)
+                             -> GraphResult<Option<Box<dyn EdgeResultIter<E=EdgeImpl> + 'a>>> {
+    if let (EdgeDirection::Both, Some(c), Some(table)) = (direction, condition.as_ref(), info.get_table(si)) {
+        let ts = si - table.start_si;
+        let get_prefix = |prop_id| {
+            info.get_index(si, prop_id).map(|index| edge_property_index_prefix(table.id, prop_id, index.get_start_si()))
+        };
+        if let Some(ids) = index::scan_by_condition(storage, ts, c, get_prefix)? {
+            let iter = IndexedEdgeIter::new(si, &table, info, c.clone(), ids, storage);
+            return Ok(Some(Box::new(iter)));
+        }
+    }
+    let ret = SingleTypeEdgeIter::create(si, id, direction, info, storage, condition)?;
+    Ok(ret.map(|iter| Box::new(iter) as Box<dyn EdgeResultIter<E=EdgeImpl> + 'a>))
+}
+
 pub struct MultiLabelsEdgeIter<'a> {
     inner: EdgeResultIterList<SingleLabelEdgeIter<'a>>,
 }
@@ -146,18 +172,20 @@ impl<'a> EdgeResultIter for MultiLabelsEdgeIter<'a> {
     }
 }
 
-struct SingleTypeEdgeIter<'a> {
+pub struct SingleTypeEdgeIter<'a> {
     si: SnapshotId,
     ts: SnapshotId,
     info: EdgeKindInfoRef,
     condition: Option<Arc<Condition>>,
     iter: StorageIter<'a>,
     last_id: Option<EdgeId>,
+    // records expired before this time are skipped
+    now: i64,
     err: Option<GraphError>,
 }
 
 impl<'a> SingleTypeEdgeIter<'a> {
-    fn create(si: SnapshotId,
+    pub fn create(si: SnapshotId,
               id: VertexId,
               direction: EdgeDirection,
               info: EdgeKindInfoRef,
@@ -191,6 +219,7 @@ impl<'a> SingleTypeEdgeIter<'a> {
             condition,
             iter,
             last_id: None,
+            now: current_time_millis(),
             err: None,
         }
     }
@@ -233,6 +262,9 @@ impl<'a> EdgeResultIter for SingleTypeEdgeIter<'a> {
             let codec_version = get_codec_version(val);
             return match self.info.get_decoder(self.si, codec_version) {
                 Ok(decoder) => {
+                    if decoder.is_expired(val, self.now) {
+                        continue;
+                    }
                     if let Some(ref condition) = self.condition {
                         if !query::check_condition(&decoder, val, condition.as_ref()) {
                             continue;
@@ -259,6 +291,96 @@ impl<'a> EdgeResultIter for SingleTypeEdgeIter<'a> {
     }
 }
 
+/// Iterate the forward edges found by an index scan. The index only tells which edges may match, so
+/// every edge is fetched and checked against the whole condition.
+pub struct IndexedEdgeIter<'a> {
+    si: SnapshotId,
+    ts: SnapshotId, // real si - table.start_si
+    table_id: TableId,
+    info: EdgeKindInfoRef,
+    condition: Arc<Condition>,
+    ids: std::vec::IntoIter<EdgeId>,
+    storage: &'a dyn ExternalStorage,
+    now: i64,
+    err: Option<GraphError>,
+}
+
+impl<'a> IndexedEdgeIter<'a> {
+    pub fn new(si: SnapshotId,
+               table: &Table,
+               info: EdgeKindInfoRef,
+               condition: Arc<Condition>,
+               ids: Vec<EdgeId>,
+               storage: &'a dyn ExternalStorage) -> Self {
+        IndexedEdgeIter {
+            si,
+            ts: si - table.start_si,
+            table_id: table.id,
+            info,
+            condition,
+            ids: ids.into_iter(),
+            storage,
+            now: current_time_millis(),
+            err: None,
+        }
+    }
+
+    fn get_edge(&self, id: EdgeId) -> GraphResult<Option<EdgeImpl>> {
+        let key = edge_key(self.table_id, id, EdgeDirection::Out, self.ts);
+        let mut iter = self.storage.scan_from(&key)?;
+        if let Some((k, v)) = iter.next() {
+            if k.len() == key.len() && k[0..32] == key[0..32] && v.len() >= 4 {
+                let decoder = self.info.get_decoder(self.si, get_codec_version(v))?;
+                if !decoder.is_expired(v, self.now) && query::check_condition(&decoder, v, self.condition.as_ref()) {
+                    let ret = EdgeImpl::new(id, self.info.get_type().clone(), PropData::Owned(v.to_vec()), decoder);
+                    return Ok(Some(ret));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> EdgeResultIter for IndexedEdgeIter<'a> {
+    type E = EdgeImpl;
+
+    fn next(&mut self) -> Option<EdgeWrapper<Self::E>> {
+        if self.err.is_some() {
+            return None;
+        }
+        while let Some(id) = self.ids.next() {
+            match self.get_edge(id) {
+                Ok(Some(e)) => return Some(EdgeWrapper::new(e)),
+                Ok(None) => {}
+                Err(e) => {
+                    self.err = Some(e);
+                    return None;
+                }
+            }
+        }
+        None
+    }
+
+    fn ok(&self) -> GraphResult<()> {
+        if let Some(ref err) = self.err {
+            return Err(err.clone());
+        }
+        Ok(())
+    }
+}
+
+impl<'a> EdgeResultIter for Box<dyn EdgeResultIter<E=EdgeImpl> + 'a> {
+    type E = EdgeImpl;
+
+    fn next(&mut self) -> Option<EdgeWrapper<Self::E>> {
+        self.as_mut().next()
+    }
+
+    fn ok(&self) -> GraphResult<()> {
+        self.as_ref().ok()
+    }
+}
+
 struct EdgeResultIterList<I> {
     iters: Vec<I>,
     cur: usize,
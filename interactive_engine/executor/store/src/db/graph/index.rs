@@ -0,0 +1,248 @@
+//! Secondary indexes on vertex and edge properties.
+//!
+//! An index entry maps the value of the indexed property to a vertex or an edge. Entries are
+//! multi-versioned like vertices and edges: when the value of a record changes at ts, a tombstone of
+//! the old entry and the new entry are both written at ts, so an index scan at any ts only returns
+//! records whose value was in the range at that ts.
+//!
+//! Numbers are indexed as doubles, so that values of different numeric types are comparable the
+//! same way as `ValueRef`. Different longs may share an entry value in this way, so the scan is
+//! inclusive on both ends and callers must check the condition on the record again.
+use std::collections::HashSet;
+use std::hash::Hash;
+use crate::db::api::*;
+use crate::db::storage::ExternalStorage;
+use crate::db::common::bytes::transform;
+use super::bin::*;
+
+const NUMBER_TAG: u8 = 1;
+const STRING_TAG: u8 = 2;
+
+/// Encode `value` in an order preserving and prefix free way. Return None if values of its type
+/// are not indexed.
+pub fn encode_value(value: &ValueRef) -> Option<Vec<u8>> {
+    match *value.get_type() {
+        ValueType::String => {
+            let s = value.get_str().ok()?;
+            let mut ret = Vec::with_capacity(s.len() + 3);
+            ret.push(STRING_TAG);
+            for b in s.as_bytes() {
+                ret.push(*b);
+                if *b == 0 {
+                    ret.push(u8::MAX);
+                }
+            }
+            ret.push(0);
+            ret.push(1);
+            Some(ret)
+        }
+        _ => {
+            let mut x = value.to_double()?;
+            if x.is_nan() {
+                return None;
+            }
+            if x == 0.0 {
+                // -0.0 is equal to 0.0
+                x = 0.0;
+            }
+            let bits = x.to_bits();
+            let ordered = if bits >> 63 == 1 {
+                !bits
+            } else {
+                bits | (1 << 63)
+            };
+            let mut ret = Vec::with_capacity(9);
+            ret.push(NUMBER_TAG);
+            ret.extend_from_slice(&ordered.to_be_bytes());
+            Some(ret)
+        }
+    }
+}
+
+/// Id of an indexed record, which is written into the key of an entry and its value as fixed length
+/// bytes.
+pub trait IndexedId: Copy + Eq + Hash {
+    fn to_bytes(&self) -> Vec<u8>;
+
+    fn from_bytes(bytes: &[u8]) -> GraphResult<Self>;
+}
+
+impl IndexedId for VertexId {
+    fn to_bytes(&self) -> Vec<u8> {
+        transform::i64_to_vec(self.to_be())
+    }
+
+    fn from_bytes(bytes: &[u8]) -> GraphResult<Self> {
+        Ok(transform::bytes_to_i64(bytes)?.to_be())
+    }
+}
+
+impl IndexedId for EdgeId {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut ret = Vec::with_capacity(24);
+        for x in &[self.src_id, self.dst_id, self.inner_id] {
+            ret.extend_from_slice(&transform::i64_to_arr(x.to_be()));
+        }
+        ret
+    }
+
+    fn from_bytes(bytes: &[u8]) -> GraphResult<Self> {
+        if bytes.len() != 24 {
+            let msg = format!("invalid edge id, len is {}", bytes.len());
+            let err = gen_graph_err!(GraphErrorCode::InvalidData, msg, from_bytes);
+            return Err(err);
+        }
+        let src_id = transform::bytes_to_i64(&bytes[0..8])?.to_be();
+        let dst_id = transform::bytes_to_i64(&bytes[8..16])?.to_be();
+        let inner_id = transform::bytes_to_i64(&bytes[16..24])?.to_be();
+        Ok(EdgeId::new(src_id, dst_id, inner_id))
+    }
+}
+
+/// Replace the entry of `old` value with the entry of `new` value for `id` at `ts`.
+pub fn update_entry<I: IndexedId>(storage: &dyn ExternalStorage, prefix: &[u8], id: I, old: Option<ValueRef>,
+                                  new: Option<ValueRef>, ts: SnapshotId) -> GraphResult<()> {
+    let old = old.as_ref().and_then(encode_value);
+    let new = new.as_ref().and_then(encode_value);
+    if old == new {
+        return Ok(());
+    }
+    let id = id.to_bytes();
+    if let Some(value) = old {
+        storage.put(&property_index_key(prefix, &value, &id, ts), &[])?;
+    }
+    if let Some(value) = new {
+        let key = property_index_key(prefix, &value, &id, ts);
+        storage.put(&key, &id)?;
+    }
+    Ok(())
+}
+
+/// Return ids of records whose value may satisfy `op` with `value` at `ts`, or None if the index
+/// cannot help.
+pub fn scan<I: IndexedId>(storage: &dyn ExternalStorage, prefix: &[u8], op: &ComparisonOp, value: &ValueRef, ts: SnapshotId) -> GraphResult<Option<Vec<I>>> {
+    let value = match encode_value(value) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let mut key = prefix.to_vec();
+    key.extend_from_slice(&value);
+    let mut class_prefix = prefix.to_vec();
+    class_prefix.push(value[0]);
+    let (start, end) = match *op {
+        ComparisonOp::Equal => (key.clone(), prefix_upper_bound(&key)),
+        ComparisonOp::LessThan | ComparisonOp::LessEqual => (class_prefix, prefix_upper_bound(&key)),
+        ComparisonOp::GreaterThan | ComparisonOp::GreaterEqual => {
+            let end = prefix_upper_bound(&class_prefix);
+            (key, end)
+        }
+        _ => return Ok(None),
+    };
+    let mut iter = storage.scan_range(&start, &end)?;
+    let mut ret = Vec::new();
+    let mut last_entry: Vec<u8> = Vec::new();
+    while let Some((k, v)) = iter.next() {
+        let entry = &k[..k.len() - 8];
+        if parse_key_ts(k) > ts || last_entry.as_slice() == entry {
+            continue;
+        }
+        last_entry = entry.to_vec();
+        if !v.is_empty() {
+            ret.push(I::from_bytes(v)?);
+        }
+    }
+    Ok(Some(ret))
+}
+
+/// Answer one predicate of `condition` by an index and return the ids of the candidates visible at
+/// `ts`, or None if no index can help. `get_prefix` returns the prefix of the entries of the index on
+/// a property, or None if the property isn't indexed. Only a top level predicate or the predicates of
+/// a top level AND condition are considered, since no other predicate alone narrows the result.
+pub fn scan_by_condition<I, F>(storage: &dyn ExternalStorage, ts: SnapshotId, condition: &Condition,
+                               get_prefix: F) -> GraphResult<Option<Vec<I>>>
+    where I: IndexedId,
+          F: Fn(PropId) -> Option<Vec<u8>> {
+    let candidates = match *condition {
+        Condition::And(ref c) => c.sub_conditions.iter().map(|c| c.as_ref()).collect(),
+        _ => vec![condition],
+    };
+    for c in candidates {
+        let ids = match *c {
+            Condition::Predicate(ref p) => {
+                match get_prefix(p.prop) {
+                    Some(prefix) => scan(storage, &prefix, &p.predicate, &p.value.as_ref(), ts)?,
+                    None => None,
+                }
+            }
+            Condition::In(ref c) => {
+                match get_prefix(c.prop) {
+                    Some(prefix) => scan_in(storage, &prefix, &c.values, ts)?,
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+        if ids.is_some() {
+            return Ok(ids);
+        }
+    }
+    Ok(None)
+}
+
+/// Union the results of equality scans of all `values`, or None if any of them cannot be indexed.
+fn scan_in<I: IndexedId>(storage: &dyn ExternalStorage, prefix: &[u8], values: &[Value], ts: SnapshotId) -> GraphResult<Option<Vec<I>>> {
+    let mut ret = Vec::new();
+    for value in values {
+        match scan(storage, prefix, &ComparisonOp::Equal, &value.as_ref(), ts)? {
+            Some(ids) => ret.extend(ids),
+            None => return Ok(None),
+        }
+    }
+    // equal values of different types share the same entries
+    let mut found = HashSet::new();
+    ret.retain(|id| found.insert(*id));
+    Ok(Some(ret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_value() {
+        let values = vec![Value::double(std::f64::NEG_INFINITY), Value::long(i64::min_value()), Value::double(-1.5),
+                          Value::int(-1), Value::double(-0.0), Value::short(0), Value::bool(true),
+                          Value::float(1.5), Value::long(2), Value::double(std::f64::INFINITY)];
+        for i in 1..values.len() {
+            let a = encode_value(&values[i - 1].as_ref()).unwrap();
+            let b = encode_value(&values[i].as_ref()).unwrap();
+            assert!(a <= b, "{:?} {:?}", values[i - 1], values[i]);
+        }
+        assert_eq!(encode_value(&Value::int(3).as_ref()), encode_value(&Value::double(3.0).as_ref()));
+        assert_eq!(encode_value(&Value::double(0.0).as_ref()), encode_value(&Value::double(-0.0).as_ref()));
+        assert!(encode_value(&Value::double(std::f64::NAN).as_ref()).is_none());
+        assert!(encode_value(&Value::bytes(&[1, 2]).as_ref()).is_none());
+
+        let strs = vec!["", "\0", "\0\0", "\0a", "a", "a\0", "ab", "b"];
+        for i in 1..strs.len() {
+            let a = encode_value(&Value::string(strs[i - 1]).as_ref()).unwrap();
+            let b = encode_value(&Value::string(strs[i]).as_ref()).unwrap();
+            assert!(a < b, "{:?} {:?}", strs[i - 1], strs[i]);
+            assert!(!b.starts_with(&a), "{:?} {:?}", strs[i - 1], strs[i]);
+        }
+    }
+
+    #[test]
+    fn test_indexed_id() {
+        for id in vec![0, 1, -1, i64::max_value(), i64::min_value()] {
+            let bytes = IndexedId::to_bytes(&id);
+            assert_eq!(bytes.len(), 8);
+            assert_eq!(<VertexId as IndexedId>::from_bytes(&bytes).unwrap(), id);
+        }
+        let id = EdgeId::new(-1, i64::max_value(), 3);
+        let bytes = id.to_bytes();
+        assert_eq!(bytes.len(), 24);
+        assert_eq!(EdgeId::from_bytes(&bytes).unwrap(), id);
+        assert!(EdgeId::from_bytes(&bytes[..16]).is_err());
+    }
+}
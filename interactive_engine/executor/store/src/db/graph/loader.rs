@@ -0,0 +1,282 @@
+//! Bulk load CSV files into a `GraphStore` without going through the realtime write path.
+//!
+//! Rows of a load are encoded by the current codec of the target type and written into a new table
+//! as an sst file, which is then ingested and onlined by `prepare_data_load` and `commit_data_load`.
+//! The new table replaces the old one at the si of the load, so a load always brings the full data
+//! of a vertex type or an edge kind.
+//!
+//! The first column of a vertex row is the vertex id. The first two columns of an edge row are the
+//! ids of the source and destination vertices, and edges are given inner ids by their order in the
+//! load. Columns are split by the separator without any quoting, an empty column means the property
+//! is absent, and elements of a list are separated by `;`. All rows are sorted in memory, so this is
+//! meant for datasets which fit in memory.
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+
+use crate::config::CSVLoadConfig;
+use crate::db::api::*;
+use crate::db::proto::model::TypeEnumPb;
+use crate::db::storage::rocksdb::write_sst_file;
+use crate::schema::prelude as schema;
+use super::bin::*;
+use super::codec::Encoder;
+use super::store::GraphStore;
+
+const LIST_SEPARATOR: char = ';';
+
+pub struct CsvLoader<'a> {
+    graph: &'a GraphStore,
+}
+
+impl<'a> CsvLoader<'a> {
+    pub fn new(graph: &'a GraphStore) -> Self {
+        CsvLoader {
+            graph,
+        }
+    }
+
+    /// Parse a `CSVLoadConfig` against the current schema of the graph.
+    pub fn parse_config(&self, text: &str) -> GraphResult<CSVLoadConfig> {
+        let graph_def = self.graph.get_graph_def()?;
+        let schema = to_schema(&graph_def);
+        CSVLoadConfig::from_str(schema, text).map_err(|e| {
+            let msg = e.to_string();
+            gen_graph_err!(GraphErrorCode::InvalidData, msg, parse_config)
+        })
+    }
+
+    /// Load `files` described by `config` at `si`, using `sst_path` as the temporary sst file.
+    /// Return the number of loaded rows.
+    pub fn load(&self, si: SnapshotId, config: &CSVLoadConfig, files: &[&str], sst_path: &str) -> GraphResult<usize> {
+        let res = self.do_load(si, config, files, sst_path);
+        res_unwrap!(res, load, si, files, sst_path)
+    }
+
+    fn do_load(&self, si: SnapshotId, config: &CSVLoadConfig, files: &[&str], sst_path: &str) -> GraphResult<usize> {
+        let graph_def = self.graph.get_graph_def()?;
+        let label = config.label as LabelId;
+        let type_def = match graph_def.label_to_types.get(&label) {
+            Some(t) => t,
+            None => {
+                let msg = format!("label#{} not found", label);
+                return Err(gen_graph_err!(GraphErrorCode::TypeNotFound, msg, do_load));
+            }
+        };
+        let columns = get_columns(config, type_def)?;
+        let table_id = graph_def.get_table_idx() + 1;
+        let (target, rows) = match (config.src_label, config.dst_label) {
+            (Some(src_label), Some(dst_label)) => {
+                let edge_kind = EdgeKind::new(label, src_label as LabelId, dst_label as LabelId);
+                let encoder = self.graph.get_edge_encoder(si, &edge_kind)?;
+                let rows = build_edge_rows(files, &config.separator, &columns, &encoder, table_id)?;
+                (DataLoadTarget::new(label, edge_kind.src_vertex_label_id, edge_kind.dst_vertex_label_id), rows)
+            }
+            _ => {
+                let encoder = self.graph.get_vertex_encoder(si, label)?;
+                let rows = build_vertex_rows(files, &config.separator, &columns, &encoder, table_id)?;
+                (DataLoadTarget::new(label, 0, 0), rows)
+            }
+        };
+
+        let schema_version = graph_def.get_version();
+        if !self.graph.prepare_data_load(si, schema_version + 1, &target, table_id)? {
+            let msg = format!("schema version {} is outdated", schema_version);
+            return Err(gen_graph_err!(GraphErrorCode::InvalidOperation, msg, do_load));
+        }
+        // rocksdb cannot write an empty sst file
+        if !rows.is_empty() {
+            write_sst_file(sst_path, rows.iter().map(|(k, v)| (k.as_slice(), v.as_slice())))?;
+            self.graph.ingest(sst_path)?;
+        }
+        if !self.graph.commit_data_load(si, schema_version + 2, &target, table_id)? {
+            let msg = format!("schema version {} is outdated", schema_version + 1);
+            return Err(gen_graph_err!(GraphErrorCode::InvalidOperation, msg, do_load));
+        }
+        let count = match target.src_label_id {
+            0 => rows.len(),
+            _ => rows.len() / 2,
+        };
+        info!("loaded {} rows of {:?} into table#{} at si#{}", count, target, table_id, si);
+        Ok(count)
+    }
+}
+
+/// Column index, property id and value type of every loaded property
+type Columns = Vec<(usize, PropId, ValueType)>;
+
+fn get_columns(config: &CSVLoadConfig, type_def: &TypeDef) -> GraphResult<Columns> {
+    let mut ret = Vec::with_capacity(config.properties.len());
+    for (idx, prop_id, data_type) in &config.properties {
+        let prop_id = *prop_id as PropId;
+        let prop_def = match type_def.get_prop_def(prop_id) {
+            Some(p) => p,
+            None => {
+                let msg = format!("property#{} not found in label {}", prop_id, type_def.get_label());
+                return Err(gen_graph_err!(GraphErrorCode::InvalidData, msg, get_columns));
+            }
+        };
+        if to_data_type(prop_def.r#type) != *data_type {
+            let msg = format!("property {} is {:?} but {:?} in config", prop_def.name, prop_def.r#type, data_type);
+            return Err(gen_graph_err!(GraphErrorCode::InvalidData, msg, get_columns));
+        }
+        ret.push((*idx, prop_id, prop_def.r#type));
+    }
+    Ok(ret)
+}
+
+fn build_vertex_rows(files: &[&str], separator: &str, columns: &Columns, encoder: &Encoder, table_id: i64) -> GraphResult<BTreeMap<Vec<u8>, Vec<u8>>> {
+    let mut rows = BTreeMap::new();
+    for_each_row(files, separator, |fields| {
+        let id = parse_id(fields, 0)?;
+        let data = encode_row(fields, columns, encoder)?;
+        rows.insert(vertex_key(table_id, id, 0).to_vec(), data);
+        Ok(())
+    })?;
+    Ok(rows)
+}
+
+/// Both directions of an edge are stored, just like the realtime write path does.
+fn build_edge_rows(files: &[&str], separator: &str, columns: &Columns, encoder: &Encoder, table_id: i64) -> GraphResult<BTreeMap<Vec<u8>, Vec<u8>>> {
+    let mut rows = BTreeMap::new();
+    let mut inner_id = 0;
+    for_each_row(files, separator, |fields| {
+        let id = EdgeId::new(parse_id(fields, 0)?, parse_id(fields, 1)?, inner_id);
+        inner_id += 1;
+        let data = encode_row(fields, columns, encoder)?;
+        rows.insert(edge_key(table_id, id, EdgeDirection::In, 0).to_vec(), data.clone());
+        rows.insert(edge_key(table_id, id, EdgeDirection::Out, 0).to_vec(), data);
+        Ok(())
+    })?;
+    Ok(rows)
+}
+
+fn for_each_row<F: FnMut(&[&str]) -> GraphResult<()>>(files: &[&str], separator: &str, mut f: F) -> GraphResult<()> {
+    for file in files {
+        let reader = match File::open(file) {
+            Ok(r) => BufReader::new(r),
+            Err(e) => {
+                let msg = format!("open {} failed because {:?}", file, e);
+                return Err(gen_graph_err!(GraphErrorCode::InvalidOperation, msg, for_each_row, file));
+            }
+        };
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = match line {
+                Ok(l) => l,
+                Err(e) => {
+                    let msg = format!("read {} failed because {:?}", file, e);
+                    return Err(gen_graph_err!(GraphErrorCode::InvalidData, msg, for_each_row, file));
+                }
+            };
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(separator).collect();
+            res_unwrap!(f(&fields), for_each_row, file, line_no)?;
+        }
+    }
+    Ok(())
+}
+
+fn parse_id(fields: &[&str], idx: usize) -> GraphResult<i64> {
+    match fields.get(idx).map(|s| s.trim().parse::<i64>()) {
+        Some(Ok(id)) => Ok(id),
+        _ => {
+            let msg = format!("invalid id in column {} of {:?}", idx, fields);
+            Err(gen_graph_err!(GraphErrorCode::InvalidData, msg, parse_id, idx))
+        }
+    }
+}
+
+fn encode_row(fields: &[&str], columns: &Columns, encoder: &Encoder) -> GraphResult<Vec<u8>> {
+    let mut properties = HashMap::new();
+    for (idx, prop_id, value_type) in columns {
+        match fields.get(*idx) {
+            Some(s) if !s.is_empty() => {
+                properties.insert(*prop_id, parse_value(s, *value_type)?);
+            }
+            _ => {}
+        }
+    }
+    let mut buf = Vec::new();
+    encoder.encode(&properties, &mut buf)?;
+    Ok(buf)
+}
+
+fn parse_value(s: &str, value_type: ValueType) -> GraphResult<Value> {
+    fn parse<T: std::str::FromStr>(s: &str) -> Option<T> {
+        s.trim().parse().ok()
+    }
+    fn parse_list<T: std::str::FromStr>(s: &str) -> Option<Vec<T>> {
+        s.split(LIST_SEPARATOR).map(parse).collect()
+    }
+    let ret = match value_type {
+        ValueType::Bool => parse(s).map(Value::bool),
+        ValueType::Char => s.as_bytes().first().map(|c| Value::char(*c)),
+        ValueType::Short => parse(s).map(Value::short),
+        ValueType::Int => parse(s).map(Value::int),
+        ValueType::Long => parse(s).map(Value::long),
+        ValueType::Float => parse(s).map(Value::float),
+        ValueType::Double => parse(s).map(Value::double),
+        ValueType::String => Some(Value::string(s)),
+        ValueType::Bytes => Some(Value::bytes(s.as_bytes())),
+        ValueType::IntList => parse_list(s).map(|v: Vec<i32>| Value::int_list(&v)),
+        ValueType::LongList => parse_list(s).map(|v: Vec<i64>| Value::long_list(&v)),
+        ValueType::FloatList => parse_list(s).map(|v: Vec<f32>| Value::float_list(&v)),
+        ValueType::DoubleList => parse_list(s).map(|v: Vec<f64>| Value::double_list(&v)),
+        ValueType::StringList => {
+            let v: Vec<String> = s.split(LIST_SEPARATOR).map(|x| x.to_owned()).collect();
+            Some(Value::string_list(&v))
+        }
+    };
+    ret.ok_or_else(|| {
+        let msg = format!("cannot parse {} as {:?}", s, value_type);
+        gen_graph_err!(GraphErrorCode::InvalidData, msg, parse_value)
+    })
+}
+
+fn to_data_type(value_type: ValueType) -> schema::DataType {
+    match value_type {
+        ValueType::Bool => schema::DataType::Bool,
+        ValueType::Char => schema::DataType::Char,
+        ValueType::Short => schema::DataType::Short,
+        ValueType::Int => schema::DataType::Int,
+        ValueType::Long => schema::DataType::Long,
+        ValueType::Float => schema::DataType::Float,
+        ValueType::Double => schema::DataType::Double,
+        ValueType::String => schema::DataType::String,
+        ValueType::Bytes => schema::DataType::Bytes,
+        ValueType::IntList => schema::DataType::ListInt,
+        ValueType::LongList => schema::DataType::ListLong,
+        ValueType::FloatList => schema::DataType::ListFloat,
+        ValueType::DoubleList => schema::DataType::ListDouble,
+        ValueType::StringList => schema::DataType::ListString,
+    }
+}
+
+/// `CSVLoadConfig` resolves names by the schema of the query layer, so build one from `graph_def`.
+fn to_schema(graph_def: &GraphDef) -> Arc<dyn schema::Schema> {
+    let mut builder = schema::SchemaBuilder::new().version(graph_def.get_version() as u32);
+    for type_def in graph_def.label_to_types.values() {
+        let data_type = match type_def.get_type_enum() {
+            TypeEnumPb::VERTEX => schema::Type::Vertex,
+            TypeEnumPb::EDGE => schema::Type::Edge,
+        };
+        let mut type_builder = schema::TypeDefBuilder::new()
+            .name(&type_def.get_label())
+            .label(type_def.get_label_id() as crate::schema::LabelId)
+            .data_type(data_type);
+        for prop_def in type_def.get_prop_defs() {
+            let prop_id = prop_def.id as schema::PropId;
+            let prop = schema::PropDefBuilder::new()
+                .prop_id(prop_id)
+                .name(&prop_def.name)
+                .data_type(to_data_type(prop_def.r#type))
+                .build();
+            type_builder = type_builder.add_prop(prop_id, prop);
+        }
+        builder = builder.add_type_def(type_builder.build());
+    }
+    builder.build()
+}
@@ -15,7 +15,7 @@ use crate::db::proto::model::EdgeKindPb;
 use std::collections::{HashMap, HashSet};
 use crate::db::proto::common::DataLoadTargetPb;
 
-const META_TABLE_ID: TableId = i64::min_value();
+pub const META_TABLE_ID: TableId = i64::min_value();
 
 pub struct Meta {
     store: Arc<dyn ExternalStorage>,
@@ -49,30 +49,7 @@ impl Meta {
                 i64::min_value() / 2 + 1,
             );
         }
-        let mut all: Vec<MetaItem> = Vec::new();
-        let store_ref = self.store.as_ref();
-        let create_vertex_items = res_unwrap!(get_items::<CreateVertexTypeItem>(store_ref), recover)?;
-        all.extend(create_vertex_items.into_iter().map(|i| MetaItem::CreateVertexType(i)));
-        let create_edge_items = res_unwrap!(get_items::<CreateEdgeTypeItem>(store_ref), recover)?;
-        all.extend(create_edge_items.into_iter().map(|i| MetaItem::CreateEdgeType(i)));
-        let add_edge_kind_items = res_unwrap!(get_items::<AddEdgeKindItem>(store_ref), recover)?;
-        all.extend(add_edge_kind_items.into_iter().map(|i| MetaItem::AddEdgeKind(i)));
-        let drop_vertex_items = res_unwrap!(get_items::<DropVertexTypeItem>(store_ref), recover)?;
-        all.extend(drop_vertex_items.into_iter().map(|i| MetaItem::DropVertexType(i)));
-        let drop_edge_items = res_unwrap!(get_items::<DropEdgeTypeItem>(store_ref), recover)?;
-        all.extend(drop_edge_items.into_iter().map(|i| MetaItem::DropEdgeType(i)));
-        let remove_edge_kind_items = res_unwrap!(get_items::<RemoveEdgeKindItem>(store_ref), recover)?;
-        all.extend(remove_edge_kind_items.into_iter().map(|i| MetaItem::RemoveEdgeKind(i)));
-        let prepare_data_load_items = res_unwrap!(get_items::<PrepareDataLoadItem>(store_ref), recover)?;
-        all.extend(prepare_data_load_items.into_iter().map(|i| MetaItem::PrepareDataLoad(i)));
-        let commit_data_load_items = res_unwrap!(get_items::<CommitDataLoadItem>(store_ref), recover)?;
-        all.extend(commit_data_load_items.into_iter().map(|i| MetaItem::CommitDataLoad(i)));
-        all.sort_by(|a, b| {
-            let s1 = a.get_schema_version();
-            let s2 = b.get_schema_version();
-            return s1.cmp(&s2);
-        });
-
+        let all = res_unwrap!(get_all_items(self.store.as_ref()), recover)?;
         let mut vertex_manager_builder = VertexTypeManagerBuilder::new();
         let mut edge_manager_builder = EdgeManagerBuilder::new();
         for item in all {
@@ -101,6 +78,12 @@ impl Meta {
                     graph_def.remove_type(&x.label_id);
                     graph_def.increase_version();
                 }
+                MetaItem::AlterVertexType(x) => {
+                    vertex_manager_builder.alter(x.si, x.label_id, &x.type_def)?;
+                    let mut graph_def = self.graph_def_lock.lock()?;
+                    graph_def.update_type(x.label_id, x.type_def.clone())?;
+                    graph_def.increase_version();
+                }
                 MetaItem::CreateEdgeType(x) => {
                     let label_id = x.type_def.get_label_id();
                     let mut graph_def = self.graph_def_lock.lock()?;
@@ -114,6 +97,12 @@ impl Meta {
                     graph_def.increase_version();
                     edge_manager_builder.create_edge_type(x.si, x.label_id, &x.type_def)?;
                 }
+                MetaItem::AlterEdgeType(x) => {
+                    edge_manager_builder.alter_edge_type(x.si, x.label_id, &x.type_def)?;
+                    let mut graph_def = self.graph_def_lock.lock()?;
+                    graph_def.update_type(x.label_id, x.type_def.clone())?;
+                    graph_def.increase_version();
+                }
                 MetaItem::AddEdgeKind(x) => {
                     edge_manager_builder.add_edge_kind(x.si, &x.edge_kind)?;
                     edge_manager_builder.add_edge_table(x.si, &x.edge_kind, Table::new(x.si, x.table_id))?;
@@ -158,6 +147,34 @@ impl Meta {
                         })?;
                     }
                 }
+                MetaItem::CreateIndex(x) => {
+                    if edge_manager_builder.contains_edge(x.label_id) {
+                        edge_manager_builder.get_edge_info(x.si, x.label_id).and_then(|info| {
+                            info.create_index(x.si, x.prop_id)
+                        })?;
+                    } else {
+                        vertex_manager_builder.get_info(x.si, x.label_id).and_then(|info| {
+                            info.create_index(x.si, x.prop_id)
+                        })?;
+                    }
+                    let mut graph_def = self.graph_def_lock.lock()?;
+                    graph_def.add_index(x.label_id, x.prop_id)?;
+                    graph_def.increase_version();
+                }
+                MetaItem::DropIndex(x) => {
+                    if edge_manager_builder.contains_edge(x.label_id) {
+                        edge_manager_builder.get_edge_info(x.si, x.label_id).and_then(|info| {
+                            info.drop_index(x.si, x.prop_id)
+                        })?;
+                    } else {
+                        vertex_manager_builder.get_info(x.si, x.label_id).and_then(|info| {
+                            info.drop_index(x.si, x.prop_id)
+                        })?;
+                    }
+                    let mut graph_def = self.graph_def_lock.lock()?;
+                    graph_def.remove_index(x.label_id, x.prop_id);
+                    graph_def.increase_version();
+                }
             }
         }
         Ok((vertex_manager_builder.build(), edge_manager_builder.build()))
@@ -174,6 +191,26 @@ impl Meta {
         Ok(())
     }
 
+    /// Delete all meta items after `si` from the store and return the start si of every table which
+    /// is online at or before `si`. The store must be recovered again to see the result.
+    pub fn rollback(&self, si: SnapshotId) -> GraphResult<HashMap<TableId, SnapshotId>> {
+        let mut tables = HashMap::new();
+        for item in res_unwrap!(get_all_items(self.store.as_ref()), rollback, si)? {
+            if item.get_si() > si {
+                let (key, _) = item.to_kv()?;
+                res_unwrap!(self.store.delete(&key), rollback, si)?;
+                continue;
+            }
+            match item {
+                MetaItem::CreateVertexType(x) => { tables.insert(x.table_id, x.si); }
+                MetaItem::AddEdgeKind(x) => { tables.insert(x.table_id, x.si); }
+                MetaItem::CommitDataLoad(x) => { tables.insert(x.table_id, x.si); }
+                _ => {}
+            }
+        }
+        Ok(tables)
+    }
+
     #[allow(dead_code)]
     pub fn gc(&self, _si: SnapshotId) -> GraphResult<()> {
         unimplemented!()
@@ -244,6 +281,18 @@ impl Meta {
         Ok(())
     }
 
+    pub fn alter_vertex_type(&self, si: SnapshotId, schema_version: i64, label_id: LabelId, type_def: &TypeDef) -> GraphResult<()> {
+        self.check_version(schema_version)?;
+        let item = AlterVertexTypeItem::new(si, schema_version, label_id, type_def.clone());
+        self.write_item(item)?;
+        {
+            let mut graph_def = self.graph_def_lock.lock()?;
+            graph_def.update_type(label_id, type_def.clone())?;
+            graph_def.increase_version();
+        }
+        Ok(())
+    }
+
     pub fn create_edge_type(&self, si: SnapshotId, schema_version: i64, label_id: LabelId, type_def: &TypeDef) -> GraphResult<()> {
         self.check_version(schema_version)?;
         let item = CreateEdgeTypeItem::new(si, schema_version, label_id, type_def.clone());
@@ -262,6 +311,18 @@ impl Meta {
         Ok(())
     }
 
+    pub fn alter_edge_type(&self, si: SnapshotId, schema_version: i64, label_id: LabelId, type_def: &TypeDef) -> GraphResult<()> {
+        self.check_version(schema_version)?;
+        let item = AlterEdgeTypeItem::new(si, schema_version, label_id, type_def.clone());
+        self.write_item(item)?;
+        {
+            let mut graph_def = self.graph_def_lock.lock()?;
+            graph_def.update_type(label_id, type_def.clone())?;
+            graph_def.increase_version();
+        }
+        Ok(())
+    }
+
     pub fn add_edge_kind(&self, si: SnapshotId, schema_version: i64, edge_kind: &EdgeKind, table_id: i64) -> GraphResult<Table> {
         self.check_version(schema_version)?;
         let item = AddEdgeKindItem::new(si, schema_version, table_id, edge_kind.clone());
@@ -300,6 +361,30 @@ impl Meta {
         Ok(())
     }
 
+    pub fn create_index(&self, si: SnapshotId, schema_version: i64, label_id: LabelId, prop_id: PropId) -> GraphResult<()> {
+        self.check_version(schema_version)?;
+        let item = CreateIndexItem::new(si, schema_version, label_id, prop_id);
+        self.write_item(item)?;
+        {
+            let mut graph_def = self.graph_def_lock.lock()?;
+            graph_def.add_index(label_id, prop_id)?;
+            graph_def.increase_version();
+        }
+        Ok(())
+    }
+
+    pub fn drop_index(&self, si: SnapshotId, schema_version: i64, label_id: LabelId, prop_id: PropId) -> GraphResult<()> {
+        self.check_version(schema_version)?;
+        let item = DropIndexItem::new(si, schema_version, label_id, prop_id);
+        self.write_item(item)?;
+        {
+            let mut graph_def = self.graph_def_lock.lock()?;
+            graph_def.remove_index(label_id, prop_id);
+            graph_def.increase_version();
+        }
+        Ok(())
+    }
+
     pub fn _gen_next_table_id(&self) -> GraphResult<TableId> {
         let key = _gen_key("NextTableId");
         let table_id = match res_unwrap!(self.store.get(&key), get_next_table_id)? {
@@ -327,6 +412,24 @@ impl Meta {
         Ok(table_id)
     }
 
+    /// Return the latest snapshot gc has run at, versions invisible at it may be gone.
+    pub fn get_gc_watermark(&self) -> GraphResult<SnapshotId> {
+        let key = _gen_key("GcWatermark");
+        match res_unwrap!(self.store.get(&key), get_gc_watermark)? {
+            Some(v) => {
+                let si = res_unwrap!(transform::bytes_to_i64(v.as_bytes()), get_gc_watermark)?;
+                Ok(si.to_be())
+            }
+            None => Ok(0),
+        }
+    }
+
+    pub fn set_gc_watermark(&self, si: SnapshotId) -> GraphResult<()> {
+        let key = _gen_key("GcWatermark");
+        let v = transform::i64_to_vec(si.to_be());
+        res_unwrap!(self.store.put(&key, &v), set_gc_watermark, si)
+    }
+
     fn write_item<I: ItemCommon>(&self, item: I) -> GraphResult<()> {
         let (k, v) = item.to_kv()?;
         res_unwrap!(self.store.put(&k, &v), write_item)
@@ -357,6 +460,10 @@ enum MetaItem {
     RemoveEdgeKind(RemoveEdgeKindItem),
     PrepareDataLoad(PrepareDataLoadItem),
     CommitDataLoad(CommitDataLoadItem),
+    CreateIndex(CreateIndexItem),
+    DropIndex(DropIndexItem),
+    AlterVertexType(AlterVertexTypeItem),
+    AlterEdgeType(AlterEdgeTypeItem),
 }
 
 impl MetaItem {
@@ -370,6 +477,44 @@ impl MetaItem {
             MetaItem::RemoveEdgeKind(ref item) => item.schema_version,
             MetaItem::PrepareDataLoad(ref item) => item.schema_version,
             MetaItem::CommitDataLoad(ref item) => item.schema_version,
+            MetaItem::CreateIndex(ref item) => item.schema_version,
+            MetaItem::DropIndex(ref item) => item.schema_version,
+            MetaItem::AlterVertexType(ref item) => item.schema_version,
+            MetaItem::AlterEdgeType(ref item) => item.schema_version,
+        }
+    }
+
+    fn get_si(&self) -> SnapshotId {
+        match *self {
+            MetaItem::CreateVertexType(ref item) => item.si,
+            MetaItem::CreateEdgeType(ref item) => item.si,
+            MetaItem::AddEdgeKind(ref item) => item.si,
+            MetaItem::DropVertexType(ref item) => item.si,
+            MetaItem::DropEdgeType(ref item) => item.si,
+            MetaItem::RemoveEdgeKind(ref item) => item.si,
+            MetaItem::PrepareDataLoad(ref item) => item.si,
+            MetaItem::CommitDataLoad(ref item) => item.si,
+            MetaItem::CreateIndex(ref item) => item.si,
+            MetaItem::DropIndex(ref item) => item.si,
+            MetaItem::AlterVertexType(ref item) => item.si,
+            MetaItem::AlterEdgeType(ref item) => item.si,
+        }
+    }
+
+    fn to_kv(&self) -> GraphResult<(Vec<u8>, Vec<u8>)> {
+        match *self {
+            MetaItem::CreateVertexType(ref item) => item.to_kv(),
+            MetaItem::CreateEdgeType(ref item) => item.to_kv(),
+            MetaItem::AddEdgeKind(ref item) => item.to_kv(),
+            MetaItem::DropVertexType(ref item) => item.to_kv(),
+            MetaItem::DropEdgeType(ref item) => item.to_kv(),
+            MetaItem::RemoveEdgeKind(ref item) => item.to_kv(),
+            MetaItem::PrepareDataLoad(ref item) => item.to_kv(),
+            MetaItem::CommitDataLoad(ref item) => item.to_kv(),
+            MetaItem::CreateIndex(ref item) => item.to_kv(),
+            MetaItem::DropIndex(ref item) => item.to_kv(),
+            MetaItem::AlterVertexType(ref item) => item.to_kv(),
+            MetaItem::AlterEdgeType(ref item) => item.to_kv(),
         }
     }
 }
@@ -732,6 +877,198 @@ impl ItemCommon for RemoveEdgeKindItem {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+struct CreateIndexItem {
+    si: SnapshotId,
+    schema_version: i64,
+    label_id: LabelId,
+    prop_id: PropId,
+}
+
+impl CreateIndexItem {
+    fn new(si: SnapshotId, schema_version: i64, label_id: LabelId, prop_id: PropId) -> Self {
+        CreateIndexItem {
+            si,
+            schema_version,
+            label_id,
+            prop_id,
+        }
+    }
+}
+
+impl ItemCommon for CreateIndexItem {
+    fn from_kv(k: &[u8], v: &[u8]) -> GraphResult<Self> {
+        let items = res_unwrap!(common_parse_key(k, Self::prefix(), 4), from_kv)?;
+        let label_id = res_unwrap!(parse_str(items[1]), from_kv)?;
+        let prop_id = res_unwrap!(parse_str(items[2]), from_kv)?;
+        let schema_version = res_unwrap!(parse_str(items[3]), from_kv)?;
+        let si = res_unwrap!(transform::bytes_to_i64(v), from_kv)?.to_be();
+        let ret = Self::new(si, schema_version, label_id, prop_id);
+        Ok(ret)
+    }
+
+    fn prefix() -> &'static str {
+        "CreateIndex"
+    }
+
+    fn to_kv(&self) -> GraphResult<(Vec<u8>, Vec<u8>)> {
+        let key = format!("{}#{}#{}#{}", Self::prefix(), self.label_id, self.prop_id, self.schema_version);
+        Ok((meta_key(&key), transform::i64_to_vec(self.si.to_be())))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct DropIndexItem {
+    si: SnapshotId,
+    schema_version: i64,
+    label_id: LabelId,
+    prop_id: PropId,
+}
+
+impl DropIndexItem {
+    fn new(si: SnapshotId, schema_version: i64, label_id: LabelId, prop_id: PropId) -> Self {
+        DropIndexItem {
+            si,
+            schema_version,
+            label_id,
+            prop_id,
+        }
+    }
+}
+
+impl ItemCommon for DropIndexItem {
+    fn from_kv(k: &[u8], v: &[u8]) -> GraphResult<Self> {
+        let items = res_unwrap!(common_parse_key(k, Self::prefix(), 4), from_kv)?;
+        let label_id = res_unwrap!(parse_str(items[1]), from_kv)?;
+        let prop_id = res_unwrap!(parse_str(items[2]), from_kv)?;
+        let schema_version = res_unwrap!(parse_str(items[3]), from_kv)?;
+        let si = res_unwrap!(transform::bytes_to_i64(v), from_kv)?.to_be();
+        let ret = Self::new(si, schema_version, label_id, prop_id);
+        Ok(ret)
+    }
+
+    fn prefix() -> &'static str {
+        "DropIndex"
+    }
+
+    fn to_kv(&self) -> GraphResult<(Vec<u8>, Vec<u8>)> {
+        let key = format!("{}#{}#{}#{}", Self::prefix(), self.label_id, self.prop_id, self.schema_version);
+        Ok((meta_key(&key), transform::i64_to_vec(self.si.to_be())))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AlterVertexTypeItem {
+    si: SnapshotId,
+    schema_version: i64,
+    label_id: LabelId,
+    type_def: TypeDef,
+}
+
+impl AlterVertexTypeItem {
+    fn new(si: SnapshotId, schema_version: i64, label_id: LabelId, type_def: TypeDef) -> Self {
+        AlterVertexTypeItem {
+            si,
+            schema_version,
+            label_id,
+            type_def,
+        }
+    }
+}
+
+impl ItemCommon for AlterVertexTypeItem {
+    fn from_kv(k: &[u8], v: &[u8]) -> GraphResult<Self> {
+        let items = res_unwrap!(common_parse_key(k, Self::prefix(), 4), from_kv)?;
+        let label_id = res_unwrap!(parse_str(items[1]), from_kv)?;
+        let si = res_unwrap!(parse_str(items[2]), from_kv)?;
+        let schema_version = res_unwrap!(parse_str(items[3]), from_kv)?;
+        let type_def = res_unwrap!(TypeDef::from_bytes(v), from_kv)?;
+        Ok(Self::new(si, schema_version, label_id, type_def))
+    }
+
+    fn prefix() -> &'static str {
+        "AlterVertexType"
+    }
+
+    fn to_kv(&self) -> GraphResult<(Vec<u8>, Vec<u8>)> {
+        let key = format!("{}#{}#{}#{}", Self::prefix(), self.label_id, self.si, self.schema_version);
+        Ok((meta_key(&key), self.type_def.to_bytes()?))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AlterEdgeTypeItem {
+    si: SnapshotId,
+    schema_version: i64,
+    label_id: LabelId,
+    type_def: TypeDef,
+}
+
+impl AlterEdgeTypeItem {
+    fn new(si: SnapshotId, schema_version: i64, label_id: LabelId, type_def: TypeDef) -> Self {
+        AlterEdgeTypeItem {
+            si,
+            schema_version,
+            label_id,
+            type_def,
+        }
+    }
+}
+
+impl ItemCommon for AlterEdgeTypeItem {
+    fn from_kv(k: &[u8], v: &[u8]) -> GraphResult<Self> {
+        let items = res_unwrap!(common_parse_key(k, Self::prefix(), 4), from_kv)?;
+        let label_id = res_unwrap!(parse_str(items[1]), from_kv)?;
+        let si = res_unwrap!(parse_str(items[2]), from_kv)?;
+        let schema_version = res_unwrap!(parse_str(items[3]), from_kv)?;
+        let type_def = res_unwrap!(TypeDef::from_bytes(v), from_kv)?;
+        Ok(Self::new(si, schema_version, label_id, type_def))
+    }
+
+    fn prefix() -> &'static str {
+        "AlterEdgeType"
+    }
+
+    fn to_kv(&self) -> GraphResult<(Vec<u8>, Vec<u8>)> {
+        let key = format!("{}#{}#{}#{}", Self::prefix(), self.label_id, self.si, self.schema_version);
+        Ok((meta_key(&key), self.type_def.to_bytes()?))
+    }
+}
+
+fn get_all_items(store: &dyn ExternalStorage) -> GraphResult<Vec<MetaItem>> {
+    let mut all: Vec<MetaItem> = Vec::new();
+    let create_vertex_items = res_unwrap!(get_items::<CreateVertexTypeItem>(store), get_all_items)?;
+    all.extend(create_vertex_items.into_iter().map(|i| MetaItem::CreateVertexType(i)));
+    let create_edge_items = res_unwrap!(get_items::<CreateEdgeTypeItem>(store), get_all_items)?;
+    all.extend(create_edge_items.into_iter().map(|i| MetaItem::CreateEdgeType(i)));
+    let add_edge_kind_items = res_unwrap!(get_items::<AddEdgeKindItem>(store), get_all_items)?;
+    all.extend(add_edge_kind_items.into_iter().map(|i| MetaItem::AddEdgeKind(i)));
+    let drop_vertex_items = res_unwrap!(get_items::<DropVertexTypeItem>(store), get_all_items)?;
+    all.extend(drop_vertex_items.into_iter().map(|i| MetaItem::DropVertexType(i)));
+    let drop_edge_items = res_unwrap!(get_items::<DropEdgeTypeItem>(store), get_all_items)?;
+    all.extend(drop_edge_items.into_iter().map(|i| MetaItem::DropEdgeType(i)));
+    let remove_edge_kind_items = res_unwrap!(get_items::<RemoveEdgeKindItem>(store), get_all_items)?;
+    all.extend(remove_edge_kind_items.into_iter().map(|i| MetaItem::RemoveEdgeKind(i)));
+    let prepare_data_load_items = res_unwrap!(get_items::<PrepareDataLoadItem>(store), get_all_items)?;
+    all.extend(prepare_data_load_items.into_iter().map(|i| MetaItem::PrepareDataLoad(i)));
+    let commit_data_load_items = res_unwrap!(get_items::<CommitDataLoadItem>(store), get_all_items)?;
+    all.extend(commit_data_load_items.into_iter().map(|i| MetaItem::CommitDataLoad(i)));
+    let create_index_items = res_unwrap!(get_items::<CreateIndexItem>(store), get_all_items)?;
+    all.extend(create_index_items.into_iter().map(|i| MetaItem::CreateIndex(i)));
+    let drop_index_items = res_unwrap!(get_items::<DropIndexItem>(store), get_all_items)?;
+    all.extend(drop_index_items.into_iter().map(|i| MetaItem::DropIndex(i)));
+    let alter_vertex_items = res_unwrap!(get_items::<AlterVertexTypeItem>(store), get_all_items)?;
+    all.extend(alter_vertex_items.into_iter().map(|i| MetaItem::AlterVertexType(i)));
+    let alter_edge_items = res_unwrap!(get_items::<AlterEdgeTypeItem>(store), get_all_items)?;
+    all.extend(alter_edge_items.into_iter().map(|i| MetaItem::AlterEdgeType(i)));
+    all.sort_by(|a, b| {
+        let s1 = a.get_schema_version();
+        let s2 = b.get_schema_version();
+        return s1.cmp(&s2);
+    });
+    Ok(all)
+}
+
 fn get_items<I: ItemCommon>(store: &dyn ExternalStorage) -> GraphResult<Vec<I>> {
     let mut ret = Vec::new();
     let mut prefix = Vec::new();
@@ -787,6 +1124,26 @@ mod tests {
         let (k, v) = item.to_kv().unwrap();
         let item2 = RemoveEdgeKindItem::from_kv(&k, &v).unwrap();
         assert_eq!(item, item2);
+
+        let item = CreateIndexItem::new(10, 5, 1, 3);
+        let (k, v) = item.to_kv().unwrap();
+        let item2 = CreateIndexItem::from_kv(&k, &v).unwrap();
+        assert_eq!(item, item2);
+
+        let item = DropIndexItem::new(20, 6, 1, 3);
+        let (k, v) = item.to_kv().unwrap();
+        let item2 = DropIndexItem::from_kv(&k, &v).unwrap();
+        assert_eq!(item, item2);
+
+        let item = AlterVertexTypeItem::new(30, 7, 1, type_def.clone());
+        let (k, v) = item.to_kv().unwrap();
+        let item2 = AlterVertexTypeItem::from_kv(&k, &v).unwrap();
+        assert_eq!(item, item2);
+
+        let item = AlterEdgeTypeItem::new(40, 8, 2, type_def.clone());
+        let (k, v) = item.to_kv().unwrap();
+        let item2 = AlterEdgeTypeItem::from_kv(&k, &v).unwrap();
+        assert_eq!(item, item2);
     }
 
     #[test]
@@ -8,7 +8,11 @@ mod meta;
 pub mod store;
 pub mod vertex;
 pub mod edge;
-mod query;
+pub mod query;
+pub mod adjacency;
+pub mod index;
+pub mod loader;
+pub mod ttl;
 #[cfg(test)]
 mod tests;
 #[cfg(test)]
@@ -23,6 +23,20 @@ pub fn check_condition(decoder: &Decoder, data: &[u8], condition: &Condition) ->
         Condition::Not(ref c) => {
             !check_condition(decoder, data, c.sub_condition.as_ref())
         }
+        Condition::In(ref c) => {
+            match decoder.decode_property(data, c.prop) {
+                Some(v) => c.values.iter().any(|x| v == x.as_ref()),
+                None => false,
+            }
+        }
+        Condition::NotIn(ref c) => {
+            match decoder.decode_property(data, c.prop) {
+                Some(v) => c.values.iter().all(|x| v != x.as_ref()),
+                None => false,
+            }
+        }
+        Condition::Exists(ref c) => decoder.has_property(data, c.prop),
+        Condition::NotExists(ref c) => !decoder.has_property(data, c.prop),
     }
 }
 
@@ -35,7 +49,39 @@ fn single_predicate_check(condition: &PredicateCondition, decoder: &Decoder, dat
             ComparisonOp::GreaterThan => v > condition.value.as_ref(),
             ComparisonOp::GreaterEqual => v >= condition.value.as_ref(),
             ComparisonOp::NotEqual => v != condition.value.as_ref(),
+            ComparisonOp::StartsWith => check_str(&v, &condition.value, |s, x| s.starts_with(x)),
+            ComparisonOp::EndsWith => check_str(&v, &condition.value, |s, x| s.ends_with(x)),
+            ComparisonOp::Contains => check_str(&v, &condition.value, |s, x| s.contains(x)),
+            ComparisonOp::ListContains => list_contains(&v, &condition.value.as_ref()),
         };
     }
     false
-}
\ No newline at end of file
+}
+fn check_str<F: Fn(&str, &str) -> bool>(v: &ValueRef, value: &Value, f: F) -> bool {
+    match (v.get_str(), value.get_str()) {
+        (Ok(s), Ok(x)) => f(s, x),
+        _ => false,
+    }
+}
+
+/// elements are compared the same way as `ValueRef`, so an int list may contain a long or a double
+fn list_contains(list: &ValueRef, value: &ValueRef) -> bool {
+    match *list.get_type() {
+        ValueType::IntList => list.get_int_list().map_or(false, |arr| arr.iter().any(|e| integer_eq(e as i64, value))),
+        ValueType::LongList => list.get_long_list().map_or(false, |arr| arr.iter().any(|e| integer_eq(e, value))),
+        ValueType::FloatList => list.get_float_list().map_or(false, |arr| arr.iter().any(|e| value.to_double() == Some(e as f64))),
+        ValueType::DoubleList => list.get_double_list().map_or(false, |arr| arr.iter().any(|e| value.to_double() == Some(e))),
+        ValueType::StringList => match (list.get_str_list(), value.get_str()) {
+            (Ok(arr), Ok(x)) => arr.iter().any(|e| e == x),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn integer_eq(e: i64, value: &ValueRef) -> bool {
+    match *value.get_type() {
+        ValueType::Float | ValueType::Double => value.to_double() == Some(e as f64),
+        _ => value.to_long() == Some(e),
+    }
+}
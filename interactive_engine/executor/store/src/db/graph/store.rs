@@ -1,11 +1,13 @@
 #![allow(dead_code)]
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicIsize, Ordering};
+use std::sync::atomic::{AtomicI64, AtomicIsize, Ordering};
 use crate::db::api::*;
 use crate::db::storage::{ExternalStorage, ExternalStorageBackup};
 use crate::db::storage::rocksdb::{RocksDB};
+use crate::db::storage::memory::MemoryStorage;
 use crate::db::util::lock::GraphMutexLock;
+use crate::db::util::time::current_time_millis;
 use super::vertex::*;
 use super::edge::*;
 use super::types::*;
@@ -13,23 +15,48 @@ use super::codec::*;
 use super::property::*;
 use super::meta::*;
 use super::bin::*;
+use super::adjacency;
+use super::index;
+use super::ttl::TtlCompactionFilter;
 use protobuf::Message;
 use crate::db::api::GraphErrorCode::{InvalidData, TypeNotFound};
 use crate::db::graph::table_manager::Table;
 
+/// max number of keys deleted in one write by gc and rollback
+const GC_BATCH_SIZE: usize = 4096;
+
 pub struct GraphStore {
     config: GraphConfig,
     meta: Meta,
     vertex_manager: VertexTypeManager,
     edge_manager: EdgeTypeManager,
     storage: Arc<dyn ExternalStorage>,
+    // tells the storage which tables hold records with ttl
+    ttl_filter: Arc<TtlCompactionFilter>,
     // ensure all modification to graph is in ascending order of snapshot_id
     si_guard: AtomicIsize,
     lock: GraphMutexLock<()>,
+    // serialize writers of the adjacency index of the same vertex
+    adjacency_locks: adjacency::AdjacencyLocks,
+    // the latest si gc has run at, shared with backup engines so every backup records it
+    gc_watermark: Arc<AtomicI64>,
 }
 
 pub struct GraphBackupEngine {
     engine: Box<dyn ExternalStorageBackup>,
+    // config of the graph store, used to open the restored store
+    config: GraphConfig,
+    // backup id to the snapshot the backup represents, which is persisted in `snapshots_path`
+    snapshots: BTreeMap<BackupId, BackupSnapshot>,
+    snapshots_path: String,
+    gc_watermark: Arc<AtomicI64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct BackupSnapshot {
+    si: SnapshotId,
+    // versions invisible at snapshots older than it may have been collected when the backup is created
+    gc_si: SnapshotId,
 }
 
 impl GraphStorage for GraphStore {
@@ -82,7 +109,7 @@ impl GraphStorage for GraphStore {
             let mut info_iter = self.vertex_manager.get_all(si);
             let mut iters = Vec::new();
             while let Some(info) = info_iter.next() {
-                let res = SingleLabelVertexIter::create(si, info, self.storage.as_ref(), condition.clone());
+                let res = self.create_vertex_iter(si, info, condition.clone());
                 match res_unwrap!(res, query_vertices, si, label)? {
                     Some(iter) => iters.push(iter),
                     None => {},
@@ -108,6 +135,26 @@ impl GraphStorage for GraphStore {
         res_unwrap!(res, query_edges, si, label)
     }
 
+    fn get_out_degree(&self, si: SnapshotId, src_id: VertexId, edge_kind: &EdgeKind) -> GraphResult<usize> {
+        let res = self.get_degree(si, src_id, edge_kind, EdgeDirection::Out);
+        res_unwrap!(res, get_out_degree, si, src_id, edge_kind)
+    }
+
+    fn get_in_degree(&self, si: SnapshotId, dst_id: VertexId, edge_kind: &EdgeKind) -> GraphResult<usize> {
+        let res = self.get_degree(si, dst_id, edge_kind, EdgeDirection::In);
+        res_unwrap!(res, get_in_degree, si, dst_id, edge_kind)
+    }
+
+    fn get_kth_out_edge(&self, si: SnapshotId, src_id: VertexId, edge_kind: &EdgeKind, k: usize) -> GraphResult<Option<EdgeWrapper<Self::E>>> {
+        let res = self.get_kth_edge(si, src_id, edge_kind, EdgeDirection::Out, k);
+        res_unwrap!(res, get_kth_out_edge, si, src_id, edge_kind, k)
+    }
+
+    fn get_kth_in_edge(&self, si: SnapshotId, dst_id: VertexId, edge_kind: &EdgeKind, k: usize) -> GraphResult<Option<EdgeWrapper<Self::E>>> {
+        let res = self.get_kth_edge(si, dst_id, edge_kind, EdgeDirection::In, k);
+        res_unwrap!(res, get_kth_in_edge, si, dst_id, edge_kind, k)
+    }
+
     fn create_vertex_type(&self, si: i64, schema_version: i64, label_id: LabelId, type_def: &TypeDef, table_id: i64) -> GraphResult<bool> {
         let _guard = res_unwrap!(self.lock.lock(), create_vertex_type)?;
         self.check_si_guard(si)?;
@@ -123,6 +170,7 @@ impl GraphStorage for GraphStore {
             let codec = Codec::from(type_def);
             self.vertex_manager.create_type(si, label_id, codec, table)
         }).map(|_| self.update_si_guard(si))?;
+        self.register_ttl_tables()?;
         Ok(true)
     }
 
@@ -143,6 +191,51 @@ impl GraphStorage for GraphStore {
         Ok(true)
     }
 
+    fn alter_vertex_type(&self, si: i64, schema_version: i64, label_id: LabelId, type_def: &TypeDef) -> GraphResult<bool> {
+        let _guard = res_unwrap!(self.lock.lock(), alter_vertex_type, si, label_id)?;
+        self.check_si_guard(si)?;
+        if let Err(_) = self.meta.check_version(schema_version) {
+            return Ok(false);
+        }
+        let info = res_unwrap!(self.vertex_manager.get_type_info(si, label_id), alter_vertex_type, si, label_id)?;
+        let type_def = self.gen_altered_type_def(label_id, type_def, &info.get_all_inner_ids())?;
+        for index in info.get_indexes(si) {
+            let prop_id = index.get_prop_id();
+            if type_def.get_prop_def(prop_id).is_none() {
+                let msg = format!("property#{} of vertex#{} is indexed and cannot be dropped", prop_id, label_id);
+                let err = gen_graph_err!(GraphErrorCode::InvalidOperation, msg, alter_vertex_type, si, label_id);
+                return Err(err);
+            }
+        }
+        self.meta.alter_vertex_type(si, schema_version, label_id, &type_def).and_then(|_| {
+            let codec = Codec::from(&type_def);
+            self.vertex_manager.alter_type(si, label_id, codec)
+        }).map(|_| self.update_si_guard(si))?;
+        Ok(true)
+    }
+
+    fn alter_edge_type(&self, si: i64, schema_version: i64, label_id: LabelId, type_def: &TypeDef) -> GraphResult<bool> {
+        let _guard = res_unwrap!(self.lock.lock(), alter_edge_type, si, label_id)?;
+        self.check_si_guard(si)?;
+        if let Err(_) = self.meta.check_version(schema_version) {
+            return Ok(false);
+        }
+        let info = res_unwrap!(self.edge_manager.get_edge_info(si, label_id), alter_edge_type, si, label_id)?;
+        let type_def = self.gen_altered_type_def(label_id, type_def, &info.get_all_inner_ids())?;
+        for index in info.get_indexes(si) {
+            let prop_id = index.get_prop_id();
+            if type_def.get_prop_def(prop_id).is_none() {
+                let msg = format!("property#{} of edge#{} is indexed and cannot be dropped", prop_id, label_id);
+                let err = gen_graph_err!(GraphErrorCode::InvalidOperation, msg, alter_edge_type, si, label_id);
+                return Err(err);
+            }
+        }
+        self.meta.alter_edge_type(si, schema_version, label_id, &type_def).and_then(|_| {
+            self.edge_manager.alter_edge_type(si, label_id, &type_def)
+        }).map(|_| self.update_si_guard(si))?;
+        Ok(true)
+    }
+
     fn add_edge_kind(&self, si: i64, schema_version: i64, edge_kind: &EdgeKind, table_id: i64) -> GraphResult<bool> {
         let _guard = res_unwrap!(self.lock.lock(), add_edge_kind)?;
         self.check_si_guard(si)?;
@@ -159,6 +252,7 @@ impl GraphStorage for GraphStore {
             let info = self.edge_manager.get_edge_kind(si, edge_kind)?;
             info.online_table(table)
         }).map(|_| self.update_si_guard(si))?;
+        self.register_ttl_tables()?;
         Ok(true)
     }
 
@@ -198,6 +292,84 @@ impl GraphStorage for GraphStore {
         Ok(true)
     }
 
+    fn create_vertex_index(&self, si: i64, schema_version: i64, label_id: LabelId, prop_id: PropId) -> GraphResult<bool> {
+        let _guard = res_unwrap!(self.lock.lock(), create_vertex_index, si, label_id, prop_id)?;
+        self.check_si_guard(si)?;
+        if let Err(_) = self.meta.check_version(schema_version) {
+            return Ok(false);
+        }
+        let info = res_unwrap!(self.vertex_manager.get_type_info(si, label_id), create_vertex_index, si, label_id, prop_id)?;
+        if info.get_index(si, prop_id).is_some() {
+            let msg = format!("index on property#{} of vertex#{} already exists", prop_id, label_id);
+            let err = gen_graph_err!(GraphErrorCode::InvalidOperation, msg, create_vertex_index, si, label_id, prop_id);
+            return Err(err);
+        }
+        self.meta.create_index(si, schema_version, label_id, prop_id).and_then(|_| {
+            let index = info.create_index(si, prop_id)?;
+            self.build_vertex_index(si, label_id, index.as_ref())
+        }).map(|_| self.update_si_guard(si))?;
+        Ok(true)
+    }
+
+    fn drop_vertex_index(&self, si: i64, schema_version: i64, label_id: LabelId, prop_id: PropId) -> GraphResult<bool> {
+        let _guard = res_unwrap!(self.lock.lock(), drop_vertex_index, si, label_id, prop_id)?;
+        self.check_si_guard(si)?;
+        if let Err(_) = self.meta.check_version(schema_version) {
+            return Ok(false);
+        }
+        let info = res_unwrap!(self.vertex_manager.get_type_info(si, label_id), drop_vertex_index, si, label_id, prop_id)?;
+        if info.get_index(si, prop_id).is_none() {
+            let msg = format!("index on property#{} of vertex#{} not found", prop_id, label_id);
+            let err = gen_graph_err!(GraphErrorCode::MetaNotFound, msg, drop_vertex_index, si, label_id, prop_id);
+            return Err(err);
+        }
+        self.meta.drop_index(si, schema_version, label_id, prop_id).and_then(|_| {
+            info.drop_index(si, prop_id)
+        }).map(|_| self.update_si_guard(si))?;
+        Ok(true)
+    }
+
+    fn create_edge_index(&self, si: i64, schema_version: i64, label_id: LabelId, prop_id: PropId) -> GraphResult<bool> {
+        let _guard = res_unwrap!(self.lock.lock(), create_edge_index, si, label_id, prop_id)?;
+        self.check_si_guard(si)?;
+        if let Err(_) = self.meta.check_version(schema_version) {
+            return Ok(false);
+        }
+        let info = res_unwrap!(self.edge_manager.get_edge_info(si, label_id), create_edge_index, si, label_id, prop_id)?;
+        if info.get_index(si, prop_id).is_some() {
+            let msg = format!("index on property#{} of edge#{} already exists", prop_id, label_id);
+            let err = gen_graph_err!(GraphErrorCode::InvalidOperation, msg, create_edge_index, si, label_id, prop_id);
+            return Err(err);
+        }
+        self.meta.create_index(si, schema_version, label_id, prop_id).and_then(|_| {
+            let index = info.create_index(si, prop_id)?;
+            let mut kind_iter = self.edge_manager.get_edge(si, label_id)?.into_iter();
+            while let Some(kind_info) = kind_iter.next() {
+                self.build_edge_index(si, kind_info, index.as_ref())?;
+            }
+            Ok(())
+        }).map(|_| self.update_si_guard(si))?;
+        Ok(true)
+    }
+
+    fn drop_edge_index(&self, si: i64, schema_version: i64, label_id: LabelId, prop_id: PropId) -> GraphResult<bool> {
+        let _guard = res_unwrap!(self.lock.lock(), drop_edge_index, si, label_id, prop_id)?;
+        self.check_si_guard(si)?;
+        if let Err(_) = self.meta.check_version(schema_version) {
+            return Ok(false);
+        }
+        let info = res_unwrap!(self.edge_manager.get_edge_info(si, label_id), drop_edge_index, si, label_id, prop_id)?;
+        if info.get_index(si, prop_id).is_none() {
+            let msg = format!("index on property#{} of edge#{} not found", prop_id, label_id);
+            let err = gen_graph_err!(GraphErrorCode::MetaNotFound, msg, drop_edge_index, si, label_id, prop_id);
+            return Err(err);
+        }
+        self.meta.drop_index(si, schema_version, label_id, prop_id).and_then(|_| {
+            info.drop_index(si, prop_id)
+        }).map(|_| self.update_si_guard(si))?;
+        Ok(true)
+    }
+
     fn insert_overwrite_vertex(&self, si: SnapshotId, id: VertexId, label: LabelId, properties: &dyn PropertyMap) -> GraphResult<()> {
         self.check_si_guard(si)?;
         let res = self.vertex_manager.get_type(si, label).and_then(|info| {
@@ -211,9 +383,9 @@ impl GraphStorage for GraphStore {
         let info = res_unwrap!(self.vertex_manager.get_type(si, label), si, id, label)?;
         match res_unwrap!(self.get_vertex_data(si, id, &info), insert_update_vertex, si, id, label)? {
             Some(data) => {
-                let version = get_codec_version(data);
+                let version = get_codec_version(&data);
                 let decoder = info.get_decoder(si, version)?;
-                let mut old = decoder.decode_all(data);
+                let mut old = decode_unexpired(&decoder, &data);
                 merge_updates(&mut old, properties);
                 let res = self.do_insert_vertex_data(si, info, id, &old).map(|_| self.update_si_guard(si));
                 res_unwrap!(res, insert_update_vertex, si, id, label)
@@ -231,7 +403,8 @@ impl GraphStorage for GraphStore {
         if let Some(table) = info.get_table(si) {
             let ts = si - table.start_si;
             let key = vertex_key(table.id, id, ts);
-            let res = self.storage.put(&key, &[]);
+            let res = self.update_vertex_indexes(si, &info, &table, id, None)
+                .and_then(|_| self.storage.put(&key, &[]));
             return res_unwrap!(res, delete_vertex, si, id, label);
         }
         self.update_si_guard(si);
@@ -262,9 +435,9 @@ impl GraphStorage for GraphStore {
         let data_res = self.get_edge_data(si, id, &info, direction);
         match res_unwrap!(data_res, insert_update_edge, si, id, edge_kind)? {
             Some(data) => {
-                let version = get_codec_version(data);
+                let version = get_codec_version(&data);
                 let decoder = info.get_decoder(si, version)?;
-                let mut old = decoder.decode_all(data);
+                let mut old = decode_unexpired(&decoder, &data);
                 merge_updates(&mut old, properties);
                 let res = self.do_insert_edge_data(si, id, info, direction, &old).map(|_| self.update_si_guard(si));
                 res_unwrap!(res, insert_update_edge, si, id, edge_kind)
@@ -286,6 +459,17 @@ impl GraphStorage for GraphStore {
         };
         if let Some(table) = info.get_table(si) {
             let ts = si - table.start_si;
+            let _guard = res_unwrap!(self.adjacency_locks.lock(table.id, &id, direction), delete_edge, si, id, edge_kind)?;
+            // edge types with ttl have no adjacency index
+            let indexed = res_unwrap!(info.get_encoder(si), delete_edge, si, id, edge_kind)?.get_ttl().is_none();
+            if indexed && res_unwrap!(self.get_edge_data(si, id, &info, direction), delete_edge, si, id, edge_kind)?.is_some() {
+                let res = adjacency::remove_edge(self.storage.as_ref(), table.id, id, direction, ts);
+                res_unwrap!(res, delete_edge, si, id, edge_kind)?;
+            }
+            if direction == EdgeDirection::Out {
+                let res = self.update_edge_indexes(si, &info, &table, id, None);
+                res_unwrap!(res, delete_edge, si, id, edge_kind)?;
+            }
             let key = edge_key(table.id, id, direction, ts);
             res_unwrap!(self.storage.put(&key, &[]), delete_edge, si, id, edge_kind)?;
         }
@@ -293,8 +477,10 @@ impl GraphStorage for GraphStore {
         Ok(())
     }
 
-    fn gc(&self, _si: i64) {
-        unimplemented!()
+    fn gc(&self, si: i64) {
+        if let Err(e) = self.do_gc(si) {
+            error!("gc at si#{} failed: {:?}", si, e);
+        }
     }
 
     fn get_graph_def_blob(&self) -> GraphResult<Vec<u8>> {
@@ -310,6 +496,7 @@ impl GraphStorage for GraphStore {
             return Ok(false);
         }
         self.meta.prepare_data_load(si, schema_version, target, table_id)?;
+        self.register_ttl_tables()?;
         Ok(true)
     }
 
@@ -324,10 +511,19 @@ impl GraphStorage for GraphStore {
             let edge_kind = EdgeKind::new(target.label_id, target.src_label_id, target.dst_label_id);
             let info = self.edge_manager.get_edge_kind(si, &edge_kind)?;
             info.online_table(Table::new(si, table_id))?;
+            // ingested data has no index entries
+            for index in info.get_indexes(si) {
+                let info = self.edge_manager.get_edge_kind(si, &edge_kind)?;
+                self.build_edge_index(si, info, index.as_ref())?;
+            }
             info!("online edge. target {:?}, tableId {}, si {}", target, table_id, si);
         } else {
             let info = self.vertex_manager.get_type(si, target.label_id)?;
             info.online_table(Table::new(si, table_id))?;
+            // ingested data has no index entries
+            for index in info.get_indexes(si) {
+                self.build_vertex_index(si, target.label_id, index.as_ref())?;
+            }
             info!("online vertex. labelId {}, tableId {}, si {}", target.label_id, table_id, si);
         }
         Ok(true)
@@ -335,9 +531,8 @@ impl GraphStorage for GraphStore {
 
     fn open_backup_engine(&self, backup_path: &str) -> GraphResult<Box<dyn GraphBackup>> {
         let engine = res_unwrap!(self.storage.open_backup_engine(backup_path), open_backup_engine, backup_path)?;
-        let ret = GraphBackupEngine {
-            engine
-        };
+        let res = GraphBackupEngine::new(engine, self.config.clone(), backup_path, self.gc_watermark.clone());
+        let ret = res_unwrap!(res, open_backup_engine, backup_path)?;
         Ok(Box::from(ret))
     }
 }
@@ -362,18 +557,131 @@ impl GraphBackup for GraphBackupEngine {
     fn get_backup_list(&self) -> Vec<BackupId> {
         self.engine.get_backup_list()
     }
+
+    fn create_new_backup_at(&mut self, si: SnapshotId) -> GraphResult<BackupId> {
+        if let Some((_, latest)) = self.snapshots.iter().next_back() {
+            if latest.si > si {
+                let msg = format!("si#{} is older than si#{} of the latest backup", si, latest.si);
+                return Err(gen_graph_err!(GraphErrorCode::InvalidOperation, msg, create_new_backup_at, si));
+            }
+        }
+        let backup_id = res_unwrap!(self.engine.create_new_backup(), create_new_backup_at, si)?;
+        // gc raises the watermark before it deletes anything, so the one read after the backup is
+        // never lower than that of the data in the backup
+        let gc_si = self.gc_watermark.load(Ordering::SeqCst);
+        self.snapshots.insert(backup_id, BackupSnapshot { si, gc_si });
+        res_unwrap!(self.save_snapshots(), create_new_backup_at, si)?;
+        info!("created backup#{} of si#{}", backup_id, si);
+        Ok(backup_id)
+    }
+
+    fn get_backup_info_list(&self) -> Vec<BackupInfo> {
+        let mut ret = self.engine.get_backup_info_list();
+        for info in ret.iter_mut() {
+            info.snapshot_id = self.snapshots.get(&info.backup_id).map(|s| s.si);
+        }
+        ret
+    }
+
+    fn purge_backups(&mut self, retention: BackupRetention) -> GraphResult<Vec<BackupId>> {
+        let infos = self.get_backup_info_list();
+        let num_to_keep = match retention {
+            BackupRetention::KeepLast(n) => n,
+            // snapshots never go back as backup ids grow, so the backups to keep are the latest ones
+            BackupRetention::KeepNewerThan(si) => infos.iter().rev()
+                .take_while(|info| info.snapshot_id.map_or(false, |s| s > si))
+                .count(),
+        };
+        res_unwrap!(self.engine.purge_old_backups(num_to_keep), purge_backups, retention)?;
+        let alive: HashSet<BackupId> = self.engine.get_backup_list().into_iter().collect();
+        let purged: Vec<BackupId> = infos.into_iter().map(|info| info.backup_id)
+            .filter(|id| !alive.contains(id)).collect();
+        self.snapshots.retain(|id, _| alive.contains(id));
+        res_unwrap!(self.save_snapshots(), purge_backups, retention)?;
+        info!("purged backups {:?} by {:?}", purged, retention);
+        Ok(purged)
+    }
+
+    fn restore_at_snapshot(&mut self, restore_path: &str, si: SnapshotId) -> GraphResult<BackupId> {
+        let backup = self.snapshots.iter()
+            .filter(|(_, s)| s.si >= si && s.gc_si <= si)
+            .min_by_key(|(id, s)| (s.si, **id))
+            .map(|(id, s)| (*id, s.si));
+        let (backup_id, backup_si) = match backup {
+            Some(b) => b,
+            None => {
+                let msg = if self.snapshots.values().any(|s| s.si >= si) {
+                    format!("versions visible at si#{} are garbage collected in all backups after it", si)
+                } else {
+                    format!("no backup covers si#{}", si)
+                };
+                return Err(gen_graph_err!(GraphErrorCode::InvalidOperation, msg, restore_at_snapshot, restore_path, si));
+            }
+        };
+        res_unwrap!(self.engine.restore_from_backup(restore_path, backup_id), restore_at_snapshot, restore_path, si)?;
+        // the backup may contain writes after its snapshot too, so always roll back, which scans the
+        // whole store as no delta between backups is recorded
+        let res = GraphStore::open(&self.config, restore_path).and_then(|store| store.rollback(si));
+        res_unwrap!(res, restore_at_snapshot, restore_path, si)?;
+        info!("restored si#{} at {} from backup#{} of si#{}", si, restore_path, backup_id, backup_si);
+        Ok(backup_id)
+    }
+}
+
+impl GraphBackupEngine {
+    fn new(engine: Box<dyn ExternalStorageBackup>, config: GraphConfig, backup_path: &str, gc_watermark: Arc<AtomicI64>) -> GraphResult<Self> {
+        let snapshots_path = format!("{}/snapshot_ids.json", backup_path);
+        let mut snapshots: BTreeMap<BackupId, BackupSnapshot> = match std::fs::read_to_string(&snapshots_path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| {
+                let msg = format!("parse {} failed, because {:?}", snapshots_path, e);
+                gen_graph_err!(GraphErrorCode::InvalidData, msg, new)
+            })?,
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => {
+                let msg = format!("read {} failed, because {:?}", snapshots_path, e);
+                return Err(gen_graph_err!(GraphErrorCode::ExternalStorageError, msg, new));
+            }
+        };
+        let alive: HashSet<BackupId> = engine.get_backup_list().into_iter().collect();
+        snapshots.retain(|id, _| alive.contains(id));
+        Ok(GraphBackupEngine {
+            engine,
+            config,
+            snapshots,
+            snapshots_path,
+            gc_watermark,
+        })
+    }
+
+    /// Write to a temporary file first, so a crash never leaves a broken file.
+    fn save_snapshots(&self) -> GraphResult<()> {
+        let tmp_path = format!("{}.tmp", self.snapshots_path);
+        let res = serde_json::to_string(&self.snapshots).map_err(|e| format!("{:?}", e))
+            .and_then(|text| std::fs::write(&tmp_path, text).map_err(|e| format!("{:?}", e)))
+            .and_then(|_| std::fs::rename(&tmp_path, &self.snapshots_path).map_err(|e| format!("{:?}", e)));
+        res.map_err(|e| {
+            let msg = format!("save {} failed, because {}", self.snapshots_path, e);
+            gen_graph_err!(GraphErrorCode::ExternalStorageError, msg, save_snapshots)
+        })
+    }
 }
 
 impl GraphStore {
     pub fn open(config: &GraphConfig, path: &str) -> GraphResult<Self> {
         match config.get_storage_engine() {
             "rocksdb" => {
-                let res = RocksDB::open(config.get_storage_options(), path).and_then(|db| {
+                let ttl_filter = Arc::new(TtlCompactionFilter::new());
+                let res = RocksDB::open_with_filter(config.get_storage_options(), path, Some(ttl_filter.clone())).and_then(|db| {
                     let storage = Arc::new(db);
-                    Self::init(config, storage)
+                    Self::init(config, storage, ttl_filter)
                 });
                 res_unwrap!(res, open, config, path)
             }
+            "memory" => {
+                // nothing is persisted, so `path` is ignored and the store is always empty
+                let storage = Arc::new(MemoryStorage::new());
+                Self::init(config, storage, Arc::new(TtlCompactionFilter::new()))
+            }
             "alibtree" => {
                 let msg = format!("alibtree is not supported yet");
                 let err = gen_graph_err!(GraphErrorCode::NotSupported, msg, open, config, path);
@@ -387,29 +695,34 @@ impl GraphStore {
         }
     }
 
-    fn init(config: &GraphConfig, storage: Arc<dyn ExternalStorage>) -> GraphResult<Self> {
+    fn init(config: &GraphConfig, storage: Arc<dyn ExternalStorage>, ttl_filter: Arc<TtlCompactionFilter>) -> GraphResult<Self> {
         let meta = Meta::new(storage.clone());
         let (vertex_manager, edge_manager) = res_unwrap!(meta.recover(), init)?;
+        let gc_watermark = res_unwrap!(meta.get_gc_watermark(), init)?;
         let ret = GraphStore {
             config: config.clone(),
             meta,
             vertex_manager,
             edge_manager,
             storage,
+            ttl_filter,
             si_guard: AtomicIsize::new(0),
             lock: GraphMutexLock::new(()),
+            adjacency_locks: adjacency::AdjacencyLocks::new(),
+            gc_watermark: Arc::new(AtomicI64::new(gc_watermark)),
         };
+        ret.register_ttl_tables()?;
         Ok(ret)
     }
 
-    fn get_vertex_data(&self, si: SnapshotId, id: VertexId, info: &VertexTypeInfoRef) -> GraphResult<Option<&[u8]>> {
+    /// The data is copied out, as it's only valid until the storage iterator is dropped.
+    fn get_vertex_data(&self, si: SnapshotId, id: VertexId, info: &VertexTypeInfoRef) -> GraphResult<Option<Vec<u8>>> {
         if let Some(table) = info.get_table(si) {
             let key = vertex_key(table.id, id, si - table.start_si);
             let mut iter = self.storage.scan_from(&key)?;
             if let Some((k, v)) = iter.next() {
                 if k.len() == key.len() && k[0..16] == key[0..16] && v.len() >= 4 {
-                    let ret = unsafe { std::mem::transmute(v) };
-                    return Ok(Some(ret));
+                    return Ok(Some(v.to_vec()));
                 }
             }
         }
@@ -419,9 +732,12 @@ impl GraphStore {
     fn do_get_vertex(&self, si: SnapshotId, id: VertexId, info: &VertexTypeInfoRef) -> GraphResult<Option<VertexWrapper<VertexImpl>>> {
         let data = self.get_vertex_data(si, id, info)?;
         if let Some(v) = data {
-            let version = get_codec_version(v);
+            let version = get_codec_version(&v);
             let decoder = res_unwrap!(info.get_decoder(si, version), do_get_vertex)?;
-            let ret = VertexImpl::new(id, info.get_label(), PropData::Owned(v.to_vec()), decoder);
+            if decoder.is_expired(&v, current_time_millis()) {
+                return Ok(None);
+            }
+            let ret = VertexImpl::new(id, info.get_label(), PropData::Owned(v), decoder);
             return Ok(Some(VertexWrapper::new(ret)));
         }
         Ok(None)
@@ -429,11 +745,11 @@ impl GraphStore {
 
     fn do_query_vertices<'a>(&'a self, si: SnapshotId, label: LabelId, condition: Option<Arc<Condition>>) -> GraphResult<Box<dyn VertexResultIter<V=VertexImpl> + 'a>> {
         let res = self.vertex_manager.get_type(si, label)
-            .and_then(|type_info| SingleLabelVertexIter::create(si, type_info, self.storage.as_ref(), condition));
+            .and_then(|type_info| self.create_vertex_iter(si, type_info, condition));
         match res_unwrap!(res, do_query_vertices, si, label) {
             Ok(iter_option) => {
                 match iter_option {
-                    Some(iter) => Ok(Box::new(iter)),
+                    Some(iter) => Ok(iter),
                     None => Ok(Box::new(EmptyResultIter)),
                 }
             },
@@ -447,6 +763,23 @@ impl GraphStore {
         }
     }
 
+    /// Scan an index instead of the whole table if some index can answer a part of `condition`.
+    fn create_vertex_iter<'a>(&'a self, si: SnapshotId, info: VertexTypeInfoRef, condition: Option<Arc<Condition>>) -> GraphResult<Option<Box<dyn VertexResultIter<V=VertexImpl> + 'a>>> {
+        let storage = self.storage.as_ref();
+        if let (Some(c), Some(table)) = (condition.as_ref(), info.get_table(si)) {
+            let ts = si - table.start_si;
+            let get_prefix = |prop_id| {
+                info.get_index(si, prop_id).map(|index| property_index_prefix(table.id, prop_id, index.get_start_si()))
+            };
+            if let Some(ids) = index::scan_by_condition(storage, ts, c, get_prefix)? {
+                let iter = IndexedVertexIter::new(si, &table, info, c.clone(), ids, storage);
+                return Ok(Some(Box::new(iter)));
+            }
+        }
+        let ret = SingleLabelVertexIter::create(si, info, storage, condition)?;
+        Ok(ret.map(|iter| Box::new(iter) as Box<dyn VertexResultIter<V=VertexImpl> + 'a>))
+    }
+
     fn do_query_edges<'a>(&'a self, si: SnapshotId, id: VertexId, label: Option<LabelId>, direction: EdgeDirection, condition: Option<Arc<Condition>>) -> GraphResult<Box<dyn EdgeResultIter<E=EdgeImpl> + 'a>> {
         let storage = self.storage.as_ref();
         if let Some(label) = label {
@@ -470,15 +803,14 @@ impl GraphStore {
         }
     }
 
-    fn get_edge_data(&self, si: SnapshotId, id: EdgeId, info: &EdgeKindInfoRef, direction: EdgeDirection) -> GraphResult<Option<&[u8]>> {
+    fn get_edge_data(&self, si: SnapshotId, id: EdgeId, info: &EdgeKindInfoRef, direction: EdgeDirection) -> GraphResult<Option<Vec<u8>>> {
         if let Some(table) = info.get_table(si) {
             let ts = si - table.start_si;
             let key = edge_key(table.id, id, direction, ts);
             let mut iter = self.storage.scan_from(&key)?;
             if let Some((k, v)) = iter.next() {
                 if k.len() == key.len() && k[0..32] == key[0..32] && v.len() >= 4 {
-                    let ret = unsafe { std::mem::transmute(v) };
-                    return Ok(Some(ret));
+                    return Ok(Some(v.to_vec()));
                 }
             }
         }
@@ -488,15 +820,59 @@ impl GraphStore {
     fn do_get_edge(&self, si: SnapshotId, id: EdgeId, info: EdgeKindInfoRef, direction: EdgeDirection) -> GraphResult<Option<EdgeWrapper<EdgeImpl>>> {
         let data = self.get_edge_data(si, id, &info, direction)?;
         if let Some(v) = data {
-            let version = get_codec_version(v);
+            let version = get_codec_version(&v);
             let decoder = res_unwrap!(info.get_decoder(si, version))?;
+            if decoder.is_expired(&v, current_time_millis()) {
+                return Ok(None);
+            }
             let edge_kind = info.get_type().clone();
-            let ret = EdgeImpl::new(id, edge_kind, PropData::from(v.to_vec()), decoder);
+            let ret = EdgeImpl::new(id, edge_kind, PropData::from(v), decoder);
             return Ok(Some(EdgeWrapper::new(ret)));
         }
         Ok(None)
     }
 
+    fn get_degree(&self, si: SnapshotId, vertex_id: VertexId, edge_kind: &EdgeKind, direction: EdgeDirection) -> GraphResult<usize> {
+        let info = self.edge_manager.get_edge_kind(si, edge_kind)?;
+        if info.get_encoder(si)?.get_ttl().is_some() {
+            return self.scan_unexpired_edge_ids(si, vertex_id, info, direction).map(|ids| ids.len());
+        }
+        if let Some(table) = info.get_table(si) {
+            return adjacency::get_degree(self.storage.as_ref(), table.id, vertex_id, direction, si - table.start_si);
+        }
+        Ok(0)
+    }
+
+    fn get_kth_edge(&self, si: SnapshotId, vertex_id: VertexId, edge_kind: &EdgeKind, direction: EdgeDirection, k: usize) -> GraphResult<Option<EdgeWrapper<EdgeImpl>>> {
+        let info = self.edge_manager.get_edge_kind(si, edge_kind)?;
+        if info.get_encoder(si)?.get_ttl().is_some() {
+            if let Some(id) = self.scan_unexpired_edge_ids(si, vertex_id, info, direction)?.get(k) {
+                let info = self.edge_manager.get_edge_kind(si, edge_kind)?;
+                return self.do_get_edge(si, *id, info, direction);
+            }
+            return Ok(None);
+        }
+        if let Some(table) = info.get_table(si) {
+            let ts = si - table.start_si;
+            if let Some(id) = adjacency::get_kth_edge_id(self.storage.as_ref(), table.id, vertex_id, direction, ts, k)? {
+                return self.do_get_edge(si, id, info, direction);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Edge types with ttl have no adjacency index, so their edges are counted by scanning.
+    fn scan_unexpired_edge_ids(&self, si: SnapshotId, vertex_id: VertexId, info: EdgeKindInfoRef, direction: EdgeDirection) -> GraphResult<Vec<EdgeId>> {
+        let mut ret = Vec::new();
+        if let Some(mut iter) = SingleTypeEdgeIter::create(si, vertex_id, direction, info, self.storage.as_ref(), None)? {
+            while let Some(e) = iter.next() {
+                ret.push(*e.get_id());
+            }
+            iter.ok()?;
+        }
+        Ok(ret)
+    }
+
     fn do_insert_vertex_data(&self, si: SnapshotId, info: VertexTypeInfoRef, id: VertexId, properties: &dyn PropertyMap) -> GraphResult<()> {
         if let Some(table) = info.get_table(si) {
             let encoder = res_unwrap!(info.get_encoder(si), do_insert_vertex_data)?;
@@ -504,6 +880,7 @@ impl GraphStore {
             return encoder.encode(properties, &mut buf).and_then(|_| {
                 let ts = si - table.start_si;
                 let key = vertex_key(table.id, id, ts);
+                self.update_vertex_indexes(si, &info, &table, id, Some(&buf))?;
                 self.storage.put(&key, &buf)
             });
         }
@@ -512,12 +889,62 @@ impl GraphStore {
         Err(err)
     }
 
+    /// Update entries of all indexes for vertex `id` whose data will be replaced by `new` at `si`.
+    /// `new` is None if the vertex is deleted.
+    fn update_vertex_indexes(&self, si: SnapshotId, info: &VertexTypeInfoRef, table: &Table, id: VertexId, new: Option<&[u8]>) -> GraphResult<()> {
+        let indexes = info.get_indexes(si);
+        if indexes.is_empty() {
+            return Ok(());
+        }
+        let old = match self.get_vertex_data(si, id, info)? {
+            Some(data) => Some((info.get_decoder(si, get_codec_version(&data))?, data)),
+            None => None,
+        };
+        let new = match new {
+            Some(data) => Some((info.get_decoder(si, get_codec_version(data))?, data)),
+            None => None,
+        };
+        let ts = si - table.start_si;
+        for index in indexes {
+            let prop_id = index.get_prop_id();
+            let prefix = property_index_prefix(table.id, prop_id, index.get_start_si());
+            let old_value = old.as_ref().and_then(|(decoder, data)| decoder.decode_property(data, prop_id));
+            let new_value = new.as_ref().and_then(|(decoder, data)| decoder.decode_property(data, prop_id));
+            index::update_entry(self.storage.as_ref(), &prefix, id, old_value, new_value, ts)?;
+        }
+        Ok(())
+    }
+
+    /// Put entries of all vertices visible at `si` into `index`.
+    fn build_vertex_index(&self, si: SnapshotId, label: LabelId, index: &IndexInfo) -> GraphResult<()> {
+        let info = self.vertex_manager.get_type(si, label)?;
+        if let Some(table) = info.get_table(si) {
+            let prop_id = index.get_prop_id();
+            let prefix = property_index_prefix(table.id, prop_id, index.get_start_si());
+            let ts = si - table.start_si;
+            if let Some(mut iter) = SingleLabelVertexIter::create(si, info, self.storage.as_ref(), None)? {
+                while let Some(v) = iter.next() {
+                    index::update_entry(self.storage.as_ref(), &prefix, v.get_id(), None, v.get_property(prop_id), ts)?;
+                }
+                iter.ok()?;
+            }
+        }
+        Ok(())
+    }
+
     fn do_insert_edge_data(&self, si: SnapshotId, edge_id: EdgeId, info: EdgeKindInfoRef, direction: EdgeDirection, properties: &dyn PropertyMap) -> GraphResult<()> {
         if let Some(table) = info.get_table(si) {
             let encoder = res_unwrap!(info.get_encoder(si), do_insert_edge_data)?;
             let mut buf = Vec::new();
             return encoder.encode(properties, &mut buf).and_then(|_| {
                 let ts = si - table.start_si;
+                let _guard = self.adjacency_locks.lock(table.id, &edge_id, direction)?;
+                if encoder.get_ttl().is_none() && self.get_edge_data(si, edge_id, &info, direction)?.is_none() {
+                    adjacency::add_edge(self.storage.as_ref(), table.id, edge_id, direction, ts)?;
+                }
+                if direction == EdgeDirection::Out {
+                    self.update_edge_indexes(si, &info, &table, edge_id, Some(&buf))?;
+                }
                 let key = edge_key(table.id, edge_id, direction, ts);
                 self.storage.put(&key, &buf)
             });
@@ -527,6 +954,143 @@ impl GraphStore {
         Err(err)
     }
 
+    /// Update entries of all indexes for forward edge `id` whose data will be replaced by `new` at `si`.
+    /// `new` is None if the edge is deleted.
+    fn update_edge_indexes(&self, si: SnapshotId, info: &EdgeKindInfoRef, table: &Table, id: EdgeId, new: Option<&[u8]>) -> GraphResult<()> {
+        let indexes = info.get_indexes(si);
+        if indexes.is_empty() {
+            return Ok(());
+        }
+        let old = match self.get_edge_data(si, id, info, EdgeDirection::Out)? {
+            Some(data) => Some((info.get_decoder(si, get_codec_version(&data))?, data)),
+            None => None,
+        };
+        let new = match new {
+            Some(data) => Some((info.get_decoder(si, get_codec_version(data))?, data)),
+            None => None,
+        };
+        let ts = si - table.start_si;
+        for index in indexes {
+            let prop_id = index.get_prop_id();
+            let prefix = edge_property_index_prefix(table.id, prop_id, index.get_start_si());
+            let old_value = old.as_ref().and_then(|(decoder, data)| decoder.decode_property(data, prop_id));
+            let new_value = new.as_ref().and_then(|(decoder, data)| decoder.decode_property(data, prop_id));
+            index::update_entry(self.storage.as_ref(), &prefix, id, old_value, new_value, ts)?;
+        }
+        Ok(())
+    }
+
+    /// Put entries of all forward edges of the edge kind visible at `si` into `index`.
+    fn build_edge_index(&self, si: SnapshotId, info: EdgeKindInfoRef, index: &IndexInfo) -> GraphResult<()> {
+        if let Some(table) = info.get_table(si) {
+            let prop_id = index.get_prop_id();
+            let prefix = edge_property_index_prefix(table.id, prop_id, index.get_start_si());
+            let ts = si - table.start_si;
+            if let Some(mut iter) = SingleTypeEdgeIter::create(si, 0, EdgeDirection::Both, info, self.storage.as_ref(), None)? {
+                while let Some(e) = iter.next() {
+                    index::update_entry(self.storage.as_ref(), &prefix, *e.get_id(), None, e.get_property(prop_id), ts)?;
+                }
+                iter.ok()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove all meta and data written after `si`, including tables onlined after it. The store
+    /// doesn't see the result until it's reopened.
+    fn rollback(&self, si: SnapshotId) -> GraphResult<()> {
+        let _guard = res_unwrap!(self.lock.lock(), rollback, si)?;
+        let tables = res_unwrap!(self.meta.rollback(si), rollback, si)?;
+        let mut dropped_tables = HashSet::new();
+        let mut garbage = Vec::with_capacity(GC_BATCH_SIZE);
+        {
+            let mut iter = res_unwrap!(self.storage.scan_from(&[]), rollback, si)?;
+            while let Some((k, _)) = iter.next() {
+                if let Some(table_id) = parse_table_id(k) {
+                    match tables.get(&table_id) {
+                        Some(start_si) => {
+                            if parse_key_ts(k) > si - start_si {
+                                garbage.push(k.to_vec());
+                            }
+                        }
+                        None => {
+                            dropped_tables.insert(table_id);
+                        }
+                    }
+                }
+                if garbage.len() >= GC_BATCH_SIZE {
+                    res_unwrap!(self.storage.delete_batch(&garbage), rollback, si)?;
+                    garbage.clear();
+                }
+            }
+        }
+        if !garbage.is_empty() {
+            res_unwrap!(self.storage.delete_batch(&garbage), rollback, si)?;
+        }
+        for table_id in dropped_tables {
+            for (start, end) in vec![vertex_table_range(table_id), property_index_table_range(table_id),
+                                     edge_table_range(table_id), adjacency_table_range(table_id),
+                                     edge_property_index_table_range(table_id)] {
+                res_unwrap!(self.storage.delete_range(&start, &end), rollback, si, table_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that `type_def` only adds properties to or drops properties from the current definition of
+    /// `label`, and return it with the next codec version. `used_inner_ids` are inner ids which may appear
+    /// in stored data of the type, which cannot be reused by added properties, or old values of a dropped
+    /// property would be seen as values of the added one.
+    fn gen_altered_type_def(&self, label: LabelId, type_def: &TypeDef, used_inner_ids: &HashSet<PropId>) -> GraphResult<TypeDef> {
+        let graph_def = res_unwrap!(self.meta.get_graph_def().lock(), gen_altered_type_def, label)?;
+        let current = match graph_def.label_to_types.get(&label) {
+            Some(current) => current,
+            None => {
+                let msg = format!("type#{} not found in graph def", label);
+                let err = gen_graph_err!(GraphErrorCode::TypeNotFound, msg, gen_altered_type_def, label);
+                return Err(err);
+            }
+        };
+        for prop_def in type_def.get_prop_defs() {
+            let valid = match current.get_prop_def(prop_def.id) {
+                Some(p) => p.inner_id == prop_def.inner_id && p.r#type == prop_def.r#type,
+                None => !used_inner_ids.contains(&prop_def.inner_id),
+            };
+            if !valid {
+                let msg = format!("property#{} of type#{} can only be added or dropped, {:?} is invalid", prop_def.id, label, prop_def);
+                let err = gen_graph_err!(GraphErrorCode::InvalidOperation, msg, gen_altered_type_def, label);
+                return Err(err);
+            }
+        }
+        if type_def.get_ttl() != current.get_ttl() {
+            let msg = format!("ttl of type#{} cannot be changed from {:?} to {:?}", label, current.get_ttl(), type_def.get_ttl());
+            let err = gen_graph_err!(GraphErrorCode::InvalidOperation, msg, gen_altered_type_def, label);
+            return Err(err);
+        }
+        let mut ret = type_def.clone();
+        ret.set_version(current.get_version() + 1);
+        Ok(ret)
+    }
+
+    /// Tell the compaction filter the ttl of current tables of all types with ttl. Tables of a type
+    /// are registered once they are onlined or prepared for a data load, and stay registered after
+    /// they're dropped, which does no harm.
+    fn register_ttl_tables(&self) -> GraphResult<()> {
+        let graph_def = res_unwrap!(self.meta.get_graph_def().lock(), register_ttl_tables)?;
+        let get_ttl = |label: &LabelId| graph_def.label_to_types.get(label).and_then(|t| t.get_ttl());
+        for (label, table_id) in graph_def.get_vertex_table_ids() {
+            if let Some(ttl) = get_ttl(label) {
+                self.ttl_filter.set_ttl(*table_id, ttl);
+            }
+        }
+        for (edge_kind, table_id) in graph_def.get_edge_table_ids() {
+            if let Some(ttl) = get_ttl(&edge_kind.edge_label_id) {
+                self.ttl_filter.set_ttl(*table_id, ttl);
+            }
+        }
+        Ok(())
+    }
+
     fn check_si_guard(&self, si: SnapshotId) -> GraphResult<()> {
         let guard = self.si_guard.load(Ordering::Relaxed) as SnapshotId;
         if si <  guard {
@@ -541,17 +1105,216 @@ impl GraphStore {
         self.si_guard.store(si as isize, Ordering::Relaxed);
     }
 
+    /// Reclaim all data which is invisible to any snapshot not less than `si`, including superseded
+    /// versions of vertices and edges, tables of dropped types and offline tables, and codecs which
+    /// no longer have any data encoded by them. Reads at any si not less than `si` are not affected.
+    fn do_gc(&self, si: SnapshotId) -> GraphResult<()> {
+        let _guard = res_unwrap!(self.lock.lock(), gc, si)?;
+        if si > self.gc_watermark.load(Ordering::SeqCst) {
+            res_unwrap!(self.meta.set_gc_watermark(si), gc, si)?;
+            self.gc_watermark.store(si, Ordering::SeqCst);
+        }
+        for info in self.vertex_manager.gc(si) {
+            let label = info.get_label();
+            for table in info.get_all_tables() {
+                let (start, end) = vertex_table_range(table.id);
+                res_unwrap!(self.storage.delete_range(&start, &end), gc, si, label, table)?;
+                let (start, end) = property_index_table_range(table.id);
+                res_unwrap!(self.storage.delete_range(&start, &end), gc, si, label, table)?;
+            }
+            info!("gc dropped vertex#{} at si#{}", label, si);
+        }
+        for info in self.edge_manager.gc(si) {
+            let edge_kind = info.get_type();
+            for table in info.get_all_tables() {
+                let (start, end) = edge_table_range(table.id);
+                res_unwrap!(self.storage.delete_range(&start, &end), gc, si, edge_kind, table)?;
+                let (start, end) = adjacency_table_range(table.id);
+                res_unwrap!(self.storage.delete_range(&start, &end), gc, si, edge_kind, table)?;
+                let (start, end) = edge_property_index_table_range(table.id);
+                res_unwrap!(self.storage.delete_range(&start, &end), gc, si, edge_kind, table)?;
+            }
+            info!("gc dropped {:?} at si#{}", edge_kind, si);
+        }
+
+        let mut vertex_iter = self.vertex_manager.get_all(si);
+        while let Some(info) = vertex_iter.next_info() {
+            let label = info.get_label();
+            res_unwrap!(self.gc_vertex_type(si, info.as_ref()), gc, si, label)?;
+        }
+        let mut edge_iter = self.edge_manager.get_all_edges(si);
+        while let Some(info) = edge_iter.next_info() {
+            let label = info.get_label();
+            res_unwrap!(self.gc_edge_type(si, info.as_ref()), gc, si, label)?;
+        }
+        Ok(())
+    }
+
+    fn gc_vertex_type(&self, si: SnapshotId, info: &VertexTypeInfo) -> GraphResult<()> {
+        for table_id in info.gc(si)? {
+            let (start, end) = vertex_table_range(table_id);
+            self.storage.delete_range(&start, &end)?;
+            let (start, end) = property_index_table_range(table_id);
+            self.storage.delete_range(&start, &end)?;
+        }
+        for index in info.gc_indexes(si)? {
+            for table in info.get_all_tables() {
+                let start = property_index_prefix(table.id, index.get_prop_id(), index.get_start_si());
+                let end = prefix_upper_bound(&start);
+                self.storage.delete_range(&start, &end)?;
+            }
+        }
+        let mut codecs = HashSet::new();
+        if let Some(table) = info.get_table(si) {
+            let ts = si - table.start_si;
+            let (start, end) = vertex_table_range(table.id);
+            self.compact_table(&start, &end, ts, &mut codecs)?;
+            // index entries are not encoded, so the codec versions collected here are useless
+            let (start, end) = property_index_table_range(table.id);
+            self.compact_table(&start, &end, ts, &mut HashSet::new())?;
+        }
+        let obsolete = info.get_obsolete_codecs();
+        if !obsolete.is_empty() {
+            for table in info.get_all_tables().into_iter().filter(|t| t.start_si > si) {
+                let (start, end) = vertex_table_range(table.id);
+                self.collect_codec_versions(&start, &end, &mut codecs)?;
+            }
+            for version in obsolete.into_iter().filter(|v| !codecs.contains(v)) {
+                info.drop_codec(version)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// All edge kinds of a label share the same codecs, so an obsolete codec can only be dropped
+    /// when no data of any kind is encoded by it.
+    fn gc_edge_type(&self, si: SnapshotId, info: &EdgeInfo) -> GraphResult<()> {
+        let dropped_indexes = info.gc_indexes(si)?;
+        let mut codecs = HashSet::new();
+        for kind_info in info.get_all_kinds() {
+            for table_id in kind_info.gc(si)? {
+                let (start, end) = edge_table_range(table_id);
+                self.storage.delete_range(&start, &end)?;
+                let (start, end) = adjacency_table_range(table_id);
+                self.storage.delete_range(&start, &end)?;
+                let (start, end) = edge_property_index_table_range(table_id);
+                self.storage.delete_range(&start, &end)?;
+            }
+            for index in &dropped_indexes {
+                for table in kind_info.get_all_tables() {
+                    let start = edge_property_index_prefix(table.id, index.get_prop_id(), index.get_start_si());
+                    let end = prefix_upper_bound(&start);
+                    self.storage.delete_range(&start, &end)?;
+                }
+            }
+            if let Some(table) = kind_info.get_table(si) {
+                let ts = si - table.start_si;
+                let (start, end) = edge_table_range(table.id);
+                self.compact_table(&start, &end, ts, &mut codecs)?;
+                // adjacency records and index entries are not encoded, so the codec versions collected
+                // here are useless
+                let (start, end) = adjacency_table_range(table.id);
+                self.compact_table(&start, &end, ts, &mut HashSet::new())?;
+                let (start, end) = edge_property_index_table_range(table.id);
+                self.compact_table(&start, &end, ts, &mut HashSet::new())?;
+            }
+        }
+        let obsolete = info.get_obsolete_codecs();
+        if !obsolete.is_empty() {
+            for kind_info in info.get_all_kinds() {
+                for table in kind_info.get_all_tables().into_iter().filter(|t| t.start_si > si) {
+                    let (start, end) = edge_table_range(table.id);
+                    self.collect_codec_versions(&start, &end, &mut codecs)?;
+                }
+            }
+            for version in obsolete.into_iter().filter(|v| !codecs.contains(v)) {
+                info.drop_codec(version)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete versions in [start, end) which are older than the version visible at `ts`. If the
+    /// visible version is a tombstone, it's deleted too. Every key ends with its ts and the rest of it
+    /// identifies a record, and no such identity may be a prefix of another one. Codec versions of all
+    /// remaining data are collected. Garbage is deleted by key in batches of `GC_BATCH_SIZE`, because
+    /// a range tombstone per record slows down every later read of rocksdb.
+    fn compact_table(&self, start: &[u8], end: &[u8], ts: SnapshotId, codecs: &mut HashSet<CodecVersion>) -> GraphResult<()> {
+        let mut garbage = Vec::with_capacity(GC_BATCH_SIZE);
+        let mut iter = self.storage.scan_range(start, end)?;
+        let mut cur_id: Vec<u8> = Vec::new();
+        let mut visible_found = false;
+        while let Some((k, v)) = iter.next() {
+            let id_len = k.len() - 8;
+            if cur_id.as_slice() != &k[..id_len] {
+                cur_id = k[..id_len].to_vec();
+                visible_found = false;
+            }
+            if visible_found {
+                // versions are sorted from new to old, so all the left versions are invisible
+                garbage.push(k.to_vec());
+            } else if parse_key_ts(k) <= ts {
+                visible_found = true;
+                if v.len() < 4 {
+                    garbage.push(k.to_vec());
+                } else {
+                    codecs.insert(get_codec_version(v));
+                }
+            } else if v.len() >= 4 {
+                codecs.insert(get_codec_version(v));
+            }
+            if garbage.len() >= GC_BATCH_SIZE {
+                self.storage.delete_batch(&garbage)?;
+                garbage.clear();
+            }
+        }
+        if !garbage.is_empty() {
+            self.storage.delete_batch(&garbage)?;
+        }
+        Ok(())
+    }
+
+    fn collect_codec_versions(&self, start: &[u8], end: &[u8], codecs: &mut HashSet<CodecVersion>) -> GraphResult<()> {
+        let mut iter = self.storage.scan_range(start, end)?;
+        while let Some((_, v)) = iter.next() {
+            if v.len() >= 4 {
+                codecs.insert(get_codec_version(v));
+            }
+        }
+        Ok(())
+    }
+
     pub fn ingest(&self, data_path: &str) -> GraphResult<()> {
         let p = [data_path];
         self.storage.load(&p)
     }
 
+    /// Return the encoder of the current codec of vertex type `label` at `si`.
+    pub fn get_vertex_encoder(&self, si: SnapshotId, label: LabelId) -> GraphResult<Encoder> {
+        let res = self.vertex_manager.get_type(si, label).and_then(|info| info.get_encoder(si));
+        res_unwrap!(res, get_vertex_encoder, si, label)
+    }
+
+    /// Return the encoder of the current codec of `edge_kind` at `si`.
+    pub fn get_edge_encoder(&self, si: SnapshotId, edge_kind: &EdgeKind) -> GraphResult<Encoder> {
+        let res = self.edge_manager.get_edge_kind(si, edge_kind).and_then(|info| info.get_encoder(si));
+        res_unwrap!(res, get_edge_encoder, si, edge_kind)
+    }
+
     pub fn get_graph_def(&self) -> GraphResult<GraphDef> {
         let graph_def = self.meta.get_graph_def().lock()?;
         Ok((&*graph_def).clone())
     }
 }
 
+/// Decode all properties of `data`, an expired record has no property.
+fn decode_unexpired<'a>(decoder: &Decoder, data: &'a [u8]) -> HashMap<PropId, ValueRef<'a>> {
+    if decoder.is_expired(data, current_time_millis()) {
+        return HashMap::new();
+    }
+    decoder.decode_all(data)
+}
+
 fn merge_updates<'a>(old: &mut HashMap<PropId, ValueRef<'a>>, updates: &'a dyn PropertyMap) {
     for (prop_id, v) in updates.as_map() {
         old.insert(prop_id, v);
@@ -590,6 +1353,9 @@ mod tests {
     use super::super::tests;
     use crate::db::util::fs;
 
+    /// storage engines which the tests of graph semantics run against
+    const STORAGE_ENGINES: [&str; 2] = ["rocksdb", "memory"];
+
     #[test]
     fn test_get_vertex() {
         let path = "test_get_vertex";
@@ -620,6 +1386,12 @@ mod tests {
         do_test(path, |graph| tests::vertex::test_drop_vertex_type(graph));
     }
 
+    #[test]
+    fn test_query_with_condition() {
+        let path = "test_query_with_condition";
+        do_test(path, |graph| tests::condition::test_query_with_condition(graph));
+    }
+
     #[test]
     fn test_get_edge() {
         let path = "test_get_edge";
@@ -662,12 +1434,42 @@ mod tests {
         do_test(path, |graph| tests::edge::test_remove_edge_kind(graph));
     }
 
+    #[test]
+    fn test_degree_and_kth_edge() {
+        let path = "test_degree_and_kth_edge";
+        do_test(path, |graph| tests::adjacency::test_degree_and_kth_edge(graph));
+    }
+
+    #[test]
+    fn test_concurrent_adjacency_update() {
+        let path = "test_concurrent_adjacency_update";
+        do_test(path, |graph| tests::adjacency::test_concurrent_update(graph));
+    }
+
+    #[test]
+    fn test_vertex_index() {
+        let path = "test_vertex_index";
+        do_test(path, |graph| tests::index::test_vertex_index(graph));
+    }
+
+    #[test]
+    fn test_edge_index() {
+        let path = "test_edge_index";
+        do_test(path, |graph| tests::index::test_edge_index(graph));
+    }
+
     #[test]
     fn test_si_guard() {
         let path = "test_si_guard";
         do_test(path, |graph| tests::graph::test_si_guard(graph));
     }
 
+    #[test]
+    fn test_gc() {
+        let path = "test_gc";
+        do_test(path, |graph| tests::gc::test_gc(graph));
+    }
+
     #[test]
     fn test_backup_engine() {
         let test_dir = "store_test/test_backup_engine";
@@ -678,20 +1480,85 @@ mod tests {
         fs::rmr(&test_dir).unwrap();
     }
 
+    #[test]
+    fn test_restore_at_snapshot() {
+        let test_dir = "store_test/test_restore_at_snapshot";
+        fs::rmr(&test_dir).unwrap();
+        let store_path = format!("{}/store", test_dir);
+        let graph = create_empty_graph(&store_path);
+        tests::backup::test_restore_at_snapshot(graph, test_dir);
+        fs::rmr(&test_dir).unwrap();
+    }
+
+    #[test]
+    fn test_csv_loader() {
+        let test_dir = "store_test/test_csv_loader";
+        fs::rmr(&test_dir).unwrap();
+        let store_path = format!("{}/store", test_dir);
+        let graph = create_empty_graph(&store_path);
+        tests::loader::test_csv_loader(graph, test_dir);
+        fs::rmr(&test_dir).unwrap();
+    }
+
+    #[test]
+    fn test_alter_type() {
+        let path = "store_test/test_alter_type";
+        for engine in &STORAGE_ENGINES {
+            fs::rmr(&path).unwrap();
+            let graph = open_empty_graph(engine, &path);
+            tests::alter::test_alter_type(graph, |graph| reopen(graph, path));
+            fs::rmr(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_ttl() {
+        let path = "store_test/test_ttl";
+        for engine in &STORAGE_ENGINES {
+            fs::rmr(&path).unwrap();
+            let graph = open_empty_graph(engine, &path);
+            tests::ttl::test_ttl(graph, |graph| reopen(graph, path));
+            fs::rmr(&path).unwrap();
+        }
+    }
+
+    /// Run `func` against every storage engine.
     fn do_test<F: Fn(GraphStore)>(path: &str, func: F) {
         let path = format!("store_test/{}", path);
-        fs::rmr(&path).unwrap();
-        let graph = create_empty_graph(&path);
-        func(graph);
-        fs::rmr(&path).unwrap();
+        for engine in &STORAGE_ENGINES {
+            fs::rmr(&path).unwrap();
+            let graph = open_empty_graph(engine, &path);
+            func(graph);
+            fs::rmr(&path).unwrap();
+        }
     }
 
+    /// Tests of backups and sst loading only run against rocksdb, as the memory storage has neither.
     pub fn create_empty_graph(path: &str) -> GraphStore {
+        open_empty_graph("rocksdb", path)
+    }
+
+    fn open_empty_graph(engine: &str, path: &str) -> GraphStore {
         let mut builder = GraphConfigBuilder::new();
-        builder.set_storage_engine("rocksdb");
+        builder.set_storage_engine(engine);
         let config = builder.build();
         GraphStore::open(&config, path).unwrap()
     }
+
+    /// Reopen `graph` so that everything is recovered from meta. Data in memory is lost once the store
+    /// is dropped, so a memory store is recovered from its storage instead.
+    fn reopen(graph: GraphStore, path: &str) -> GraphStore {
+        let config = graph.config.clone();
+        let storage = graph.storage.clone();
+        drop(graph);
+        match config.get_storage_engine() {
+            "memory" => GraphStore::init(&config, storage, Arc::new(TtlCompactionFilter::new())).unwrap(),
+            _ => {
+                drop(storage);
+                GraphStore::open(&config, path).unwrap()
+            }
+        }
+    }
 }
 
 #[cfg(test)]
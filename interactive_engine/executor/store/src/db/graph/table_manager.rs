@@ -40,6 +40,10 @@ impl TableManager {
     pub fn size(&self) -> usize {
         self.versions.size()
     }
+
+    pub fn get_all(&self) -> Vec<Table> {
+        self.versions.get_all().into_iter().map(|v| Table::new(v.start_si, v.data)).collect()
+    }
 }
 
 #[derive(Debug, Clone, PartialOrd, PartialEq, Eq, Hash)]
@@ -0,0 +1,216 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::thread;
+use crate::db::api::*;
+
+pub fn test_degree_and_kth_edge<G: GraphStorage>(graph: G) {
+    let tester = tester::AdjacencyTester::new(graph);
+    tester.execute();
+}
+
+pub fn test_concurrent_update<G: GraphStorage + Send + Sync + 'static>(graph: G) {
+    tester::test_concurrent_update(Arc::new(graph));
+}
+
+mod tester {
+    use super::*;
+
+    const VERTEX_LABEL: LabelId = 1;
+    const EDGE_LABEL: LabelId = 2;
+    const WEIGHT: PropId = 1;
+
+    pub struct AdjacencyTester<G: GraphStorage> {
+        graph: G,
+        edge_kind: EdgeKind,
+    }
+
+    impl<G: GraphStorage> AdjacencyTester<G> {
+        pub fn new(graph: G) -> Self {
+            AdjacencyTester {
+                graph,
+                edge_kind: EdgeKind::new(EDGE_LABEL, VERTEX_LABEL, VERTEX_LABEL),
+            }
+        }
+
+        pub fn execute(&self) {
+            self.graph.create_vertex_type(1, 1, VERTEX_LABEL, &vertex_type_def(), 1).unwrap();
+            self.graph.create_edge_type(1, 2, EDGE_LABEL, &edge_type_def()).unwrap();
+            self.graph.add_edge_kind(1, 3, &self.edge_kind, 3).unwrap();
+
+            // all edges alive at each si
+            let mut snapshots = Vec::new();
+            let mut edges = HashSet::new();
+            for src_id in 1..=3 {
+                for dst_id in 1..=8 {
+                    self.insert_edge(2, edge_id(src_id, dst_id), &mut edges);
+                }
+            }
+            snapshots.push((2, edges.clone()));
+
+            for dst_id in (2..=8).step_by(2) {
+                self.delete_edge(3, edge_id(1, dst_id), &mut edges);
+            }
+            // overwrite an existing edge, the degree should not change
+            self.insert_edge(3, edge_id(2, 3), &mut edges);
+            // delete an edge which doesn't exist
+            self.delete_edge(3, edge_id(2, 100), &mut edges);
+            snapshots.push((3, edges.clone()));
+
+            self.insert_edge(4, edge_id(1, 2), &mut edges);
+            for dst_id in 1..=8 {
+                self.delete_edge(4, edge_id(3, dst_id), &mut edges);
+            }
+            for dst_id in 9..=12 {
+                self.insert_edge(4, edge_id(1, dst_id), &mut edges);
+            }
+            snapshots.push((4, edges.clone()));
+
+            let out_edges: Vec<EdgeId> = edges.iter().filter(|id| id.src_id == 1).cloned().collect();
+            for id in out_edges {
+                self.delete_edge(5, id, &mut edges);
+            }
+            self.insert_edge(5, edge_id(1, 5), &mut edges);
+            snapshots.push((5, edges.clone()));
+
+            for (si, edges) in &snapshots {
+                self.check_snapshot(*si, edges);
+            }
+            self.graph.gc(4);
+            for (si, edges) in &snapshots[2..] {
+                self.check_snapshot(*si, edges);
+            }
+        }
+
+        fn insert_edge(&self, si: SnapshotId, id: EdgeId, edges: &mut HashSet<EdgeId>) {
+            let mut properties = HashMap::new();
+            properties.insert(WEIGHT, Value::long(si));
+            self.graph.insert_overwrite_edge(si, id, &self.edge_kind, true, &properties).unwrap();
+            self.graph.insert_overwrite_edge(si, id, &self.edge_kind, false, &properties).unwrap();
+            edges.insert(id);
+        }
+
+        fn delete_edge(&self, si: SnapshotId, id: EdgeId, edges: &mut HashSet<EdgeId>) {
+            self.graph.delete_edge(si, id, &self.edge_kind, true).unwrap();
+            self.graph.delete_edge(si, id, &self.edge_kind, false).unwrap();
+            edges.remove(&id);
+        }
+
+        fn check_snapshot(&self, si: SnapshotId, edges: &HashSet<EdgeId>) {
+            for vertex_id in 1..=12 {
+                let expected = edges.iter().filter(|id| id.src_id == vertex_id).cloned().collect();
+                let degree = self.graph.get_out_degree(si, vertex_id, &self.edge_kind).unwrap();
+                let kth_edges = self.collect_kth_edges(degree, |k| self.graph.get_kth_out_edge(si, vertex_id, &self.edge_kind, k));
+                assert_eq!(kth_edges, expected, "out edges of vertex#{} at si#{}", vertex_id, si);
+                assert_eq!(degree, self.count_edges(self.graph.get_out_edges(si, vertex_id, Some(EDGE_LABEL), None)));
+
+                let expected = edges.iter().filter(|id| id.dst_id == vertex_id).cloned().collect();
+                let degree = self.graph.get_in_degree(si, vertex_id, &self.edge_kind).unwrap();
+                let kth_edges = self.collect_kth_edges(degree, |k| self.graph.get_kth_in_edge(si, vertex_id, &self.edge_kind, k));
+                assert_eq!(kth_edges, expected, "in edges of vertex#{} at si#{}", vertex_id, si);
+                assert_eq!(degree, self.count_edges(self.graph.get_in_edges(si, vertex_id, Some(EDGE_LABEL), None)));
+            }
+        }
+
+        /// get edges in slots [0, degree], the last one must be None
+        fn collect_kth_edges<'a, F>(&self, degree: usize, f: F) -> HashSet<EdgeId>
+            where F: Fn(usize) -> GraphResult<Option<EdgeWrapper<'a, G::E>>> {
+            let mut ret = HashSet::new();
+            for k in 0..degree {
+                let e = f(k).unwrap().unwrap();
+                assert!(ret.insert(*e.get_id()));
+            }
+            assert!(f(degree).unwrap().is_none());
+            ret
+        }
+
+        fn count_edges<'a>(&self, iter: GraphResult<Box<dyn EdgeResultIter<E=G::E> + 'a>>) -> usize {
+            let mut iter = iter.unwrap();
+            let mut count = 0;
+            while let Some(_) = iter.next() {
+                count += 1;
+            }
+            count
+        }
+    }
+
+    const THREADS: i64 = 8;
+    const EDGES_PER_THREAD: i64 = 50;
+
+    /// Writers of different edges of vertex#1 run at the same time, no update of its index is lost.
+    pub fn test_concurrent_update<G: GraphStorage + Send + Sync + 'static>(graph: Arc<G>) {
+        let edge_kind = EdgeKind::new(EDGE_LABEL, VERTEX_LABEL, VERTEX_LABEL);
+        graph.create_vertex_type(1, 1, VERTEX_LABEL, &vertex_type_def(), 1).unwrap();
+        graph.create_edge_type(1, 2, EDGE_LABEL, &edge_type_def()).unwrap();
+        graph.add_edge_kind(1, 3, &edge_kind, 3).unwrap();
+
+        // out edges of vertex#1 and in edges of vertex#1, written by all threads
+        let edges_of = |t: i64| -> Vec<EdgeId> {
+            (t * EDGES_PER_THREAD..(t + 1) * EDGES_PER_THREAD)
+                .flat_map(|i| vec![EdgeId::new(1, i + 2, i), EdgeId::new(i + 2, 1, i)])
+                .collect()
+        };
+        let run = |si: SnapshotId, delete: bool| {
+            let mut handles = Vec::new();
+            for t in 0..THREADS {
+                let graph = graph.clone();
+                let edges: Vec<EdgeId> = edges_of(t).into_iter().filter(|id| !delete || id.inner_id % 2 == 0).collect();
+                let edge_kind = edge_kind.clone();
+                handles.push(thread::spawn(move || {
+                    for id in edges {
+                        for forward in vec![true, false] {
+                            if delete {
+                                graph.delete_edge(si, id, &edge_kind, forward).unwrap();
+                            } else {
+                                let mut properties = HashMap::new();
+                                properties.insert(WEIGHT, Value::long(si));
+                                graph.insert_overwrite_edge(si, id, &edge_kind, forward, &properties).unwrap();
+                            }
+                        }
+                    }
+                }));
+            }
+            for h in handles {
+                h.join().unwrap();
+            }
+        };
+        let check = |si: SnapshotId, edges: &HashSet<EdgeId>| {
+            let expected: HashSet<EdgeId> = edges.iter().filter(|id| id.src_id == 1).cloned().collect();
+            let degree = graph.get_out_degree(si, 1, &edge_kind).unwrap();
+            let kth_edges: HashSet<EdgeId> = (0..degree)
+                .map(|k| *graph.get_kth_out_edge(si, 1, &edge_kind, k).unwrap().unwrap().get_id())
+                .collect();
+            assert_eq!(kth_edges, expected, "out edges of vertex#1 at si#{}", si);
+
+            let expected: HashSet<EdgeId> = edges.iter().filter(|id| id.dst_id == 1).cloned().collect();
+            let degree = graph.get_in_degree(si, 1, &edge_kind).unwrap();
+            let kth_edges: HashSet<EdgeId> = (0..degree)
+                .map(|k| *graph.get_kth_in_edge(si, 1, &edge_kind, k).unwrap().unwrap().get_id())
+                .collect();
+            assert_eq!(kth_edges, expected, "in edges of vertex#1 at si#{}", si);
+        };
+
+        let mut edges: HashSet<EdgeId> = (0..THREADS).flat_map(|t| edges_of(t)).collect();
+        run(2, false);
+        check(2, &edges);
+        edges.retain(|id| id.inner_id % 2 != 0);
+        run(3, true);
+        check(3, &edges);
+    }
+
+    fn edge_id(src_id: VertexId, dst_id: VertexId) -> EdgeId {
+        EdgeId::new(src_id, dst_id, src_id * 100 + dst_id)
+    }
+
+    fn vertex_type_def() -> TypeDef {
+        let mut builder = TypeDefBuilder::new();
+        builder.set_label_id(VERTEX_LABEL);
+        builder.build()
+    }
+
+    fn edge_type_def() -> TypeDef {
+        let mut builder = TypeDefBuilder::new();
+        builder.add_property(WEIGHT, 1, "weight".to_string(), ValueType::Long, None, false, "cmt".to_string());
+        builder.set_label_id(EDGE_LABEL);
+        builder.build()
+    }
+}
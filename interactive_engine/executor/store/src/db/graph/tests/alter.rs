@@ -0,0 +1,157 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::db::api::*;
+use crate::db::graph::store::GraphStore;
+use crate::db::proto::model::TypeEnumPb;
+
+const PERSON: LabelId = 1;
+const KNOWS: LabelId = 2;
+const NAME: PropId = 1;
+const AGE: PropId = 2;
+const WEIGHT: PropId = 3;
+const CITY: PropId = 4;
+const SCORE: PropId = 5;
+const SINCE: PropId = 6;
+
+pub fn test_alter_type<F: Fn(GraphStore) -> GraphStore>(graph: GraphStore, reopen: F) {
+    let edge_kind = EdgeKind::new(KNOWS, PERSON, PERSON);
+    graph.create_vertex_type(1, 1, PERSON, &person_type_def(&[NAME, AGE]), 1).unwrap();
+    graph.create_edge_type(1, 2, KNOWS, &knows_type_def(&[WEIGHT])).unwrap();
+    graph.add_edge_kind(1, 3, &edge_kind, 2).unwrap();
+    graph.insert_overwrite_vertex(2, 1, PERSON, &properties(vec![(NAME, Value::string("a")), (AGE, Value::long(10))])).unwrap();
+    graph.insert_overwrite_vertex(2, 2, PERSON, &properties(vec![(NAME, Value::string("b"))])).unwrap();
+    let edge_1 = EdgeId::new(1, 2, 1);
+    graph.insert_overwrite_edge(2, edge_1, &edge_kind, true, &properties(vec![(WEIGHT, Value::double(0.5))])).unwrap();
+
+    // only adding and dropping properties are allowed
+    let mut builder = TypeDefBuilder::new();
+    builder.add_property(NAME, 1, "name".to_string(), ValueType::Long, None, false, "cmt".to_string());
+    builder.set_label_id(PERSON).set_label("person");
+    assert!(graph.alter_vertex_type(3, 4, PERSON, &builder.build()).is_err());
+    assert!(graph.alter_vertex_type(3, 4, 10, &person_type_def(&[NAME])).is_err());
+    // a stale schema version is ignored
+    assert!(!graph.alter_vertex_type(3, 3, PERSON, &person_type_def(&[NAME])).unwrap());
+
+    // drop age and add city with a default value and score without default value
+    assert!(graph.alter_vertex_type(3, 4, PERSON, &person_type_def(&[NAME, CITY, SCORE])).unwrap());
+    assert!(graph.alter_edge_type(3, 5, KNOWS, &knows_type_def(&[WEIGHT, SINCE])).unwrap());
+    let graph_def = graph.get_graph_def().unwrap();
+    assert_eq!(graph_def.label_to_types.get(&PERSON).unwrap().get_version(), 1);
+    assert_eq!(graph_def.label_to_types.get(&KNOWS).unwrap().get_version(), 1);
+    assert!(graph_def.property_name_to_id.get("age").is_none());
+    assert_eq!(*graph_def.property_name_to_id.get("city").unwrap(), CITY);
+
+    graph.insert_overwrite_vertex(4, 3, PERSON, &properties(vec![(NAME, Value::string("c")), (CITY, Value::string("hz")), (SCORE, Value::int(9))])).unwrap();
+    graph.insert_update_vertex(4, 1, PERSON, &properties(vec![(SCORE, Value::int(1))])).unwrap();
+    let edge_2 = EdgeId::new(2, 3, 2);
+    graph.insert_overwrite_edge(4, edge_2, &edge_kind, true, &properties(vec![(SINCE, Value::long(2020))])).unwrap();
+
+    // an added property must not reuse the inner id of a dropped one, and an indexed property cannot be dropped
+    let mut builder = TypeDefBuilder::new();
+    builder.add_property(NAME, 1, "name".to_string(), ValueType::String, None, false, "cmt".to_string());
+    builder.add_property(AGE, 2, "age".to_string(), ValueType::Long, None, false, "cmt".to_string());
+    builder.set_label_id(PERSON).set_label("person");
+    assert!(graph.alter_vertex_type(5, 6, PERSON, &builder.build()).is_err());
+    assert!(graph.create_vertex_index(5, 6, PERSON, SCORE).unwrap());
+    assert!(graph.alter_vertex_type(5, 7, PERSON, &person_type_def(&[NAME, CITY])).is_err());
+
+    check_snapshots(&graph);
+    let graph = reopen(graph);
+    check_snapshots(&graph);
+
+    // age is added again as a new property, so old values of age are never seen
+    let mut builder = TypeDefBuilder::new();
+    builder.add_property(NAME, 1, "name".to_string(), ValueType::String, None, false, "cmt".to_string());
+    builder.add_property(AGE, 7, "age".to_string(), ValueType::Long, None, false, "cmt".to_string());
+    builder.add_property(SCORE, 5, "score".to_string(), ValueType::Int, None, false, "cmt".to_string());
+    builder.set_label_id(PERSON).set_label("person");
+    assert!(graph.alter_vertex_type(6, 7, PERSON, &builder.build()).unwrap());
+    let v = graph.get_vertex(6, 1, Some(PERSON)).unwrap().unwrap();
+    assert!(v.get_property(AGE).is_none());
+    assert!(v.get_property(CITY).is_none());
+    assert_eq!(v.get_property(SCORE).unwrap().get_int().unwrap(), 1);
+    assert_eq!(graph.get_vertex(2, 1, Some(PERSON)).unwrap().unwrap().get_property(AGE).unwrap().get_long().unwrap(), 10);
+}
+
+fn check_snapshots(graph: &GraphStore) {
+    let edge_kind = EdgeKind::new(KNOWS, PERSON, PERSON);
+    // before the alter, data is read with the old definition
+    let v = graph.get_vertex(2, 1, Some(PERSON)).unwrap().unwrap();
+    assert_eq!(v.get_property(AGE).unwrap().get_long().unwrap(), 10);
+    assert!(v.get_property(CITY).is_none());
+    let e = graph.get_edge(2, EdgeId::new(1, 2, 1), Some(&edge_kind)).unwrap().unwrap();
+    assert!(e.get_property(SINCE).is_none());
+
+    // old data gets default values of added properties and loses dropped properties
+    for si in 3..=4 {
+        let v = graph.get_vertex(si, 2, Some(PERSON)).unwrap().unwrap();
+        assert_eq!(v.get_property(NAME).unwrap().get_str().unwrap(), "b");
+        assert_eq!(v.get_property(CITY).unwrap().get_str().unwrap(), "unknown");
+        assert!(v.get_property(SCORE).is_none());
+        let mut props = HashSet::new();
+        let mut iter = v.get_properties_iter();
+        while let Some((prop_id, _)) = iter.next() {
+            props.insert(prop_id);
+        }
+        assert_eq!(props, vec![NAME, CITY].into_iter().collect());
+        let e = graph.get_edge(si, EdgeId::new(1, 2, 1), Some(&edge_kind)).unwrap().unwrap();
+        assert_eq!(e.get_property(WEIGHT).unwrap().get_double().unwrap(), 0.5);
+        assert_eq!(e.get_property(SINCE).unwrap().get_long().unwrap(), 2000);
+    }
+    let v = graph.get_vertex(3, 1, Some(PERSON)).unwrap().unwrap();
+    assert!(v.get_property(AGE).is_none());
+    assert!(v.get_property(SCORE).is_none());
+
+    // data written after the alter uses the new definition
+    let v = graph.get_vertex(4, 1, Some(PERSON)).unwrap().unwrap();
+    assert_eq!(v.get_property(NAME).unwrap().get_str().unwrap(), "a");
+    assert_eq!(v.get_property(CITY).unwrap().get_str().unwrap(), "unknown");
+    assert_eq!(v.get_property(SCORE).unwrap().get_int().unwrap(), 1);
+    assert!(v.get_property(AGE).is_none());
+    let v = graph.get_vertex(4, 3, Some(PERSON)).unwrap().unwrap();
+    assert_eq!(v.get_property(CITY).unwrap().get_str().unwrap(), "hz");
+    let e = graph.get_edge(4, EdgeId::new(2, 3, 2), Some(&edge_kind)).unwrap().unwrap();
+    assert_eq!(e.get_property(SINCE).unwrap().get_long().unwrap(), 2020);
+    assert!(e.get_property(WEIGHT).is_none());
+
+    let condition = Arc::new(Condition::Predicate(PredicateCondition::new(CITY, ComparisonOp::Equal, Value::string("unknown"))));
+    let mut iter = graph.query_vertices(4, Some(PERSON), Some(condition)).unwrap();
+    let mut ids = HashSet::new();
+    while let Some(v) = iter.next() {
+        ids.insert(v.get_id());
+    }
+    assert_eq!(ids, vec![1, 2].into_iter().collect());
+}
+
+fn properties(props: Vec<(PropId, Value)>) -> HashMap<PropId, Value> {
+    props.into_iter().collect()
+}
+
+fn person_type_def(props: &[PropId]) -> TypeDef {
+    let mut builder = TypeDefBuilder::new();
+    for prop_id in props {
+        match *prop_id {
+            NAME => builder.add_property(NAME, 1, "name".to_string(), ValueType::String, None, false, "cmt".to_string()),
+            AGE => builder.add_property(AGE, 2, "age".to_string(), ValueType::Long, None, false, "cmt".to_string()),
+            CITY => builder.add_property(CITY, 4, "city".to_string(), ValueType::String, Some(Value::string("unknown")), false, "cmt".to_string()),
+            SCORE => builder.add_property(SCORE, 5, "score".to_string(), ValueType::Int, None, false, "cmt".to_string()),
+            _ => unreachable!(),
+        };
+    }
+    builder.set_label_id(PERSON).set_label("person");
+    builder.build()
+}
+
+fn knows_type_def(props: &[PropId]) -> TypeDef {
+    let mut builder = TypeDefBuilder::new();
+    for prop_id in props {
+        match *prop_id {
+            WEIGHT => builder.add_property(WEIGHT, 3, "weight".to_string(), ValueType::Double, None, false, "cmt".to_string()),
+            SINCE => builder.add_property(SINCE, 6, "since".to_string(), ValueType::Long, Some(Value::long(2000)), false, "cmt".to_string()),
+            _ => unreachable!(),
+        };
+    }
+    builder.set_label_id(KNOWS).set_label("knows").set_type_enum(TypeEnumPb::EDGE);
+    builder.build()
+}
@@ -45,6 +45,86 @@ pub fn test_backup_engine<G: GraphStorage>(graph: G, test_dir: &str) {
     assert!(restore_store_2.get_vertex(18, 2, Some(1)).unwrap().is_none());
 }
 
+pub fn test_restore_at_snapshot(graph: GraphStore, test_dir: &str) {
+    let backup_path = format!("{}/backup", test_dir);
+    let mut backup_engine = graph.open_backup_engine(&backup_path).unwrap();
+    let prop_id = 41;
+    let properties = |v: i64| {
+        let mut ret = HashMap::new();
+        ret.insert(prop_id, Value::long(v));
+        ret
+    };
+    let edge_kind = EdgeKind::new(2, 1, 1);
+    graph.create_vertex_type(10, 1, 1, &types::create_test_type_def(1), 1).unwrap();
+    graph.create_edge_type(10, 2, 2, &types::create_test_type_def(2)).unwrap();
+    graph.add_edge_kind(10, 3, &edge_kind, 2).unwrap();
+    graph.insert_overwrite_vertex(11, 1, 1, &properties(1)).unwrap();
+    graph.insert_overwrite_vertex(11, 2, 1, &properties(2)).unwrap();
+    graph.insert_overwrite_edge(11, EdgeId::new(1, 2, 1), &edge_kind, true, &HashMap::<PropId, Value>::new()).unwrap();
+    let backup_1 = backup_engine.create_new_backup_at(11).unwrap();
+
+    graph.insert_overwrite_vertex(12, 3, 1, &properties(3)).unwrap();
+    graph.insert_overwrite_vertex(13, 2, 1, &properties(20)).unwrap();
+    graph.delete_vertex(13, 1, 1).unwrap();
+    graph.insert_overwrite_edge(13, EdgeId::new(2, 3, 2), &edge_kind, true, &HashMap::<PropId, Value>::new()).unwrap();
+    graph.create_vertex_type(14, 4, 3, &types::create_test_type_def(3), 3).unwrap();
+    graph.insert_overwrite_vertex(14, 4, 3, &HashMap::<PropId, Value>::new()).unwrap();
+    let backup_2 = backup_engine.create_new_backup_at(14).unwrap();
+    // backups never go back to older snapshots
+    assert!(backup_engine.create_new_backup_at(13).is_err());
+    let infos = backup_engine.get_backup_info_list();
+    let backups: Vec<(BackupId, Option<SnapshotId>)> = infos.iter().map(|i| (i.backup_id, i.snapshot_id)).collect();
+    assert_eq!(backups, vec![(backup_1, Some(11)), (backup_2, Some(14))]);
+
+    // writes after si#12 in backup 2 are rolled back
+    let restore_path = format!("{}/restore_12", test_dir);
+    assert_eq!(backup_engine.restore_at_snapshot(&restore_path, 12).unwrap(), backup_2);
+    let store = open_graph(&restore_path);
+    for si in vec![12, 20] {
+        for (id, v) in vec![(1, 1), (2, 2), (3, 3)] {
+            let vertex = store.get_vertex(si, id, Some(1)).unwrap().unwrap();
+            assert_eq!(vertex.get_property(prop_id).unwrap().get_long().unwrap(), v);
+        }
+        assert!(store.get_vertex(si, 4, None).unwrap().is_none());
+        let mut iter = store.get_out_edges(si, 2, Some(2), None).unwrap();
+        assert!(iter.next().is_none());
+        let mut iter = store.get_out_edges(si, 1, Some(2), None).unwrap();
+        assert_eq!(iter.next().unwrap().get_id().dst_id, 2);
+        assert!(iter.next().is_none());
+    }
+    assert!(!store.get_graph_def().unwrap().label_to_types.contains_key(&3));
+    // the restored store accepts schema changes after si#12
+    assert!(store.create_vertex_type(13, 4, 3, &types::create_test_type_def(3), 3).unwrap());
+
+    let restore_path = format!("{}/restore_11", test_dir);
+    assert_eq!(backup_engine.restore_at_snapshot(&restore_path, 11).unwrap(), backup_1);
+    let store = open_graph(&restore_path);
+    assert!(store.get_vertex(20, 3, Some(1)).unwrap().is_none());
+    let restore_path = format!("{}/restore_15", test_dir);
+    assert!(backup_engine.restore_at_snapshot(&restore_path, 15).is_err());
+
+    assert_eq!(backup_engine.purge_backups(BackupRetention::KeepNewerThan(11)).unwrap(), vec![backup_1]);
+    let backup_3 = backup_engine.create_new_backup_at(16).unwrap();
+    assert_eq!(backup_engine.purge_backups(BackupRetention::KeepLast(1)).unwrap(), vec![backup_2]);
+    // snapshots of backups are persisted
+    let mut backup_engine = graph.open_backup_engine(&backup_path).unwrap();
+    let infos = backup_engine.get_backup_info_list();
+    assert_eq!(infos.len(), 1);
+    assert_eq!((infos[0].backup_id, infos[0].snapshot_id), (backup_3, Some(16)));
+
+    // versions visible at si#17 are collected before backup 4, so it cannot be restored at si#17
+    graph.insert_overwrite_vertex(17, 3, 1, &properties(30)).unwrap();
+    graph.gc(18);
+    let backup_4 = backup_engine.create_new_backup_at(19).unwrap();
+    let restore_path = format!("{}/restore_17", test_dir);
+    assert!(backup_engine.restore_at_snapshot(&restore_path, 17).is_err());
+    let restore_path = format!("{}/restore_18", test_dir);
+    assert_eq!(backup_engine.restore_at_snapshot(&restore_path, 18).unwrap(), backup_4);
+    let store = open_graph(&restore_path);
+    let vertex = store.get_vertex(18, 3, Some(1)).unwrap().unwrap();
+    assert_eq!(vertex.get_property(prop_id).unwrap().get_long().unwrap(), 30);
+}
+
 fn open_graph(path: &str) -> GraphStore {
     let mut builder = GraphConfigBuilder::new();
     builder.set_storage_engine("rocksdb");
@@ -0,0 +1,208 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use crate::db::api::*;
+
+pub fn test_query_with_condition<G: GraphStorage>(graph: G) {
+    let tester = tester::ConditionTester::new(graph);
+    tester.execute();
+}
+
+mod tester {
+    use super::*;
+
+    const VERTEX_LABEL: LabelId = 1;
+    const EDGE_LABEL: LabelId = 2;
+    const AGE: PropId = 1;
+    const NAME: PropId = 2;
+    const WEIGHT: PropId = 3;
+    const SCORES: PropId = 4;
+    const TAGS: PropId = 5;
+
+    pub struct ConditionTester<G: GraphStorage> {
+        graph: G,
+    }
+
+    impl<G: GraphStorage> ConditionTester<G> {
+        pub fn new(graph: G) -> Self {
+            ConditionTester {
+                graph,
+            }
+        }
+
+        pub fn execute(&self) {
+            let edge_kind = EdgeKind::new(EDGE_LABEL, VERTEX_LABEL, VERTEX_LABEL);
+            self.graph.create_vertex_type(1, 1, VERTEX_LABEL, &vertex_type_def(), 1).unwrap();
+            self.graph.create_edge_type(1, 2, EDGE_LABEL, &edge_type_def()).unwrap();
+            self.graph.add_edge_kind(1, 3, &edge_kind, 3).unwrap();
+            for id in 1..=20 {
+                let mut properties = HashMap::new();
+                properties.insert(AGE, Value::long(id));
+                properties.insert(NAME, Value::string(&format!("name_{}", id % 3)));
+                properties.insert(SCORES, Value::int_list(&[id as i32, id as i32 * 2]));
+                if id % 2 == 0 {
+                    properties.insert(TAGS, Value::string_list(&[format!("t{}", id % 4), "all".to_string()]));
+                }
+                self.graph.insert_overwrite_vertex(2, id, VERTEX_LABEL, &properties).unwrap();
+            }
+            for dst_id in 1..=20 {
+                let edge_id = EdgeId::new(1, dst_id, dst_id);
+                let mut properties = HashMap::new();
+                properties.insert(WEIGHT, Value::double(dst_id as f64 / 10.0));
+                self.graph.insert_overwrite_edge(2, edge_id, &edge_kind, true, &properties).unwrap();
+                self.graph.insert_overwrite_edge(2, edge_id, &edge_kind, false, &properties).unwrap();
+            }
+            // overwrite vertex 5 at si 3, its old version should not be matched any more
+            let mut properties = HashMap::new();
+            properties.insert(AGE, Value::long(100));
+            self.graph.insert_overwrite_vertex(3, 5, VERTEX_LABEL, &properties).unwrap();
+
+            let condition = ConditionBuilder::new()
+                .and(predicate(AGE, ComparisonOp::GreaterEqual, Value::long(5)))
+                .and(predicate(AGE, ComparisonOp::LessThan, Value::int(10)))
+                .build().unwrap();
+            let condition = Arc::new(condition);
+            self.check_query_vertices(2, Some(VERTEX_LABEL), condition.clone(), (5..10).collect());
+            self.check_query_vertices(3, Some(VERTEX_LABEL), condition.clone(), (6..10).collect());
+            self.check_query_vertices(3, None, condition, (6..10).collect());
+
+            let condition = ConditionBuilder::new()
+                .and(predicate(NAME, ComparisonOp::Equal, Value::string("name_0")))
+                .or(predicate(AGE, ComparisonOp::Equal, Value::long(100)))
+                .not()
+                .build().unwrap();
+            let expected = (1..=20).filter(|id| id % 3 != 0 && *id != 5).collect();
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), expected);
+
+            // vertex 5 has no NAME at si 3, so it never matches a predicate on NAME
+            let condition = predicate(NAME, ComparisonOp::NotEqual, Value::string("name_0"));
+            let expected = (1..=20).filter(|id| id % 3 != 0 && *id != 5).collect();
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), expected);
+
+            self.check_string_predicates();
+            self.check_set_conditions();
+            self.check_exists_conditions();
+            self.check_list_contains();
+
+            let condition = Arc::new(predicate(WEIGHT, ComparisonOp::GreaterThan, Value::double(1.5)));
+            let mut iter = self.graph.get_out_edges(3, 1, Some(EDGE_LABEL), Some(condition.clone())).unwrap();
+            let mut ids = HashSet::new();
+            while let Some(e) = iter.next() {
+                ids.insert(e.get_id().dst_id);
+            }
+            assert_eq!(ids, (16..=20).collect());
+            let mut iter = self.graph.query_edges(3, None, Some(condition.clone())).unwrap();
+            let mut ids = HashSet::new();
+            while let Some(e) = iter.next() {
+                ids.insert(e.get_id().dst_id);
+            }
+            assert_eq!(ids, (16..=20).collect());
+            let mut iter = self.graph.get_in_edges(3, 18, Some(EDGE_LABEL), Some(condition.clone())).unwrap();
+            assert_eq!(iter.next().unwrap().get_id().src_id, 1);
+            assert!(iter.next().is_none());
+            let mut iter = self.graph.get_in_edges(3, 10, Some(EDGE_LABEL), Some(condition)).unwrap();
+            assert!(iter.next().is_none());
+        }
+
+        fn check_string_predicates(&self) {
+            let condition = predicate(NAME, ComparisonOp::StartsWith, Value::string("name_1"));
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), (1..=20).filter(|id| id % 3 == 1).collect());
+            let condition = predicate(NAME, ComparisonOp::EndsWith, Value::string("_2"));
+            let expected = (1..=20).filter(|id| id % 3 == 2 && *id != 5).collect();
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), expected);
+            let condition = predicate(NAME, ComparisonOp::Contains, Value::string("me_0"));
+            self.check_query_vertices(3, None, Arc::new(condition), (1..=20).filter(|id| id % 3 == 0).collect());
+            let condition = predicate(NAME, ComparisonOp::Contains, Value::string(""));
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), (1..=20).filter(|id| *id != 5).collect());
+            // string predicates never match a value which is not a string
+            let condition = predicate(AGE, ComparisonOp::StartsWith, Value::string("1"));
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), HashSet::new());
+            let condition = predicate(NAME, ComparisonOp::StartsWith, Value::long(1));
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), HashSet::new());
+        }
+
+        fn check_set_conditions(&self) {
+            let values = vec![Value::int(3), Value::double(4.0), Value::long(100), Value::string("3")];
+            let condition = Condition::In(SetCondition::new(AGE, values));
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), vec![3, 4, 5].into_iter().collect());
+            let values = (1..=18).map(|x| Value::long(x)).collect();
+            let condition = Condition::NotIn(SetCondition::new(AGE, values));
+            self.check_query_vertices(3, None, Arc::new(condition), vec![5, 19, 20].into_iter().collect());
+            // vertex 5 has no NAME, so it's neither in nor not in any set
+            let condition = Condition::NotIn(SetCondition::new(NAME, vec![Value::string("name_0")]));
+            let expected = (1..=20).filter(|id| id % 3 != 0 && *id != 5).collect();
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), expected);
+            let condition = ConditionBuilder::new()
+                .and(Condition::In(SetCondition::new(NAME, vec![Value::string("name_0"), Value::string("name_1")])))
+                .not()
+                .build().unwrap();
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), (1..=20).filter(|id| id % 3 == 2).collect());
+            let condition = Condition::In(SetCondition::new(AGE, Vec::new()));
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), HashSet::new());
+        }
+
+        fn check_exists_conditions(&self) {
+            let condition = Condition::Exists(ExistsCondition::new(TAGS));
+            self.check_query_vertices(2, Some(VERTEX_LABEL), Arc::new(condition), (1..=20).filter(|id| id % 2 == 0).collect());
+            let condition = Condition::NotExists(ExistsCondition::new(TAGS));
+            self.check_query_vertices(2, None, Arc::new(condition), (1..=20).filter(|id| id % 2 == 1).collect());
+            let condition = Condition::NotExists(ExistsCondition::new(NAME));
+            self.check_query_vertices(2, Some(VERTEX_LABEL), Arc::new(condition), HashSet::new());
+            let condition = Condition::NotExists(ExistsCondition::new(NAME));
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), vec![5].into_iter().collect());
+            let condition = Condition::Exists(ExistsCondition::new(NAME));
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), (1..=20).filter(|id| *id != 5).collect());
+            // properties not defined in the type never exist
+            let condition = Condition::Exists(ExistsCondition::new(WEIGHT));
+            self.check_query_vertices(3, Some(VERTEX_LABEL), Arc::new(condition), HashSet::new());
+        }
+
+        fn check_list_contains(&self) {
+            let condition = predicate(SCORES, ComparisonOp::ListContains, Value::long(8));
+            self.check_query_vertices(2, Some(VERTEX_LABEL), Arc::new(condition), vec![4, 8].into_iter().collect());
+            let condition = predicate(SCORES, ComparisonOp::ListContains, Value::double(6.0));
+            self.check_query_vertices(2, Some(VERTEX_LABEL), Arc::new(condition), vec![3, 6].into_iter().collect());
+            let condition = predicate(SCORES, ComparisonOp::ListContains, Value::double(6.5));
+            self.check_query_vertices(2, Some(VERTEX_LABEL), Arc::new(condition), HashSet::new());
+            let condition = predicate(SCORES, ComparisonOp::ListContains, Value::string("8"));
+            self.check_query_vertices(2, Some(VERTEX_LABEL), Arc::new(condition), HashSet::new());
+            let condition = predicate(TAGS, ComparisonOp::ListContains, Value::string("t2"));
+            self.check_query_vertices(2, Some(VERTEX_LABEL), Arc::new(condition), vec![2, 6, 10, 14, 18].into_iter().collect());
+            let condition = predicate(TAGS, ComparisonOp::ListContains, Value::string("all"));
+            self.check_query_vertices(2, None, Arc::new(condition), (1..=20).filter(|id| id % 2 == 0).collect());
+            // list contains never matches a scalar property
+            let condition = predicate(AGE, ComparisonOp::ListContains, Value::long(8));
+            self.check_query_vertices(2, Some(VERTEX_LABEL), Arc::new(condition), HashSet::new());
+        }
+
+        fn check_query_vertices(&self, si: SnapshotId, label: Option<LabelId>, condition: Arc<Condition>, expected: HashSet<VertexId>) {
+            let mut iter = self.graph.query_vertices(si, label, Some(condition)).unwrap();
+            let mut ids = HashSet::new();
+            while let Some(v) = iter.next() {
+                assert!(ids.insert(v.get_id()));
+            }
+            iter.ok().unwrap();
+            assert_eq!(ids, expected);
+        }
+    }
+
+    fn predicate(prop: PropId, op: ComparisonOp, value: Value) -> Condition {
+        Condition::Predicate(PredicateCondition::new(prop, op, value))
+    }
+
+    fn vertex_type_def() -> TypeDef {
+        let mut builder = TypeDefBuilder::new();
+        builder.add_property(AGE, 1, "age".to_string(), ValueType::Long, None, false, "cmt".to_string());
+        builder.add_property(NAME, 3, "name".to_string(), ValueType::String, None, false, "cmt".to_string());
+        builder.add_property(SCORES, 6, "scores".to_string(), ValueType::IntList, None, false, "cmt".to_string());
+        builder.add_property(TAGS, 7, "tags".to_string(), ValueType::StringList, None, false, "cmt".to_string());
+        builder.set_label_id(VERTEX_LABEL);
+        builder.build()
+    }
+
+    fn edge_type_def() -> TypeDef {
+        let mut builder = TypeDefBuilder::new();
+        builder.add_property(WEIGHT, 5, "weight".to_string(), ValueType::Double, None, false, "cmt".to_string());
+        builder.set_label_id(EDGE_LABEL);
+        builder.build()
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+use crate::db::api::*;
+use super::types;
+use super::helper::GraphTestHelper;
+
+pub fn test_gc<G: GraphStorage>(graph: G) {
+    let tester = tester::GCTester::new(graph);
+    tester.execute();
+}
+
+mod tester {
+    use super::*;
+
+    const LABEL1: LabelId = 1;
+    const LABEL2: LabelId = 2;
+    const EDGE_LABEL: LabelId = 10;
+
+    /// all vertices and edges alive at a snapshot
+    #[derive(Clone)]
+    struct Snapshot {
+        label1: HashSet<VertexId>,
+        label2: Option<HashSet<VertexId>>,
+        kind1: HashSet<EdgeId>,
+        kind2: Option<HashSet<EdgeId>>,
+    }
+
+    pub struct GCTester<G: GraphStorage> {
+        graph: G,
+    }
+
+    impl<G: GraphStorage> GCTester<G> {
+        pub fn new(graph: G) -> Self {
+            GCTester {
+                graph,
+            }
+        }
+
+        pub fn execute(&self) {
+            let mut helper = GraphTestHelper::new(&self.graph);
+            let mut schema_version = 1;
+            let (label1, label2, edge_label) = (LABEL1, LABEL2, EDGE_LABEL);
+            let kind1 = EdgeKind::new(edge_label, label1, label1);
+            let kind2 = EdgeKind::new(edge_label, label1, label2);
+            helper.create_vertex_type(1, schema_version, label1, types::create_full_type_def(label1)).unwrap();
+            schema_version += 1;
+            helper.create_vertex_type(1, schema_version, label2, types::create_full_type_def(label2)).unwrap();
+            schema_version += 1;
+            helper.create_edge_type(1, schema_version, edge_label, types::create_full_type_def(edge_label)).unwrap();
+            schema_version += 1;
+            helper.add_edge_kind(1, schema_version, &kind1).unwrap();
+            schema_version += 1;
+            helper.add_edge_kind(1, schema_version, &kind2).unwrap();
+            schema_version += 1;
+
+            let mut snapshot = Snapshot {
+                label1: HashSet::new(),
+                label2: Some(HashSet::new()),
+                kind1: HashSet::new(),
+                kind2: Some(HashSet::new()),
+            };
+            let mut snapshots = vec![snapshot.clone(), snapshot.clone()];
+
+            let kind1_ids = edge_ids(1);
+            let kind2_ids = edge_ids(2);
+            helper.insert_vertex(2, label1, (1..=20).collect()).unwrap();
+            helper.insert_vertex(2, label2, (101..=120).collect()).unwrap();
+            helper.insert_edge(2, &kind1, kind1_ids.clone().into_iter()).unwrap();
+            helper.insert_edge(2, &kind2, kind2_ids.clone().into_iter()).unwrap();
+            snapshot.label1.extend(1..=20);
+            snapshot.label2.as_mut().unwrap().extend(101..=120);
+            snapshot.kind1.extend(kind1_ids.iter().cloned());
+            snapshot.kind2.as_mut().unwrap().extend(kind2_ids.iter().cloned());
+            snapshots.push(snapshot.clone());
+
+            helper.update_vertex(3, label1, (1..=10).collect()).unwrap();
+            helper.update_vertex(3, label2, (101..=110).collect()).unwrap();
+            helper.update_edge(3, &kind1, kind1_ids[..10].to_vec().into_iter()).unwrap();
+            snapshots.push(snapshot.clone());
+
+            helper.insert_vertex(4, label1, (5..=15).collect()).unwrap();
+            helper.delete_edge(4, &kind1, kind1_ids[5..15].to_vec()).unwrap();
+            for id in &kind1_ids[5..15] {
+                snapshot.kind1.remove(id);
+            }
+            snapshots.push(snapshot.clone());
+
+            helper.delete_vertex(5, label1, (16..=20).collect()).unwrap();
+            helper.drop_vertex_type(5, schema_version, label2).unwrap();
+            schema_version += 1;
+            helper.remove_edge_kind(5, schema_version, &kind2).unwrap();
+            for id in 16..=20 {
+                snapshot.label1.remove(&id);
+            }
+            snapshot.label2 = None;
+            snapshot.kind2 = None;
+            snapshots.push(snapshot.clone());
+
+            helper.insert_vertex(6, label1, (18..=19).collect()).unwrap();
+            helper.insert_edge(6, &kind1, kind1_ids[10..15].to_vec().into_iter()).unwrap();
+            snapshot.label1.extend(18..=19);
+            snapshot.kind1.extend(kind1_ids[10..15].iter().cloned());
+            snapshots.push(snapshot.clone());
+
+            helper.update_vertex(7, label1, (1..=3).collect()).unwrap();
+            helper.update_edge(7, &kind1, kind1_ids[..3].to_vec().into_iter()).unwrap();
+            snapshots.push(snapshot.clone());
+            snapshots.push(snapshot.clone());
+
+            let max_si = snapshots.len() as SnapshotId - 1;
+            for si in 1..=max_si {
+                check_snapshot(&helper, si, &snapshots[si as usize], &kind1, &kind2);
+            }
+            for watermark in vec![2, 4, 5, 6, 8] {
+                self.graph.gc(watermark);
+                for si in watermark..=max_si {
+                    check_snapshot(&helper, si, &snapshots[si as usize], &kind1, &kind2);
+                }
+            }
+
+            // the graph still works well after gc
+            helper.update_vertex(9, label1, (1..=5).collect()).unwrap();
+            helper.delete_vertex(9, label1, (6..=7).collect()).unwrap();
+            helper.delete_edge(9, &kind1, kind1_ids[..2].to_vec()).unwrap();
+            for id in 6..=7 {
+                snapshot.label1.remove(&id);
+            }
+            for id in &kind1_ids[..2] {
+                snapshot.kind1.remove(id);
+            }
+            self.graph.gc(9);
+            check_snapshot(&helper, 9, &snapshot, &kind1, &kind2);
+            check_snapshot(&helper, 10, &snapshot, &kind1, &kind2);
+        }
+    }
+
+    fn edge_ids(kind_idx: i64) -> Vec<EdgeId> {
+        let mut ret = Vec::new();
+        for src_id in 1..=5 {
+            for dst_id in 1..=4 {
+                ret.push(EdgeId::new(src_id, dst_id + kind_idx * 100, kind_idx * 1000 + src_id * 10 + dst_id));
+            }
+        }
+        ret
+    }
+
+    fn check_snapshot<G: GraphStorage>(helper: &GraphTestHelper<G>, si: SnapshotId, snapshot: &Snapshot, kind1: &EdgeKind, kind2: &EdgeKind) {
+        let all_vertices: Vec<VertexId> = (1..=20).collect();
+        let alive: Vec<VertexId> = all_vertices.iter().cloned().filter(|id| snapshot.label1.contains(id)).collect();
+        let dead: Vec<VertexId> = all_vertices.iter().cloned().filter(|id| !snapshot.label1.contains(id)).collect();
+        helper.check_get_vertex(si, LABEL1, &alive);
+        helper.check_get_vertex_none(si, LABEL1, &dead);
+        helper.check_query_vertices(si, Some(LABEL1), snapshot.label1.clone());
+        if let Some(ref ids) = snapshot.label2 {
+            helper.check_get_vertex(si, LABEL2, &ids.iter().cloned().collect());
+            helper.check_query_vertices(si, Some(LABEL2), ids.clone());
+        } else {
+            helper.check_get_vertex_err(si, LABEL2, &(101..=120).collect());
+            helper.check_query_vertices_empty(si, LABEL2);
+        }
+
+        let all_edges = edge_ids(1);
+        helper.check_get_edge(si, kind1, all_edges.iter().filter(|id| snapshot.kind1.contains(id)));
+        helper.check_get_edge_none(si, kind1, all_edges.iter().filter(|id| !snapshot.kind1.contains(id)));
+        let mut label_edges = snapshot.kind1.clone();
+        if let Some(ref ids) = snapshot.kind2 {
+            helper.check_get_edge(si, kind2, ids.iter());
+            label_edges.extend(ids.iter().cloned());
+        } else {
+            helper.check_get_edge_err(si, kind2, edge_ids(2).iter());
+        }
+        helper.check_query_edges(si, Some(EDGE_LABEL), label_edges.clone());
+        for src_id in 1..=5 {
+            let ids = label_edges.iter().cloned().filter(|id| id.src_id == src_id).collect();
+            helper.check_get_out_edges(si, src_id, Some(EDGE_LABEL), ids);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use crate::db::api::*;
+
+pub fn test_vertex_index<G: GraphStorage>(graph: G) {
+    let tester = tester::IndexTester::new(graph);
+    tester.execute();
+}
+
+pub fn test_edge_index<G: GraphStorage>(graph: G) {
+    let tester = tester::EdgeIndexTester::new(graph);
+    tester.execute();
+}
+
+mod tester {
+    use super::*;
+
+    const VERTEX_LABEL: LabelId = 1;
+    const AGE: PropId = 1;
+    const NAME: PropId = 2;
+    const EDGE_LABEL: LabelId = 2;
+    const WEIGHT: PropId = 1;
+
+    pub struct IndexTester<G: GraphStorage> {
+        graph: G,
+    }
+
+    impl<G: GraphStorage> IndexTester<G> {
+        pub fn new(graph: G) -> Self {
+            IndexTester {
+                graph,
+            }
+        }
+
+        pub fn execute(&self) {
+            self.graph.create_vertex_type(1, 1, VERTEX_LABEL, &vertex_type_def(), 1).unwrap();
+            // age and name of all vertices alive at each si
+            let mut snapshots = Vec::new();
+            let mut vertices = HashMap::new();
+            for id in 1..=20 {
+                self.insert_vertex(2, id, Some(id), &format!("name_{}", id % 3), &mut vertices);
+            }
+            snapshots.push((2, vertices.clone()));
+
+            // existing vertices are put into the new index
+            assert!(self.graph.create_vertex_index(3, 2, VERTEX_LABEL, AGE).unwrap());
+            assert!(self.graph.create_vertex_index(3, 3, VERTEX_LABEL, NAME).unwrap());
+            assert!(self.graph.create_vertex_index(3, 4, VERTEX_LABEL, AGE).is_err());
+            assert!(!self.graph.create_vertex_index(3, 3, VERTEX_LABEL, AGE).unwrap());
+            let graph_def = self.graph.get_graph_def_blob().unwrap();
+            assert!(!graph_def.is_empty());
+            snapshots.push((3, vertices.clone()));
+
+            for id in 1..=5 {
+                self.insert_vertex(4, id, Some(id + 100), "name_x", &mut vertices);
+            }
+            // a vertex without the indexed property is not in the index
+            self.insert_vertex(4, 6, None, "name_x", &mut vertices);
+            for id in 16..=20 {
+                self.graph.delete_vertex(4, id, VERTEX_LABEL).unwrap();
+                vertices.remove(&id);
+            }
+            // updating another property doesn't change the entry
+            let mut properties = HashMap::new();
+            properties.insert(NAME, Value::string("name_y"));
+            self.graph.insert_update_vertex(4, 7, VERTEX_LABEL, &properties).unwrap();
+            vertices.get_mut(&7).unwrap().1 = "name_y".to_string();
+            for id in 21..=25 {
+                self.insert_vertex(4, id, Some(id), "name_0", &mut vertices);
+            }
+            snapshots.push((4, vertices.clone()));
+
+            assert!(self.graph.drop_vertex_index(5, 4, VERTEX_LABEL, AGE).unwrap());
+            assert!(self.graph.drop_vertex_index(5, 5, VERTEX_LABEL, AGE).is_err());
+            self.insert_vertex(5, 8, Some(200), "name_0", &mut vertices);
+            snapshots.push((5, vertices.clone()));
+
+            // a new index on the same property doesn't see the data of the dropped one
+            assert!(self.graph.create_vertex_index(6, 5, VERTEX_LABEL, AGE).unwrap());
+            self.insert_vertex(6, 9, Some(300), "name_0", &mut vertices);
+            snapshots.push((6, vertices.clone()));
+
+            for (si, vertices) in &snapshots {
+                self.check_snapshot(*si, vertices);
+            }
+            self.graph.gc(5);
+            for (si, vertices) in &snapshots[3..] {
+                self.check_snapshot(*si, vertices);
+            }
+        }
+
+        fn insert_vertex(&self, si: SnapshotId, id: VertexId, age: Option<i64>, name: &str, vertices: &mut HashMap<VertexId, (Option<i64>, String)>) {
+            let mut properties = HashMap::new();
+            if let Some(age) = age {
+                properties.insert(AGE, Value::long(age));
+            }
+            properties.insert(NAME, Value::string(name));
+            self.graph.insert_overwrite_vertex(si, id, VERTEX_LABEL, &properties).unwrap();
+            vertices.insert(id, (age, name.to_string()));
+        }
+
+        fn check_snapshot(&self, si: SnapshotId, vertices: &HashMap<VertexId, (Option<i64>, String)>) {
+            let age_in = |lo: i64, hi: i64| -> HashSet<VertexId> {
+                vertices.iter().filter(|(_, (age, _))| age.map_or(false, |a| a >= lo && a <= hi)).map(|(id, _)| *id).collect()
+            };
+            let name_is = |name: &str| -> HashSet<VertexId> {
+                vertices.iter().filter(|(_, (_, n))| n == name).map(|(id, _)| *id).collect()
+            };
+
+            let condition = predicate(AGE, ComparisonOp::Equal, Value::int(3));
+            self.check_query_vertices(si, condition, age_in(3, 3));
+            let condition = predicate(AGE, ComparisonOp::GreaterThan, Value::double(10.5));
+            self.check_query_vertices(si, condition, age_in(11, i64::max_value()));
+            let condition = predicate(AGE, ComparisonOp::LessEqual, Value::long(8));
+            self.check_query_vertices(si, condition, age_in(i64::min_value(), 8));
+            let condition = ConditionBuilder::new()
+                .and(predicate(AGE, ComparisonOp::GreaterEqual, Value::long(5)))
+                .and(predicate(AGE, ComparisonOp::LessThan, Value::long(12)))
+                .and(predicate(NAME, ComparisonOp::NotEqual, Value::string("name_1")))
+                .build().unwrap();
+            let expected = age_in(5, 11).intersection(&vertices.iter()
+                .filter(|(_, (_, n))| n != "name_1").map(|(id, _)| *id).collect()).cloned().collect();
+            self.check_query_vertices(si, condition, expected);
+            let values = vec![Value::int(3), Value::double(3.0), Value::long(102), Value::long(1000)];
+            let condition = Condition::In(SetCondition::new(AGE, values));
+            self.check_query_vertices(si, condition, age_in(3, 3).union(&age_in(102, 102)).cloned().collect());
+            // a string never equals to a number
+            let condition = predicate(AGE, ComparisonOp::Equal, Value::string("3"));
+            self.check_query_vertices(si, condition, HashSet::new());
+
+            let condition = predicate(NAME, ComparisonOp::Equal, Value::string("name_0"));
+            self.check_query_vertices(si, condition, name_is("name_0"));
+            let condition = predicate(NAME, ComparisonOp::LessThan, Value::string("name_1"));
+            let expected = vertices.iter().filter(|(_, (_, n))| n.as_str() < "name_1").map(|(id, _)| *id).collect();
+            self.check_query_vertices(si, condition, expected);
+            let condition = ConditionBuilder::new()
+                .and(predicate(NAME, ComparisonOp::Equal, Value::string("name_x")))
+                .or(predicate(AGE, ComparisonOp::Equal, Value::long(1)))
+                .build().unwrap();
+            let expected = name_is("name_x").union(&age_in(1, 1)).cloned().collect();
+            self.check_query_vertices(si, condition, expected);
+        }
+
+        fn check_query_vertices(&self, si: SnapshotId, condition: Condition, expected: HashSet<VertexId>) {
+            let condition = Arc::new(condition);
+            for label in vec![Some(VERTEX_LABEL), None] {
+                let mut iter = self.graph.query_vertices(si, label, Some(condition.clone())).unwrap();
+                let mut ids = HashSet::new();
+                while let Some(v) = iter.next() {
+                    assert!(ids.insert(v.get_id()));
+                }
+                iter.ok().unwrap();
+                assert_eq!(ids, expected, "si#{} label {:?}", si, label);
+            }
+        }
+    }
+
+    pub struct EdgeIndexTester<G: GraphStorage> {
+        graph: G,
+        edge_kinds: Vec<EdgeKind>,
+    }
+
+    impl<G: GraphStorage> EdgeIndexTester<G> {
+        pub fn new(graph: G) -> Self {
+            let edge_kinds = vec![EdgeKind::new(EDGE_LABEL, 10, 11), EdgeKind::new(EDGE_LABEL, 10, 12)];
+            EdgeIndexTester {
+                graph,
+                edge_kinds,
+            }
+        }
+
+        pub fn execute(&self) {
+            self.graph.create_edge_type(1, 1, EDGE_LABEL, &edge_type_def()).unwrap();
+            self.graph.add_edge_kind(1, 2, &self.edge_kinds[0], 2).unwrap();
+            self.graph.add_edge_kind(1, 3, &self.edge_kinds[1], 3).unwrap();
+            // weight of all forward edges alive at each si
+            let mut snapshots = Vec::new();
+            let mut edges = HashMap::new();
+            for id in 1..=20 {
+                self.insert_edge(2, id, Some(id), &mut edges);
+            }
+            snapshots.push((2, edges.clone()));
+
+            // existing edges of all kinds are put into the new index
+            assert!(self.graph.create_edge_index(3, 4, EDGE_LABEL, WEIGHT).unwrap());
+            assert!(self.graph.create_edge_index(3, 5, EDGE_LABEL, WEIGHT).is_err());
+            assert!(!self.graph.create_edge_index(3, 4, EDGE_LABEL, WEIGHT).unwrap());
+            snapshots.push((3, edges.clone()));
+
+            for id in 1..=5 {
+                self.insert_edge(4, id, Some(id + 100), &mut edges);
+            }
+            // an edge without the indexed property is not in the index
+            self.insert_edge(4, 6, None, &mut edges);
+            for id in 16..=20 {
+                let edge_id = edge_id(id);
+                let edge_kind = self.edge_kind(id);
+                self.graph.delete_edge(4, edge_id, edge_kind, true).unwrap();
+                self.graph.delete_edge(4, edge_id, edge_kind, false).unwrap();
+                edges.remove(&edge_id);
+            }
+            // a backward edge is never returned by `query_edges`, so it's not indexed
+            let mut properties = HashMap::new();
+            properties.insert(WEIGHT, Value::long(3));
+            self.graph.insert_overwrite_edge(4, edge_id(30), self.edge_kind(30), false, &properties).unwrap();
+            snapshots.push((4, edges.clone()));
+
+            assert!(self.graph.drop_edge_index(5, 5, EDGE_LABEL, WEIGHT).unwrap());
+            assert!(self.graph.drop_edge_index(5, 6, EDGE_LABEL, WEIGHT).is_err());
+            self.insert_edge(5, 7, Some(200), &mut edges);
+            snapshots.push((5, edges.clone()));
+
+            // a new index on the same property doesn't see the data of the dropped one
+            assert!(self.graph.create_edge_index(6, 6, EDGE_LABEL, WEIGHT).unwrap());
+            self.insert_edge(6, 8, Some(300), &mut edges);
+            snapshots.push((6, edges.clone()));
+
+            for (si, edges) in &snapshots {
+                self.check_snapshot(*si, edges);
+            }
+            self.graph.gc(5);
+            for (si, edges) in &snapshots[3..] {
+                self.check_snapshot(*si, edges);
+            }
+        }
+
+        fn edge_kind(&self, id: i64) -> &EdgeKind {
+            &self.edge_kinds[id as usize % self.edge_kinds.len()]
+        }
+
+        /// insert both directions of the edge
+        fn insert_edge(&self, si: SnapshotId, id: i64, weight: Option<i64>, edges: &mut HashMap<EdgeId, Option<i64>>) {
+            let mut properties = HashMap::new();
+            if let Some(weight) = weight {
+                properties.insert(WEIGHT, Value::long(weight));
+            }
+            let edge_id = edge_id(id);
+            for forward in vec![true, false] {
+                self.graph.insert_overwrite_edge(si, edge_id, self.edge_kind(id), forward, &properties).unwrap();
+            }
+            edges.insert(edge_id, weight);
+        }
+
+        fn check_snapshot(&self, si: SnapshotId, edges: &HashMap<EdgeId, Option<i64>>) {
+            let weight_in = |lo: i64, hi: i64| -> HashSet<EdgeId> {
+                edges.iter().filter(|(_, weight)| weight.map_or(false, |w| w >= lo && w <= hi)).map(|(id, _)| *id).collect()
+            };
+
+            let condition = predicate(WEIGHT, ComparisonOp::Equal, Value::int(3));
+            self.check_query_edges(si, condition, weight_in(3, 3));
+            let condition = predicate(WEIGHT, ComparisonOp::GreaterThan, Value::double(10.5));
+            self.check_query_edges(si, condition, weight_in(11, i64::max_value()));
+            let condition = predicate(WEIGHT, ComparisonOp::LessEqual, Value::long(8));
+            self.check_query_edges(si, condition, weight_in(i64::min_value(), 8));
+            let condition = ConditionBuilder::new()
+                .and(predicate(WEIGHT, ComparisonOp::GreaterEqual, Value::long(5)))
+                .and(predicate(WEIGHT, ComparisonOp::LessThan, Value::long(12)))
+                .build().unwrap();
+            self.check_query_edges(si, condition, weight_in(5, 11));
+            let condition = ConditionBuilder::new()
+                .and(predicate(WEIGHT, ComparisonOp::Equal, Value::long(1)))
+                .or(predicate(WEIGHT, ComparisonOp::Equal, Value::long(300)))
+                .build().unwrap();
+            let expected = weight_in(1, 1).union(&weight_in(300, 300)).cloned().collect();
+            self.check_query_edges(si, condition, expected);
+        }
+
+        fn check_query_edges(&self, si: SnapshotId, condition: Condition, expected: HashSet<EdgeId>) {
+            let condition = Arc::new(condition);
+            for label in vec![Some(EDGE_LABEL), None] {
+                let mut iter = self.graph.query_edges(si, label, Some(condition.clone())).unwrap();
+                let mut ids = HashSet::new();
+                while let Some(e) = iter.next() {
+                    assert_eq!(e.get_kind(), self.edge_kind(e.get_id().inner_id));
+                    assert!(ids.insert(*e.get_id()));
+                }
+                iter.ok().unwrap();
+                assert_eq!(ids, expected, "si#{} label {:?}", si, label);
+            }
+        }
+    }
+
+    fn edge_id(id: i64) -> EdgeId {
+        EdgeId::new(id, id + 1000, id)
+    }
+
+    fn edge_type_def() -> TypeDef {
+        let mut builder = TypeDefBuilder::new();
+        builder.add_property(WEIGHT, 1, "weight".to_string(), ValueType::Long, None, false, "cmt".to_string());
+        builder.set_label_id(EDGE_LABEL);
+        builder.build()
+    }
+
+    fn predicate(prop: PropId, op: ComparisonOp, value: Value) -> Condition {
+        Condition::Predicate(PredicateCondition::new(prop, op, value))
+    }
+
+    fn vertex_type_def() -> TypeDef {
+        let mut builder = TypeDefBuilder::new();
+        builder.add_property(AGE, 1, "age".to_string(), ValueType::Long, None, false, "cmt".to_string());
+        builder.add_property(NAME, 3, "name".to_string(), ValueType::String, None, false, "cmt".to_string());
+        builder.set_label_id(VERTEX_LABEL);
+        builder.build()
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Write;
+
+use crate::db::api::*;
+use crate::db::graph::loader::CsvLoader;
+use crate::db::graph::store::GraphStore;
+use crate::db::proto::model::TypeEnumPb;
+
+const PERSON: LabelId = 1;
+const KNOWS: LabelId = 2;
+const NAME: PropId = 1;
+const AGE: PropId = 2;
+const TAGS: PropId = 3;
+const WEIGHT: PropId = 4;
+
+pub fn test_csv_loader(graph: GraphStore, test_dir: &str) {
+    graph.create_vertex_type(1, 1, PERSON, &person_type_def(), 1).unwrap();
+    graph.create_edge_type(1, 2, KNOWS, &knows_type_def()).unwrap();
+    let edge_kind = EdgeKind::new(KNOWS, PERSON, PERSON);
+    graph.add_edge_kind(1, 3, &edge_kind, 2).unwrap();
+    let mut properties = HashMap::new();
+    properties.insert(NAME, Value::string("old"));
+    graph.insert_overwrite_vertex(2, 100, PERSON, &properties).unwrap();
+
+    let loader = CsvLoader::new(&graph);
+    let person_config = loader.parse_config(r#"{
+        "label": "person",
+        "separator": "|",
+        "type": "vertex",
+        "properties": [
+            {"propertyName": "name", "dataType": "string", "index": "1"},
+            {"propertyName": "age", "dataType": "int", "index": "2"},
+            {"propertyName": "tags", "dataType": "list<string>", "index": "3"}
+        ]
+    }"#).unwrap();
+    let person_1 = write_file(test_dir, "person_1.csv", &["1|tom|20|a;b", "2|jerry||c", "", "3|spike|30|"]);
+    let person_2 = write_file(test_dir, "person_2.csv", &["4|tyke|3|d;e;f"]);
+    let sst = format!("{}/load.sst", test_dir);
+    assert_eq!(loader.load(3, &person_config, &[&person_1, &person_2], &sst).unwrap(), 4);
+
+    // loaded data replaces the data of the type at the si of the load
+    assert!(graph.get_vertex(2, 1, Some(PERSON)).unwrap().is_none());
+    assert!(graph.get_vertex(2, 100, Some(PERSON)).unwrap().is_some());
+    assert!(graph.get_vertex(3, 100, Some(PERSON)).unwrap().is_none());
+    let v = graph.get_vertex(3, 1, Some(PERSON)).unwrap().unwrap();
+    assert_eq!(v.get_property(NAME).unwrap().get_str().unwrap(), "tom");
+    assert_eq!(v.get_property(AGE).unwrap().get_int().unwrap(), 20);
+    let tags = v.get_property(TAGS).unwrap();
+    assert_eq!(tags.get_str_list().unwrap().iter().collect::<Vec<&str>>(), vec!["a", "b"]);
+    let v = graph.get_vertex(3, 2, Some(PERSON)).unwrap().unwrap();
+    assert!(v.get_property(AGE).is_none());
+    let v = graph.get_vertex(3, 3, Some(PERSON)).unwrap().unwrap();
+    assert!(v.get_property(TAGS).is_none());
+    let mut iter = graph.query_vertices(3, Some(PERSON), None).unwrap();
+    let mut ids = HashSet::new();
+    while let Some(v) = iter.next() {
+        ids.insert(v.get_id());
+    }
+    assert_eq!(ids, vec![1, 2, 3, 4].into_iter().collect());
+
+    // realtime writes go on after the load
+    graph.insert_overwrite_vertex(4, 5, PERSON, &properties).unwrap();
+    assert!(graph.get_vertex(4, 5, Some(PERSON)).unwrap().is_some());
+    assert!(graph.get_vertex(4, 1, Some(PERSON)).unwrap().is_some());
+
+    let knows_config = loader.parse_config(r#"{
+        "label": "knows",
+        "type": "edge",
+        "srcLabel": "person",
+        "dstLabel": "person",
+        "properties": [
+            {"propertyName": "weight", "dataType": "double", "index": "2"}
+        ]
+    }"#).unwrap();
+    let knows = write_file(test_dir, "knows.csv", &["1,2,0.5", "1,3,1.5", "2,3,"]);
+    assert_eq!(loader.load(5, &knows_config, &[&knows], &sst).unwrap(), 3);
+    let mut iter = graph.get_out_edges(5, 1, Some(KNOWS), None).unwrap();
+    let mut weights = HashMap::new();
+    while let Some(e) = iter.next() {
+        weights.insert(e.get_dst_id(), e.get_property(WEIGHT).unwrap().get_double().unwrap());
+    }
+    assert_eq!(weights, vec![(2, 0.5), (3, 1.5)].into_iter().collect());
+    let mut iter = graph.get_in_edges(5, 3, Some(KNOWS), None).unwrap();
+    let mut src_ids = HashSet::new();
+    while let Some(e) = iter.next() {
+        src_ids.insert(e.get_src_id());
+    }
+    assert_eq!(src_ids, vec![1, 2].into_iter().collect());
+
+    // a bad row fails the whole load and leaves the graph untouched
+    let bad = write_file(test_dir, "bad.csv", &["6|tom|20|", "x|jerry|10|"]);
+    assert!(loader.load(6, &person_config, &[&bad], &sst).is_err());
+    let bad = write_file(test_dir, "bad.csv", &["6|tom|twenty|"]);
+    assert!(loader.load(6, &person_config, &[&bad], &sst).is_err());
+    assert!(graph.get_vertex(6, 6, Some(PERSON)).unwrap().is_none());
+    assert!(graph.get_vertex(6, 5, Some(PERSON)).unwrap().is_some());
+    // data type in config must match the schema
+    let config = loader.parse_config(r#"{
+        "label": "person",
+        "type": "vertex",
+        "properties": [{"propertyName": "age", "dataType": "long", "index": "1"}]
+    }"#).unwrap();
+    assert!(loader.load(6, &config, &[&person_2], &sst).is_err());
+    assert!(loader.parse_config(r#"{"label": "software", "properties": []}"#).is_err());
+}
+
+fn write_file(dir: &str, name: &str, lines: &[&str]) -> String {
+    let path = format!("{}/{}", dir, name);
+    let mut file = File::create(&path).unwrap();
+    for line in lines {
+        writeln!(file, "{}", line).unwrap();
+    }
+    path
+}
+
+fn person_type_def() -> TypeDef {
+    let mut builder = TypeDefBuilder::new();
+    builder.add_property(NAME, 1, "name".to_string(), ValueType::String, None, false, "cmt".to_string());
+    builder.add_property(AGE, 2, "age".to_string(), ValueType::Int, None, false, "cmt".to_string());
+    builder.add_property(TAGS, 3, "tags".to_string(), ValueType::StringList, None, false, "cmt".to_string());
+    builder.set_label_id(PERSON).set_label("person");
+    builder.build()
+}
+
+fn knows_type_def() -> TypeDef {
+    let mut builder = TypeDefBuilder::new();
+    builder.add_property(WEIGHT, 4, "weight".to_string(), ValueType::Double, None, false, "cmt".to_string());
+    builder.set_label_id(KNOWS).set_label("knows").set_type_enum(TypeEnumPb::EDGE);
+    builder.build()
+}
@@ -5,4 +5,10 @@ pub mod vertex;
 pub mod edge;
 pub mod graph;
 pub mod backup;
-
+pub mod gc;
+pub mod condition;
+pub mod adjacency;
+pub mod index;
+pub mod loader;
+pub mod alter;
+pub mod ttl;
@@ -0,0 +1,120 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::db::api::*;
+use crate::db::graph::store::GraphStore;
+use crate::db::proto::model::TypeEnumPb;
+use crate::db::util::time::sleep_ms;
+
+const PERSON: LabelId = 1;
+const SESSION: LabelId = 2;
+const CLICK: LabelId = 3;
+const NAME: PropId = 1;
+const TOKEN: PropId = 2;
+const PAGE: PropId = 3;
+const TTL: i64 = 1000;
+
+pub fn test_ttl<F: Fn(GraphStore) -> GraphStore>(graph: GraphStore, reopen: F) {
+    let edge_kind = EdgeKind::new(CLICK, PERSON, PERSON);
+    graph.create_vertex_type(1, 1, PERSON, &type_def(PERSON, "person", NAME, "name", None), 1).unwrap();
+    graph.create_vertex_type(1, 2, SESSION, &type_def(SESSION, "session", TOKEN, "token", Some(TTL)), 2).unwrap();
+    graph.create_edge_type(1, 3, CLICK, &type_def(CLICK, "click", PAGE, "page", Some(TTL))).unwrap();
+    graph.add_edge_kind(1, 4, &edge_kind, 3).unwrap();
+    for id in 1..=3 {
+        graph.insert_overwrite_vertex(2, id, PERSON, &properties(NAME, "p")).unwrap();
+    }
+    graph.insert_overwrite_vertex(2, 1, SESSION, &properties(TOKEN, "a")).unwrap();
+    graph.insert_overwrite_vertex(2, 2, SESSION, &properties(TOKEN, "b")).unwrap();
+    graph.insert_overwrite_edge(2, EdgeId::new(1, 2, 1), &edge_kind, true, &properties(PAGE, "x")).unwrap();
+    graph.insert_overwrite_edge(2, EdgeId::new(1, 3, 2), &edge_kind, true, &properties(PAGE, "y")).unwrap();
+
+    assert_eq!(query_vertex_ids(&graph, 2, SESSION), vec![1, 2].into_iter().collect());
+    assert_eq!(query_edge_ids(&graph, 2), vec![2, 3].into_iter().collect());
+    assert_eq!(graph.get_out_degree(2, 1, &edge_kind).unwrap(), 2);
+    assert!(graph.get_kth_out_edge(2, 1, &edge_kind, 1).unwrap().is_some());
+
+    sleep_ms(TTL as u64 + 100);
+    graph.insert_overwrite_vertex(3, 3, SESSION, &properties(TOKEN, "c")).unwrap();
+    graph.insert_overwrite_edge(3, EdgeId::new(1, 2, 1), &edge_kind, true, &properties(PAGE, "z")).unwrap();
+    check_expired(&graph, 3);
+    // records expire at all snapshots
+    assert!(graph.get_vertex(2, 1, Some(SESSION)).unwrap().is_none());
+    assert!(query_vertex_ids(&graph, 2, SESSION).is_empty());
+
+    // an expired record doesn't contribute properties to updates
+    let mut builder = TypeDefBuilder::new();
+    builder.add_property(TOKEN, 2, "token".to_string(), ValueType::String, None, false, "cmt".to_string());
+    builder.add_property(NAME, 1, "name".to_string(), ValueType::String, None, false, "cmt".to_string());
+    builder.set_label_id(SESSION).set_label("session").set_ttl(TTL);
+    assert!(graph.alter_vertex_type(4, 5, SESSION, &builder.build()).unwrap());
+    graph.insert_update_vertex(5, 1, SESSION, &properties(NAME, "n")).unwrap();
+    let v = graph.get_vertex(5, 1, Some(SESSION)).unwrap().unwrap();
+    assert!(v.get_property(TOKEN).is_none());
+    assert_eq!(v.get_property(NAME).unwrap().get_str().unwrap(), "n");
+
+    // ttl cannot be altered
+    let mut builder = TypeDefBuilder::new();
+    builder.add_property(TOKEN, 2, "token".to_string(), ValueType::String, None, false, "cmt".to_string());
+    builder.set_label_id(SESSION).set_label("session");
+    assert!(graph.alter_vertex_type(6, 6, SESSION, &builder.build()).is_err());
+
+    let graph = reopen(graph);
+    let graph_def = graph.get_graph_def().unwrap();
+    assert_eq!(graph_def.label_to_types.get(&SESSION).unwrap().get_ttl(), Some(TTL));
+    assert_eq!(graph_def.label_to_types.get(&CLICK).unwrap().get_ttl(), Some(TTL));
+    assert!(graph_def.label_to_types.get(&PERSON).unwrap().get_ttl().is_none());
+    check_expired(&graph, 5);
+}
+
+fn check_expired(graph: &GraphStore, si: SnapshotId) {
+    let edge_kind = EdgeKind::new(CLICK, PERSON, PERSON);
+    assert!(graph.get_vertex(si, 2, Some(SESSION)).unwrap().is_none());
+    assert!(graph.get_vertex(si, 3, Some(SESSION)).unwrap().is_some());
+    assert_eq!(query_vertex_ids(graph, si, PERSON), vec![1, 2, 3].into_iter().collect());
+    assert!(!query_vertex_ids(graph, si, SESSION).contains(&2));
+    assert!(graph.get_edge(si, EdgeId::new(1, 3, 2), Some(&edge_kind)).unwrap().is_none());
+    let e = graph.get_edge(si, EdgeId::new(1, 2, 1), Some(&edge_kind)).unwrap().unwrap();
+    assert_eq!(e.get_property(PAGE).unwrap().get_str().unwrap(), "z");
+    assert_eq!(query_edge_ids(graph, si), vec![2].into_iter().collect());
+    assert_eq!(graph.get_out_degree(si, 1, &edge_kind).unwrap(), 1);
+    assert_eq!(graph.get_in_degree(si, 3, &edge_kind).unwrap(), 0);
+    let e = graph.get_kth_out_edge(si, 1, &edge_kind, 0).unwrap().unwrap();
+    assert_eq!(*e.get_id(), EdgeId::new(1, 2, 1));
+    assert!(graph.get_kth_out_edge(si, 1, &edge_kind, 1).unwrap().is_none());
+}
+
+fn query_vertex_ids(graph: &GraphStore, si: SnapshotId, label: LabelId) -> HashSet<VertexId> {
+    let mut iter = graph.query_vertices(si, Some(label), None).unwrap();
+    let mut ids = HashSet::new();
+    while let Some(v) = iter.next() {
+        ids.insert(v.get_id());
+    }
+    ids
+}
+
+fn query_edge_ids(graph: &GraphStore, si: SnapshotId) -> HashSet<VertexId> {
+    let mut iter = graph.get_out_edges(si, 1, Some(CLICK), None).unwrap();
+    let mut ids = HashSet::new();
+    while let Some(e) = iter.next() {
+        ids.insert(e.get_dst_id());
+    }
+    ids
+}
+
+fn properties(prop_id: PropId, value: &str) -> HashMap<PropId, Value> {
+    let mut ret = HashMap::new();
+    ret.insert(prop_id, Value::string(value));
+    ret
+}
+
+fn type_def(label: LabelId, name: &str, prop_id: PropId, prop_name: &str, ttl: Option<i64>) -> TypeDef {
+    let mut builder = TypeDefBuilder::new();
+    builder.add_property(prop_id, prop_id, prop_name.to_string(), ValueType::String, None, false, "cmt".to_string());
+    builder.set_label_id(label).set_label(name);
+    if label == CLICK {
+        builder.set_type_enum(TypeEnumPb::EDGE);
+    }
+    if let Some(ttl) = ttl {
+        builder.set_ttl(ttl);
+    }
+    builder.build()
+}
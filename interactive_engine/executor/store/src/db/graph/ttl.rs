@@ -0,0 +1,85 @@
+//! Physical reclamation of expired records.
+//!
+//! Records of a type with ttl carry their write time (see `Codec`), and readers skip the expired ones,
+//! so they are invisible as soon as they expire. The compaction filter here drops them when rocksdb
+//! compacts them. A record only depends on the versions older than it, which are written earlier and
+//! expire no later than it, so dropping an expired version never makes an older one visible again.
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::db::storage::rocksdb::CompactionFilter;
+use crate::db::util::time::current_time_millis;
+use super::bin::parse_data_table_id;
+use super::codec::get_write_time;
+use super::table_manager::TableId;
+
+pub struct TtlCompactionFilter {
+    // table id to the ttl of the type which owns the table
+    tables: RwLock<HashMap<TableId, i64>>,
+}
+
+impl TtlCompactionFilter {
+    pub fn new() -> Self {
+        TtlCompactionFilter {
+            tables: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn set_ttl(&self, table_id: TableId, ttl: i64) {
+        self.tables.write().unwrap().insert(table_id, ttl);
+    }
+
+    fn get_ttl(&self, table_id: TableId) -> Option<i64> {
+        self.tables.read().unwrap().get(&table_id).cloned()
+    }
+}
+
+impl CompactionFilter for TtlCompactionFilter {
+    fn should_remove(&self, key: &[u8], value: &[u8]) -> bool {
+        // tombstones must be kept, or older versions would be visible again
+        if value.len() < 4 {
+            return false;
+        }
+        match parse_data_table_id(key).and_then(|table_id| self.get_ttl(table_id)) {
+            Some(ttl) => get_write_time(value) + ttl <= current_time_millis(),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::api::*;
+    use crate::db::graph::bin::*;
+    use crate::db::graph::codec::*;
+
+    #[test]
+    fn test_ttl_compaction_filter() {
+        let mut builder = TypeDefBuilder::new();
+        builder.version(1).set_ttl(1000);
+        builder.add_property(1, 1, "1".to_string(), ValueType::Long, None, false, "cmt".to_string());
+        let codec = Codec::from(&builder.build());
+        let guard = ::crossbeam_epoch::pin();
+        let encoder = Encoder::new(unsafe { std::mem::transmute(&codec) }, guard);
+        let mut properties = HashMap::new();
+        properties.insert(1, Value::long(10));
+        let mut buf = Vec::new();
+        encoder.encode(&properties, &mut buf).unwrap();
+        let mut expired = buf.clone();
+        let len = expired.len();
+        expired[len - 8..].copy_from_slice(&(current_time_millis() - 1000).to_be_bytes());
+
+        let filter = TtlCompactionFilter::new();
+        let key = vertex_key(10, 1, 1);
+        assert!(!filter.should_remove(&key, &expired));
+        filter.set_ttl(10, 1000);
+        assert!(filter.should_remove(&key, &expired));
+        assert!(!filter.should_remove(&key, &buf));
+        assert!(!filter.should_remove(&key, &[]));
+        // only data of the table is filtered
+        assert!(!filter.should_remove(&vertex_key(12, 1, 1), &expired));
+        let index_key = degree_key(10, 1, EdgeDirection::Out, 1);
+        assert!(!filter.should_remove(&index_key, &expired));
+    }
+}
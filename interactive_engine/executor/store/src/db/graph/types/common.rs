@@ -1,7 +1,8 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use crate::db::common::concurrency::volatile::Volatile;
-use crate::db::api::{SnapshotId, GraphResult};
+use crate::db::api::{SnapshotId, GraphResult, PropId};
 use super::super::codec::*;
 use super::super::table_manager::*;
 
@@ -48,8 +49,28 @@ impl TypeCommon {
         self.codec_manager.add_codec(si, codec)
     }
 
-    pub fn gc(&self, _si: SnapshotId) {
-        unimplemented!()
+    /// Remove the table versions and codec versions which are invisible to any snapshot not less than
+    /// `si`, and return ids of the removed tables so that their data can be deleted.
+    pub fn gc(&self, si: SnapshotId) -> GraphResult<Vec<TableId>> {
+        let tables = res_unwrap!(self.table_manager.gc(si), gc, si)?;
+        res_unwrap!(self.codec_manager.gc(si), gc, si)?;
+        Ok(tables)
+    }
+
+    pub fn get_all_tables(&self) -> Vec<Table> {
+        self.table_manager.get_all()
+    }
+
+    pub fn get_obsolete_codecs(&self) -> Vec<CodecVersion> {
+        self.codec_manager.get_obsolete_versions()
+    }
+
+    pub fn drop_codec(&self, version: CodecVersion) -> GraphResult<()> {
+        self.codec_manager.drop_codec(version)
+    }
+
+    pub fn get_all_inner_ids(&self) -> HashSet<PropId> {
+        self.codec_manager.get_all_inner_ids()
     }
 }
 
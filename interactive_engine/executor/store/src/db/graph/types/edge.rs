@@ -2,7 +2,7 @@
 use ::crossbeam_epoch as epoch;
 use ::crossbeam_epoch::{Atomic, Owned, Guard};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use std::ops::Deref;
@@ -10,14 +10,16 @@ use std::collections::hash_map::Values;
 
 use crate::db::api::*;
 use crate::db::common::unsafe_util;
-use super::super::table_manager::Table;
+use super::super::table_manager::{Table, TableId};
 use super::super::codec::*;
 use super::common::*;
+use super::index::*;
 
 pub struct EdgeKindInfo {
     edge_kind: EdgeKind,
     lifetime: LifeTime,
     info: TypeCommon,
+    indexes: Arc<IndexManager>,
 }
 
 impl EdgeKindInfo {
@@ -33,8 +35,12 @@ impl EdgeKindInfo {
         res_unwrap!(self.info.online_table(table), online_table)
     }
 
-    pub fn gc(&self, si: SnapshotId) {
-        self.info.gc(si)
+    pub fn gc(&self, si: SnapshotId) -> GraphResult<Vec<TableId>> {
+        res_unwrap!(self.info.gc(si), gc, si)
+    }
+
+    pub fn get_all_tables(&self) -> Vec<Table> {
+        self.info.get_all_tables()
     }
 
     pub fn get_decoder(&self, si: SnapshotId, version: CodecVersion) -> GraphResult<Decoder> {
@@ -49,11 +55,20 @@ impl EdgeKindInfo {
         self.lifetime.is_alive_at(si)
     }
 
-    fn new(si: SnapshotId, edge_kind: EdgeKind, codec_manager: Arc<CodecManager>) -> Self {
+    pub fn get_index(&self, si: SnapshotId, prop_id: PropId) -> Option<Arc<IndexInfo>> {
+        self.indexes.get(si, prop_id)
+    }
+
+    pub fn get_indexes(&self, si: SnapshotId) -> Vec<Arc<IndexInfo>> {
+        self.indexes.get_all(si)
+    }
+
+    fn new(si: SnapshotId, edge_kind: EdgeKind, codec_manager: Arc<CodecManager>, indexes: Arc<IndexManager>) -> Self {
         EdgeKindInfo {
             edge_kind,
             lifetime: LifeTime::new(si),
             info: TypeCommon::init_with_codec_manager(codec_manager),
+            indexes,
         }
     }
 }
@@ -80,11 +95,13 @@ impl Deref for EdgeKindInfoRef {
     }
 }
 
+/// All kinds of an edge type share its codecs and its indexes, while each kind has its own tables.
 #[derive(Clone)]
 pub struct EdgeInfo {
     label: LabelId,
     lifetime: LifeTime,
     codec_manager: Arc<CodecManager>,
+    indexes: Arc<IndexManager>,
     kinds: Vec<Arc<EdgeKindInfo>>,
 }
 
@@ -94,6 +111,7 @@ impl EdgeInfo {
             label,
             lifetime: LifeTime::new(start_si),
             codec_manager: Arc::new(CodecManager::new()),
+            indexes: Arc::new(IndexManager::new()),
             kinds: Vec::new(),
         }
     }
@@ -110,6 +128,48 @@ impl EdgeInfo {
         self.lifetime.is_alive_at(si)
     }
 
+    pub fn get_label(&self) -> LabelId {
+        self.label
+    }
+
+    /// all edge kinds of this label which haven't been removed by gc, no matter whether they are
+    /// visible at a certain snapshot
+    pub fn get_all_kinds(&self) -> &[Arc<EdgeKindInfo>] {
+        &self.kinds
+    }
+
+    pub fn get_obsolete_codecs(&self) -> Vec<CodecVersion> {
+        self.codec_manager.get_obsolete_versions()
+    }
+
+    pub fn drop_codec(&self, version: CodecVersion) -> GraphResult<()> {
+        res_unwrap!(self.codec_manager.drop_codec(version), drop_codec, version)
+    }
+
+    pub fn get_all_inner_ids(&self) -> HashSet<PropId> {
+        self.codec_manager.get_all_inner_ids()
+    }
+
+    pub fn create_index(&self, si: SnapshotId, prop_id: PropId) -> GraphResult<Arc<IndexInfo>> {
+        res_unwrap!(self.indexes.create(si, prop_id), create_index, si, prop_id)
+    }
+
+    pub fn drop_index(&self, si: SnapshotId, prop_id: PropId) -> GraphResult<()> {
+        res_unwrap!(self.indexes.drop(si, prop_id), drop_index, si, prop_id)
+    }
+
+    pub fn get_index(&self, si: SnapshotId, prop_id: PropId) -> Option<Arc<IndexInfo>> {
+        self.indexes.get(si, prop_id)
+    }
+
+    pub fn get_indexes(&self, si: SnapshotId) -> Vec<Arc<IndexInfo>> {
+        self.indexes.get_all(si)
+    }
+
+    pub fn gc_indexes(&self, si: SnapshotId) -> GraphResult<Vec<Arc<IndexInfo>>> {
+        res_unwrap!(self.indexes.gc(si), gc_indexes, si)
+    }
+
     pub fn get_kinds(&self, si: SnapshotId) -> impl Iterator<Item=Arc<EdgeKindInfo>> + '_ {
         self.kinds.iter().filter_map(move |edge_kind| if edge_kind.is_alive_at(si) {
             Some(edge_kind.clone())
@@ -266,6 +326,13 @@ impl EdgeTypeManager {
         })
     }
 
+    /// Make the codec of `type_def` the codec of all kinds of edge type `label` since `si`
+    pub fn alter_edge_type(&self, si: SnapshotId, label: LabelId, type_def: &TypeDef) -> GraphResult<()> {
+        let guard = epoch::pin();
+        let inner = self.get_inner(&guard);
+        res_unwrap!(inner.alter_edge_type(si, label, type_def), alter_edge_type, si, label, type_def)
+    }
+
     pub fn drop_edge_type(&self, si: SnapshotId, label: LabelId) -> GraphResult<()> {
         self.modify(|inner| {
             res_unwrap!(inner.drop_edge_type(si, label), drop_edge, si, label)
@@ -284,9 +351,11 @@ impl EdgeTypeManager {
         })
     }
 
-    pub fn gc(&self, si: SnapshotId) {
+    /// Remove edge kinds which have been removed or whose label has been dropped at or before `si`,
+    /// and return them so that the data in their tables can be deleted.
+    pub fn gc(&self, si: SnapshotId) -> Vec<Arc<EdgeKindInfo>> {
         self.modify(|inner| {
-            inner.gc(si);
+            inner.gc(si)
         })
     }
 
@@ -325,6 +394,10 @@ impl EdgeManagerBuilder {
         self.inner.create_edge_type(si, label, type_def)
     }
 
+    pub fn alter_edge_type(&mut self, si: SnapshotId, label: LabelId, type_def: &TypeDef) -> GraphResult<()> {
+        self.inner.alter_edge_type(si, label, type_def)
+    }
+
     pub fn drop_edge_type(&mut self, si: SnapshotId, label: LabelId) -> GraphResult<()> {
         self.inner.drop_edge_type(si, label)
     }
@@ -337,6 +410,14 @@ impl EdgeManagerBuilder {
         self.inner.remove_edge_kind(si, kind)
     }
 
+    pub fn get_edge_info(&self, si: SnapshotId, label: LabelId) -> GraphResult<&EdgeInfo> {
+        self.inner.get_edge(si, label)
+    }
+
+    pub fn contains_edge(&self, label: LabelId) -> bool {
+        self.inner.contains_edge(label)
+    }
+
     pub fn add_edge_table(&mut self, si: SnapshotId, kind: &EdgeKind, table: Table) -> GraphResult<()> {
         let info = res_unwrap!(self.inner.get_edge_kind(si, kind), add_edge_table, si, kind, table)?;
         let info_mut = unsafe { unsafe_util::to_mut(info) };
@@ -441,6 +522,13 @@ impl EdgeManagerInner {
         Ok(())
     }
 
+    fn alter_edge_type(&self, si: SnapshotId, label: LabelId, type_def: &TypeDef) -> GraphResult<()> {
+        let info = res_unwrap!(self.get_edge(si, label), alter_edge_type, si, label, type_def)?;
+        let codec = Codec::from(type_def);
+        let res = info.add_codec(si, codec);
+        res_unwrap!(res, alter_edge_type, si, label, type_def)
+    }
+
     fn drop_edge_type(&mut self, si: SnapshotId, label: LabelId) -> GraphResult<()> {
         if let Some(info) = self.info_map.get(&label) {
             info.lifetime.set_end(si);
@@ -456,7 +544,8 @@ impl EdgeManagerInner {
 
     fn add_edge_kind(&mut self, si: SnapshotId, kind: &EdgeKind) -> GraphResult<()> {
         if let Some(edge_info) = self.info_map.get(&kind.edge_label_id) {
-            let type_info = Arc::new(EdgeKindInfo::new(si, kind.clone(), edge_info.codec_manager.clone()));
+            let type_info = Arc::new(EdgeKindInfo::new(si, kind.clone(), edge_info.codec_manager.clone(),
+                                                       edge_info.indexes.clone()));
             let info_mut = unsafe { &mut *(edge_info.as_ref() as *const EdgeInfo as *mut EdgeInfo) };
             info_mut.add_edge_kind(type_info.clone());
             if let Some(list) = self.type_map.get_mut(kind) {
@@ -483,23 +572,17 @@ impl EdgeManagerInner {
         Err(err)
     }
 
-    /// if gc nothing return false
-    #[allow(dead_code)]
-    fn gc(&mut self, si: SnapshotId) {
+    fn gc(&mut self, si: SnapshotId) -> Vec<Arc<EdgeKindInfo>> {
         let mut dropped_labels = Vec::new();
-        let mut dropped_types = Vec::new();
+        let mut dropped_kinds = Vec::new();
         for (label, info) in &self.info_map {
-            if !info.lifetime.is_alive_at(si) {
+            if info.lifetime.get_end() <= si {
                 dropped_labels.push(*label);
-                for t in &info.kinds {
-                    dropped_types.push(t.edge_kind.clone());
-                }
+                dropped_kinds.extend(info.kinds.iter().cloned());
             } else {
                 for t in &info.kinds {
-                    if !t.lifetime.is_alive_at(si) {
-                        dropped_types.push(t.edge_kind.clone());
-                    } else {
-                        t.gc(si);
+                    if t.lifetime.get_end() <= si {
+                        dropped_kinds.push(t.clone());
                     }
                 }
             }
@@ -507,8 +590,25 @@ impl EdgeManagerInner {
         for label in dropped_labels {
             self.info_map.remove(&label);
         }
-        for t in dropped_types {
-            self.type_map.remove(&t);
+        for info in self.info_map.values_mut() {
+            if info.kinds.iter().any(|t| t.lifetime.get_end() <= si) {
+                let mut info_clone = info.as_ref().clone();
+                info_clone.kinds.retain(|t| t.lifetime.get_end() > si);
+                *info = Arc::new(info_clone);
+            }
+        }
+        for t in &dropped_kinds {
+            let empty = match self.type_map.get_mut(&t.edge_kind) {
+                Some(list) => {
+                    list.retain(|x| !Arc::ptr_eq(x, t));
+                    list.is_empty()
+                }
+                None => false,
+            };
+            if empty {
+                self.type_map.remove(&t.edge_kind);
+            }
         }
+        dropped_kinds
     }
 }
@@ -0,0 +1,154 @@
+use ::crossbeam_epoch as epoch;
+use ::crossbeam_epoch::{Atomic, Owned, Guard};
+
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use crate::db::api::*;
+use crate::db::util::lock::GraphMutexLock;
+use super::common::LifeTime;
+
+/// A secondary index on a property. An index is identified by its property and the si it's created
+/// at, so that the data of a dropped index won't be mixed up with a new index on the same property.
+pub struct IndexInfo {
+    prop_id: PropId,
+    lifetime: LifeTime,
+}
+
+impl IndexInfo {
+    pub fn get_prop_id(&self) -> PropId {
+        self.prop_id
+    }
+
+    pub fn get_start_si(&self) -> SnapshotId {
+        self.lifetime.get_start()
+    }
+
+    pub fn is_alive_at(&self, si: SnapshotId) -> bool {
+        self.lifetime.is_alive_at(si)
+    }
+
+    fn new(si: SnapshotId, prop_id: PropId) -> Self {
+        IndexInfo {
+            prop_id,
+            lifetime: LifeTime::new(si),
+        }
+    }
+}
+
+type IndexList = Vec<Arc<IndexInfo>>;
+
+/// Every vertex type has an index manager to maintain the multi versions of its indexes. Readers are
+/// lock free and writers are serialized by a lock.
+pub struct IndexManager {
+    indexes: Atomic<IndexList>,
+    lock: GraphMutexLock<()>,
+}
+
+impl IndexManager {
+    pub fn new() -> Self {
+        IndexManager {
+            indexes: Atomic::new(IndexList::new()),
+            lock: GraphMutexLock::new(()),
+        }
+    }
+
+    pub fn create(&self, si: SnapshotId, prop_id: PropId) -> GraphResult<Arc<IndexInfo>> {
+        let _lock = res_unwrap!(self.lock.lock(), create, si, prop_id)?;
+        let guard = epoch::pin();
+        let indexes = self.get_list(&guard);
+        if indexes.iter().any(|index| index.prop_id == prop_id && index.lifetime.get_end() > si) {
+            let msg = format!("index on property#{} already exists", prop_id);
+            let err = gen_graph_err!(GraphErrorCode::InvalidOperation, msg, create, si, prop_id);
+            return Err(err);
+        }
+        let index = Arc::new(IndexInfo::new(si, prop_id));
+        let mut list_clone = indexes.clone();
+        list_clone.push(index.clone());
+        self.store(list_clone, &guard);
+        Ok(index)
+    }
+
+    pub fn drop(&self, si: SnapshotId, prop_id: PropId) -> GraphResult<()> {
+        let _lock = res_unwrap!(self.lock.lock(), drop, si, prop_id)?;
+        let guard = epoch::pin();
+        if let Some(index) = self.get_list(&guard).iter().find(|index| index.prop_id == prop_id && index.is_alive_at(si)) {
+            index.lifetime.set_end(si);
+            return Ok(());
+        }
+        let msg = format!("index on property#{} not found at si#{}", prop_id, si);
+        let err = gen_graph_err!(GraphErrorCode::MetaNotFound, msg, drop, si, prop_id);
+        Err(err)
+    }
+
+    pub fn get(&self, si: SnapshotId, prop_id: PropId) -> Option<Arc<IndexInfo>> {
+        let guard = epoch::pin();
+        self.get_list(&guard).iter()
+            .find(|index| index.prop_id == prop_id && index.is_alive_at(si))
+            .cloned()
+    }
+
+    pub fn get_all(&self, si: SnapshotId) -> Vec<Arc<IndexInfo>> {
+        let guard = epoch::pin();
+        self.get_list(&guard).iter().filter(|index| index.is_alive_at(si)).cloned().collect()
+    }
+
+    /// Return all indexes including the dropped ones which are not gc yet
+    pub fn get_all_versions(&self) -> Vec<Arc<IndexInfo>> {
+        let guard = epoch::pin();
+        self.get_list(&guard).clone()
+    }
+
+    /// Remove indexes which have been dropped at or before `si` and return them, so that their data
+    /// can be deleted.
+    pub fn gc(&self, si: SnapshotId) -> GraphResult<Vec<Arc<IndexInfo>>> {
+        let _lock = res_unwrap!(self.lock.lock(), gc, si)?;
+        let guard = epoch::pin();
+        let indexes = self.get_list(&guard);
+        let (removed, alive): (IndexList, IndexList) = indexes.iter().cloned()
+            .partition(|index| index.lifetime.get_end() <= si);
+        if !removed.is_empty() {
+            self.store(alive, &guard);
+        }
+        Ok(removed)
+    }
+
+    fn get_list(&self, guard: &Guard) -> &'static IndexList {
+        unsafe { &*self.indexes.load(Ordering::Relaxed, guard).as_raw() }
+    }
+
+    fn store(&self, indexes: IndexList, guard: &Guard) {
+        let old = self.indexes.swap(Owned::new(indexes), Ordering::Relaxed, guard);
+        unsafe { guard.defer_destroy(old); }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_manager() {
+        let manager = IndexManager::new();
+        manager.create(10, 1).unwrap();
+        manager.create(10, 2).unwrap();
+        assert!(manager.create(11, 1).is_err());
+        assert!(manager.get(9, 1).is_none());
+        assert_eq!(manager.get(10, 1).unwrap().get_start_si(), 10);
+        assert_eq!(manager.get_all(10).len(), 2);
+
+        manager.drop(20, 1).unwrap();
+        assert!(manager.drop(20, 1).is_err());
+        assert!(manager.get(19, 1).is_some());
+        assert!(manager.get(20, 1).is_none());
+        let index = manager.create(30, 1).unwrap();
+        assert_eq!(manager.get(30, 1).unwrap().get_start_si(), index.get_start_si());
+        assert_eq!(manager.get(15, 1).unwrap().get_start_si(), 10);
+
+        assert!(manager.gc(19).unwrap().is_empty());
+        let removed = manager.gc(20).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].get_start_si(), 10);
+        assert_eq!(manager.get_all_versions().len(), 2);
+    }
+}
@@ -1,6 +1,8 @@
 mod common;
 pub mod vertex;
 pub mod edge;
+pub mod index;
 
 pub use self::vertex::*;
 pub use self::edge::*;
+pub use self::index::*;
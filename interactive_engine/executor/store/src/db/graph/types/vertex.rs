@@ -2,7 +2,7 @@
 use ::crossbeam_epoch as epoch;
 use ::crossbeam_epoch::{Atomic, Owned, Guard, Shared};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::collections::hash_map::Values;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
@@ -11,11 +11,13 @@ use crate::db::api::*;
 use super::super::table_manager::*;
 use super::super::codec::*;
 use super::common::*;
+use super::index::*;
 
 pub struct VertexTypeInfo {
     label: LabelId,
     lifetime: LifeTime,
     info: TypeCommon,
+    indexes: IndexManager,
 }
 
 impl VertexTypeInfo {
@@ -35,9 +37,24 @@ impl VertexTypeInfo {
         res_unwrap!(self.info.update_codec(si, codec), update_codec)
     }
 
-    #[allow(dead_code)]
-    pub fn gc(&self, si: SnapshotId) {
-        self.info.gc(si)
+    pub fn gc(&self, si: SnapshotId) -> GraphResult<Vec<TableId>> {
+        res_unwrap!(self.info.gc(si), gc, si)
+    }
+
+    pub fn get_all_tables(&self) -> Vec<Table> {
+        self.info.get_all_tables()
+    }
+
+    pub fn get_obsolete_codecs(&self) -> Vec<CodecVersion> {
+        self.info.get_obsolete_codecs()
+    }
+
+    pub fn drop_codec(&self, version: CodecVersion) -> GraphResult<()> {
+        res_unwrap!(self.info.drop_codec(version), drop_codec, version)
+    }
+
+    pub fn get_all_inner_ids(&self) -> HashSet<PropId> {
+        self.info.get_all_inner_ids()
     }
 
     pub fn get_decoder(&self, si: SnapshotId, version: CodecVersion) -> GraphResult<Decoder> {
@@ -48,6 +65,30 @@ impl VertexTypeInfo {
         res_unwrap!(self.info.get_encoder(si), get_encoder, si)
     }
 
+    pub fn create_index(&self, si: SnapshotId, prop_id: PropId) -> GraphResult<Arc<IndexInfo>> {
+        res_unwrap!(self.indexes.create(si, prop_id), create_index, si, prop_id)
+    }
+
+    pub fn drop_index(&self, si: SnapshotId, prop_id: PropId) -> GraphResult<()> {
+        res_unwrap!(self.indexes.drop(si, prop_id), drop_index, si, prop_id)
+    }
+
+    pub fn get_index(&self, si: SnapshotId, prop_id: PropId) -> Option<Arc<IndexInfo>> {
+        self.indexes.get(si, prop_id)
+    }
+
+    pub fn get_indexes(&self, si: SnapshotId) -> Vec<Arc<IndexInfo>> {
+        self.indexes.get_all(si)
+    }
+
+    pub fn get_all_indexes(&self) -> Vec<Arc<IndexInfo>> {
+        self.indexes.get_all_versions()
+    }
+
+    pub fn gc_indexes(&self, si: SnapshotId) -> GraphResult<Vec<Arc<IndexInfo>>> {
+        res_unwrap!(self.indexes.gc(si), gc_indexes, si)
+    }
+
     fn is_alive_at(&self, si: SnapshotId) -> bool {
         self.lifetime.is_alive_at(si)
     }
@@ -57,6 +98,7 @@ impl VertexTypeInfo {
             label,
             lifetime: LifeTime::new(si),
             info: TypeCommon::new(),
+            indexes: IndexManager::new(),
         }
     }
 }
@@ -87,6 +129,14 @@ impl VertexTypeInfoRef {
         self.info.get_table(si)
     }
 
+    pub fn get_index(&self, si: SnapshotId, prop_id: PropId) -> Option<Arc<IndexInfo>> {
+        self.info.get_index(si, prop_id)
+    }
+
+    pub fn get_indexes(&self, si: SnapshotId) -> Vec<Arc<IndexInfo>> {
+        self.info.get_indexes(si)
+    }
+
     fn new(info: &'static VertexTypeInfo, guard: Guard) -> Self {
         VertexTypeInfoRef {
             info,
@@ -171,6 +221,13 @@ impl VertexTypeManager {
         }
     }
 
+    /// Make `codec` the codec of vertex type `label` since `si`. Data written before keeps its own
+    /// codec version and is decoded by the older codec.
+    pub fn alter_type(&self, si: SnapshotId, label: LabelId, codec: Codec) -> GraphResult<()> {
+        let info = res_unwrap!(self.get_type_info(si, label), alter_type, si, label)?;
+        res_unwrap!(info.update_codec(si, codec), alter_type, si, label)
+    }
+
     pub fn get_type(&self, si: SnapshotId, label: LabelId) -> GraphResult<VertexTypeInfoRef> {
         let guard = epoch::pin();
         let map = self.get_map(&guard);
@@ -220,27 +277,31 @@ impl VertexTypeManager {
         Ok(())
     }
 
-    pub fn gc(&self, si: SnapshotId) {
+    /// Remove types which have been dropped at or before `si` and return them, so that the data in
+    /// their tables can be deleted.
+    pub fn gc(&self, si: SnapshotId) -> Vec<Arc<VertexTypeInfo>> {
         unsafe {
             let guard = epoch::pin();
             let map = self.get_shared_map(&guard);
             let map_ref: &VertexMap = map.deref();
             let mut b = Vec::new();
             for (label, info) in map_ref {
-                if !info.is_alive_at(si) {
+                if info.lifetime.get_end() <= si {
                     b.push(*label);
-                } else {
-                    info.gc(si);
                 }
             }
+            let mut ret = Vec::with_capacity(b.len());
             if !b.is_empty() {
                 let mut map_clone = map_ref.clone();
                 for label in b {
-                    map_clone.remove(&label);
+                    if let Some(info) = map_clone.remove(&label) {
+                        ret.push(info);
+                    }
                 }
                 self.map.store(Owned::new(map_clone), Ordering::Relaxed);
                 guard.defer_destroy(map);
             }
+            ret
         }
     }
 
@@ -280,6 +341,13 @@ impl VertexTypeManagerBuilder {
         Ok(())
     }
 
+    pub fn alter(&mut self, si: SnapshotId, label: LabelId, type_def: &TypeDef) -> GraphResult<()> {
+        let info = res_unwrap!(self.get_info(si, label), alter, si, label, type_def)?;
+        let codec = Codec::from(type_def);
+        let res = info.update_codec(si, codec);
+        res_unwrap!(res, alter, si, label, type_def)
+    }
+
     pub fn drop(&mut self, si: SnapshotId, label: LabelId) -> GraphResult<()> {
         if let Some(info) = self.map.get(&label) {
             info.lifetime.set_end(si);
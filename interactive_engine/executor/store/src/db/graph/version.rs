@@ -122,6 +122,24 @@ impl VersionManager {
         self.get_tail() - self.get_head()
     }
 
+    /// Return all versions currently held in the ring buffer in increasing order of si, tombstones
+    /// excluded.
+    pub fn get_all(&self) -> Vec<Version> {
+        let tail = self.get_tail();
+        let mut head = self.get_head();
+        let mut ret = Vec::with_capacity(tail - head);
+        while head < tail {
+            let idx = head & SIZE_MASK;
+            let si = self.slots[idx].get_si();
+            let data = self.slots[idx].get_data();
+            if si != EMPTY_SI && data != TOMBSTONE {
+                ret.push(Version::new(si, data));
+            }
+            head += 1;
+        }
+        ret
+    }
+
     fn do_add_data(&self, si: SnapshotId, data: i64) -> GraphResult<()> {
         if si <= 0 {
             let msg = format!("cannot add data with version less equal than 0");
@@ -384,6 +402,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_all() {
+        let manager = VersionManager::new();
+        for i in 1..=10 {
+            manager.add(i, i as i64 * 10).unwrap();
+        }
+        manager.add_tombstone(11).unwrap();
+        manager.gc(5).unwrap();
+        let ans: Vec<Version> = (5..=10).map(|i| Version::new(i, i as i64 * 10)).collect();
+        assert_eq!(manager.get_all(), ans);
+    }
+
     #[test]
     fn test_add_tombstone_err() {
         let manager = VersionManager::new();
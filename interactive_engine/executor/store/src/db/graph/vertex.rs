@@ -4,10 +4,13 @@ use std::sync::Arc;
 use crate::db::api::*;
 use crate::db::storage::*;
 use crate::db::common::unsafe_util;
+use crate::db::util::time::current_time_millis;
 use super::codec::*;
 use super::property::*;
 use super::types::*;
 use super::bin::*;
+use super::query;
+use super::table_manager::{Table, TableId};
 
 pub struct VertexImpl {
     id: VertexId,
@@ -68,6 +71,8 @@ pub struct SingleLabelVertexIter<'a> {
     type_info: VertexTypeInfoRef,
     condition: Option<Arc<Condition>>,
     iter: StorageIter<'a>,
+    // records expired before this time are skipped
+    now: i64,
     err: Option<GraphError>,
 }
 
@@ -94,6 +99,7 @@ impl<'a> SingleLabelVertexIter<'a> {
             type_info,
             condition,
             iter,
+            now: current_time_millis(),
             err: None,
         }
     }
@@ -117,20 +123,11 @@ impl<'a> SingleLabelVertexIter<'a> {
         unsafe { unsafe_util::to_mut(self) }.iter.next()
     }
 
-    fn create_vertex(&self, id: VertexId, val: &[u8]) -> Option<VertexWrapper<VertexImpl>> {
-        let version = get_codec_version(val);
-        match self.type_info.get_decoder(self.si, version) {
-            Ok(decoder) => {
-                let data = unsafe { std::mem::transmute(PropData::from(val)) };
-                let label = self.type_info.get_label();
-                let ret = VertexImpl::new(id, label, data, decoder);
-                Some(VertexWrapper::new(ret))
-            }
-            Err(e) => {
-                self.set_err(e);
-                return None;
-            }
-        }
+    fn create_vertex(&self, id: VertexId, val: &[u8], decoder: Decoder) -> VertexWrapper<VertexImpl> {
+        let data = unsafe { std::mem::transmute(PropData::from(val)) };
+        let label = self.type_info.get_label();
+        let ret = VertexImpl::new(id, label, data, decoder);
+        VertexWrapper::new(ret)
     }
 }
 
@@ -147,8 +144,26 @@ impl<'a> VertexResultIter for SingleLabelVertexIter<'a> {
                 Ok((id, ts)) => {
                     if self.check(id, ts) {
                         self.set_last_id(id);
-                        if val.len() >= 4 { // val.len() == 0 means this item is a tombstone of this vertex id
-                            return self.create_vertex(id, val);
+                        if val.len() < 4 { // val.len() == 0 means this item is a tombstone of this vertex id
+                            continue;
+                        }
+                        let version = get_codec_version(val);
+                        match self.type_info.get_decoder(self.si, version) {
+                            Ok(decoder) => {
+                                if decoder.is_expired(val, self.now) {
+                                    continue;
+                                }
+                                if let Some(ref condition) = self.condition {
+                                    if !query::check_condition(&decoder, val, condition.as_ref()) {
+                                        continue;
+                                    }
+                                }
+                                return Some(self.create_vertex(id, val, decoder));
+                            }
+                            Err(e) => {
+                                self.set_err(e);
+                                return None;
+                            }
                         }
                     }
                 }
@@ -168,14 +183,92 @@ impl<'a> VertexResultIter for SingleLabelVertexIter<'a> {
     }
 }
 
+/// Iterate the vertices found by an index scan. The index only tells which vertices may match, so
+/// every vertex is fetched and checked against the whole condition.
+pub struct IndexedVertexIter<'a> {
+    si: SnapshotId,
+    ts: SnapshotId, // real si - table.start_si
+    table_id: TableId,
+    type_info: VertexTypeInfoRef,
+    condition: Arc<Condition>,
+    ids: std::vec::IntoIter<VertexId>,
+    storage: &'a dyn ExternalStorage,
+    now: i64,
+    err: Option<GraphError>,
+}
+
+impl<'a> IndexedVertexIter<'a> {
+    pub fn new(si: SnapshotId,
+               table: &Table,
+               type_info: VertexTypeInfoRef,
+               condition: Arc<Condition>,
+               ids: Vec<VertexId>,
+               storage: &'a dyn ExternalStorage) -> Self {
+        IndexedVertexIter {
+            si,
+            ts: si - table.start_si,
+            table_id: table.id,
+            type_info,
+            condition,
+            ids: ids.into_iter(),
+            storage,
+            now: current_time_millis(),
+            err: None,
+        }
+    }
+
+    fn get_vertex(&self, id: VertexId) -> GraphResult<Option<VertexImpl>> {
+        let key = vertex_key(self.table_id, id, self.ts);
+        let mut iter = self.storage.scan_from(&key)?;
+        if let Some((k, v)) = iter.next() {
+            if k.len() == key.len() && k[0..16] == key[0..16] && v.len() >= 4 {
+                let decoder = self.type_info.get_decoder(self.si, get_codec_version(v))?;
+                if !decoder.is_expired(v, self.now) && query::check_condition(&decoder, v, self.condition.as_ref()) {
+                    let ret = VertexImpl::new(id, self.type_info.get_label(), PropData::Owned(v.to_vec()), decoder);
+                    return Ok(Some(ret));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> VertexResultIter for IndexedVertexIter<'a> {
+    type V = VertexImpl;
+
+    fn next(&mut self) -> Option<VertexWrapper<Self::V>> {
+        if self.err.is_some() {
+            return None;
+        }
+        while let Some(id) = self.ids.next() {
+            match self.get_vertex(id) {
+                Ok(Some(v)) => return Some(VertexWrapper::new(v)),
+                Ok(None) => {}
+                Err(e) => {
+                    self.err = Some(e);
+                    return None;
+                }
+            }
+        }
+        None
+    }
+
+    fn ok(&self) -> GraphResult<()> {
+        if let Some(ref err) = self.err {
+            return Err(err.clone());
+        }
+        Ok(())
+    }
+}
+
 pub struct MultiLabelsVertexIter<'a> {
-    iters: Vec<SingleLabelVertexIter<'a>>,
+    iters: Vec<Box<dyn VertexResultIter<V=VertexImpl> + 'a>>,
     cur: usize,
     err: Option<GraphError>,
 }
 
 impl<'a> MultiLabelsVertexIter<'a> {
-    pub fn new(iters: Vec<SingleLabelVertexIter<'a>>) -> Self {
+    pub fn new(iters: Vec<Box<dyn VertexResultIter<V=VertexImpl> + 'a>>) -> Self {
         MultiLabelsVertexIter {
             iters,
             cur: 0,
@@ -197,8 +290,8 @@ impl<'a> VertexResultIter for MultiLabelsVertexIter<'a> {
             if let Some(v) = iter_mut.next() {
                 return Some(v);
             }
-            if let Some(ref e) = iter.err {
-                self.err = Some(e.clone());
+            if let Err(e) = iter.ok() {
+                self.err = Some(e);
                 return None;
             }
             self.cur += 1;
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::{Arc, RwLock};
+
+use crate::db::api::*;
+use crate::db::common::bytes::util::bytes_upper_bound;
+use super::{StorageIter, StorageRes, ExternalStorage, ExternalStorageBackup, KvPair, RawBytes};
+
+type KvMap = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// A sorted in-memory map with the same semantics as rocksdb, so that the graph store can run
+/// without any disk. It has no compaction, so expired records of types with ttl are hidden from
+/// readers but never reclaimed. Sst files and backups are not supported.
+pub struct MemoryStorage {
+    map: Arc<RwLock<KvMap>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        MemoryStorage {
+            map: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    fn iter(&self, start: &[u8], end: Option<Vec<u8>>) -> MemoryIter {
+        MemoryIter::new(self.map.clone(), start.to_vec(), end)
+    }
+}
+
+impl ExternalStorage for MemoryStorage {
+    fn get(&self, key: &[u8]) -> GraphResult<Option<StorageRes>> {
+        let map = res_unwrap!(self.map.read().map_err(lock_err), get)?;
+        Ok(map.get(key).map(|v| StorageRes::Memory(v.clone())))
+    }
+
+    fn put(&self, key: &[u8], val: &[u8]) -> GraphResult<()> {
+        let mut map = res_unwrap!(self.map.write().map_err(lock_err), put)?;
+        map.insert(key.to_vec(), val.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, key: &[u8]) -> GraphResult<()> {
+        let mut map = res_unwrap!(self.map.write().map_err(lock_err), delete)?;
+        map.remove(key);
+        Ok(())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> GraphResult<StorageIter> {
+        Ok(StorageIter::Memory(self.iter(prefix, bytes_upper_bound(prefix))))
+    }
+
+    fn scan_from(&self, start: &[u8]) -> GraphResult<StorageIter> {
+        Ok(StorageIter::Memory(self.iter(start, None)))
+    }
+
+    fn scan_range(&self, start: &[u8], end: &[u8]) -> GraphResult<StorageIter> {
+        Ok(StorageIter::Memory(self.iter(start, Some(end.to_vec()))))
+    }
+
+    fn delete_range(&self, start: &[u8], end: &[u8]) -> GraphResult<()> {
+        if start >= end {
+            return Ok(());
+        }
+        let mut map = res_unwrap!(self.map.write().map_err(lock_err), delete_range)?;
+        let mut tail = map.split_off(start);
+        let mut rest = tail.split_off(end);
+        map.append(&mut rest);
+        Ok(())
+    }
+
+    fn delete_batch(&self, keys: &[Vec<u8>]) -> GraphResult<()> {
+        let mut map = res_unwrap!(self.map.write().map_err(lock_err), delete_batch)?;
+        for key in keys {
+            map.remove(key);
+        }
+        Ok(())
+    }
+
+    fn load(&self, _files: &[&str]) -> GraphResult<()> {
+        let msg = "memory storage cannot load sst files".to_string();
+        Err(gen_graph_err!(GraphErrorCode::NotSupported, msg, load))
+    }
+
+    fn open_backup_engine(&self, backup_path: &str) -> GraphResult<Box<dyn ExternalStorageBackup>> {
+        let msg = "memory storage has no backup".to_string();
+        Err(gen_graph_err!(GraphErrorCode::NotSupported, msg, open_backup_engine, backup_path))
+    }
+
+    fn new_scan(&self, prefix: &[u8]) -> GraphResult<Box<dyn Iterator<Item=KvPair> + Send>> {
+        Ok(Box::new(self.iter(prefix, bytes_upper_bound(prefix))))
+    }
+}
+
+fn lock_err<T>(e: std::sync::PoisonError<T>) -> GraphError {
+    let msg = format!("{:?}", e);
+    gen_graph_err!(GraphErrorCode::LockFailed, msg)
+}
+
+/// Iterate keys in [start, end). Every step looks up the key following the last returned one, so
+/// the map is never locked between steps, and the returned kv pair is valid until the next step.
+/// Unlike a rocksdb iterator it has no implicit snapshot and sees writes made after it's created,
+/// which is fine for the graph store because readers only see versions not newer than their si.
+pub struct MemoryIter {
+    map: Arc<RwLock<KvMap>>,
+    start: Vec<u8>,
+    end: Option<Vec<u8>>,
+    cur: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl MemoryIter {
+    fn new(map: Arc<RwLock<KvMap>>, start: Vec<u8>, end: Option<Vec<u8>>) -> Self {
+        MemoryIter {
+            map,
+            start,
+            end,
+            cur: None,
+        }
+    }
+
+    pub fn next(&mut self) -> Option<(&[u8], &[u8])> {
+        let lower = match self.cur {
+            Some((ref k, _)) => Bound::Excluded(k.as_slice()),
+            None => Bound::Included(self.start.as_slice()),
+        };
+        let upper = match self.end {
+            Some(ref end) => Bound::Excluded(end.as_slice()),
+            None => Bound::Unbounded,
+        };
+        let next = match (lower, upper) {
+            (Bound::Included(s), Bound::Excluded(e)) | (Bound::Excluded(s), Bound::Excluded(e)) if s >= e => None,
+            _ => {
+                let map = self.map.read().ok()?;
+                map.range::<[u8], _>((lower, upper)).next().map(|(k, v)| (k.clone(), v.clone()))
+            }
+        };
+        self.cur = Some(next?);
+        self.cur.as_ref().map(|(k, v)| (k.as_slice(), v.as_slice()))
+    }
+}
+
+impl Iterator for MemoryIter {
+    type Item = KvPair;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        MemoryIter::next(self).map(|(k, v)| (RawBytes::new(k), RawBytes::new(v)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_storage() {
+        let storage = MemoryStorage::new();
+        for i in 1..=10 {
+            let key = format!("aaa#{:02}", i);
+            storage.put(key.as_bytes(), i.to_string().as_bytes()).unwrap();
+        }
+        storage.put(b"bbb", b"b").unwrap();
+        assert_eq!(storage.get(b"aaa#03").unwrap().unwrap().as_bytes(), b"3");
+        storage.delete(b"aaa#03").unwrap();
+        assert!(storage.get(b"aaa#03").unwrap().is_none());
+
+        let mut iter = storage.scan_prefix(b"aaa").unwrap();
+        let mut ids = Vec::new();
+        while let Some((k, v)) = iter.next() {
+            assert!(k.starts_with(b"aaa#"));
+            ids.push(String::from_utf8(v.to_vec()).unwrap().parse::<i32>().unwrap());
+        }
+        assert_eq!(ids, vec![1, 2, 4, 5, 6, 7, 8, 9, 10]);
+
+        let mut iter = storage.scan_from(b"aaa#095").unwrap();
+        assert_eq!(iter.next().unwrap().0, b"aaa#10");
+        assert_eq!(iter.next().unwrap().0, b"bbb");
+        assert!(iter.next().is_none());
+
+        let mut iter = storage.scan_range(b"aaa#05", b"aaa#07").unwrap();
+        assert_eq!(iter.next().unwrap().0, b"aaa#05");
+        // a put during the iteration is seen
+        storage.put(b"aaa#055", b"55").unwrap();
+        assert_eq!(iter.next().unwrap().0, b"aaa#055");
+        assert_eq!(iter.next().unwrap().0, b"aaa#06");
+        assert!(iter.next().is_none());
+        assert!(storage.scan_range(b"aaa#07", b"aaa#05").unwrap().next().is_none());
+
+        storage.delete_range(b"aaa#02", b"aaa#09").unwrap();
+        let keys: Vec<Vec<u8>> = storage.new_scan(b"").unwrap().map(|(k, _)| unsafe { k.to_slice() }.to_vec()).collect();
+        assert_eq!(keys, vec![b"aaa#01".to_vec(), b"aaa#09".to_vec(), b"aaa#10".to_vec(), b"bbb".to_vec()]);
+
+        assert!(storage.load(&["x.sst"]).is_err());
+        assert!(storage.open_backup_engine("backup").is_err());
+    }
+}
@@ -1,7 +1,9 @@
-use crate::db::api::{GraphResult, BackupId};
+use crate::db::api::{GraphResult, BackupId, BackupInfo};
 
 pub mod rocksdb;
+pub mod memory;
 use self::rocksdb::RocksDBIter;
+use self::memory::MemoryIter;
 
 pub trait ExternalStorage: Send + Sync {
     fn get(&self, key: &[u8]) -> GraphResult<Option<StorageRes>>;
@@ -11,6 +13,8 @@ pub trait ExternalStorage: Send + Sync {
     fn scan_from(&self, start: &[u8]) -> GraphResult<StorageIter>;
     fn scan_range(&self, start: &[u8], end: &[u8]) -> GraphResult<StorageIter>;
     fn delete_range(&self, start: &[u8], end: &[u8]) -> GraphResult<()>;
+    /// delete all `keys` in one atomic write, which is much cheaper than a range tombstone per key
+    fn delete_batch(&self, keys: &[Vec<u8>]) -> GraphResult<()>;
     fn load(&self, files: &[&str]) -> GraphResult<()>;
     fn open_backup_engine(&self, backup_path: &str) -> GraphResult<Box<dyn ExternalStorageBackup>>;
     fn new_scan(&self, prefix: &[u8]) -> GraphResult<Box<dyn Iterator<Item=KvPair> + Send>>;
@@ -22,28 +26,36 @@ pub trait ExternalStorageBackup {
     fn restore_from_backup(&mut self, restore_path: &str, backup_id: BackupId) -> GraphResult<()>;
     fn verify_backup(&self, backup_id: BackupId) -> GraphResult<()>;
     fn get_backup_list(&self) -> Vec<BackupId>;
+    /// snapshot ids of the returned backups are unknown to storage, so they're always None
+    fn get_backup_info_list(&self) -> Vec<BackupInfo>;
+    /// delete all backups except the latest `num_backups_to_keep` ones
+    fn purge_old_backups(&mut self, num_backups_to_keep: usize) -> GraphResult<()>;
 }
 
 pub enum StorageRes {
     RocksDB(Vec<u8>),
+    Memory(Vec<u8>),
 }
 
 impl StorageRes {
     pub fn as_bytes(&self) -> &[u8] {
         match self {
             StorageRes::RocksDB(v) => v,
+            StorageRes::Memory(v) => v,
         }
     }
 }
 
 pub enum StorageIter<'a> {
     RocksDB(RocksDBIter<'a>),
+    Memory(MemoryIter),
 }
 
 impl<'a> StorageIter<'a> {
     pub fn next(&mut self) -> Option<(&[u8], &[u8])> {
         match *self {
             StorageIter::RocksDB(ref mut iter) => iter.next(),
+            StorageIter::Memory(ref mut iter) => iter.next(),
         }
     }
 }
@@ -76,6 +88,9 @@ impl<'a> Iterator for StorageIter<'a> {
             StorageIter::RocksDB(ref mut iter) => iter.next().map(|(k, v)| {
                 (RawBytes::new(k), RawBytes::new(v))
             }),
+            StorageIter::Memory(ref mut iter) => MemoryIter::next(iter).map(|(k, v)| {
+                (RawBytes::new(k), RawBytes::new(v))
+            }),
         }
     }
 }
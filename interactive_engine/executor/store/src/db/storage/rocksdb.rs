@@ -1,4 +1,4 @@
-use ::rocksdb::{DB, Options, ReadOptions, DBRawIterator, IngestExternalFileOptions};
+use ::rocksdb::{DB, Options, ReadOptions, DBRawIterator, IngestExternalFileOptions, SstFileWriter, CompactionDecision, WriteBatch};
 use ::rocksdb::backup::{BackupEngine, BackupEngineOptions, RestoreOptions};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -6,6 +6,7 @@ use std::sync::Arc;
 use crate::db::api::*;
 use super::{StorageIter, StorageRes, ExternalStorage, ExternalStorageBackup};
 use crate::db::storage::{KvPair, RawBytes};
+use crate::db::common::bytes::util::bytes_upper_bound;
 
 pub struct RocksDB {
     db: Arc<DB>,
@@ -16,9 +17,28 @@ pub struct RocksDBBackupEngine {
     backup_engine: BackupEngine,
 }
 
+/// Decides which kv pairs are dropped when rocksdb compacts them, so that data which will never be
+/// read again can be reclaimed in the background.
+pub trait CompactionFilter: Send + Sync {
+    fn should_remove(&self, key: &[u8], value: &[u8]) -> bool;
+}
+
 impl RocksDB {
     pub fn open(options: &HashMap<String, String>, path: &str) -> GraphResult<Self> {
-        let opts = init_options(options);
+        Self::open_with_filter(options, path, None)
+    }
+
+    pub fn open_with_filter(options: &HashMap<String, String>, path: &str, filter: Option<Arc<dyn CompactionFilter>>) -> GraphResult<Self> {
+        let mut opts = init_options(options);
+        if let Some(filter) = filter {
+            opts.set_compaction_filter("graph_compaction_filter", move |_level: u32, key: &[u8], value: &[u8]| {
+                if filter.should_remove(key, value) {
+                    CompactionDecision::Remove
+                } else {
+                    CompactionDecision::Keep
+                }
+            });
+        }
         let db = DB::open(&opts, path).map_err(|e| {
             let msg = format!("open rocksdb at {} failed, because {}", path, e.into_string());
             gen_graph_err!(GraphErrorCode::ExternalStorageError, msg, open, options, path)
@@ -28,6 +48,33 @@ impl RocksDB {
         };
         Ok(ret)
     }
+
+    /// Compact all data, kv pairs are passed to the compaction filter on the way.
+    pub fn compact(&self) {
+        self.db.compact_range(None::<&[u8]>, None::<&[u8]>);
+    }
+}
+
+/// Write `kvs` into a new sst file at `path`, which can be loaded by `ExternalStorage::load`. Keys
+/// must be in strictly ascending order.
+pub fn write_sst_file<'a, I>(path: &str, kvs: I) -> GraphResult<()>
+    where I: IntoIterator<Item=(&'a [u8], &'a [u8])> {
+    let opts = Options::default();
+    let mut writer = SstFileWriter::create(&opts);
+    writer.open(path).map_err(|e| {
+        let msg = format!("open sst file writer at {} failed, because {}", path, e.into_string());
+        gen_graph_err!(GraphErrorCode::ExternalStorageError, msg, write_sst_file, path)
+    })?;
+    for (k, v) in kvs {
+        writer.put(k, v).map_err(|e| {
+            let msg = format!("write sst file {} failed, because {}", path, e.into_string());
+            gen_graph_err!(GraphErrorCode::ExternalStorageError, msg, write_sst_file, path)
+        })?;
+    }
+    writer.finish().map_err(|e| {
+        let msg = format!("finish sst file {} failed, because {}", path, e.into_string());
+        gen_graph_err!(GraphErrorCode::ExternalStorageError, msg, write_sst_file, path)
+    })
 }
 
 impl ExternalStorage for RocksDB {
@@ -92,6 +139,17 @@ impl ExternalStorage for RocksDB {
         })
     }
 
+    fn delete_batch(&self, keys: &[Vec<u8>]) -> GraphResult<()> {
+        let mut batch = WriteBatch::default();
+        for key in keys {
+            batch.delete(key);
+        }
+        self.db.write(batch).map_err(|e| {
+            let msg = format!("rocksdb.write failed because {}", e.into_string());
+            gen_graph_err!(GraphErrorCode::ExternalStorageError, msg)
+        })
+    }
+
     fn load(&self, files: &[&str]) -> GraphResult<()> {
         let mut options = IngestExternalFileOptions::default();
         options.set_move_files(true);
@@ -194,6 +252,27 @@ impl ExternalStorageBackup for RocksDBBackupEngine {
     fn get_backup_list(&self) -> Vec<BackupId> {
         self.backup_engine.get_backup_info().into_iter().map(|info| info.backup_id as BackupId).collect()
     }
+
+    fn get_backup_info_list(&self) -> Vec<BackupInfo> {
+        let mut ret: Vec<BackupInfo> = self.backup_engine.get_backup_info().into_iter().map(|info| {
+            BackupInfo {
+                backup_id: info.backup_id as BackupId,
+                snapshot_id: None,
+                timestamp: info.timestamp,
+                size: info.size,
+                num_files: info.num_files,
+            }
+        }).collect();
+        ret.sort_by_key(|info| info.backup_id);
+        ret
+    }
+
+    fn purge_old_backups(&mut self, num_backups_to_keep: usize) -> GraphResult<()> {
+        self.backup_engine.purge_old_backups(num_backups_to_keep).map_err(|e| {
+            let msg = format!("purge old rocksdb backups failed, because {}", e.into_string());
+            gen_graph_err!(GraphErrorCode::ExternalStorageError, msg, purge_old_backups, num_backups_to_keep)
+        })
+    }
 }
 
 #[allow(unused_variables)]
@@ -241,20 +320,6 @@ impl<'a> RocksDBIter<'a> {
     }
 }
 
-fn bytes_upper_bound(bytes: &[u8]) -> Option<Vec<u8>> {
-    for i in (0..bytes.len()).rev() {
-        if bytes[i] != u8::MAX {
-            let mut ret = bytes.to_vec();
-            ret[i] += 1;
-            for j in i+1..bytes.len() {
-                ret[j] = 0;
-            }
-            return Some(ret);
-        }
-    }
-    None
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -311,4 +376,32 @@ mod tests {
         fs::rmr(path).unwrap();
     }
 
+    #[test]
+    fn test_compaction_filter() {
+        struct OddValueFilter;
+
+        impl CompactionFilter for OddValueFilter {
+            fn should_remove(&self, _key: &[u8], value: &[u8]) -> bool {
+                transform::bytes_to_i64(value).unwrap() % 2 == 1
+            }
+        }
+
+        let path = "test_compaction_filter";
+        fs::rmr(path).unwrap();
+        {
+            let db = RocksDB::open_with_filter(&HashMap::new(), path, Some(Arc::new(OddValueFilter))).unwrap();
+            for i in 1..=10 {
+                let key = format!("aaa#{:010}", i);
+                db.put(key.as_bytes(), transform::i64_to_vec(i).as_slice()).unwrap();
+            }
+            db.compact();
+            let mut iter = db.scan_prefix(b"aaa").unwrap();
+            for i in (2..=10).step_by(2) {
+                let (_, v) = iter.next().unwrap();
+                assert_eq!(transform::bytes_to_i64(v).unwrap(), i);
+            }
+            assert!(iter.next().is_none());
+        }
+        fs::rmr(path).unwrap();
+    }
 }
@@ -2,6 +2,7 @@ use crate::v2::api::{Vertex, VertexId, LabelId, PropertyReader, PropertyId, Prop
 use crate::db::graph::codec::{Decoder, IterDecoder};
 use crate::db::storage::RawBytes;
 use crate::v2::{parse_property_value, GraphResult};
+use std::sync::Arc;
 
 pub struct PropertyImpl {
     property_id: PropertyId,
@@ -20,32 +21,49 @@ impl Property for PropertyImpl {
 
 pub struct PropertiesIter<'a> {
     decode_iter: IterDecoder<'a>,
+    property_ids: Option<Arc<Vec<PropertyId>>>,
 }
 
 impl<'a> Iterator for PropertiesIter<'a> {
     type Item = GraphResult<PropertyImpl>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.decode_iter.next().map(|(prop_id, v)| {
+        loop {
+            let (prop_id, v) = self.decode_iter.next()?;
+            let property_id = prop_id as PropertyId;
+            if !is_projected(self.property_ids.as_ref(), property_id) {
+                continue;
+            }
             let property_value = parse_property_value(v);
-            Ok(PropertyImpl {
-                property_id: prop_id as PropertyId,
+            return Some(Ok(PropertyImpl {
+                property_id,
                 property_value,
-            })
-        })
+            }));
+        }
     }
 }
 
+/// `None` means all properties are required
+fn is_projected(property_ids: Option<&Arc<Vec<PropertyId>>>, property_id: PropertyId) -> bool {
+    property_ids.map_or(true, |ids| ids.contains(&property_id))
+}
+
 pub struct VertexImpl {
     vertex_id: VertexId,
     label_id: LabelId,
     decoder: Decoder,
     raw_bytes: RawBytes,
+    property_ids: Option<Arc<Vec<PropertyId>>>,
 }
 
 impl VertexImpl {
-    pub fn new(vertex_id: VertexId, label_id: LabelId, decoder: Decoder, raw_bytes: RawBytes) -> Self {
-        VertexImpl { vertex_id, label_id, decoder, raw_bytes }
+    pub fn new(vertex_id: VertexId,
+               label_id: LabelId,
+               decoder: Decoder,
+               raw_bytes: RawBytes,
+               property_ids: Option<Arc<Vec<PropertyId>>>,
+    ) -> Self {
+        VertexImpl { vertex_id, label_id, decoder, raw_bytes, property_ids }
     }
 }
 
@@ -54,6 +72,9 @@ impl PropertyReader for VertexImpl {
     type PropertyIterator = PropertiesIter<'static>;
 
     fn get_property(&self, property_id: PropertyId) -> Option<Self::P> {
+        if !is_projected(self.property_ids.as_ref(), property_id) {
+            return None;
+        }
         let bytes = unsafe { self.raw_bytes.to_slice() };
         let value_ref = self.decoder.decode_property(bytes, property_id as i32);
         value_ref.map(|v| {
@@ -69,7 +90,8 @@ impl PropertyReader for VertexImpl {
         let bytes = unsafe { std::mem::transmute(self.raw_bytes.to_slice()) };
         let decode_iter = self.decoder.decode_properties(bytes);
         PropertiesIter {
-            decode_iter
+            decode_iter,
+            property_ids: self.property_ids.clone(),
         }
     }
 }
@@ -89,11 +111,17 @@ pub struct EdgeImpl {
     edge_relation: EdgeRelation,
     decoder: Decoder,
     raw_bytes: RawBytes,
+    property_ids: Option<Arc<Vec<PropertyId>>>,
 }
 
 impl EdgeImpl {
-    pub fn new(edge_id: EdgeId, edge_relation: EdgeRelation, decoder: Decoder, raw_bytes: RawBytes) -> Self {
-        EdgeImpl { edge_id, edge_relation, decoder, raw_bytes }
+    pub fn new(edge_id: EdgeId,
+               edge_relation: EdgeRelation,
+               decoder: Decoder,
+               raw_bytes: RawBytes,
+               property_ids: Option<Arc<Vec<PropertyId>>>,
+    ) -> Self {
+        EdgeImpl { edge_id, edge_relation, decoder, raw_bytes, property_ids }
     }
 }
 
@@ -102,6 +130,9 @@ impl PropertyReader for EdgeImpl {
     type PropertyIterator = PropertiesIter<'static>;
 
     fn get_property(&self, property_id: PropertyId) -> Option<Self::P> {
+        if !is_projected(self.property_ids.as_ref(), property_id) {
+            return None;
+        }
         let bytes = unsafe { self.raw_bytes.to_slice() };
         let value_ref = self.decoder.decode_property(bytes, property_id as i32);
         value_ref.map(|v| {
@@ -117,7 +148,8 @@ impl PropertyReader for EdgeImpl {
         let bytes = unsafe { std::mem::transmute(self.raw_bytes.to_slice()) };
         let decode_iter = self.decoder.decode_properties(bytes);
         PropertiesIter {
-            decode_iter
+            decode_iter,
+            property_ids: self.property_ids.clone(),
         }
     }
 }
@@ -3,26 +3,34 @@ use std::sync::Arc;
 use crate::db::graph::types::{VertexTypeInfo, EdgeInfo, EdgeKindInfo};
 use crate::v2::GraphResult;
 use crate::v2::graph::entity::{VertexImpl, EdgeImpl};
-use crate::v2::api::{SnapshotId, VertexId, LabelId, Records, EdgeRelation};
+use crate::v2::api::{SnapshotId, VertexId, LabelId, Records, EdgeRelation, PropertyId};
 use crate::db::graph::bin::{vertex_table_prefix_key, parse_vertex_key, edge_table_prefix_key, parse_edge_key, edge_prefix};
 use crate::db::graph::codec::{get_codec_version};
-use crate::db::api::EdgeDirection;
+use crate::db::api::{EdgeDirection, Condition};
+use crate::db::graph::query;
+use crate::db::util::time::current_time_millis;
 
 pub struct VertexTypeScan {
     storage: Arc<dyn ExternalStorage>,
     snapshot_id: SnapshotId,
     vertex_type_info: Arc<VertexTypeInfo>,
+    condition: Option<Arc<Condition>>,
+    property_ids: Option<Arc<Vec<PropertyId>>>,
 }
 
 impl VertexTypeScan {
     pub fn new(storage: Arc<dyn ExternalStorage>,
                snapshot_id: SnapshotId,
                vertex_type_info: Arc<VertexTypeInfo>,
+               condition: Option<Arc<Condition>>,
+               property_ids: Option<Arc<Vec<PropertyId>>>,
     ) -> Self {
         VertexTypeScan {
             storage,
             snapshot_id,
             vertex_type_info,
+            condition,
+            property_ids,
         }
     }
 }
@@ -36,6 +44,7 @@ impl IntoIterator for VertexTypeScan {
         if let Some(table) = self.vertex_type_info.get_table(snapshot_id) {
             let prefix = vertex_table_prefix_key(table.id);
             let data_ts = snapshot_id - table.start_si;
+            let now = current_time_millis();
             let mut previous_vertex = None;
             let v_iter = self.storage.new_scan(&prefix).unwrap().filter_map(move |(raw_key, raw_val)| {
                 let key = unsafe { raw_key.to_slice() };
@@ -55,8 +64,17 @@ impl IntoIterator for VertexTypeScan {
                         let codec_version = get_codec_version(val);
                         match self.vertex_type_info.get_decoder(snapshot_id, codec_version) {
                             Ok(decoder) => {
+                                if decoder.is_expired(val, now) {
+                                    return None;
+                                }
+                                if let Some(ref condition) = self.condition {
+                                    if !query::check_condition(&decoder, val, condition.as_ref()) {
+                                        return None;
+                                    }
+                                }
                                 let label = self.vertex_type_info.get_label();
-                                Some(Ok(VertexImpl::new(vertex_id as VertexId, label as LabelId, decoder, raw_val)))
+                                Some(Ok(VertexImpl::new(vertex_id as VertexId, label as LabelId, decoder, raw_val,
+                                                        self.property_ids.clone())))
                             }
                             Err(e) => {
                                 Some(Err(e.into()))
@@ -80,6 +98,8 @@ pub struct EdgeTypeScan {
     edge_info: Arc<EdgeInfo>,
     vertex_id: Option<VertexId>,
     direction: EdgeDirection,
+    condition: Option<Arc<Condition>>,
+    property_ids: Option<Arc<Vec<PropertyId>>>,
 }
 
 impl EdgeTypeScan {
@@ -87,9 +107,11 @@ impl EdgeTypeScan {
                snapshot_id: SnapshotId,
                edge_info: Arc<EdgeInfo>,
                vertex_id: Option<VertexId>,
-               direction: EdgeDirection
+               direction: EdgeDirection,
+               condition: Option<Arc<Condition>>,
+               property_ids: Option<Arc<Vec<PropertyId>>>,
     ) -> Self {
-            EdgeTypeScan { storage, snapshot_id, edge_info, vertex_id, direction }
+            EdgeTypeScan { storage, snapshot_id, edge_info, vertex_id, direction, condition, property_ids }
     }
 }
 
@@ -101,7 +123,8 @@ impl IntoIterator for EdgeTypeScan {
         let mut edge_kind_iter = self.edge_info.get_kinds(self.snapshot_id as i64);
         let mut res: Records<EdgeImpl> = Box::new(::std::iter::empty());
         while let Some(edge_kind) = edge_kind_iter.next() {
-            let iter = EdgeKindScan::new(self.storage.clone(), self.snapshot_id, edge_kind.clone(), self.vertex_id, self.direction).into_iter();
+            let iter = EdgeKindScan::new(self.storage.clone(), self.snapshot_id, edge_kind.clone(), self.vertex_id,
+                                         self.direction, self.condition.clone(), self.property_ids.clone()).into_iter();
             res = Box::new(res.chain(iter));
         }
         res
@@ -114,6 +137,8 @@ pub struct EdgeKindScan {
     edge_kind_info: Arc<EdgeKindInfo>,
     vertex_id: Option<VertexId>,
     direction: EdgeDirection,
+    condition: Option<Arc<Condition>>,
+    property_ids: Option<Arc<Vec<PropertyId>>>,
 }
 
 impl EdgeKindScan {
@@ -121,9 +146,11 @@ impl EdgeKindScan {
                snapshot_id: SnapshotId,
                edge_kind_info: Arc<EdgeKindInfo>,
                vertex_id: Option<VertexId>,
-               direction: EdgeDirection
+               direction: EdgeDirection,
+               condition: Option<Arc<Condition>>,
+               property_ids: Option<Arc<Vec<PropertyId>>>,
     ) -> Self {
-        EdgeKindScan { storage, snapshot_id, edge_kind_info, vertex_id, direction }
+        EdgeKindScan { storage, snapshot_id, edge_kind_info, vertex_id, direction, condition, property_ids }
     }
 }
 
@@ -135,6 +162,7 @@ impl IntoIterator for EdgeKindScan {
         let snapshot_id = self.snapshot_id as i64;
         if let Some(table) = self.edge_kind_info.get_table(snapshot_id) {
             let data_ts = snapshot_id - table.start_si;
+            let now = current_time_millis();
             let scan_iter = match self.direction {
                 EdgeDirection::In | EdgeDirection::Out => {
                     let prefix = edge_prefix(table.id, self.vertex_id.unwrap() as i64, self.direction);
@@ -163,11 +191,19 @@ impl IntoIterator for EdgeKindScan {
                 let codec_version = get_codec_version(val);
                 match self.edge_kind_info.get_decoder(snapshot_id, codec_version) {
                     Ok(decoder) => {
+                        if decoder.is_expired(val, now) {
+                            return None;
+                        }
+                        if let Some(ref condition) = self.condition {
+                            if !query::check_condition(&decoder, val, condition.as_ref()) {
+                                return None;
+                            }
+                        }
                         let edge_kind = self.edge_kind_info.get_type();
                         let edge_relation = EdgeRelation::new(edge_kind.edge_label_id as LabelId,
                                                               edge_kind.src_vertex_label_id as LabelId,
                                                               edge_kind.dst_vertex_label_id as LabelId);
-                        Some(Ok(EdgeImpl::new(edge_id.into(), edge_relation, decoder, raw_val)))
+                        Some(Ok(EdgeImpl::new(edge_id.into(), edge_relation, decoder, raw_val, self.property_ids.clone())))
                     }
                     Err(e) => {
                         Some(Err(e.into()))
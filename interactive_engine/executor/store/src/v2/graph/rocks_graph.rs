@@ -3,12 +3,14 @@ use crate::db::storage::{ExternalStorage, RawBytes};
 use crate::v2::multi_version_graph::MultiVersionGraph;
 use crate::v2::api::{SnapshotId, VertexId, LabelId, PropertyId, EdgeId, EdgeRelation, Condition, Records, SerialId};
 use crate::db::graph::types::{VertexTypeManager, EdgeTypeManager};
-use crate::v2::GraphResult;
+use crate::v2::{GraphResult, parse_condition};
 use crate::db::graph::bin::{vertex_key, edge_key};
 use crate::db::graph::codec::get_codec_version;
+use crate::db::graph::adjacency;
 use crate::v2::graph::entity::{VertexImpl, EdgeImpl};
-use crate::v2::graph::iter::{VertexTypeScan, EdgeTypeScan};
-use crate::db::api::EdgeDirection;
+use crate::v2::graph::iter::{VertexTypeScan, EdgeTypeScan, EdgeKindScan};
+use crate::db::api::{EdgeDirection, EdgeKind};
+use crate::db::util::time::current_time_millis;
 
 pub struct RocksGraph {
     vertex_manager: VertexTypeManager,
@@ -26,7 +28,7 @@ impl RocksGraph {
                              snapshot_id: SnapshotId,
                              vertex_id: VertexId,
                              label_id: LabelId,
-                             _property_ids: Option<&Vec<PropertyId>>
+                             property_ids: Option<&Vec<PropertyId>>
     ) -> GraphResult<Option<VertexImpl>> {
         let snapshot_id = snapshot_id as i64;
         let vertex_type_info = self.vertex_manager.get_type_info(snapshot_id, label_id as i32)?;
@@ -37,7 +39,11 @@ impl RocksGraph {
                 if k[0..16] == key[0..16] && v.len() > 4 {
                     let codec_version = get_codec_version(v);
                     let decoder = vertex_type_info.get_decoder(snapshot_id, codec_version)?;
-                    let vertex = VertexImpl::new(vertex_id, vertex_type_info.get_label() as LabelId, decoder, RawBytes::new(v));
+                    if decoder.is_expired(v, current_time_millis()) {
+                        return Ok(None);
+                    }
+                    let vertex = VertexImpl::new(vertex_id, vertex_type_info.get_label() as LabelId, decoder, RawBytes::new(v),
+                                                 Self::parse_property_ids(property_ids));
                     return Ok(Some(vertex));
                 }
             }
@@ -49,18 +55,32 @@ impl RocksGraph {
                               snapshot_id: SnapshotId,
                               edge_id: EdgeId,
                               edge_relation: &EdgeRelation,
-                              _property_ids: Option<&Vec<PropertyId>>
+                              property_ids: Option<&Vec<PropertyId>>
+    ) -> GraphResult<Option<EdgeImpl>> {
+        self.get_edge_with_direction(snapshot_id, edge_id, edge_relation, EdgeDirection::Out, property_ids)
+    }
+
+    fn get_edge_with_direction(&self,
+                               snapshot_id: SnapshotId,
+                               edge_id: EdgeId,
+                               edge_relation: &EdgeRelation,
+                               direction: EdgeDirection,
+                               property_ids: Option<&Vec<PropertyId>>
     ) -> GraphResult<Option<EdgeImpl>> {
         let snapshot_id = snapshot_id as i64;
         let info = self.edge_manager.get_edge_kind(snapshot_id, &edge_relation.into())?;
         if let Some(table) = info.get_table(snapshot_id) {
-            let key = edge_key(table.id, edge_id.into(), EdgeDirection::Out, snapshot_id - table.start_si);
+            let key = edge_key(table.id, edge_id.into(), direction, snapshot_id - table.start_si);
             let mut iter = self.storage.scan_from(&key)?;
             if let Some((k, v)) = iter.next() {
                 if k[0..32] == key[0..32] && v.len() >= 4 {
                     let codec_version = get_codec_version(v);
                     let decoder = info.get_decoder(snapshot_id, codec_version)?;
-                    let edge = EdgeImpl::new(edge_id, info.get_type().into(), decoder, RawBytes::new(v));
+                    if decoder.is_expired(v, current_time_millis()) {
+                        return Ok(None);
+                    }
+                    let edge = EdgeImpl::new(edge_id, info.get_type().into(), decoder, RawBytes::new(v),
+                                             Self::parse_property_ids(property_ids));
                     return Ok(Some(edge));
                 }
             }
@@ -73,23 +93,104 @@ impl RocksGraph {
                    vertex_id: Option<VertexId>,
                    direction: EdgeDirection,
                    label_id: Option<LabelId>,
-                   _condition: Option<&Condition>,
-                   _property_ids: Option<&Vec<PropertyId>>,
+                   condition: Option<&Condition>,
+                   property_ids: Option<&Vec<PropertyId>>,
     ) -> GraphResult<Records<EdgeImpl>> {
+        let condition = Self::parse_condition(condition)?;
+        let property_ids = Self::parse_property_ids(property_ids);
         if let Some(label_id) = label_id {
             let edge_info = self.edge_manager.get_edge_info(snapshot_id as i64, label_id as i32)?;
-            let scan = EdgeTypeScan::new(self.storage.clone(), snapshot_id, edge_info, vertex_id, direction);
+            let scan = EdgeTypeScan::new(self.storage.clone(), snapshot_id, edge_info, vertex_id, direction,
+                                         condition, property_ids);
             Ok(scan.into_iter())
         } else {
             let mut edge_info_iter = self.edge_manager.get_all_edges(snapshot_id as i64);
             let mut res: Records<EdgeImpl> = Box::new(::std::iter::empty());
             while let Some(info) = edge_info_iter.next_info() {
-                let label_iter = EdgeTypeScan::new(self.storage.clone(), snapshot_id, info, vertex_id, direction).into_iter();
+                let label_iter = EdgeTypeScan::new(self.storage.clone(), snapshot_id, info, vertex_id, direction,
+                                                   condition.clone(), property_ids.clone()).into_iter();
                 res = Box::new(res.chain(label_iter));
             }
             Ok(res)
         }
     }
+
+    fn get_degree(&self,
+                  snapshot_id: SnapshotId,
+                  vertex_id: VertexId,
+                  edge_relation: &EdgeRelation,
+                  direction: EdgeDirection,
+    ) -> GraphResult<usize> {
+        let si = snapshot_id as i64;
+        let info = self.edge_manager.get_edge_kind(si, &edge_relation.into())?;
+        if info.get_encoder(si)?.get_ttl().is_some() {
+            let edges = self.scan_unexpired_edges(snapshot_id, vertex_id, edge_relation, direction, None)?;
+            return edges.try_fold(0, |n, e| e.map(|_| n + 1));
+        }
+        if let Some(table) = info.get_table(si) {
+            let ts = si - table.start_si;
+            return Ok(adjacency::get_degree(self.storage.as_ref(), table.id, vertex_id as i64, direction, ts)?);
+        }
+        Ok(0)
+    }
+
+    fn get_kth_edge(&self,
+                    snapshot_id: SnapshotId,
+                    vertex_id: VertexId,
+                    edge_relation: &EdgeRelation,
+                    direction: EdgeDirection,
+                    k: SerialId,
+                    property_ids: Option<&Vec<PropertyId>>,
+    ) -> GraphResult<Option<EdgeImpl>> {
+        let si = snapshot_id as i64;
+        let info = self.edge_manager.get_edge_kind(si, &edge_relation.into())?;
+        if info.get_encoder(si)?.get_ttl().is_some() {
+            let property_ids = Self::parse_property_ids(property_ids);
+            let mut edges = self.scan_unexpired_edges(snapshot_id, vertex_id, edge_relation, direction, property_ids)?;
+            return edges.nth(k as usize).transpose();
+        }
+        if let Some(table) = info.get_table(si) {
+            let ts = si - table.start_si;
+            let id = adjacency::get_kth_edge_id(self.storage.as_ref(), table.id, vertex_id as i64, direction, ts, k as usize)?;
+            if let Some(id) = id {
+                // in edges are stored in the partition of dst vertex, so read the edge in the same direction
+                return self.get_edge_with_direction(snapshot_id, id.into(), edge_relation, direction, property_ids);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Edge kinds with ttl have no adjacency index, so their unexpired edges are scanned instead.
+    fn scan_unexpired_edges(&self,
+                            snapshot_id: SnapshotId,
+                            vertex_id: VertexId,
+                            edge_relation: &EdgeRelation,
+                            direction: EdgeDirection,
+                            property_ids: Option<Arc<Vec<PropertyId>>>,
+    ) -> GraphResult<Records<EdgeImpl>> {
+        let edge_kind: EdgeKind = edge_relation.into();
+        let edge_info = self.edge_manager.get_edge_info(snapshot_id as i64, edge_kind.edge_label_id)?;
+        let kind_info = edge_info.get_kinds(snapshot_id as i64).find(|info| *info.get_type() == edge_kind);
+        match kind_info {
+            Some(info) => {
+                let scan = EdgeKindScan::new(self.storage.clone(), snapshot_id, info, Some(vertex_id), direction,
+                                             None, property_ids);
+                Ok(scan.into_iter())
+            }
+            None => Ok(Box::new(::std::iter::empty())),
+        }
+    }
+
+    fn parse_condition(condition: Option<&Condition>) -> GraphResult<Option<Arc<crate::db::api::Condition>>> {
+        match condition {
+            Some(condition) => Ok(Some(Arc::new(parse_condition(condition)?))),
+            None => Ok(None),
+        }
+    }
+
+    fn parse_property_ids(property_ids: Option<&Vec<PropertyId>>) -> Option<Arc<Vec<PropertyId>>> {
+        property_ids.map(|ids| Arc::new(ids.clone()))
+    }
 }
 
 impl MultiVersionGraph for RocksGraph {
@@ -140,18 +241,21 @@ impl MultiVersionGraph for RocksGraph {
     fn scan_vertex(&self,
                    snapshot_id: SnapshotId,
                    label_id: Option<LabelId>,
-                   _condition: Option<&Condition>,
-                   _property_ids: Option<&Vec<PropertyId>>
+                   condition: Option<&Condition>,
+                   property_ids: Option<&Vec<PropertyId>>
     ) -> GraphResult<Records<Self::V>> {
+        let condition = Self::parse_condition(condition)?;
+        let property_ids = Self::parse_property_ids(property_ids);
         if let Some(label_id) = label_id {
             let vertex_type_info = self.vertex_manager.get_type_info(snapshot_id as i64, label_id as i32)?;
-            let scan = VertexTypeScan::new(self.storage.clone(), snapshot_id, vertex_type_info);
+            let scan = VertexTypeScan::new(self.storage.clone(), snapshot_id, vertex_type_info, condition, property_ids);
             Ok(scan.into_iter())
         } else {
             let mut vertex_type_info_iter = self.vertex_manager.get_all(snapshot_id as i64);
             let mut res: Records<Self::V> = Box::new(::std::iter::empty());
             while let Some(info) = vertex_type_info_iter.next_info() {
-                let label_iter = VertexTypeScan::new(self.storage.clone(), snapshot_id, info).into_iter();
+                let label_iter = VertexTypeScan::new(self.storage.clone(), snapshot_id, info, condition.clone(),
+                                                     property_ids.clone()).into_iter();
                 res = Box::new(res.chain(label_iter));
             }
             Ok(res)
@@ -176,26 +280,18 @@ impl MultiVersionGraph for RocksGraph {
     }
 
     fn get_out_degree(&self, snapshot_id: SnapshotId, vertex_id: VertexId, edge_relation: &EdgeRelation) -> GraphResult<usize> {
-        let edges_iter = self.get_out_edges(snapshot_id, vertex_id, Some(edge_relation.get_edge_label_id()), None,
-                                            Some(vec![]).as_ref())?;
-        Ok(edges_iter.count())
+        self.get_degree(snapshot_id, vertex_id, edge_relation, EdgeDirection::Out)
     }
 
     fn get_in_degree(&self, snapshot_id: SnapshotId, vertex_id: VertexId, edge_relation: &EdgeRelation) -> GraphResult<usize> {
-        let edges_iter = self.get_in_edges(snapshot_id, vertex_id, Some(edge_relation.get_edge_label_id()), None,
-                                           Some(vec![]).as_ref())?;
-        Ok(edges_iter.count())
+        self.get_degree(snapshot_id, vertex_id, edge_relation, EdgeDirection::In)
     }
 
     fn get_kth_out_edge(&self, snapshot_id: SnapshotId, vertex_id: VertexId, edge_relation: &EdgeRelation, k: SerialId, property_ids: Option<&Vec<PropertyId>>) -> GraphResult<Option<Self::E>> {
-        let mut edges_iter = self.get_out_edges(snapshot_id, vertex_id, Some(edge_relation.get_edge_label_id()), None,
-                                                property_ids)?;
-        edges_iter.nth(k as usize).transpose()
+        self.get_kth_edge(snapshot_id, vertex_id, edge_relation, EdgeDirection::Out, k, property_ids)
     }
 
     fn get_kth_in_edge(&self, snapshot_id: SnapshotId, vertex_id: VertexId, edge_relation: &EdgeRelation, k: SerialId, property_ids: Option<&Vec<PropertyId>>) -> GraphResult<Option<Self::E>> {
-        let mut edges_iter = self.get_in_edges(snapshot_id, vertex_id, Some(edge_relation.get_edge_label_id()), None,
-                                               property_ids)?;
-        edges_iter.nth(k as usize).transpose()
+        self.get_kth_edge(snapshot_id, vertex_id, edge_relation, EdgeDirection::In, k, property_ids)
     }
 }
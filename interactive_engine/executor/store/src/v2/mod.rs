@@ -14,8 +14,10 @@
 //! limitations under the License.
 
 use crate::v2::errors::GraphError;
-use crate::db::api::{ValueRef, ValueType};
-use crate::v2::api::PropertyValue;
+use crate::db::api::{ValueRef, ValueType, Value, ComparisonOp, PredicateCondition, AndCondition, OrCondition,
+                     NotCondition};
+use crate::v2::api::{PropertyValue, PropertyId};
+use crate::v2::api::condition::Condition;
 
 pub mod api;
 pub mod errors;
@@ -44,3 +46,61 @@ fn parse_property_value(value_ref: ValueRef) -> PropertyValue {
             .map(String::from).collect()),
     }
 }
+
+fn parse_condition(condition: &Condition) -> GraphResult<crate::db::api::Condition> {
+    let ret = match condition {
+        Condition::And(left, right) => {
+            let sub_conditions = vec![parse_condition(left)?, parse_condition(right)?];
+            crate::db::api::Condition::And(AndCondition::new(sub_conditions))
+        }
+        Condition::Or(left, right) => {
+            let sub_conditions = vec![parse_condition(left)?, parse_condition(right)?];
+            crate::db::api::Condition::Or(OrCondition::new(sub_conditions))
+        }
+        Condition::Not(sub_condition) => {
+            crate::db::api::Condition::Not(NotCondition::new(parse_condition(sub_condition)?))
+        }
+        Condition::LessThan(property_id, value) => parse_predicate(*property_id, ComparisonOp::LessThan, value)?,
+        Condition::LessEqual(property_id, value) => parse_predicate(*property_id, ComparisonOp::LessEqual, value)?,
+        Condition::GreaterThan(property_id, value) => parse_predicate(*property_id, ComparisonOp::GreaterThan, value)?,
+        Condition::GreaterEqual(property_id, value) => parse_predicate(*property_id, ComparisonOp::GreaterEqual, value)?,
+        Condition::Equal(property_id, value) => parse_predicate(*property_id, ComparisonOp::Equal, value)?,
+        Condition::NotEqual(property_id, value) => parse_predicate(*property_id, ComparisonOp::NotEqual, value)?,
+    };
+    Ok(ret)
+}
+
+fn parse_predicate(property_id: PropertyId, op: ComparisonOp, value: &PropertyValue)
+    -> GraphResult<crate::db::api::Condition> {
+    let value = parse_value(value)?;
+    Ok(crate::db::api::Condition::Predicate(PredicateCondition::new(property_id as i32, op, value)))
+}
+
+fn parse_value(property_value: &PropertyValue) -> GraphResult<Value> {
+    let value = match property_value {
+        PropertyValue::Null => {
+            return Err(GraphError::InvalidArgument("null value cannot be used in condition".to_owned()));
+        }
+        PropertyValue::Boolean(v) => Value::bool(*v),
+        PropertyValue::Char(v) => {
+            if !v.is_ascii() {
+                let msg = format!("char {:?} cannot be used in condition, only ascii chars are supported", v);
+                return Err(GraphError::InvalidArgument(msg));
+            }
+            Value::char(*v as u8)
+        }
+        PropertyValue::Short(v) => Value::short(*v),
+        PropertyValue::Int(v) => Value::int(*v),
+        PropertyValue::Long(v) => Value::long(*v),
+        PropertyValue::Float(v) => Value::float(*v),
+        PropertyValue::Double(v) => Value::double(*v),
+        PropertyValue::String(v) => Value::string(v),
+        PropertyValue::Bytes(v) => Value::bytes(v),
+        PropertyValue::IntList(v) => Value::int_list(v),
+        PropertyValue::LongList(v) => Value::long_list(v),
+        PropertyValue::FloatList(v) => Value::float_list(v),
+        PropertyValue::DoubleList(v) => Value::double_list(v),
+        PropertyValue::StringList(v) => Value::string_list(v),
+    };
+    Ok(value)
+}
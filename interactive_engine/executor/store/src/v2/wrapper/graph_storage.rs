@@ -16,7 +16,7 @@ use crate::v2::multi_version_graph::MultiVersionGraph;
 use crate::v2::api::{SnapshotId, VertexId, LabelId, PropertyId, EdgeId, Records, SerialId, EdgeInnerId};
 use crate::v2::api::types::{EdgeRelation, Vertex, PropertyReader, PropertyValue, Property, Edge};
 use crate::v2::api::condition::Condition;
-use crate::v2::{GraphResult, parse_property_value};
+use crate::v2::{GraphResult, parse_property_value, parse_condition};
 use std::sync::Arc;
 use crate::db::api::{GraphStorage, EdgeKind, PropIter, PropertiesRef};
 use std::collections::{HashMap, HashSet};
@@ -102,13 +102,13 @@ impl<G: GraphStorage> GraphStorageWrapper<G> {
         WrapperEdge::new(edge_id, edge_relation, edge_properteis)
     }
 
-    fn parse_condition(condition: Option<&Condition>) -> Option<Arc<crate::db::api::Condition>> {
+    fn parse_condition(condition: Option<&Condition>) -> GraphResult<Option<Arc<crate::db::api::Condition>>> {
         match condition {
             None => {
-                None
+                Ok(None)
             }
-            Some(_) => {
-                unimplemented!()
+            Some(condition) => {
+                Ok(Some(Arc::new(parse_condition(condition)?)))
             }
         }
     }
@@ -152,7 +152,7 @@ impl<G: GraphStorage> MultiVersionGraph for GraphStorageWrapper<G> {
     ) -> GraphResult<Records<Self::V>> {
         let snapshot_id = Self::parse_snapshot_id(snapshot_id);
         let label_id = Self::parse_label_id(label_id);
-        let condition = Self::parse_condition(condition);
+        let condition = Self::parse_condition(condition)?;
         let mut raw_iter = self.storage.query_vertices(snapshot_id, label_id, condition)?;
         let mut res = vec![];
         while let Some(v) = raw_iter.next() {
@@ -168,7 +168,7 @@ impl<G: GraphStorage> MultiVersionGraph for GraphStorageWrapper<G> {
                  property_ids: Option<&Vec<PropertyId>>
     ) -> GraphResult<Records<Self::E>> {
         let snapshot_id = Self::parse_snapshot_id(snapshot_id);
-        let condition = Self::parse_condition(condition);
+        let condition = Self::parse_condition(condition)?;
         let mut raw_iter = self.storage.query_edges(snapshot_id, label_id.map(|l| l as i32), condition)?;
         let mut res = vec![];
         while let Some(e) = raw_iter.next() {
@@ -187,7 +187,7 @@ impl<G: GraphStorage> MultiVersionGraph for GraphStorageWrapper<G> {
         let snapshot_id = Self::parse_snapshot_id(snapshot_id);
         let vertex_id = Self::parse_vertex_id(vertex_id);
         let label_id = Self::parse_label_id(label_id);
-        let condition = Self::parse_condition(condition);
+        let condition = Self::parse_condition(condition)?;
         let mut raw_iter = self.storage.get_out_edges(snapshot_id, vertex_id, label_id, condition)?;
         let mut res = vec![];
         while let Some(e) = raw_iter.next() {
@@ -206,7 +206,7 @@ impl<G: GraphStorage> MultiVersionGraph for GraphStorageWrapper<G> {
         let snapshot_id = Self::parse_snapshot_id(snapshot_id);
         let vertex_id = Self::parse_vertex_id(vertex_id);
         let label_id = Self::parse_label_id(label_id);
-        let condition = Self::parse_condition(condition);
+        let condition = Self::parse_condition(condition)?;
         let mut raw_iter = self.storage.get_in_edges(snapshot_id, vertex_id, label_id, condition)?;
         let mut res = vec![];
         while let Some(e) = raw_iter.next() {
@@ -220,9 +220,10 @@ impl<G: GraphStorage> MultiVersionGraph for GraphStorageWrapper<G> {
                       vertex_id: VertexId,
                       edge_relation: &EdgeRelation
     ) -> GraphResult<usize> {
-        let edges_iter = self.get_out_edges(snapshot_id, vertex_id, Some(edge_relation.get_edge_label_id()), None,
-                                            Some(vec![]).as_ref())?;
-        Ok(edges_iter.count())
+        let snapshot_id = Self::parse_snapshot_id(snapshot_id);
+        let vertex_id = Self::parse_vertex_id(vertex_id);
+        let edge_kind = Self::parse_edge_kind(edge_relation);
+        Ok(self.storage.get_out_degree(snapshot_id, vertex_id, &edge_kind)?)
     }
 
     fn get_in_degree(&self,
@@ -230,9 +231,10 @@ impl<G: GraphStorage> MultiVersionGraph for GraphStorageWrapper<G> {
                      vertex_id: VertexId,
                      edge_relation: &EdgeRelation
     ) -> GraphResult<usize> {
-        let edges_iter = self.get_in_edges(snapshot_id, vertex_id, Some(edge_relation.get_edge_label_id()), None,
-                                            Some(vec![]).as_ref())?;
-        Ok(edges_iter.count())
+        let snapshot_id = Self::parse_snapshot_id(snapshot_id);
+        let vertex_id = Self::parse_vertex_id(vertex_id);
+        let edge_kind = Self::parse_edge_kind(edge_relation);
+        Ok(self.storage.get_in_degree(snapshot_id, vertex_id, &edge_kind)?)
     }
 
     fn get_kth_out_edge(&self,
@@ -242,9 +244,11 @@ impl<G: GraphStorage> MultiVersionGraph for GraphStorageWrapper<G> {
                         k: SerialId,
                         property_ids: Option<&Vec<PropertyId>>
     ) -> GraphResult<Option<Self::E>> {
-        let mut edges_iter = self.get_out_edges(snapshot_id, vertex_id, Some(edge_relation.get_edge_label_id()), None,
-                                                property_ids)?;
-        edges_iter.nth(k as usize).transpose()
+        let snapshot_id = Self::parse_snapshot_id(snapshot_id);
+        let vertex_id = Self::parse_vertex_id(vertex_id);
+        let edge_kind = Self::parse_edge_kind(edge_relation);
+        let raw_e = self.storage.get_kth_out_edge(snapshot_id, vertex_id, &edge_kind, k as usize)?;
+        Ok(raw_e.map(|inner| Self::parse_edge(inner, property_ids)))
     }
 
     fn get_kth_in_edge(&self,
@@ -254,9 +258,11 @@ impl<G: GraphStorage> MultiVersionGraph for GraphStorageWrapper<G> {
                        k: SerialId,
                        property_ids: Option<&Vec<PropertyId>>
     ) -> GraphResult<Option<Self::E>> {
-        let mut edges_iter = self.get_in_edges(snapshot_id, vertex_id, Some(edge_relation.get_edge_label_id()), None,
-                                                property_ids)?;
-        edges_iter.nth(k as usize).transpose()
+        let snapshot_id = Self::parse_snapshot_id(snapshot_id);
+        let vertex_id = Self::parse_vertex_id(vertex_id);
+        let edge_kind = Self::parse_edge_kind(edge_relation);
+        let raw_e = self.storage.get_kth_in_edge(snapshot_id, vertex_id, &edge_kind, k as usize)?;
+        Ok(raw_e.map(|inner| Self::parse_edge(inner, property_ids)))
     }
 }
 
@@ -361,3 +367,77 @@ impl PropertyReader for WrapperEdge {
             .into_iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::api::{GraphConfigBuilder, TypeDefBuilder, ValueType, Value, PropId};
+    use crate::db::graph::store::GraphStore;
+
+    #[test]
+    fn test_query_with_condition() {
+        let path = "store_test/test_wrapper_query_with_condition";
+        let _ = std::fs::remove_dir_all(path);
+        let mut builder = GraphConfigBuilder::new();
+        builder.set_storage_engine("rocksdb");
+        let store = GraphStore::open(&builder.build(), path).unwrap();
+        let kind = EdgeKind::new(2, 1, 1);
+        let mut type_builder = TypeDefBuilder::new();
+        type_builder.add_property(1, 1, "age".to_string(), ValueType::Int, None, false, "cmt".to_string());
+        type_builder.add_property(2, 3, "name".to_string(), ValueType::String, None, false, "cmt".to_string());
+        type_builder.set_label_id(1);
+        store.create_vertex_type(1, 1, 1, &type_builder.build(), 1).unwrap();
+        let mut type_builder = TypeDefBuilder::new();
+        type_builder.add_property(1, 5, "weight".to_string(), ValueType::Long, None, false, "cmt".to_string());
+        type_builder.set_label_id(2);
+        store.create_edge_type(1, 2, 2, &type_builder.build()).unwrap();
+        store.add_edge_kind(1, 3, &kind, 3).unwrap();
+        for id in 1..=10 {
+            let mut properties = HashMap::<PropId, Value>::new();
+            properties.insert(1, Value::int(id as i32));
+            properties.insert(2, Value::string(&format!("v{}", id)));
+            store.insert_overwrite_vertex(2, id, 1, &properties).unwrap();
+            let mut properties = HashMap::<PropId, Value>::new();
+            properties.insert(1, Value::long(id));
+            let edge_id = crate::db::api::EdgeId::new(1, id, id);
+            store.insert_overwrite_edge(2, edge_id, &kind, true, &properties).unwrap();
+            store.insert_overwrite_edge(2, edge_id, &kind, false, &properties).unwrap();
+        }
+        let graph = GraphStorageWrapper::new(Arc::new(store));
+
+        let condition = Condition::And(
+            Box::new(Condition::GreaterThan(1, PropertyValue::Long(3))),
+            Box::new(Condition::Not(Box::new(Condition::Equal(2, PropertyValue::String("v5".to_owned()))))),
+        );
+        let property_ids = vec![2];
+        let mut ids = vec![];
+        for v in graph.scan_vertex(2, Some(1), Some(&condition), Some(&property_ids)).unwrap() {
+            let v = v.unwrap();
+            assert!(v.get_property(1).is_none());
+            match v.get_property(2).unwrap().get_property_value() {
+                PropertyValue::String(name) => assert_eq!(*name, format!("v{}", v.get_vertex_id())),
+                _ => panic!("property 2 should be a string"),
+            }
+            ids.push(v.get_vertex_id());
+        }
+        ids.sort();
+        assert_eq!(ids, vec![4, 6, 7, 8, 9, 10]);
+
+        let condition = Condition::Or(
+            Box::new(Condition::LessEqual(1, PropertyValue::Int(2))),
+            Box::new(Condition::GreaterEqual(1, PropertyValue::Double(9.5))),
+        );
+        let mut ids = vec![];
+        for e in graph.get_out_edges(2, 1, Some(2), Some(&condition), None).unwrap() {
+            let e = e.unwrap();
+            assert!(e.get_property(1).is_some());
+            ids.push(e.get_edge_id().get_dst_vertex_id());
+        }
+        ids.sort();
+        assert_eq!(ids, vec![1, 2, 10]);
+
+        let condition = Condition::Equal(1, PropertyValue::Null);
+        assert!(graph.scan_vertex(2, Some(1), Some(&condition), None).is_err());
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
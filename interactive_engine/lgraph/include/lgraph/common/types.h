@@ -78,6 +78,15 @@ enum OpType : int32_t {
   COMMIT_DATA_LOAD = 14,
   };
 
+enum CmpOperator : int32_t {
+  LESS_THAN = 0,
+  LESS_EQUAL = 1,
+  GREATER_THAN = 2,
+  GREATER_EQUAL = 3,
+  EQUAL = 4,
+  NOT_EQUAL = 5,
+};
+
 struct EdgeId {
   EdgeInnerId edge_inner_id;
   VertexId src_vertex_id;
@@ -123,5 +132,6 @@ typedef void *EdgeHandle;
 typedef void *EdgeIterHandle;
 typedef void *PropertyHandle;
 typedef void *PropertyIterHandle;
+typedef void *ConditionHandle;
 
 }
@@ -16,6 +16,8 @@
 
 #pragma once
 
+#include <vector>
+
 #include "lgraph/db/vertex.h"
 #include "lgraph/db/edge.h"
 
@@ -45,18 +47,25 @@ public:
   // Default : edge_relation not specified.
   Result<Edge, Error> GetEdge(EdgeId edge_id, const EdgeRelation &edge_relation = none_edge_relation);
 
-  // Scan vertex by label_id.
-  // Default : label_id not specified.
-  Result<VertexIterator, Error> ScanVertex(LabelId label_id = none_label_id);
+  // Scan vertex by label_id, only vertices matching condition are returned.
+  // If prop_ids is not null, only these properties are fetched.
+  // Default : label_id, condition and prop_ids not specified.
+  Result<VertexIterator, Error> ScanVertex(LabelId label_id = none_label_id, ConditionHandle condition = nullptr,
+                                           const std::vector<PropertyId> *prop_ids = nullptr);
 
   // Scan edge by edge_relation.
   // Default : edge_relation not specified.
   Result<EdgeIterator, Error> ScanEdge(const EdgeRelation &edge_relation = none_edge_relation);
 
   // Get out/in edges of vertex_id by edge_label_id.
-  // Default : edge_label_id not specified.
-  Result<EdgeIterator, Error> GetOutEdges(VertexId vertex_id, LabelId edge_label_id = none_label_id);
-  Result<EdgeIterator, Error> GetInEdges(VertexId vertex_id, LabelId edge_label_id = none_label_id);
+  // Both can be further filtered by condition and projected by prop_ids.
+  // Default : edge_label_id, condition and prop_ids not specified.
+  Result<EdgeIterator, Error> GetOutEdges(VertexId vertex_id, LabelId edge_label_id = none_label_id,
+                                          ConditionHandle condition = nullptr,
+                                          const std::vector<PropertyId> *prop_ids = nullptr);
+  Result<EdgeIterator, Error> GetInEdges(VertexId vertex_id, LabelId edge_label_id = none_label_id,
+                                         ConditionHandle condition = nullptr,
+                                         const std::vector<PropertyId> *prop_ids = nullptr);
 
   // Get out/in degree of vertex_id by edge_relation.
   // edge_relation must be specified.
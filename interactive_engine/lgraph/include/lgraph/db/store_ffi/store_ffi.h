@@ -29,10 +29,13 @@ extern "C" {
   PartitionSnapshotHandle GetSnapshot(PartitionGraphHandle graph, SnapshotId snapshot_id);
   VertexHandle GetVertex(PartitionSnapshotHandle snapshot, VertexId vertex_id, LabelId label_id, ErrorHandle* error);
   EdgeHandle GetEdge(PartitionSnapshotHandle snapshot, EdgeId edge_id, const EdgeRelation& edge_relation, ErrorHandle* error);
-  VertexIterHandle ScanVertex(PartitionSnapshotHandle snapshot, LabelId label_id, ErrorHandle* error);
+  VertexIterHandle ScanVertex(PartitionSnapshotHandle snapshot, LabelId label_id, ConditionHandle condition,
+                              const PropertyId* prop_ids, size_t prop_count, ErrorHandle* error);
   EdgeIterHandle ScanEdge(PartitionSnapshotHandle snapshot, const EdgeRelation& edge_relation, ErrorHandle* error);
-  EdgeIterHandle GetOutEdges(PartitionSnapshotHandle snapshot, VertexId vertex_id, LabelId edge_label_id, ErrorHandle* error);
-  EdgeIterHandle GetInEdges(PartitionSnapshotHandle snapshot, VertexId vertex_id, LabelId edge_label_id, ErrorHandle* error);
+  EdgeIterHandle GetOutEdges(PartitionSnapshotHandle snapshot, VertexId vertex_id, LabelId edge_label_id,
+                             ConditionHandle condition, const PropertyId* prop_ids, size_t prop_count, ErrorHandle* error);
+  EdgeIterHandle GetInEdges(PartitionSnapshotHandle snapshot, VertexId vertex_id, LabelId edge_label_id,
+                            ConditionHandle condition, const PropertyId* prop_ids, size_t prop_count, ErrorHandle* error);
   size_t GetOutDegree(PartitionSnapshotHandle snapshot, VertexId vertex_id, const EdgeRelation& edge_relation, ErrorHandle* error);
   size_t GetInDegree(PartitionSnapshotHandle snapshot, VertexId vertex_id, const EdgeRelation& edge_relation, ErrorHandle* error);
   EdgeHandle GetKthOutEdge(PartitionSnapshotHandle snapshot, VertexId vertex_id, const EdgeRelation& edge_relation, SerialId k, ErrorHandle* error);
@@ -62,6 +65,16 @@ extern "C" {
   double GetPropertyAsDouble(PropertyHandle prop_hdl, ErrorHandle* error);
   StringSlice GetPropertyAsString(PropertyHandle prop_hdl, ErrorHandle* error);
 
+  /// Condition FFIs
+  ConditionHandle CreateInt32Condition(CmpOperator op, PropertyId prop_id, int32_t value);
+  ConditionHandle CreateInt64Condition(CmpOperator op, PropertyId prop_id, int64_t value);
+  ConditionHandle CreateFloatCondition(CmpOperator op, PropertyId prop_id, float value);
+  ConditionHandle CreateDoubleCondition(CmpOperator op, PropertyId prop_id, double value);
+  ConditionHandle CreateStringCondition(CmpOperator op, PropertyId prop_id, const char* value);
+  ConditionHandle CreateAndCondition(ConditionHandle left, ConditionHandle right);
+  ConditionHandle CreateOrCondition(ConditionHandle left, ConditionHandle right);
+  ConditionHandle CreateNotCondition(ConditionHandle condition);
+
   /// Error FFIs
   StringSlice GetErrorInfo(ErrorHandle error_hdl);
 
@@ -75,6 +88,7 @@ extern "C" {
   void ReleaseEdgeIteratorHandle(EdgeIterHandle ptr);
   void ReleasePropertyHandle(PropertyHandle ptr);
   void ReleasePropertyIteratorHandle(PropertyIterHandle ptr);
+  void ReleaseConditionHandle(ConditionHandle ptr);
 }
 
 }
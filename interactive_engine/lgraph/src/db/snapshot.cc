@@ -46,9 +46,12 @@ Result<Edge, Error> Snapshot::GetEdge(EdgeId edge_id, const EdgeRelation &edge_r
   return Result<Edge, Error>(Err(Error(err_hdl)));
 }
 
-Result<VertexIterator, Error> Snapshot::ScanVertex(LabelId label_id) {
+Result<VertexIterator, Error> Snapshot::ScanVertex(LabelId label_id, ConditionHandle condition,
+                                                  const std::vector<PropertyId> *prop_ids) {
   ErrorHandle err_hdl = nullptr;
-  VertexIterHandle vertex_iter_hdl = ffi::ScanVertex(handle_, label_id, &err_hdl);
+  const PropertyId *prop_data = prop_ids == nullptr ? nullptr : prop_ids->data();
+  size_t prop_count = prop_ids == nullptr ? 0 : prop_ids->size();
+  VertexIterHandle vertex_iter_hdl = ffi::ScanVertex(handle_, label_id, condition, prop_data, prop_count, &err_hdl);
   if (err_hdl == nullptr) {
     return Result<VertexIterator, Error>(Ok(VertexIterator(vertex_iter_hdl)));
   }
@@ -64,18 +67,26 @@ Result<EdgeIterator, Error> Snapshot::ScanEdge(const EdgeRelation &edge_relation
   return Result<EdgeIterator, Error>(Err(Error(err_hdl)));
 }
 
-Result<EdgeIterator, Error> Snapshot::GetOutEdges(VertexId vertex_id, LabelId edge_label_id) {
+Result<EdgeIterator, Error> Snapshot::GetOutEdges(VertexId vertex_id, LabelId edge_label_id, ConditionHandle condition,
+                                                  const std::vector<PropertyId> *prop_ids) {
   ErrorHandle err_hdl = nullptr;
-  EdgeIterHandle edge_iter_hdl = ffi::GetOutEdges(handle_, vertex_id, edge_label_id, &err_hdl);
+  const PropertyId *prop_data = prop_ids == nullptr ? nullptr : prop_ids->data();
+  size_t prop_count = prop_ids == nullptr ? 0 : prop_ids->size();
+  EdgeIterHandle edge_iter_hdl = ffi::GetOutEdges(handle_, vertex_id, edge_label_id, condition, prop_data, prop_count,
+                                                  &err_hdl);
   if (err_hdl == nullptr) {
     return Result<EdgeIterator, Error>(Ok(EdgeIterator(edge_iter_hdl)));
   }
   return Result<EdgeIterator, Error>(Err(Error(err_hdl)));
 }
 
-Result<EdgeIterator, Error> Snapshot::GetInEdges(VertexId vertex_id, LabelId edge_label_id) {
+Result<EdgeIterator, Error> Snapshot::GetInEdges(VertexId vertex_id, LabelId edge_label_id, ConditionHandle condition,
+                                                 const std::vector<PropertyId> *prop_ids) {
   ErrorHandle err_hdl = nullptr;
-  EdgeIterHandle edge_iter_hdl = ffi::GetInEdges(handle_, vertex_id, edge_label_id, &err_hdl);
+  const PropertyId *prop_data = prop_ids == nullptr ? nullptr : prop_ids->data();
+  size_t prop_count = prop_ids == nullptr ? 0 : prop_ids->size();
+  EdgeIterHandle edge_iter_hdl = ffi::GetInEdges(handle_, vertex_id, edge_label_id, condition, prop_data, prop_count,
+                                                 &err_hdl);
   if (err_hdl == nullptr) {
     return Result<EdgeIterator, Error>(Ok(EdgeIterator(edge_iter_hdl)));
   }
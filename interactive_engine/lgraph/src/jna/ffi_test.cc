@@ -460,6 +460,106 @@ bool TestGetInEdges(db::Snapshot* ss, std::stringstream& logger) {
   return true;
 }
 
+bool TestGetInEdgesWithCondition(db::Snapshot* ss, std::stringstream& logger) {
+  // Query src vertex: <VertexID: 10454779632061085998> <Label: software> <id: 3> <name: lop> <lang: java>
+  // In-edges with 'weight' > 0.3: created by marko and josh, both with <weight: 0.400000>
+  VertexId query_vid = 10454779632061085998U;
+  unsigned expect_nbr_num = 2;
+
+  ConditionHandle condition = ffi::CreateDoubleCondition(GREATER_THAN, weight_PropId, 0.3);
+  auto r = ss->GetInEdges(query_vid, created_EdgeLabelId, condition);
+  ffi::ReleaseConditionHandle(condition);
+  if (r.isErr()) {
+    logger << "[Error] GetInEdges: " << r.unwrapErr().GetInfo() << "\n";
+    return false;
+  }
+  auto ei = r.unwrap();
+  if (!ei.Valid()) {
+    logger << "[Error] Got invalid edge iterator handle at GetInEdges for (" << query_vid << ", created)!\n";
+    return false;
+  }
+  unsigned nbr_cnt = 0;
+  while (true) {
+    auto re = ei.Next();
+    if (re.isErr()) {
+      logger << "[Error] EdgeIterator.Next(): " << re.unwrapErr().GetInfo() << "\n";
+      return false;
+    }
+    auto e = re.unwrap();
+    if (!e.Valid()) { break; }
+    nbr_cnt++;
+    if (!LogEdgeInfo(&e, logger)) {
+      return false;
+    }
+    auto weight_r = e.GetPropertyBy(weight_PropId).GetAsDouble();
+    if (weight_r.isErr()) {
+      logger << "[Error] Property.GetAsDouble(): " << weight_r.unwrapErr().GetInfo() << "\n";
+      return false;
+    }
+    if (weight_r.unwrap() <= 0.3) {
+      logger << "[Error] Got in-edge with 'weight' " << weight_r.unwrap() << " not matching the condition!\n";
+      return false;
+    }
+  }
+  if (nbr_cnt != expect_nbr_num) {
+    logger << "[Error] Incorrect filtered in-neighbour number! "
+           << "Expect: " << expect_nbr_num << ", Got: " << nbr_cnt << "!\n";
+    return false;
+  }
+  logger << "[INFO] --- Get filtered in-edges passed! Total in-neighbour number: " << nbr_cnt << "\n";
+
+  return true;
+}
+
+bool TestGetInEdgesWithProjection(db::Snapshot* ss, std::stringstream& logger) {
+  // Query src vertex: <VertexID: 10454779632061085998> <Label: software> <id: 3> <name: lop> <lang: java>
+  // Only the 'weight' property of the in-edges is projected, the 'id' property is left out.
+  VertexId query_vid = 10454779632061085998U;
+  unsigned expect_nbr_num = 3;
+
+  std::vector<PropertyId> prop_ids{weight_PropId};
+  auto r = ss->GetInEdges(query_vid, created_EdgeLabelId, nullptr, &prop_ids);
+  if (r.isErr()) {
+    logger << "[Error] GetInEdges: " << r.unwrapErr().GetInfo() << "\n";
+    return false;
+  }
+  auto ei = r.unwrap();
+  if (!ei.Valid()) {
+    logger << "[Error] Got invalid edge iterator handle at GetInEdges for (" << query_vid << ", created)!\n";
+    return false;
+  }
+  unsigned nbr_cnt = 0;
+  while (true) {
+    auto re = ei.Next();
+    if (re.isErr()) {
+      logger << "[Error] EdgeIterator.Next(): " << re.unwrapErr().GetInfo() << "\n";
+      return false;
+    }
+    auto e = re.unwrap();
+    if (!e.Valid()) { break; }
+    nbr_cnt++;
+    if (!LogEdgeInfo(&e, logger)) {
+      return false;
+    }
+    if (!e.GetPropertyBy(weight_PropId).Valid()) {
+      logger << "[Error] Got invalid 'weight' property handle of projected in-edge!\n";
+      return false;
+    }
+    if (e.GetPropertyBy(id_PropId).Valid()) {
+      logger << "[Error] Got 'id' property of in-edge which is not projected!\n";
+      return false;
+    }
+  }
+  if (nbr_cnt != expect_nbr_num) {
+    logger << "[Error] Incorrect projected in-neighbour number! "
+           << "Expect: " << expect_nbr_num << ", Got: " << nbr_cnt << "!\n";
+    return false;
+  }
+  logger << "[INFO] --- Get projected in-edges passed! Total in-neighbour number: " << nbr_cnt << "\n";
+
+  return true;
+}
+
 bool TestGetOutDegree(db::Snapshot* ss, std::stringstream& logger) {
   // Query src vertex: <VertexID: 12334515728491031937> <Label: person> <name: josh> <id: 4> <age: 32>
   VertexId query_vid = 12334515728491031937U;
@@ -603,7 +703,7 @@ bool TestGetSnapshotId(db::Snapshot* ss, std::stringstream& logger) {
   return true;
 }
 
-const unsigned test_num = 11;
+const unsigned test_num = 13;
 
 typedef bool (*TestFunc)(db::Snapshot* ss, std::stringstream& logger);
 
@@ -633,11 +733,13 @@ TestResult* runLocalTests() {
     success_num += RunTest(4, "GetEdge", TestGetEdge, &latest_ss, logger) ? 1 : 0;
     success_num += RunTest(5, "GetOutEdges", TestGetOutEdges, &latest_ss, logger) ? 1 : 0;
     success_num += RunTest(6, "GetInEdges", TestGetInEdges, &latest_ss, logger) ? 1 : 0;
-    success_num += RunTest(7, "GetOutDegree", TestGetOutDegree, &latest_ss, logger) ? 1 : 0;
-    success_num += RunTest(8, "GetInDegree", TestGetInDegree, &latest_ss, logger) ? 1 : 0;
-    success_num += RunTest(9, "GetKthOutEdge", TestGetKthOutEdge, &latest_ss, logger) ? 1 : 0;
-    success_num += RunTest(10, "GetKthInEdge", TestGetKthInEdge, &latest_ss, logger) ? 1 : 0;
-    success_num += RunTest(11, "GetSnapshotId", TestGetSnapshotId, &latest_ss, logger) ? 1 : 0;
+    success_num += RunTest(7, "GetInEdgesWithCondition", TestGetInEdgesWithCondition, &latest_ss, logger) ? 1 : 0;
+    success_num += RunTest(8, "GetInEdgesWithProjection", TestGetInEdgesWithProjection, &latest_ss, logger) ? 1 : 0;
+    success_num += RunTest(9, "GetOutDegree", TestGetOutDegree, &latest_ss, logger) ? 1 : 0;
+    success_num += RunTest(10, "GetInDegree", TestGetInDegree, &latest_ss, logger) ? 1 : 0;
+    success_num += RunTest(11, "GetKthOutEdge", TestGetKthOutEdge, &latest_ss, logger) ? 1 : 0;
+    success_num += RunTest(12, "GetKthInEdge", TestGetKthInEdge, &latest_ss, logger) ? 1 : 0;
+    success_num += RunTest(13, "GetSnapshotId", TestGetSnapshotId, &latest_ss, logger) ? 1 : 0;
   } else {
     logger << "[Error] Got invalid snapshot handle with SnapshotId=" << query_snapshot_id << "!\n";
   }
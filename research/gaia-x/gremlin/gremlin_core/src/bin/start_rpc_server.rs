@@ -21,7 +21,7 @@ use log::info;
 use pegasus::Configuration;
 use pegasus_server::config::combine_config;
 
-use pegasus_server::rpc::{start_rpc_server, RpcService};
+use pegasus_server::rpc::{start_rpc_server_with_tls, RpcService};
 use pegasus_server::service::Service;
 use pegasus_server::{CommonConfig, HostsConfig};
 use structopt::StructOpt;
@@ -76,7 +76,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some(CommonConfig::read_from(server_config.config)?)
     };
     let num_servers = if let Some(h) = &host_config { h.peers.len() } else { 1 };
-    let config = combine_config(server_config.server_id, host_config, common_config);
+    let config = combine_config(server_config.server_id, host_config, common_config.clone());
     let addr = format!("{}:{}", "0.0.0.0", server_config.rpc_port);
 
     create_demo_graph();
@@ -92,8 +92,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let partition = Partition { num_servers: num_servers.clone() };
     let factory = GremlinJobCompiler::new(partition, num_servers, server_config.server_id);
     let service = Service::new(factory);
-    let rpc_service = RpcService::new(service, server_config.report);
-    start_rpc_server(addr.parse().unwrap(), rpc_service, true).await?;
+    let mut rpc_service = RpcService::new(service, server_config.report);
+    if let Some(common_config) = &common_config {
+        rpc_service = rpc_service.with_config(common_config);
+    }
+    // serve clients over mutual TLS as the servers talk to each other, if it is configured;
+    let tls = common_config.and_then(|c| c.tls);
+    start_rpc_server_with_tls(addr.parse().unwrap(), rpc_service, tls, true).await?;
 
     Ok(())
 }
@@ -297,6 +297,74 @@ impl<T: Decode> Decode for Option<T> {
     }
 }
 
+/// Implement [`Encode`] and [`Decode`] for a sum type, each variant is written as a `u8` tag followed
+/// by its fields in order, and a tag not listed is decoded as an error of `InvalidData`;
+///
+/// Each variant is given as `tag => Variant(field, ...)`, or `tag => Variant` if it has no field, the
+/// field names are only used to bind the fields while encoding;
+///
+/// # Examples
+/// ```
+/// use pegasus_common::codec::*;
+/// use pegasus_common::enum_codec;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Shape<T> {
+///     Circle(T),
+///     Rect(T, T),
+///     Empty,
+/// }
+///
+/// enum_codec! {
+///     Shape<T> {
+///         0 => Circle(r),
+///         1 => Rect(w, h),
+///         2 => Empty,
+///     }
+/// }
+///
+/// let mut bytes = vec![];
+/// Shape::Rect(3u32, 4u32).write_to(&mut bytes).unwrap();
+/// Shape::<u32>::Empty.write_to(&mut bytes).unwrap();
+/// let mut reader = &bytes[0..];
+/// assert_eq!(Shape::read_from(&mut reader).unwrap(), Shape::Rect(3u32, 4u32));
+/// assert_eq!(Shape::<u32>::read_from(&mut reader).unwrap(), Shape::Empty);
+/// ```
+#[macro_export]
+macro_rules! enum_codec {
+    ( $name: ident $(< $($gen: ident),+ >)? {
+        $( $tag: literal => $variant: ident $(( $($field: ident),+ ))? ),+ $(,)?
+    } ) => {
+        impl$(<$($gen: $crate::codec::Encode),+>)? $crate::codec::Encode for $name$(<$($gen),+>)? {
+            fn write_to<W: $crate::codec::WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
+                match self {
+                    $(
+                        $name::$variant $(( $($field),+ ))? => {
+                            writer.write_u8($tag)?;
+                            $($( $crate::codec::Encode::write_to($field, writer)?; )+)?
+                            Ok(())
+                        }
+                    )+
+                }
+            }
+        }
+
+        impl$(<$($gen: $crate::codec::Decode),+>)? $crate::codec::Decode for $name$(<$($gen),+>)? {
+            fn read_from<R: $crate::codec::ReadExt>(reader: &mut R) -> std::io::Result<Self> {
+                match reader.read_u8()? {
+                    $(
+                        $tag => {
+                            $($( let $field = $crate::codec::Decode::read_from(reader)?; )+)?
+                            Ok($name::$variant $(( $($field),+ ))?)
+                        }
+                    )+
+                    _ => Err(std::io::Error::from(std::io::ErrorKind::InvalidData)),
+                }
+            }
+        }
+    };
+}
+
 mod shade;
 mod third_party;
 pub use shade::ShadeCodec;
@@ -561,6 +629,36 @@ mod test {
         assert_eq!(item, decoded);
     }
 
+    #[test]
+    fn serde_enum() {
+        #[derive(Debug, Eq, PartialEq)]
+        enum Item<T> {
+            Id(u64),
+            Edge(T, T),
+            Name(String),
+            Nil,
+        }
+
+        enum_codec! {
+            Item<T> {
+                0 => Id(id),
+                1 => Edge(src, dst),
+                2 => Name(name),
+                3 => Nil,
+            }
+        }
+
+        let items = vec![Item::Id(1), Item::Edge(2u32, 3u32), Item::Name("pegasus".to_owned()), Item::Nil];
+        let mut bytes = vec![];
+        items.write_to(&mut bytes).unwrap();
+        let mut reader = &bytes[0..];
+        let decoded = <Vec<Item<u32>>>::read_from(&mut reader).unwrap();
+        assert_eq!(items, decoded);
+
+        let mut reader = &[4u8][0..];
+        assert!(<Item<u32>>::read_from(&mut reader).is_err());
+    }
+
     #[test]
     fn as_bytes_of_copy() {
         #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -73,7 +73,7 @@ fn main() {
     println!("echo-benchmark config : {:?}", echo_config);
     let config = pegasus_network::config::read_from(echo_config.network_config_file.as_path()).unwrap();
     let addr = config.local_addr().unwrap();
-    let params = config.get_connection_param();
+    let params = config.get_connection_param().unwrap();
     println!("connection parameters: {:?}", params);
     let peers = config.get_peers().unwrap();
     if peers.is_none() {
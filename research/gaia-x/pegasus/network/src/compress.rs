@@ -0,0 +1,172 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::io::{self, Read, Write};
+
+use pegasus_common::codec::AsBytes;
+
+use crate::config::Compression;
+use crate::message::{MessageHeader, Payload, MESSAGE_HEAD_SIZE};
+
+const LZ4_CODE: u32 = 1;
+const ZSTD_CODE: u32 = 2;
+
+/// The max length of the content restored from a compressed message. Larger contents are sent without
+/// compression, so a corrupted or malicious header can't make the receiver allocate beyond it;
+pub(crate) const MAX_DECOMPRESSED_LENGTH: usize = 1 << 28;
+
+impl Compression {
+    /// The code of the compression written in `MessageHeader::compression`, 0 means not compressed;
+    fn code(&self) -> u32 {
+        match self {
+            Compression::None => 0,
+            Compression::LZ4 => LZ4_CODE,
+            Compression::Zstd => ZSTD_CODE,
+        }
+    }
+}
+
+/// Compress the payloads of a channel whose contents are larger than the threshold;
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Compressor {
+    kind: Compression,
+    threshold: usize,
+}
+
+impl Compressor {
+    pub fn new(kind: Compression, threshold: usize) -> Self {
+        Compressor { kind, threshold }
+    }
+
+    /// Compress the content of the encoded message, which starts with the header. The message is kept
+    /// as it is if it is too small, or the compressed content isn't smaller than the original one;
+    pub fn compress(&self, header: &mut MessageHeader, payload: Payload) -> io::Result<Payload> {
+        let length = header.length as usize;
+        if self.kind == Compression::None || length < self.threshold || length > MAX_DECOMPRESSED_LENGTH {
+            return Ok(payload);
+        }
+        let content = &payload.as_ref()[MESSAGE_HEAD_SIZE..];
+        let compressed = match self.kind {
+            Compression::None => unreachable!(),
+            Compression::LZ4 => lz4_flex::compress_prepend_size(content),
+            Compression::Zstd => zstd::stream::encode_all(content, zstd::DEFAULT_COMPRESSION_LEVEL)?,
+        };
+        if compressed.len() >= content.len() {
+            return Ok(payload);
+        }
+
+        header.set_length(compressed.len())?;
+        header.compression = self.kind.code();
+        let mut buffer = Vec::with_capacity(MESSAGE_HEAD_SIZE + compressed.len());
+        buffer.write_all(header.as_bytes())?;
+        buffer.write_all(&compressed)?;
+        Ok(Payload::Owned((buffer, 0)))
+    }
+}
+
+/// Restore the content of a message compressed by the code in its header;
+pub(crate) fn decompress(code: u32, content: &[u8]) -> io::Result<Vec<u8>> {
+    restore(code, content, MAX_DECOMPRESSED_LENGTH)
+}
+
+fn restore(code: u32, content: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+    match code {
+        LZ4_CODE => {
+            if content.len() < 4 {
+                return Err(invalid_data("truncated lz4 content of message".to_owned()));
+            }
+            // the length of the original content is prepended in little endian;
+            let mut size = [0u8; 4];
+            size.copy_from_slice(&content[0..4]);
+            let size = u32::from_le_bytes(size) as usize;
+            if size > limit {
+                return Err(too_large(limit));
+            }
+            lz4_flex::decompress(&content[4..], size).map_err(|e| invalid_data(e.to_string()))
+        }
+        ZSTD_CODE => {
+            let decoder = zstd::stream::read::Decoder::new(content)?;
+            let mut restored = vec![];
+            decoder
+                .take(limit as u64 + 1)
+                .read_to_end(&mut restored)?;
+            if restored.len() > limit {
+                return Err(too_large(limit));
+            }
+            Ok(restored)
+        }
+        _ => Err(invalid_data(format!("unknown compression {} of message", code))),
+    }
+}
+
+#[inline]
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[inline]
+fn too_large(limit: usize) -> io::Error {
+    invalid_data(format!("decompressed content of message exceeds {} bytes", limit))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn compress(kind: Compression, content: &[u8]) -> Vec<u8> {
+        let mut header = MessageHeader::new(1);
+        let mut bytes = vec![0u8; MESSAGE_HEAD_SIZE];
+        bytes.extend_from_slice(content);
+        header.length = content.len() as u32;
+        let payload = Compressor::new(kind, 0)
+            .compress(&mut header, bytes.into())
+            .unwrap();
+        assert_eq!(header.compression, kind.code());
+        assert_eq!(payload.len(), header.required_length());
+        payload.as_ref()[MESSAGE_HEAD_SIZE..].to_vec()
+    }
+
+    #[test]
+    fn decompress_test() {
+        let content = vec![7u8; 4096];
+        for kind in vec![Compression::LZ4, Compression::Zstd] {
+            let compressed = compress(kind, &content);
+            assert!(compressed.len() < content.len());
+            assert_eq!(decompress(kind.code(), &compressed).unwrap(), content);
+        }
+    }
+
+    #[test]
+    fn decompress_limit_test() {
+        let content = vec![7u8; 4096];
+        for kind in vec![Compression::LZ4, Compression::Zstd] {
+            let compressed = compress(kind, &content);
+            assert_eq!(restore(kind.code(), &compressed, 4096).unwrap(), content);
+            let err = restore(kind.code(), &compressed, 4095).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn decompress_forged_size_test() {
+        // a forged size prepended to the lz4 content is rejected before any allocation;
+        let mut compressed = compress(Compression::LZ4, &vec![7u8; 4096]);
+        compressed[0..4].copy_from_slice(&(std::u32::MAX).to_le_bytes());
+        let err = decompress(LZ4_CODE, &compressed).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = decompress(LZ4_CODE, &compressed[0..2]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
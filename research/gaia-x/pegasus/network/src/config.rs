@@ -15,16 +15,20 @@
 
 use std::net::SocketAddr;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 
 use serde::Deserialize;
 
+use crate::transport::tls::TlsContext;
 use crate::{NetError, Server};
 
 pub const DEFAULT_HEARTBEAT_INTERVAL_SEC: usize = 5;
 pub const DEFAULT_SEND_BUFFER_SIZE: usize = 1440;
 pub const DEFAULT_WAIT_USER_DATA_MILLSEC: usize = 100;
 pub const DEFAULT_SLAB_SIZE: usize = 1 << 16;
+pub const DEFAULT_COMPRESS_THRESHOLD: usize = 1 << 10;
+pub const DEFAULT_POLL_THREADS: usize = 2;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum BlockMode {
@@ -32,6 +36,15 @@ pub enum BlockMode {
     Nonblocking,
 }
 
+/// The compression of the contents of messages sent to remote servers;
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    LZ4,
+    Zstd,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct WriteParams {
     pub mode: BlockMode,
@@ -39,6 +52,8 @@ pub(crate) struct WriteParams {
     pub nodelay: bool,
     pub wait_data: usize,
     pub heartbeat: usize,
+    pub compression: Compression,
+    pub compress_threshold: usize,
 }
 
 impl Default for WriteParams {
@@ -49,6 +64,8 @@ impl Default for WriteParams {
             nodelay: false,
             wait_data: DEFAULT_WAIT_USER_DATA_MILLSEC,
             heartbeat: DEFAULT_HEARTBEAT_INTERVAL_SEC,
+            compression: Compression::None,
+            compress_threshold: DEFAULT_COMPRESS_THRESHOLD,
         }
     }
 }
@@ -65,18 +82,42 @@ impl Default for ReadParams {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Certificates of the mutual TLS between servers, each of which is a path of PEM file;
+#[derive(Clone, Debug, Deserialize)]
+pub struct TlsConfig {
+    /// certificate chain of this server, presented both when it accepts and when it connects;
+    pub cert: String,
+    /// private key of the certificate, in PKCS#8 or RSA format;
+    pub key: String,
+    /// certificates of the CA which signs the certificates of all servers;
+    pub ca: String,
+    /// DNS name the certificates of servers are issued to, `localhost` by default;
+    pub domain: Option<String>,
+}
+
+#[derive(Clone, Debug)]
 pub struct ConnectionParams {
     pub is_nonblocking: bool,
     write: WriteParams,
     read: ReadParams,
+    /// number of threads polling all nonblocking connections of a server;
+    poll_threads: usize,
+    tls: Option<Arc<TlsContext>>,
 }
 
 impl ConnectionParams {
+    /// The connections are multiplexed on a few threads polling them, see `set_poll_threads`, instead
+    /// of each connection holding a sending thread and a receiving thread as the blocking ones do;
     pub fn nonblocking() -> Self {
         let write = WriteParams::default();
         let read = ReadParams::default();
-        ConnectionParams { is_nonblocking: true, write, read }
+        ConnectionParams {
+            is_nonblocking: true,
+            write,
+            read,
+            poll_threads: DEFAULT_POLL_THREADS,
+            tls: None,
+        }
     }
 
     pub fn blocking() -> Self {
@@ -84,7 +125,20 @@ impl ConnectionParams {
         write.mode = BlockMode::Blocking(None);
         let mut read = ReadParams::default();
         read.mode = BlockMode::Blocking(None);
-        ConnectionParams { is_nonblocking: false, write, read }
+        ConnectionParams {
+            is_nonblocking: false,
+            write,
+            read,
+            poll_threads: DEFAULT_POLL_THREADS,
+            tls: None,
+        }
+    }
+
+    /// Enable mutual TLS on the connections between servers, a connection is rejected unless both
+    /// sides present certificates signed by the CA;
+    pub fn set_tls(&mut self, conf: &TlsConfig) -> Result<(), NetError> {
+        self.tls = Some(Arc::new(TlsContext::new(conf)?));
+        Ok(())
     }
 
     pub fn set_read_timeout(&mut self, timeout: Duration) {
@@ -119,6 +173,19 @@ impl ConnectionParams {
         self.write.heartbeat = interval;
     }
 
+    /// Set the number of threads polling the connections if the connections are nonblocking, the
+    /// connections are spread over the threads evenly;
+    pub fn set_poll_threads(&mut self, threads: usize) {
+        self.poll_threads = std::cmp::max(threads, 1);
+    }
+
+    /// Compress the contents of messages larger than the threshold in bytes, which can be overridden
+    /// by each channel through `IPCSender::set_compression`;
+    pub fn set_compression(&mut self, compression: Compression, threshold: usize) {
+        self.write.compression = compression;
+        self.write.compress_threshold = threshold;
+    }
+
     pub(crate) fn get_write_params(&self) -> &WriteParams {
         &self.write
     }
@@ -130,6 +197,14 @@ impl ConnectionParams {
     pub(crate) fn get_hb_interval_sec(&self) -> u32 {
         self.write.heartbeat as u32
     }
+
+    pub(crate) fn get_poll_threads(&self) -> usize {
+        self.poll_threads
+    }
+
+    pub(crate) fn get_tls(&self) -> Option<&Arc<TlsContext>> {
+        self.tls.as_ref()
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -144,6 +219,9 @@ pub struct NetworkConfig {
     no_delay: Option<bool>,
     send_buffer: Option<u32>,
     heartbeat_sec: Option<u32>,
+    compression: Option<Compression>,
+    compress_threshold: Option<u32>,
+    tls: Option<TlsConfig>,
     peers: Option<Vec<PeerConfig>>,
 }
 
@@ -182,6 +260,9 @@ impl NetworkConfig {
             no_delay: None,
             send_buffer: None,
             heartbeat_sec: None,
+            compression: None,
+            compress_threshold: None,
+            tls: None,
             peers: None,
         }
     }
@@ -221,6 +302,21 @@ impl NetworkConfig {
         self
     }
 
+    pub fn with_compression(mut self, compression: Option<Compression>) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn with_compress_threshold(mut self, threshold: Option<u32>) -> Self {
+        self.compress_threshold = threshold;
+        self
+    }
+
+    pub fn with_tls(mut self, tls: Option<TlsConfig>) -> Self {
+        self.tls = tls;
+        self
+    }
+
     pub fn with_peers(mut self, peers: Option<Vec<PeerConfig>>) -> Self {
         self.peers = peers;
         self
@@ -235,7 +331,7 @@ impl NetworkConfig {
         Ok(SocketAddr::new(ip, self.port))
     }
 
-    pub fn get_connection_param(&self) -> ConnectionParams {
+    pub fn get_connection_param(&self) -> Result<ConnectionParams, NetError> {
         let mut params = if self.nonblocking.unwrap_or(false) {
             ConnectionParams::nonblocking()
         } else {
@@ -273,7 +369,19 @@ impl NetworkConfig {
             }
         }
 
-        params
+        if let Some(compression) = self.compression {
+            let threshold = self
+                .compress_threshold
+                .map(|t| t as usize)
+                .unwrap_or(DEFAULT_COMPRESS_THRESHOLD);
+            params.set_compression(compression, threshold);
+        }
+
+        if let Some(ref tls) = self.tls {
+            params.set_tls(tls)?;
+        }
+
+        Ok(params)
     }
 
     pub fn get_peers(&self) -> Result<Option<Vec<Server>>, NetError> {
@@ -306,6 +414,7 @@ mod test {
             nonblocking = false
             read_timeout_ms = 8
             write_timeout_ms = 8
+            compression = 'lz4'
 
             [[peers]]
             server_id = 0
@@ -322,7 +431,7 @@ mod test {
         println!("get config {:?}", config);
         assert_eq!(config.server_id, 0);
         assert_eq!(config.nonblocking, Some(false));
-        let params = config.get_connection_param();
+        let params = config.get_connection_param().unwrap();
         assert!(!params.is_nonblocking);
         let wp = params.get_write_params();
         assert_eq!(wp.mode, BlockMode::Blocking(Some(Duration::from_millis(8))));
@@ -332,6 +441,8 @@ mod test {
         assert_eq!(wp.nodelay, false);
         assert_eq!(wp.buffer, DEFAULT_SEND_BUFFER_SIZE);
         assert_eq!(wp.heartbeat, DEFAULT_HEARTBEAT_INTERVAL_SEC);
+        assert_eq!(wp.compression, Compression::LZ4);
+        assert_eq!(wp.compress_threshold, DEFAULT_COMPRESS_THRESHOLD);
         let peers = config.get_peers().unwrap().unwrap();
         assert_eq!(peers.len(), 2);
         assert_eq!(peers[0].id, 0);
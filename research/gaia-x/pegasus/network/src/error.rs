@@ -30,6 +30,7 @@ pub enum NetError {
     AddrParseError(AddrParseError),
     HBAbnormal(SocketAddr),
     ChannelRxReset(u128),
+    TLSError(String),
 }
 
 impl Display for NetError {
@@ -68,6 +69,9 @@ impl Display for NetError {
             NetError::ChannelRxReset(id) => {
                 write!(f, "channel {}'s receiver is already in use, multi-receivers is not allowed;", id)
             }
+            NetError::TLSError(msg) => {
+                write!(f, "TLS error: {};", msg)
+            }
         }
     }
 }
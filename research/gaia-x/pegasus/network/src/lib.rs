@@ -127,6 +127,7 @@ pub(crate) fn add_network_thread(server_id: u64, guard: JoinHandle<()>) {
         .push(guard);
 }
 
+mod compress;
 pub mod config;
 mod error;
 pub mod manager;
@@ -13,11 +13,12 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
-use crate::config::ConnectionParams;
-use crate::{NetError, Server};
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::sync::{Arc, Mutex};
 
+use crate::config::ConnectionParams;
+use crate::{NetError, Server};
+
 pub trait ServerDetect: Send {
     fn fetch(&self) -> Vec<Server>;
 }
@@ -40,7 +41,7 @@ impl ServerManager {
     }
 
     pub fn bind<A: ToSocketAddrs>(&self, addr: A) -> Result<SocketAddr, NetError> {
-        let addr = crate::transport::block::listen_on(self.server_id, self.conn_params, addr)?;
+        let addr = crate::transport::block::listen_on(self.server_id, self.conn_params.clone(), addr)?;
         Ok(addr)
     }
 
@@ -48,7 +49,7 @@ impl ServerManager {
         for s in self.peer_detect.fetch() {
             if s.id < self.server_id && !crate::state::is_connected(self.server_id, s.id) {
                 if let Err(e) =
-                    crate::transport::block::connect(self.server_id, s.id, self.conn_params, s.addr)
+                    crate::transport::block::connect(self.server_id, s.id, self.conn_params.clone(), s.addr)
                 {
                     error!("fail to connect server[id={},addr={:?}], caused by {}", s.id, s.addr, e);
                 }
@@ -73,15 +74,23 @@ impl SimpleServerDetector {
     }
 
     pub fn update_peer_view<Iter: Iterator<Item = (u64, SocketAddr)>>(&self, peer_view: Iter) {
-        let new_peers = peer_view.map(|(id, addr)| Server { id, addr }).collect::<Vec<Server>>();
-        let mut peers = self.peers_mutex.lock().expect("unexpected error locking when update peer view");
+        let new_peers = peer_view
+            .map(|(id, addr)| Server { id, addr })
+            .collect::<Vec<Server>>();
+        let mut peers = self
+            .peers_mutex
+            .lock()
+            .expect("unexpected error locking when update peer view");
         *peers = new_peers;
     }
 }
 
 impl ServerDetect for SimpleServerDetector {
     fn fetch(&self) -> Vec<Server> {
-        let peers = self.peers_mutex.lock().expect("unexpected error locking when fetch servers");
+        let peers = self
+            .peers_mutex
+            .lock()
+            .expect("unexpected error locking when fetch servers");
         peers.clone()
     }
 }
@@ -13,12 +13,15 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::io;
+
 use pegasus_common::bytes::Bytes;
 use pegasus_common::codec::{AsBytes, Buf};
 
 /// 协议消息头，描述每个IPC 消息的基本信息，主要包括:
 /// - channel id : 消息所属的IPC channel;
 /// - length     : 除去消息头，消息内容的长度；
+/// - compression: 消息内容的压缩算法，0 表示消息内容未压缩；
 /// - sequence   : 该消息在其所属channel中的序号，序号的大小表明了消息的顺序；
 ///
 /// 按照约定：
@@ -28,19 +31,30 @@ use pegasus_common::codec::{AsBytes, Buf};
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Default)]
 pub struct MessageHeader {
     pub channel_id: u128,
-    pub length: u64,
+    pub length: u32,
+    pub compression: u32,
     pub sequence: u64,
 }
 
 impl MessageHeader {
     pub fn new(channel_id: u128) -> Self {
-        MessageHeader { channel_id, length: 0, sequence: 0 }
+        MessageHeader { channel_id, length: 0, compression: 0, sequence: 0 }
     }
 
     #[inline]
     pub fn required_length(&self) -> usize {
         MESSAGE_HEAD_SIZE + self.length as usize
     }
+
+    /// Set the length of the message content, which fails if it is too large for the header;
+    pub fn set_length(&mut self, length: usize) -> io::Result<()> {
+        if length > MAX_MESSAGE_LENGTH {
+            let msg = format!("too large message content, len={}", length);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+        }
+        self.length = length as u32;
+        Ok(())
+    }
 }
 
 lazy_static! {
@@ -117,6 +131,8 @@ impl From<Bytes> for Payload {
 }
 
 pub const MESSAGE_HEAD_SIZE: usize = std::mem::size_of::<MessageHeader>();
+/// The max length of the content of a message, as the `length` in its header is a u32;
+pub const MAX_MESSAGE_LENGTH: usize = std::u32::MAX as usize;
 
 pub struct Message {
     header: MessageHeader,
@@ -133,7 +149,7 @@ impl Message {
             return None;
         }
 
-        header.length = size as u64;
+        header.length = size as u32;
         Some(Message { header, payload })
     }
 
@@ -160,4 +176,17 @@ mod test {
         assert_eq!(payload.len(), 512);
         assert_eq!(payload.as_ref(), vec![8u8; 512].as_slice())
     }
+
+    #[test]
+    fn set_length_test() {
+        let mut header = MessageHeader::new(1);
+        header.set_length(MAX_MESSAGE_LENGTH).unwrap();
+        assert_eq!(header.required_length(), MESSAGE_HEAD_SIZE + MAX_MESSAGE_LENGTH);
+        // the length is never truncated silently;
+        let err = header
+            .set_length(MAX_MESSAGE_LENGTH + 1)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(header.length as usize, MAX_MESSAGE_LENGTH);
+    }
 }
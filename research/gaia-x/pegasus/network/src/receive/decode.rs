@@ -19,7 +19,7 @@ use std::io::Read;
 use pegasus_common::bytes::BytesSlab;
 use pegasus_common::codec::AsBytes;
 
-use crate::message::{Message, MessageHeader};
+use crate::message::{Message, MessageHeader, Payload};
 
 #[enum_dispatch]
 pub trait MessageDecoder {
@@ -56,7 +56,7 @@ impl MessageDecoder for SimpleBlockDecoder {
         } else {
             let mut payload = vec![0u8; header.length as usize];
             reader.read_exact(&mut payload[..])?;
-            Ok(Some(Message::new_uncheck(header, payload)))
+            Ok(Some(restore(header, payload)?))
         }
     }
 }
@@ -84,7 +84,7 @@ impl MessageDecoder for ReentrantDecoder {
             match try_read(reader, empty) {
                 Ok(size) => {
                     if size > 0 && p.len() == cur + size {
-                        Ok(Some(Message::new_uncheck(h, p)))
+                        Ok(Some(restore(h, p)?))
                     } else {
                         self.in_progress = Some((p, cur + size, h));
                         Ok(None)
@@ -141,7 +141,7 @@ impl MessageDecoder for ReentrantSlabDecoder {
             let length = try_read(reader, empty)?;
             if length == empty.len() {
                 let payload = self.slab.extract();
-                Ok(Some(Message::new_uncheck(header, payload)))
+                Ok(Some(restore(header, payload)?))
             } else {
                 assert!(length < empty.len());
                 self.in_progress = Some((cur + length, header));
@@ -169,6 +169,20 @@ impl MessageDecoder for ReentrantSlabDecoder {
     }
 }
 
+/// Restore the content of the message if it is compressed, the header is reset to describe the
+/// restored content;
+#[inline]
+fn restore<P: Into<Payload>>(mut header: MessageHeader, payload: P) -> io::Result<Message> {
+    if header.compression == 0 {
+        Ok(Message::new_uncheck(header, payload))
+    } else {
+        let content = crate::compress::decompress(header.compression, payload.into().as_ref())?;
+        header.set_length(content.len())?;
+        header.compression = 0;
+        Ok(Message::new_uncheck(header, content))
+    }
+}
+
 #[inline]
 fn try_read<R: io::Read>(reader: &mut R, bytes: &mut [u8]) -> io::Result<usize> {
     loop {
@@ -204,21 +218,41 @@ mod test {
     use pegasus_common::io::WriteExt;
 
     use super::*;
+    use crate::compress::Compressor;
+    use crate::config::Compression;
     use crate::message::DEFAULT_MESSAGE_HEADER_BYTES;
     use crate::send::MessageEncoder;
     use crate::send::SimpleEncoder;
 
     struct Array(u8);
 
+    impl Array {
+        fn len(i: u8) -> usize {
+            if i % 2 == 0 {
+                256
+            } else {
+                128
+            }
+        }
+    }
+
     impl Encode for Array {
         fn write_to<W: WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
-            let bytes = vec![self.0; 256];
+            let bytes = vec![self.0; Array::len(self.0)];
             writer.write_all(&bytes[..])
         }
     }
 
     fn decoder_test<D: MessageDecoder>(decoder: &mut D) {
+        decoder_test_with(decoder, Compression::None);
+        decoder_test_with(decoder, Compression::LZ4);
+        decoder_test_with(decoder, Compression::Zstd);
+    }
+
+    fn decoder_test_with<D: MessageDecoder>(decoder: &mut D, compression: Compression) {
         let mut encoder = SimpleEncoder::default();
+        // messages of odd sequences are smaller than the threshold, which are not compressed;
+        let compressor = Compressor::new(compression, 256);
         let mut header = MessageHeader::default();
         header.sequence = 1;
         header.channel_id = 1;
@@ -228,8 +262,13 @@ mod test {
         content.extend(&*DEFAULT_MESSAGE_HEADER_BYTES);
         for i in 1..9 {
             let b = encoder.encode(&mut header, &Array(i)).unwrap();
+            let b = compressor.compress(&mut header, b).unwrap();
+            if compression != Compression::None {
+                assert_eq!(header.compression != 0, i % 2 == 0);
+            }
             content.extend_from_slice(b.as_ref());
             header.sequence += 1;
+            header.compression = 0;
         }
 
         header.sequence = 0;
@@ -253,7 +292,8 @@ mod test {
                 break;
             } else {
                 assert_eq!(h.sequence, i as u64);
-                assert_eq!(p.as_ref(), vec![i; 256].as_slice());
+                assert_eq!(h.compression, 0);
+                assert_eq!(p.as_ref(), vec![i; Array::len(i)].as_slice());
                 i += 1;
             }
         }
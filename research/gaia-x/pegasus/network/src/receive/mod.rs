@@ -15,7 +15,6 @@
 
 use std::collections::HashMap;
 use std::io;
-use std::net::TcpStream;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
@@ -24,12 +23,15 @@ use pegasus_common::channel::{MPMCReceiver, MPMCSender, MessageReceiver};
 use pegasus_common::codec::Decode;
 
 use crate::message::Payload;
+use crate::transport::ReadHalf;
 use crate::{NetError, Server};
 
 mod decode;
 mod net_rx;
+pub(crate) use decode::GeneralDecoder;
 pub use decode::{MessageDecoder, ReentrantDecoder, ReentrantSlabDecoder, SimpleBlockDecoder};
-use net_rx::{InboxRegister, NetReceiver};
+use net_rx::InboxRegister;
+pub(crate) use net_rx::NetReceiver;
 
 use crate::config::{BlockMode::Blocking, ConnectionParams};
 
@@ -81,7 +83,7 @@ fn add_remote_register(local: u64, remote: u64, register: InboxRegister) {
     lock.insert((local, remote), register);
 }
 
-fn remove_remote_register(local: u64, remote: u64) -> Option<InboxRegister> {
+pub(crate) fn remove_remote_register(local: u64, remote: u64) -> Option<InboxRegister> {
     let mut lock = REMOTE_RECV_REGISTER
         .write()
         .expect("failure to lock REMOTE_RECV_REGISTER");
@@ -109,13 +111,13 @@ pub fn register_remotes_receiver<T: Decode + 'static>(
     Ok(IPCReceiver::new(rx))
 }
 
-pub fn start_net_receiver(
+pub(crate) fn start_net_receiver(
     local: u64, remote: Server, hb_sec: u32, params: &ConnectionParams, state: &Arc<AtomicBool>,
-    conn: TcpStream,
+    conn: ReadHalf,
 ) {
     //    let decoder = DefaultBlockDecoder::new(conn);
     if let Blocking(timeout) = params.get_read_params().mode {
-        conn.set_read_timeout(timeout).ok();
+        conn.socket().set_read_timeout(timeout).ok();
     }
 
     let slab_size = params.get_read_params().slab_size;
@@ -140,3 +142,14 @@ pub fn start_net_receiver(
         .expect("start net recv thread failure;");
     crate::add_network_thread(local, guard);
 }
+
+/// Create the receiver from a remote server whose connection is polled by an event loop;
+pub(crate) fn new_polled_receiver(
+    local: u64, remote: &Server, hb_sec: u32, params: &ConnectionParams, conn: ReadHalf,
+) -> NetReceiver<ReadHalf, GeneralDecoder> {
+    let slab_size = params.get_read_params().slab_size;
+    let decoder = self::decode::get_reentrant_decoder(slab_size);
+    let net_recv = NetReceiver::new(hb_sec as u64, remote.addr, conn, decoder);
+    add_remote_register(local, remote.id, net_recv.get_inbox_register());
+    net_recv
+}
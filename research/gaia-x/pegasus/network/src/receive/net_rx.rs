@@ -235,7 +235,8 @@ impl<R: Read, D: MessageDecoder> NetReceiver<R, D> {
         }
     }
 
-    pub fn recv(&mut self) -> Result<(), NetError> {
+    /// Receive a message and dispatch it, return `false` if no message is available now;
+    pub fn recv(&mut self) -> Result<bool, NetError> {
         if let Some(msg) = decode_next(&mut self.reader, &mut self.decoder)? {
             let (header, payload) = msg.separate();
             if header.channel_id == 0 {
@@ -249,13 +250,21 @@ impl<R: Read, D: MessageDecoder> NetReceiver<R, D> {
                     .dispatch(header.channel_id, payload);
             }
             self.last_recv = Instant::now();
+            Ok(true)
         } else {
-            let elapsed = self.last_recv.elapsed().as_secs();
-            if elapsed > self.hb_sec * 2 {
-                return Err(NetError::HBAbnormal(self.addr));
-            }
+            self.check_heartbeat()?;
+            Ok(false)
+        }
+    }
+
+    /// Check if the heartbeat from the remote server is lost;
+    pub fn check_heartbeat(&self) -> Result<(), NetError> {
+        let elapsed = self.last_recv.elapsed().as_secs();
+        if elapsed > self.hb_sec * 2 {
+            Err(NetError::HBAbnormal(self.addr))
+        } else {
+            Ok(())
         }
-        Ok(())
     }
 
     pub(crate) fn get_inbox_register(&self) -> InboxRegister {
@@ -51,7 +51,7 @@ impl<T: Encode> MessageEncoder<T> for SimpleEncoder<T> {
     fn encode(&mut self, header: &mut MessageHeader, msg: &T) -> io::Result<Payload> {
         let mut buffer = vec![0u8; MESSAGE_HEAD_SIZE];
         msg.write_to(&mut buffer)?;
-        header.length = (buffer.len() - MESSAGE_HEAD_SIZE) as u64;
+        header.set_length(buffer.len() - MESSAGE_HEAD_SIZE)?;
         let mut writer = &mut buffer[0..];
         writer.write_all(header.as_bytes())?;
         buffer.shrink_to_fit();
@@ -96,7 +96,11 @@ impl<T: Encode> MessageEncoder<T> for SlabEncoder<T> {
         self.slab.ensure_capacity(MESSAGE_HEAD_SIZE + 1);
         self.slab.write_all(&self.empty_head)?;
         msg.write_to(&mut self.slab)?;
-        header.length = (self.slab.len() - MESSAGE_HEAD_SIZE) as u64;
+        if let Err(e) = header.set_length(self.slab.len() - MESSAGE_HEAD_SIZE) {
+            // discard the message so that the slab is empty for the next one;
+            self.slab.extract();
+            return Err(e);
+        }
         {
             let rewrite_head = &mut self.slab.as_mut()[0..MESSAGE_HEAD_SIZE];
             rewrite_head.copy_from_slice(header.as_bytes());
@@ -16,7 +16,7 @@
 use std::collections::HashMap;
 use std::io;
 use std::io::Write;
-use std::net::{SocketAddr, TcpStream};
+use std::net::SocketAddr;
 use std::ops::Deref;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, Weak};
@@ -26,38 +26,48 @@ use crossbeam_channel::Sender;
 use crossbeam_utils::sync::ShardedLock;
 use pegasus_common::codec::Encode;
 
-use crate::config::{BlockMode, ConnectionParams, DEFAULT_SLAB_SIZE};
+use crate::compress::Compressor;
+use crate::config::{BlockMode, Compression, ConnectionParams, DEFAULT_SLAB_SIZE};
 use crate::message::MessageHeader;
+use crate::transport::nonblock::Notifier;
+use crate::transport::WriteHalf;
 use crate::{NetError, Server};
 
 mod encode;
 pub use encode::{GeneralEncoder, MessageEncoder, SimpleEncoder, SlabEncoder};
 
 mod net_tx;
-use net_tx::{NetData, NetSender};
+use net_tx::NetData;
+pub(crate) use net_tx::NetSender;
 
 pub struct IPCSender<T: Encode> {
     pub target: SocketAddr,
     pub channel_id: u128,
     sequence: u64,
     encoder: GeneralEncoder<T>,
+    compressor: Compressor,
     outbox_tx: Sender<NetData>,
+    notifier: Option<Arc<Notifier>>,
     close_guard: Arc<AtomicUsize>,
 }
 
 impl<T: Encode> IPCSender<T> {
-    pub fn send(&mut self, msg: &T) -> io::Result<()> {
+    /// Send the message to the target server, return the size of the encoded message in bytes;
+    pub fn send(&mut self, msg: &T) -> io::Result<usize> {
         let mut header = MessageHeader::new(self.channel_id);
         header.sequence = self.sequence;
         let payload = self.encoder.encode(&mut header, msg)?;
+        let payload = self.compressor.compress(&mut header, payload)?;
+        let size = payload.len();
         self.outbox_tx
             .send(NetData::AppData(self.channel_id, payload))
             .map_err(|_| {
                 error!("DefaultAppSender#send: network outbox disconnected;");
                 io::Error::from(io::ErrorKind::BrokenPipe)
             })?;
+        self.notify();
         self.sequence += 1;
-        Ok(())
+        Ok(size)
     }
 
     pub fn close(&mut self) -> io::Result<()> {
@@ -70,19 +80,30 @@ impl<T: Encode> IPCSender<T> {
                     error!("DefaultAppSender#close: network outbox disconnected;");
                     io::Error::from(io::ErrorKind::BrokenPipe)
                 })?;
+            self.notify();
         }
         Ok(())
     }
+
+    /// Wake up the event loop polling the connection if it is nonblocking;
+    #[inline]
+    fn notify(&self) {
+        if let Some(ref notifier) = self.notifier {
+            notifier.notify();
+        }
+    }
 }
 
 impl<T: Encode + 'static> IPCSender<T> {
-    fn new(target: SocketAddr, channel_id: u128, outbox_tx: Sender<NetData>) -> Self {
+    fn new(channel_id: u128, remote: &RemoteSender, outbox_tx: Sender<NetData>) -> Self {
         IPCSender {
-            target,
+            target: remote.addr,
             channel_id,
             sequence: 1,
             encoder: SlabEncoder::new(DEFAULT_SLAB_SIZE).into(),
+            compressor: remote.compressor,
             outbox_tx,
+            notifier: remote.notifier.clone(),
             close_guard: Arc::new(AtomicUsize::new(1)),
         }
     }
@@ -94,6 +115,12 @@ impl<T: Encode + 'static> IPCSender<T> {
             self.encoder = SlabEncoder::new(slab_size).into();
         }
     }
+
+    /// Compress the messages of this channel larger than the threshold in bytes, instead of following
+    /// the compression of the connection;
+    pub fn set_compression(&mut self, compression: Compression, threshold: usize) {
+        self.compressor = Compressor::new(compression, threshold);
+    }
 }
 
 impl<T: Encode + 'static> Clone for IPCSender<T> {
@@ -104,14 +131,25 @@ impl<T: Encode + 'static> Clone for IPCSender<T> {
             channel_id: self.channel_id,
             sequence: 1,
             encoder: self.encoder.clone(),
+            compressor: self.compressor,
             outbox_tx: self.outbox_tx.clone(),
+            notifier: self.notifier.clone(),
             close_guard: self.close_guard.clone(),
         }
     }
 }
 
+/// The outbox to a remote server, with the compression of the connection, and the notifier of the
+/// event loop polling the connection if it is nonblocking;
+struct RemoteSender {
+    addr: SocketAddr,
+    outbox: Weak<Sender<NetData>>,
+    compressor: Compressor,
+    notifier: Option<Arc<Notifier>>,
+}
+
 lazy_static! {
-    static ref REMOTE_MSG_SENDER: ShardedLock<HashMap<(u64, u64), (SocketAddr, Weak<Sender<NetData>>)>> =
+    static ref REMOTE_MSG_SENDER: ShardedLock<HashMap<(u64, u64), RemoteSender>> =
         ShardedLock::new(HashMap::new());
     static ref NETWORK_SEND_ERRORS: Mutex<HashMap<u128, Vec<SocketAddr>>> = Mutex::new(HashMap::new());
 }
@@ -150,12 +188,16 @@ pub fn check_remotes_send_ready(local: u64, remotes: &[u64]) -> bool {
     true
 }
 
-pub(crate) fn add_remote_sender(local_id: u64, server: &Server, tx: &Arc<Sender<NetData>>) {
-    let tx = Arc::downgrade(tx);
+pub(crate) fn add_remote_sender(
+    local_id: u64, server: &Server, tx: &Arc<Sender<NetData>>, compressor: Compressor,
+    notifier: Option<Arc<Notifier>>,
+) {
+    let outbox = Arc::downgrade(tx);
     let mut lock = REMOTE_MSG_SENDER
         .write()
         .expect("REMOTE_MSG_SENDER write lock poisoned");
-    lock.insert((local_id, server.id), (server.addr, tx));
+    let remote = RemoteSender { addr: server.addr, outbox, compressor, notifier };
+    lock.insert((local_id, server.id), remote);
 }
 
 pub(crate) fn remove_remote_sender(local_id: u64, remote_id: u64) {
@@ -174,10 +216,10 @@ pub fn fetch_remote_sender<T: Encode + 'static>(
     let mut app_senders = Vec::with_capacity(remotes.len());
     for id in remotes {
         if *id != local {
-            if let Some((addr, tx)) = lock.get(&(local, *id)) {
-                if let Some(tx) = tx.upgrade() {
+            if let Some(remote) = lock.get(&(local, *id)) {
+                if let Some(tx) = remote.outbox.upgrade() {
                     let tx = tx.deref().clone();
-                    let sender = IPCSender::<T>::new(*addr, channel_id, tx);
+                    let sender = IPCSender::<T>::new(channel_id, remote, tx);
                     app_senders.push(sender);
                 } else {
                     return Err(NetError::NotConnected(*id));
@@ -191,56 +233,64 @@ pub fn fetch_remote_sender<T: Encode + 'static>(
 }
 
 pub(crate) fn start_net_sender(
-    local_id: u64, remote: Server, params: &ConnectionParams, state: &Arc<AtomicBool>, conn: TcpStream,
+    local_id: u64, remote: Server, params: &ConnectionParams, state: &Arc<AtomicBool>, conn: WriteHalf,
 ) {
     let mut is_block = !params.is_nonblocking;
     let params = params.get_write_params();
     match params.mode {
         BlockMode::Blocking(timeout) => {
-            conn.set_write_timeout(timeout).ok();
+            conn.socket().set_write_timeout(timeout).ok();
             is_block = false;
         }
         _ => (),
     }
-    conn.set_nodelay(params.nodelay).ok();
+    conn.socket().set_nodelay(params.nodelay).ok();
+    let compressor = Compressor::new(params.compression, params.compress_threshold);
     let disconnected = state.clone();
     let timeout = params.wait_data as u64;
     let guard = if params.buffer > 0 {
         let writer = std::io::BufWriter::with_capacity(params.buffer, conn);
         let mut net_tx = NetSender::new(remote.addr, writer);
         let tx = net_tx.get_outbox_tx().as_ref().expect("");
-        add_remote_sender(local_id, &remote, tx);
+        add_remote_sender(local_id, &remote, tx, compressor, None);
         std::thread::Builder::new()
             .name(format!("net-sender-{}", remote.id))
             .spawn(move || {
                 busy_send(&mut net_tx, is_block, timeout, local_id, remote.id);
                 disconnected.store(true, Ordering::SeqCst);
-                net_tx
-                    .take_writer()
-                    .get_ref()
-                    .shutdown(std::net::Shutdown::Write)
-                    .ok();
+                net_tx.take_writer().get_mut().shutdown().ok();
             })
             .expect("start net-sender thread failure;")
     } else {
         let mut net_tx = NetSender::new(remote.addr, conn);
         let tx = net_tx.get_outbox_tx().as_ref().expect("");
-        add_remote_sender(local_id, &remote, &tx);
+        add_remote_sender(local_id, &remote, &tx, compressor, None);
         std::thread::Builder::new()
             .name(format!("net-sender-{}", remote.id))
             .spawn(move || {
                 busy_send(&mut net_tx, is_block, timeout, local_id, remote.id);
                 disconnected.store(true, Ordering::SeqCst);
-                net_tx
-                    .take_writer()
-                    .shutdown(std::net::Shutdown::Write)
-                    .ok();
+                net_tx.take_writer().shutdown().ok();
             })
             .expect("start net-sender thread failure;")
     };
     crate::add_network_thread(local_id, guard);
 }
 
+/// Create the sender to a remote server whose connection is polled by an event loop, which is woken up
+/// by the notifier once messages are sent;
+pub(crate) fn new_polled_sender(
+    local_id: u64, remote: &Server, params: &ConnectionParams, conn: WriteHalf, notifier: &Arc<Notifier>,
+) -> NetSender<WriteHalf> {
+    let params = params.get_write_params();
+    conn.socket().set_nodelay(params.nodelay).ok();
+    let compressor = Compressor::new(params.compression, params.compress_threshold);
+    let net_tx = NetSender::new(remote.addr, conn);
+    let tx = net_tx.get_outbox_tx().as_ref().expect("");
+    add_remote_sender(local_id, remote, tx, compressor, Some(notifier.clone()));
+    net_tx
+}
+
 fn busy_send<W: Write>(net_tx: &mut NetSender<W>, block: bool, timeout: u64, local: u64, remote: u64) {
     let heart_beat_tick = crossbeam_channel::tick(Duration::from_secs(5));
     while !crate::is_shutdown(local) {
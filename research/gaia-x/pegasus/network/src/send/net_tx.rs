@@ -93,11 +93,11 @@ impl<W: Write> NetSender<W> {
                                     }
                                 }
                                 Err(TryRecvError::Empty) => {
-                                    self.conn.flush()?;
+                                    self.try_flush()?;
                                     return Ok(false);
                                 }
                                 Err(TryRecvError::Disconnected) => {
-                                    self.conn.flush()?;
+                                    self.try_flush()?;
                                     return Ok(true);
                                 }
                             }
@@ -117,11 +117,11 @@ impl<W: Write> NetSender<W> {
                         }
                     }
                     Err(TryRecvError::Empty) => {
-                        self.conn.flush()?;
+                        self.try_flush()?;
                         return Ok(false);
                     }
                     Err(TryRecvError::Disconnected) => {
-                        self.conn.flush()?;
+                        self.try_flush()?;
                         return Ok(true);
                     }
                 }
@@ -223,6 +223,17 @@ impl<W: Write> NetSender<W> {
         }
     }
 
+    /// Flush the connection, the data left is flushed by the next call if the connection is not ready;
+    #[inline]
+    fn try_flush(&mut self) -> io::Result<()> {
+        match self.conn.flush() {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => {
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
     pub fn take_writer(self) -> W {
         self.conn
     }
@@ -26,6 +26,7 @@ struct ConnectionState {
     pub remote_id: u64,
     addr: SocketAddr,
     disconnected: Arc<AtomicBool>,
+    ready: bool,
 }
 
 impl ConnectionState {
@@ -46,7 +47,8 @@ pub fn add_connection(local_id: u64, remote_id: u64, addr: SocketAddr) -> Option
         let mut states = CONNECTION_STATES
             .write()
             .expect("lock poisoned");
-        let st = ConnectionState { local_id, remote_id, addr, disconnected: disconnected.clone() };
+        let st =
+            ConnectionState { local_id, remote_id, addr, disconnected: disconnected.clone(), ready: false };
         if let Some(s) = states.get_mut(&(local_id, remote_id)) {
             if !s.is_connected() {
                 *s = st;
@@ -69,6 +71,17 @@ pub fn add_connection(local_id: u64, remote_id: u64, addr: SocketAddr) -> Option
     Some(disconnected)
 }
 
+/// Mark the connection as ready once it is served with its sender and receiver registered, the ipc
+/// channels can't be created on it before;
+pub fn set_ready(local_id: u64, remote_id: u64) {
+    let mut states = CONNECTION_STATES
+        .write()
+        .expect("lock poisoned");
+    if let Some(s) = states.get_mut(&(local_id, remote_id)) {
+        s.ready = true;
+    }
+}
+
 pub fn is_connected(local_id: u64, remote_id: u64) -> bool {
     let states = CONNECTION_STATES.read().expect("lock poisoned");
     local_id == remote_id
@@ -84,7 +97,7 @@ pub fn check_connect(local: u64, remotes: &[u64]) -> bool {
         if *id != local
             && !states
                 .get(&(local, *id))
-                .map(|s| s.is_connected())
+                .map(|s| s.ready && s.is_connected())
                 .unwrap_or(false)
         {
             return false;
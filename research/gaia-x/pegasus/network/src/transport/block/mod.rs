@@ -17,8 +17,6 @@ use std::io;
 use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
 use std::time::Duration;
 
-use crate::receive::start_net_receiver;
-use crate::send::start_net_sender;
 use crate::transport::ConnectionParams;
 use crate::{NetError, Server};
 
@@ -35,33 +33,14 @@ pub fn listen_on<A: ToSocketAddrs>(
         .spawn(move || {
             while !crate::is_shutdown(server_id) {
                 match listener.accept() {
-                    Ok((mut stream, addr)) => {
-                        if let Ok(Some((remote_id, hb))) = super::check_connection(&mut stream) {
-                            info!("accept new connection from server {} on {:?}", remote_id, addr);
-                            if !crate::state::is_connected(server_id, remote_id) {
-                                // create network communication_old channel for lib user;
-                                let mut write_half = stream
-                                    .try_clone()
-                                    .expect("clone tcp stream failure;");
-                                if let Err(e) = super::setup_connection(server_id, hb_sec, &mut write_half)
-                                {
-                                    error!("write pass phrase to {:?} failure: {}", addr, e);
-                                } else {
-                                    let hook = crate::state::add_connection(server_id, remote_id, addr)
-                                        // add connection should never fail;
-                                        .expect("add connection failure");
-                                    let remote = Server { id: remote_id, addr };
-                                    if params.is_nonblocking {
-                                        stream.set_nonblocking(true).ok();
-                                    }
-                                    start_net_sender(server_id, remote, &params, &hook, write_half);
-                                    start_net_receiver(server_id, remote, hb, &params, &hook, stream);
-                                }
-                            } else {
-                                warn!("server {} is connected and already in use;", remote_id);
-                            }
-                        } else {
-                            warn!("illegal connection from {:?}, ignored;", addr);
+                    Ok((stream, addr)) => {
+                        let params = params.clone();
+                        // the handshake with a slow peer shouldn't stall the connections from others;
+                        let res = std::thread::Builder::new()
+                            .name(format!("network-accept-{}", addr))
+                            .spawn(move || accept(server_id, hb_sec, &params, stream, addr));
+                        if let Err(e) = res {
+                            error!("create thread to accept connection from {:?} failure: {}", addr, e);
                         }
                     }
                     Err(e) => {
@@ -79,6 +58,37 @@ pub fn listen_on<A: ToSocketAddrs>(
     Ok(bind_addr)
 }
 
+/// Finish the handshake of a connection accepted by the listener, and start serving it if it is legal;
+fn accept(server_id: u64, hb_sec: u32, params: &ConnectionParams, stream: TcpStream, addr: SocketAddr) {
+    let (mut read_half, mut write_half) = match super::split(stream, params, true) {
+        Ok(halves) => halves,
+        Err(e) => {
+            warn!("TLS handshake with {:?} failure: {}, ignored;", addr, e);
+            return;
+        }
+    };
+    if let Ok(Some((remote_id, hb))) = super::check_connection(&mut read_half) {
+        info!("accept new connection from server {} on {:?}", remote_id, addr);
+        if !crate::state::is_connected(server_id, remote_id) {
+            // create network communication_old channel for lib user;
+            if let Err(e) = super::setup_connection(server_id, hb_sec, &mut write_half) {
+                error!("write pass phrase to {:?} failure: {}", addr, e);
+            } else if let Some(hook) = crate::state::add_connection(server_id, remote_id, addr) {
+                let remote = Server { id: remote_id, addr };
+                let res =
+                    super::start_connection(server_id, remote, hb, params, &hook, read_half, write_half);
+                if let Err(e) = res {
+                    error!("start connection from {:?} failure: {}", addr, e);
+                }
+            }
+        } else {
+            warn!("server {} is connected and already in use;", remote_id);
+        }
+    } else {
+        warn!("illegal connection from {:?}, ignored;", addr);
+    }
+}
+
 /// 尝试建立新的TCP连接：
 /// - 参数 `addr`为期望建立连接的对端服务监听的 socket 地址；
 /// - 参数`server_id` 是对端服务的序号；
@@ -94,25 +104,19 @@ pub fn connect<A: ToSocketAddrs>(
     local_id: u64, remote_id: u64, params: ConnectionParams, addr: A,
 ) -> Result<(), NetError> {
     // 连接请求可能会失败， 或许由于对端服务器未启动端口监听，调用方需要根据返回内容确定是否重试;
-    let mut conn = TcpStream::connect(addr)?;
+    let conn = TcpStream::connect(addr)?;
     let addr = conn.peer_addr()?;
     debug!("connect to server {:?};", addr);
+    let (mut read_half, mut write_half) = super::split(conn, &params, false)?;
     let hb_sec = params.get_hb_interval_sec();
-    super::setup_connection(local_id, hb_sec, &mut conn)?;
+    super::setup_connection(local_id, hb_sec, &mut write_half)?;
     debug!("setup connection to {:?} success;", addr);
-    if let Some((id, hb_sec)) = super::check_connection(&mut conn)? {
+    if let Some((id, hb_sec)) = super::check_connection(&mut read_half)? {
         if id == remote_id {
             info!("connect server {} on {:?} success;", remote_id, addr);
             if let Some(state) = crate::state::add_connection(local_id, remote_id, addr) {
                 let remote = Server { id: remote_id, addr };
-                if params.is_nonblocking {
-                    conn.set_nonblocking(true).ok();
-                }
-                let read_half = conn
-                    .try_clone()
-                    .expect("clone tcp stream failure;");
-                start_net_sender(local_id, remote, &params, &state, conn);
-                start_net_receiver(local_id, remote, hb_sec, &params, &state, read_half);
+                super::start_connection(local_id, remote, hb_sec, &params, &state, read_half, write_half)?;
             } else {
                 return Err(NetError::ConflictConnect(remote_id));
             }
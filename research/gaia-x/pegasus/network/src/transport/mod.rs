@@ -13,14 +13,26 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
 use pegasus_common::io::{ReadExt, WriteExt};
 
 use crate::config::*;
+use crate::receive::start_net_receiver;
+use crate::send::start_net_sender;
+use crate::transport::tls::{TlsReader, TlsWriter};
+use crate::Server;
 
 pub(crate) mod block;
-mod nonblock;
+pub(crate) mod nonblock;
+pub(crate) mod tls;
 
 pub const PASS_PHRASE: u32 = 9;
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub fn get_handshake(server_id: u64, hb: u32) -> u128 {
     let mut value = (PASS_PHRASE as u128) << 96;
@@ -55,6 +67,112 @@ fn setup_connection<W: WriteExt>(server_id: u64, hb_sec: u32, conn: &mut W) -> s
     conn.write_u128(handshake)
 }
 
+/// Start sending and receiving messages through the connection after the handshake, it is polled by an
+/// event loop if it is nonblocking, or else it is served by a sending thread and a receiving thread.
+/// The connection is marked ready once it is served with its sender and receiver registered, which is
+/// done by the event loop if it is polled;
+fn start_connection(
+    local: u64, remote: Server, hb_sec: u32, params: &ConnectionParams, state: &Arc<AtomicBool>,
+    read_half: ReadHalf, write_half: WriteHalf,
+) -> io::Result<()> {
+    if params.is_nonblocking {
+        let res = nonblock::register(local, remote, hb_sec, params, state, read_half, write_half);
+        if res.is_err() {
+            state.store(true, Ordering::SeqCst);
+        }
+        res
+    } else {
+        start_net_sender(local, remote, params, state, write_half);
+        start_net_receiver(local, remote, hb_sec, params, state, read_half);
+        crate::state::set_ready(local, remote.id);
+        Ok(())
+    }
+}
+
+/// The read half of a connection between servers, which is encrypted if TLS is enabled;
+pub(crate) enum ReadHalf {
+    Plain(TcpStream),
+    Tls(TlsReader),
+}
+
+/// The write half of a connection between servers, which is encrypted if TLS is enabled;
+pub(crate) enum WriteHalf {
+    Plain(TcpStream),
+    Tls(TlsWriter),
+}
+
+/// Split a connection into the read and write halves, after the TLS handshake if TLS is enabled;
+pub(crate) fn split(
+    conn: TcpStream, params: &ConnectionParams, is_accepted: bool,
+) -> io::Result<(ReadHalf, WriteHalf)> {
+    if let Some(tls) = params.get_tls() {
+        // a peer that never finishes the handshake shouldn't hold the connecting or accepting thread;
+        conn.set_read_timeout(Some(TLS_HANDSHAKE_TIMEOUT))?;
+        conn.set_write_timeout(Some(TLS_HANDSHAKE_TIMEOUT))?;
+        let (reader, writer) = if is_accepted { tls.accept(conn)? } else { tls.connect(conn)? };
+        reader.socket().set_read_timeout(None)?;
+        reader.socket().set_write_timeout(None)?;
+        Ok((ReadHalf::Tls(reader), WriteHalf::Tls(writer)))
+    } else {
+        let read_half = conn.try_clone()?;
+        Ok((ReadHalf::Plain(read_half), WriteHalf::Plain(conn)))
+    }
+}
+
+impl ReadHalf {
+    pub fn socket(&self) -> &TcpStream {
+        match self {
+            ReadHalf::Plain(conn) => conn,
+            ReadHalf::Tls(conn) => conn.socket(),
+        }
+    }
+}
+
+impl Read for ReadHalf {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            ReadHalf::Plain(conn) => conn.read(buf),
+            ReadHalf::Tls(conn) => conn.read(buf),
+        }
+    }
+}
+
+impl ReadExt for ReadHalf {}
+
+impl WriteHalf {
+    pub fn socket(&self) -> &TcpStream {
+        match self {
+            WriteHalf::Plain(conn) => conn,
+            WriteHalf::Tls(conn) => conn.socket(),
+        }
+    }
+
+    pub fn shutdown(&mut self) -> io::Result<()> {
+        match self {
+            WriteHalf::Plain(conn) => conn.shutdown(std::net::Shutdown::Write),
+            WriteHalf::Tls(conn) => conn.shutdown(),
+        }
+    }
+}
+
+impl Write for WriteHalf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            WriteHalf::Plain(conn) => conn.write(buf),
+            WriteHalf::Tls(conn) => conn.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            WriteHalf::Plain(conn) => conn.flush(),
+            WriteHalf::Tls(conn) => conn.flush(),
+        }
+    }
+}
+
+impl WriteExt for WriteHalf {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -12,3 +12,285 @@
 //! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
+
+use std::collections::HashMap;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, Sender};
+use mio::unix::SourceFd;
+use mio::{Events, Interest, Poll, Token, Waker};
+
+use crate::config::ConnectionParams;
+use crate::receive::{GeneralDecoder, NetReceiver};
+use crate::send::NetSender;
+use crate::transport::{ReadHalf, WriteHalf};
+use crate::{NetError, Server};
+
+const WAKE_TOKEN: Token = Token(0);
+/// The longest time an event loop waits for events, after which it checks the heartbeats and shutdown;
+const POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Wake up an event loop once messages are sent to any connection polled by it, the loop is woken at
+/// most once until it takes the notification;
+pub(crate) struct Notifier {
+    waker: Waker,
+    notified: AtomicBool,
+}
+
+impl Notifier {
+    pub fn notify(&self) {
+        if !self.notified.swap(true, Ordering::SeqCst) {
+            if let Err(e) = self.waker.wake() {
+                error!("wake up network event loop failure: {}", e);
+            }
+        }
+    }
+
+    fn take(&self) -> bool {
+        self.notified.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// A connection to a remote server, whose socket is polled by an event loop;
+struct Connection {
+    remote: Server,
+    fd: RawFd,
+    hb_interval: Duration,
+    last_hb: Instant,
+    state: Arc<AtomicBool>,
+    net_tx: NetSender<WriteHalf>,
+    net_rx: NetReceiver<ReadHalf, GeneralDecoder>,
+}
+
+impl Connection {
+    /// Receive all messages available, until the socket would block;
+    fn recv(&mut self) -> Result<(), NetError> {
+        while self.net_rx.recv()? {}
+        Ok(())
+    }
+
+    /// Write the messages in the outbox, the rest is written once the socket is writable again;
+    fn send(&mut self) -> io::Result<()> {
+        if self.last_hb.elapsed() >= self.hb_interval {
+            self.net_tx.send_heart_beat();
+            self.last_hb = Instant::now();
+        }
+        self.net_tx.try_send(0)?;
+        Ok(())
+    }
+}
+
+struct EventLoop {
+    local: u64,
+    poll: Poll,
+    notifier: Arc<Notifier>,
+    incoming: Receiver<Connection>,
+    connections: HashMap<Token, Connection>,
+    next_token: usize,
+}
+
+impl EventLoop {
+    fn run(&mut self) {
+        let mut events = Events::with_capacity(1024);
+        while !crate::is_shutdown(self.local) {
+            if let Err(e) = self.poll.poll(&mut events, Some(POLL_TIMEOUT)) {
+                if e.kind() != io::ErrorKind::Interrupted {
+                    error!("network event loop of server {} poll error: {}", self.local, e);
+                    break;
+                }
+            }
+
+            for event in events.iter() {
+                let token = event.token();
+                if token == WAKE_TOKEN {
+                    continue;
+                }
+                if let Some(conn) = self.connections.get_mut(&token) {
+                    let mut res = Ok(());
+                    if event.is_readable() || event.is_read_closed() || event.is_error() {
+                        res = conn.recv();
+                        if res.is_ok() && (event.is_read_closed() || event.is_error()) {
+                            res = Err(NetError::IOError(io::Error::from(io::ErrorKind::ConnectionAborted)));
+                        }
+                    }
+                    if res.is_ok() && event.is_writable() {
+                        res = conn.send().map_err(NetError::from);
+                    }
+                    if let Err(e) = res {
+                        error!("connection to {:?} failure, caused by {};", conn.remote, e);
+                        self.close(token);
+                    }
+                }
+            }
+
+            // the notification is taken before the outboxes are drained, so no message is left unnoticed;
+            if self.notifier.take() {
+                self.accept();
+            }
+            self.check();
+        }
+        info!("network event loop of server {} exit;", self.local);
+        self.accept();
+        let tokens = self
+            .connections
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        for token in tokens {
+            // messages sent before shutdown are written as many as possible;
+            if let Some(conn) = self.connections.get_mut(&token) {
+                conn.net_tx.try_send(0).ok();
+            }
+            self.close(token);
+        }
+    }
+
+    fn accept(&mut self) {
+        while let Ok(conn) = self.incoming.try_recv() {
+            let token = Token(self.next_token);
+            self.next_token += 1;
+            let interest = Interest::READABLE | Interest::WRITABLE;
+            if let Err(e) = self
+                .poll
+                .registry()
+                .register(&mut SourceFd(&conn.fd), token, interest)
+            {
+                error!("register connection to {:?} failure: {}", conn.remote, e);
+                conn.state.store(true, Ordering::SeqCst);
+                crate::send::remove_remote_sender(self.local, conn.remote.id);
+                crate::receive::remove_remote_register(self.local, conn.remote.id);
+            } else {
+                debug!("connection to {:?} is polled;", conn.remote);
+                // the sender and receiver are registered before, so ipc channels can be created on it now;
+                crate::state::set_ready(self.local, conn.remote.id);
+                self.connections.insert(token, conn);
+            }
+        }
+    }
+
+    /// Write the outboxes, send heartbeats and check the heartbeats from remote servers of all connections;
+    fn check(&mut self) {
+        let mut lost = vec![];
+        for (token, conn) in self.connections.iter_mut() {
+            let res = conn
+                .send()
+                .map_err(NetError::from)
+                .and_then(|_| conn.net_rx.check_heartbeat());
+            if let Err(e) = res {
+                error!("connection to {:?} failure, caused by {};", conn.remote, e);
+                lost.push(*token);
+            }
+        }
+        for token in lost {
+            self.close(token);
+        }
+    }
+
+    fn close(&mut self, token: Token) {
+        if let Some(conn) = self.connections.remove(&token) {
+            // deregister before the socket is closed, as the registration is kept by the cloned socket;
+            self.poll
+                .registry()
+                .deregister(&mut SourceFd(&conn.fd))
+                .ok();
+            conn.state.store(true, Ordering::SeqCst);
+            crate::send::remove_remote_sender(self.local, conn.remote.id);
+            crate::receive::remove_remote_register(self.local, conn.remote.id);
+            conn.net_tx.take_writer().shutdown().ok();
+            info!("connection to {:?} closed;", conn.remote);
+        }
+    }
+}
+
+struct EventLoopHandle {
+    notifier: Arc<Notifier>,
+    incoming: Sender<Connection>,
+}
+
+/// The event loops of a server, which exit once the server shuts down;
+struct EventLoops {
+    shutdown: Arc<AtomicBool>,
+    loops: Vec<EventLoopHandle>,
+    next: usize,
+}
+
+lazy_static! {
+    static ref EVENT_LOOPS: Mutex<HashMap<u64, EventLoops>> = Mutex::new(HashMap::new());
+}
+
+fn start_event_loops(local: u64, threads: usize) -> io::Result<Vec<EventLoopHandle>> {
+    let mut loops = Vec::with_capacity(threads);
+    for i in 0..threads {
+        let poll = Poll::new()?;
+        let waker = Waker::new(poll.registry(), WAKE_TOKEN)?;
+        let notifier = Arc::new(Notifier { waker, notified: AtomicBool::new(false) });
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let mut event_loop = EventLoop {
+            local,
+            poll,
+            notifier: notifier.clone(),
+            incoming: rx,
+            connections: HashMap::new(),
+            next_token: 1,
+        };
+        let guard = std::thread::Builder::new()
+            .name(format!("net-poll-{}-{}", local, i))
+            .spawn(move || event_loop.run())?;
+        crate::add_network_thread(local, guard);
+        loops.push(EventLoopHandle { notifier, incoming: tx });
+    }
+    Ok(loops)
+}
+
+/// Poll the connection by one of the event loops of the server, which are started by the first connection;
+pub(crate) fn register(
+    local: u64, remote: Server, hb_sec: u32, params: &ConnectionParams, state: &Arc<AtomicBool>,
+    read_half: ReadHalf, write_half: WriteHalf,
+) -> io::Result<()> {
+    let shutdown =
+        crate::get_shutdown_hook(local).ok_or_else(|| io::Error::from(io::ErrorKind::NotConnected))?;
+    read_half.socket().set_nonblocking(true)?;
+    let fd = read_half.socket().as_raw_fd();
+
+    let mut lock = EVENT_LOOPS
+        .lock()
+        .expect("EVENT_LOOPS lock poisoned");
+    let started = lock
+        .get(&local)
+        .map(|l| Arc::ptr_eq(&l.shutdown, &shutdown))
+        .unwrap_or(false);
+    if !started {
+        // the event loops of a previous run of the server have exited;
+        let loops = start_event_loops(local, params.get_poll_threads())?;
+        lock.insert(local, EventLoops { shutdown, loops, next: 0 });
+    }
+    let event_loops = lock.get_mut(&local).expect("event loops lost;");
+    let handle = &event_loops.loops[event_loops.next % event_loops.loops.len()];
+    event_loops.next += 1;
+
+    let net_tx = crate::send::new_polled_sender(local, &remote, params, write_half, &handle.notifier);
+    let net_rx = crate::receive::new_polled_receiver(local, &remote, hb_sec, params, read_half);
+    let hb_interval = Duration::from_secs(params.get_hb_interval_sec() as u64);
+    let conn = Connection {
+        remote,
+        fd,
+        hb_interval,
+        last_hb: Instant::now(),
+        state: state.clone(),
+        net_tx,
+        net_rx,
+    };
+    if handle.incoming.send(conn).is_err() {
+        // the event loop has exited for a poll error;
+        state.store(true, Ordering::SeqCst);
+        crate::send::remove_remote_sender(local, remote.id);
+        crate::receive::remove_remote_register(local, remote.id);
+        return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+    }
+    handle.notifier.notify();
+    Ok(())
+}
@@ -0,0 +1,280 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+
+use rustls::internal::pemfile;
+use rustls::{
+    AllowAnyAuthenticatedClient, Certificate, ClientConfig, ClientSession, PrivateKey, RootCertStore,
+    ServerConfig, ServerSession, Session,
+};
+use webpki::DNSNameRef;
+
+use crate::config::TlsConfig;
+use crate::NetError;
+
+const DEFAULT_TLS_DOMAIN: &str = "localhost";
+const TLS_READ_BUFFER_SIZE: usize = 1 << 14;
+
+/// The configurations of TLS sessions, each server is both the server side of the connections it
+/// accepts and the client side of the connections it makes, and authenticates itself with the same
+/// certificate in both sides;
+pub struct TlsContext {
+    server: Arc<ServerConfig>,
+    client: Arc<ClientConfig>,
+    domain: String,
+}
+
+impl TlsContext {
+    pub fn new(conf: &TlsConfig) -> Result<Self, NetError> {
+        let certs = load_certs(&conf.cert)?;
+        let key = load_key(&conf.key)?;
+        let mut roots = RootCertStore::empty();
+        for ca in load_certs(&conf.ca)? {
+            roots
+                .add(&ca)
+                .map_err(|e| NetError::TLSError(format!("invalid CA certificate {}: {:?}", conf.ca, e)))?;
+        }
+
+        let mut server = ServerConfig::new(AllowAnyAuthenticatedClient::new(roots.clone()));
+        server
+            .set_single_cert(certs.clone(), key.clone())
+            .map_err(|e| NetError::TLSError(format!("invalid certificate {}: {}", conf.cert, e)))?;
+        let mut client = ClientConfig::new();
+        client.root_store = roots;
+        client
+            .set_single_client_cert(certs, key)
+            .map_err(|e| NetError::TLSError(format!("invalid certificate {}: {}", conf.cert, e)))?;
+
+        let domain = conf
+            .domain
+            .clone()
+            .unwrap_or_else(|| DEFAULT_TLS_DOMAIN.to_owned());
+        if DNSNameRef::try_from_ascii_str(&domain).is_err() {
+            return Err(NetError::TLSError(format!("invalid domain {}", domain)));
+        }
+        Ok(TlsContext { server: Arc::new(server), client: Arc::new(client), domain })
+    }
+
+    /// Complete the handshake of a connection accepted, and split it into the read and write halves;
+    pub fn accept(&self, sock: TcpStream) -> io::Result<(TlsReader, TlsWriter)> {
+        let session = ServerSession::new(&self.server);
+        split(handshake(session, sock)?)
+    }
+
+    /// Complete the handshake of a connection made, and split it into the read and write halves;
+    pub fn connect(&self, sock: TcpStream) -> io::Result<(TlsReader, TlsWriter)> {
+        let domain = DNSNameRef::try_from_ascii_str(&self.domain).expect("domain checked;");
+        let session = ClientSession::new(&self.client, domain);
+        split(handshake(session, sock)?)
+    }
+}
+
+impl Debug for TlsContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "TlsContext({})", self.domain)
+    }
+}
+
+fn handshake<S: Session + 'static>(
+    mut session: S, mut sock: TcpStream,
+) -> io::Result<(Box<dyn Session>, TcpStream)> {
+    while session.is_handshaking() {
+        session.complete_io(&mut sock)?;
+    }
+    while session.wants_write() {
+        session.write_tls(&mut sock)?;
+    }
+    Ok((Box::new(session), sock))
+}
+
+fn split((session, sock): (Box<dyn Session>, TcpStream)) -> io::Result<(TlsReader, TlsWriter)> {
+    let session = Arc::new(Mutex::new(session));
+    let read_half = sock.try_clone()?;
+    let reader = TlsReader {
+        sock: read_half,
+        session: session.clone(),
+        buf: vec![0u8; TLS_READ_BUFFER_SIZE].into_boxed_slice(),
+        start: 0,
+        end: 0,
+    };
+    let writer = TlsWriter { sock, session, pending: vec![], pos: 0 };
+    Ok((reader, writer))
+}
+
+/// The read half of a TLS connection, the session is shared with the write half, and is locked
+/// only while the records are decrypted, never while the socket is read, so that reading and
+/// writing in two threads never block each other;
+pub struct TlsReader {
+    sock: TcpStream,
+    session: Arc<Mutex<Box<dyn Session>>>,
+    /// records read from the socket but not yet taken by the session;
+    buf: Box<[u8]>,
+    start: usize,
+    end: usize,
+}
+
+impl TlsReader {
+    pub fn socket(&self) -> &TcpStream {
+        &self.sock
+    }
+}
+
+impl Read for TlsReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let mut size = 0;
+        loop {
+            {
+                let mut session = self
+                    .session
+                    .lock()
+                    .expect("tls session lock poisoned");
+                while self.start < self.end {
+                    let mut records = &self.buf[self.start..self.end];
+                    match session.read_tls(&mut records) {
+                        Ok(size) => self.start += size,
+                        // the plaintext buffer is full, the rest is taken after it is read;
+                        Err(_) => break,
+                    }
+                    session
+                        .process_new_packets()
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                }
+                size += session.read(&mut out[size..])?;
+                if size == out.len() {
+                    return Ok(size);
+                }
+            }
+
+            // the socket may return `WouldBlock` or `TimedOut`, as the plain connection does; the
+            // plaintext is read as much as possible before that, as a read shorter than required is
+            // taken as all available data by the nonblocking transport, which waits for the next event;
+            match (&self.sock).read(&mut self.buf) {
+                Ok(0) => return Ok(size),
+                Ok(read) => {
+                    self.start = 0;
+                    self.end = read;
+                }
+                Err(_) if size > 0 => return Ok(size),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// The write half of a TLS connection, the records are taken from the session with it locked, and
+/// written to the socket after it is unlocked;
+pub struct TlsWriter {
+    sock: TcpStream,
+    session: Arc<Mutex<Box<dyn Session>>>,
+    /// records taken from the session but not yet written to the socket;
+    pending: Vec<u8>,
+    pos: usize,
+}
+
+impl TlsWriter {
+    pub fn socket(&self) -> &TcpStream {
+        &self.sock
+    }
+
+    /// Notify the peer that no more data will be sent, and shutdown the write of the socket;
+    pub fn shutdown(&mut self) -> io::Result<()> {
+        {
+            let mut session = self
+                .session
+                .lock()
+                .expect("tls session lock poisoned");
+            session.send_close_notify();
+            while session.wants_write() {
+                session.write_tls(&mut self.pending)?;
+            }
+        }
+        self.write_pending()?;
+        self.sock.shutdown(std::net::Shutdown::Write)
+    }
+
+    fn write_pending(&mut self) -> io::Result<()> {
+        while self.pos < self.pending.len() {
+            let size = (&self.sock).write(&self.pending[self.pos..])?;
+            if size == 0 {
+                return Err(io::Error::from(io::ErrorKind::WriteZero));
+            }
+            self.pos += size;
+        }
+        self.pending.clear();
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl Write for TlsWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // nothing is taken if the records before can't be written, so the caller can retry;
+        self.write_pending()?;
+        let size = {
+            let mut session = self
+                .session
+                .lock()
+                .expect("tls session lock poisoned");
+            let size = session.write(buf)?;
+            while session.wants_write() {
+                session.write_tls(&mut self.pending)?;
+            }
+            size
+        };
+        match self.write_pending() {
+            Err(e) if e.kind() != io::ErrorKind::WouldBlock => Err(e),
+            _ => Ok(size),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        {
+            let mut session = self
+                .session
+                .lock()
+                .expect("tls session lock poisoned");
+            session.flush()?;
+            while session.wants_write() {
+                session.write_tls(&mut self.pending)?;
+            }
+        }
+        self.write_pending()?;
+        (&self.sock).flush()
+    }
+}
+
+fn load_certs(path: &str) -> Result<Vec<Certificate>, NetError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    match pemfile::certs(&mut reader) {
+        Ok(certs) if !certs.is_empty() => Ok(certs),
+        _ => Err(NetError::TLSError(format!("no certificate found in {}", path))),
+    }
+}
+
+fn load_key(path: &str) -> Result<PrivateKey, NetError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut keys = pemfile::pkcs8_private_keys(&mut reader).unwrap_or_default();
+    if keys.is_empty() {
+        let mut reader = BufReader::new(File::open(path)?);
+        keys = pemfile::rsa_private_keys(&mut reader).unwrap_or_default();
+    }
+    keys.pop()
+        .ok_or_else(|| NetError::TLSError(format!("no private key found in {}", path)))
+}
@@ -18,15 +18,16 @@ extern crate log;
 use std::time::Duration;
 
 use pegasus_common::codec::*;
-use pegasus_network::{config::ConnectionParams, Server, ServerDetect};
+use pegasus_network::config::{Compression, ConnectionParams};
+use pegasus_network::{Server, ServerDetect};
 
 struct MockServerDetect {
     servers: Vec<Server>,
 }
 
 impl ServerDetect for MockServerDetect {
-    fn fetch(&mut self) -> &[Server] {
-        self.servers.as_slice()
+    fn fetch(&self) -> Vec<Server> {
+        self.servers.clone()
     }
 }
 
@@ -74,6 +75,22 @@ fn ipc_test() {
         let conf = ConnectionParams::nonblocking();
         ipc_with_conf(conf);
     }
+    {
+        // all connections of a server are polled by one thread;
+        let mut conf = ConnectionParams::nonblocking();
+        conf.set_poll_threads(1);
+        ipc_with_conf(conf);
+    }
+    {
+        let mut conf = ConnectionParams::nonblocking();
+        conf.set_compression(Compression::LZ4, 128);
+        ipc_with_conf(conf);
+    }
+    {
+        let mut conf = ConnectionParams::blocking();
+        conf.set_compression(Compression::Zstd, 128);
+        ipc_with_conf(conf);
+    }
 }
 
 fn ipc_with_conf(conf: ConnectionParams) {
@@ -82,8 +99,8 @@ fn ipc_with_conf(conf: ConnectionParams) {
     servers.push(Server { id: 0, addr: "127.0.0.1:1234".parse().unwrap() });
     servers.push(Server { id: 1, addr: "127.0.0.1:1235".parse().unwrap() });
     servers.push(Server { id: 2, addr: "127.0.0.1:1236".parse().unwrap() });
-    let g1 = mock_process_0(servers.clone(), conf);
-    let g2 = mock_process_1(servers.clone(), conf);
+    let g1 = mock_process_0(servers.clone(), conf.clone());
+    let g2 = mock_process_1(servers.clone(), conf.clone());
     let g3 = mock_process_2(servers, conf);
     g1.join().unwrap();
     g2.join().unwrap();
@@ -0,0 +1,203 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+#[macro_use]
+extern crate log;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use pegasus_common::codec::*;
+use pegasus_network::config::{ConnectionParams, TlsConfig};
+use pegasus_network::{Server, ServerDetect};
+use rcgen::{BasicConstraints, Certificate, CertificateParams, ExtendedKeyUsagePurpose, IsCa};
+
+struct MockServerDetect {
+    servers: Vec<Server>,
+}
+
+impl ServerDetect for MockServerDetect {
+    fn fetch(&self) -> Vec<Server> {
+        self.servers.clone()
+    }
+}
+
+struct Entry {
+    data: Vec<u8>,
+}
+
+impl Entry {
+    pub fn new(value: u8) -> Self {
+        Entry { data: vec![value; 256] }
+    }
+}
+
+impl Encode for Entry {
+    fn write_to<W: WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.data)
+    }
+}
+
+impl Decode for Entry {
+    fn read_from<R: ReadExt>(reader: &mut R) -> std::io::Result<Self> {
+        let mut data = vec![0u8; 256];
+        reader.read_exact(&mut data[0..])?;
+        Ok(Entry { data })
+    }
+}
+
+fn new_ca() -> Certificate {
+    let mut params = CertificateParams::new(vec![]);
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    Certificate::from_params(params).unwrap()
+}
+
+/// Generate a certificate for `localhost` signed by the CA, and write the PEM files under a temp directory;
+fn gen_tls_config(name: &str, ca: &Certificate) -> TlsConfig {
+    let mut dir = std::env::temp_dir();
+    dir.push(format!("pegasus_tls_test_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut params = CertificateParams::new(vec!["localhost".to_owned()]);
+    params.extended_key_usages =
+        vec![ExtendedKeyUsagePurpose::ServerAuth, ExtendedKeyUsagePurpose::ClientAuth];
+    let cert = Certificate::from_params(params).unwrap();
+    let write = |file: &str, content: String| -> String {
+        let path: PathBuf = dir.join(file);
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    };
+    TlsConfig {
+        cert: write("server.pem", cert.serialize_pem_with_signer(ca).unwrap()),
+        key: write("server.key", cert.serialize_private_key_pem()),
+        ca: write("ca.pem", ca.serialize_pem().unwrap()),
+        domain: None,
+    }
+}
+
+fn tls_params(conf: &TlsConfig) -> ConnectionParams {
+    let mut params = ConnectionParams::blocking();
+    params.set_tls(conf).unwrap();
+    params
+}
+
+fn tls_nonblocking_params(conf: &TlsConfig) -> ConnectionParams {
+    let mut params = ConnectionParams::nonblocking();
+    params.set_tls(conf).unwrap();
+    params
+}
+
+fn mock_process(
+    id: u64, remote: u64, servers: Vec<Server>, conf: ConnectionParams,
+) -> std::thread::JoinHandle<()> {
+    std::thread::Builder::new()
+        .name(format!("process-{}", id))
+        .spawn(move || {
+            let addr = servers
+                .iter()
+                .find(|s| s.id == id)
+                .unwrap()
+                .addr;
+            let detector = MockServerDetect { servers };
+            let addr = pegasus_network::start_up(id, conf, addr, detector).unwrap();
+            info!("server {} start at {:?}", id, addr);
+            let remotes = vec![remote];
+            while !pegasus_network::check_connect(id, &remotes) {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+
+            let ipc_ch = pegasus_network::ipc_channel::<Entry>(1, id, &remotes).unwrap();
+            let (mut sends, recv) = ipc_ch.take();
+            sends[0].send(&Entry::new(id as u8)).unwrap();
+            sends[0].close().unwrap();
+            let mut receives = vec![];
+            loop {
+                match recv.recv() {
+                    Ok(Some(entry)) => {
+                        receives.extend_from_slice(&entry.data);
+                    }
+                    Err(e) => {
+                        if e.kind() == std::io::ErrorKind::BrokenPipe {
+                            break;
+                        } else {
+                            panic!("unexpected error {}", e);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            assert_eq!(receives, vec![remote as u8; 256]);
+            pegasus_network::shutdown(id);
+            pegasus_network::await_termination(id);
+        })
+        .unwrap()
+}
+
+#[test]
+fn tls_ipc_test() {
+    pegasus_common::logs::init_log();
+    let ca = new_ca();
+    let conf_0 = gen_tls_config("ipc_0", &ca);
+    let conf_1 = gen_tls_config("ipc_1", &ca);
+    let mut servers = vec![];
+    servers.push(Server { id: 10, addr: "127.0.0.1:1240".parse().unwrap() });
+    servers.push(Server { id: 11, addr: "127.0.0.1:1241".parse().unwrap() });
+    let g1 = mock_process(10, 11, servers.clone(), tls_params(&conf_0));
+    let g2 = mock_process(11, 10, servers, tls_params(&conf_1));
+    g1.join().unwrap();
+    g2.join().unwrap();
+}
+
+#[test]
+fn tls_nonblocking_ipc_test() {
+    pegasus_common::logs::init_log();
+    let ca = new_ca();
+    let conf_0 = gen_tls_config("nonblocking_0", &ca);
+    let conf_1 = gen_tls_config("nonblocking_1", &ca);
+    let mut servers = vec![];
+    servers.push(Server { id: 12, addr: "127.0.0.1:1245".parse().unwrap() });
+    servers.push(Server { id: 13, addr: "127.0.0.1:1246".parse().unwrap() });
+    let g1 = mock_process(12, 13, servers.clone(), tls_nonblocking_params(&conf_0));
+    let g2 = mock_process(13, 12, servers, tls_nonblocking_params(&conf_1));
+    g1.join().unwrap();
+    g2.join().unwrap();
+}
+
+#[test]
+fn tls_reject_test() {
+    pegasus_common::logs::init_log();
+    let ca = new_ca();
+    let conf = gen_tls_config("reject", &ca);
+    // a server with a certificate signed by another CA;
+    let untrusted = gen_tls_config("reject_untrusted", &new_ca());
+    let mut servers = vec![];
+    servers.push(Server { id: 20, addr: "127.0.0.1:1242".parse().unwrap() });
+    servers.push(Server { id: 21, addr: "127.0.0.1:1243".parse().unwrap() });
+    servers.push(Server { id: 22, addr: "127.0.0.1:1244".parse().unwrap() });
+    let peers = [(20, tls_params(&conf)), (21, ConnectionParams::blocking()), (22, tls_params(&untrusted))];
+    for (id, params) in peers.iter() {
+        let detector = MockServerDetect { servers: servers.clone() };
+        let addr = servers[(*id - 20) as usize].addr;
+        pegasus_network::start_up(*id, params.clone(), addr, detector).unwrap();
+    }
+
+    std::thread::sleep(Duration::from_secs(3));
+    assert!(!pegasus_network::check_connect(20, &[21]));
+    assert!(!pegasus_network::check_connect(20, &[22]));
+    assert!(!pegasus_network::check_connect(21, &[20]));
+    assert!(!pegasus_network::check_connect(22, &[20]));
+    for (id, _) in peers.iter() {
+        pegasus_network::shutdown(*id);
+        pegasus_network::await_termination(*id);
+    }
+}
@@ -1,9 +1,10 @@
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
 use pegasus::api::{Count, Iteration, Map, Sink};
-use pegasus::{Configuration, JobConf, ServerConf};
+use pegasus::{Configuration, JobConf, ScheduleStrategy, ServerConf};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -18,6 +19,13 @@ struct Config {
     id_from_std: bool,
     #[structopt(short = "f")]
     use_loop: bool,
+    /// Schedule operators with the volcano strategy instead of the waterfall one;
+    #[structopt(long = "volcano")]
+    volcano: bool,
+    /// Search with both strategies, and check that the volcano one holds no more neighbors in flight
+    /// than the waterfall one;
+    #[structopt(long = "compare")]
+    compare: bool,
     /// The path of the origin graph data ;
     #[structopt(long = "data", parse(from_os_str))]
     data_path: PathBuf,
@@ -41,6 +49,9 @@ fn main() {
     let graph = Arc::new(pegasus_graph::load(&config.data_path).unwrap());
     let mut conf = JobConf::new("k_hop");
     conf.set_workers(config.partitions);
+    if config.volcano {
+        conf.strategy = ScheduleStrategy::Volcano;
+    }
     if config.servers.is_some() {
         conf.reset_servers(ServerConf::All);
     }
@@ -69,19 +80,42 @@ fn main() {
 
     pegasus::wait_servers_ready(conf.servers());
 
-    let mut results = Vec::new();
-    let k_hop = config.k;
-    let nums = src.len();
-
-    if nums == 0 {
+    if src.is_empty() {
         return;
     }
 
-    println!("start search {}-hop neighbors for {} vertices;", k_hop, nums);
+    let strategies = if config.compare {
+        vec![ScheduleStrategy::Waterfall, ScheduleStrategy::Volcano]
+    } else {
+        vec![conf.strategy]
+    };
+    let mut peaks = vec![];
+    for strategy in strategies {
+        conf.strategy = strategy;
+        peaks.push(search(&conf, &graph, &src, config.k, config.use_loop));
+    }
+    if config.compare {
+        assert!(
+            peaks[1] <= peaks[0],
+            "volcano holds {} neighbors in flight at peak, more than {} of waterfall;",
+            peaks[1],
+            peaks[0]
+        );
+    }
+    pegasus::shutdown_all();
+}
+
+/// Search the k-hop neighbors of each vertex in `src`, return the peak neighbors in flight;
+fn search(
+    conf: &JobConf, graph: &Arc<pegasus_graph::Graph>, src: &[u64], k_hop: u32, use_loop: bool,
+) -> i64 {
+    let nums = src.len();
+    println!("start search {}-hop neighbors for {} vertices with {:?};", k_hop, nums, conf.strategy);
 
-    let use_loop = config.use_loop;
+    let mut results = Vec::new();
+    let in_flight = Arc::new(InFlight::default());
     let global_start = Instant::now();
-    for (i, id) in src.into_iter().enumerate() {
+    for (i, id) in src.iter().copied().enumerate() {
         let mut conf = conf.clone();
         conf.job_id = i as u64;
         conf.plan_print = i <= 0;
@@ -89,25 +123,33 @@ fn main() {
         let result = pegasus::run(conf.clone(), || {
             let index = pegasus::get_current_worker().index;
             let graph = graph.clone();
+            let in_flight = in_flight.clone();
             let src = if index == 0 { vec![id] } else { vec![] };
             move |input, output| {
                 let mut stream = input.input_from(src)?;
                 if use_loop {
+                    let in_flight = in_flight.clone();
                     stream = stream.iterate(k_hop, |start| {
                         let graph = graph.clone();
                         start
                             .repartition(|id| Ok(*id))
-                            .flat_map(move |id| Ok(graph.get_neighbors(id)))
+                            .flat_map(move |id| Ok(in_flight.expand(graph.get_neighbors(id))))
                     })?;
                 } else {
                     for _i in 0..k_hop {
                         let graph = graph.clone();
+                        let in_flight = in_flight.clone();
                         stream = stream
                             .repartition(|id| Ok(*id))
-                            .flat_map(move |id| Ok(graph.get_neighbors(id)))?;
+                            .flat_map(move |id| Ok(in_flight.expand(graph.get_neighbors(id))))?;
                     }
                 }
+                let in_flight = in_flight.clone();
                 stream
+                    .map(move |id| {
+                        in_flight.consume(1);
+                        Ok(id)
+                    })?
                     .count()?
                     .map(move |cnt| {
                         let x = start.elapsed();
@@ -171,5 +213,32 @@ fn main() {
     println!("==========================================================");
     let millis = elp.as_millis() as f64;
     println!("total use {}ms, qps: {:.1}", millis, nums as f64 * 1000.0 / millis);
-    pegasus::shutdown_all();
+    let peak = in_flight.peak.load(Ordering::SeqCst);
+    println!("peak {} neighbors in flight", peak);
+    peak
+}
+
+/// Neighbors found by a hop but not yet consumed by the next one, which is how much the memory of
+/// k-hop grows with the strategies of scheduling;
+#[derive(Default)]
+struct InFlight {
+    current: AtomicI64,
+    peak: AtomicI64,
+}
+
+impl InFlight {
+    /// The neighbors of a vertex are found, and the vertex itself is consumed;
+    fn expand(&self, neighbors: pegasus_graph::Neighbors) -> pegasus_graph::Neighbors {
+        let produced = neighbors.len() as i64 - 1;
+        let current = self
+            .current
+            .fetch_add(produced, Ordering::SeqCst)
+            + produced;
+        self.peak.fetch_max(current, Ordering::SeqCst);
+        neighbors
+    }
+
+    fn consume(&self, count: i64) {
+        self.current.fetch_sub(count, Ordering::SeqCst);
+    }
 }
@@ -30,11 +30,11 @@ use crate::Data;
 /// While [`semi_join`] and [`anti_join`] have the variances of left and right, but we only consier
 /// the left case, knowing that the right case can be easily achieved by swapping the two streams.
 ///
-/// These joins are for now executed based on the equivalence of the keys of the left and right
-/// streams. A general version may require defining any predicate on the key, for example,
-/// left.key > right.key, we leave it as a future work.
+/// These joins are executed based on the equivalence of the keys of the left and right
+/// streams. For the joins on other predicates, for example, left.key > right.key, see [`JoinBy`].
 ///
 ///
+/// [`JoinBy`]: crate::api::JoinBy
 /// [`key_by`]: crate::api::KeyBy
 /// [`inner_join`]: crate::api::Join::inner_join
 /// [`left_outer_join`]: crate::api::Join::left_outer_join
@@ -0,0 +1,109 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use crate::api::function::FnResult;
+use crate::errors::BuildJobError;
+use crate::stream::Stream;
+use crate::Data;
+
+/// Join self and other stream (we treat self stream as left stream, other stream as right stream)
+/// on a condition other than the equivalence of keys, where the items are not required to be keyed.
+///
+/// We now implement 2 types of such joins, namely [`join_by`] and [`band_join`]:
+/// [`join_by`] accepts any predicate on the left and right items, and is executed by broadcasting
+/// the right stream and comparing each pair of items (nested loop);
+/// [`band_join`] matches the items whose integer keys are close enough, e.g. events happened
+/// within a time window, and is executed by sorting both streams on the keys and merging them.
+///
+/// [`join_by`]: crate::api::JoinBy::join_by
+/// [`band_join`]: crate::api::JoinBy::band_join
+pub trait JoinBy<L: Data, R: Data> {
+    /// Theta join will return a stream containing all pairs of elements `(l, r)` of self and other
+    /// satisfying `predicate(&l, &r)`.
+    ///
+    /// As the predicate can't be used to partition the streams, the other stream is broadcast to all
+    /// workers, and each item of self is compared with all items of other. Thus prefer [`band_join`]
+    /// or [`inner_join`] if the condition can be expressed by them.
+    ///
+    /// [`band_join`]: crate::api::JoinBy::band_join
+    /// [`inner_join`]: crate::api::Join::inner_join
+    ///
+    /// # Example
+    /// ```
+    /// #     use pegasus::api::*;
+    /// #     use pegasus::JobConf;
+    /// #     let mut conf = JobConf::new("join_by_example");
+    /// #     conf.set_workers(2);
+    ///     let mut results = pegasus::run(conf, || {
+    ///         let id = pegasus::get_current_worker().index;
+    ///         move |input, output| {
+    ///             let src1 = if id == 0 { input.input_from(1..4)? } else { input.input_from(vec![])? };
+    ///             let (src1, src2) = src1.copied()?; // stream {1,2,3}
+    ///             src1.join_by(src2, |l, r| Ok(l < r))?
+    ///                 .collect::<Vec<(u32, u32)>>()?
+    ///                 .sink_into(output)
+    ///         }
+    ///     })
+    ///     .expect("run job failure;");
+    ///
+    ///     let mut expected = results.next().unwrap().unwrap();
+    ///     expected.sort();
+    ///     assert_eq!(expected, [(1, 2), (1, 3), (2, 3)]);
+    /// ```
+    fn join_by<F>(self, other: Stream<R>, predicate: F) -> Result<Stream<(L, R)>, BuildJobError>
+    where
+        F: Fn(&L, &R) -> FnResult<bool> + Send + 'static;
+
+    /// Band join will return a stream containing all pairs of elements `(l, r)` of self and other
+    /// satisfying `|left_key(l) - right_key(r)| <= width`. The pairs are output in the order of the
+    /// keys of the left items in each partition, after both streams end.
+    ///
+    /// If `partition` is given, the keys are divided into ranges of length `partition`, and both
+    /// streams are partitioned by the ranges, where the right items within `width` of a range are
+    /// copied into it. Otherwise, the other stream is broadcast to all workers as in [`join_by`].
+    /// A `partition` several times larger than `width` avoids most copies.
+    ///
+    /// [`join_by`]: crate::api::JoinBy::join_by
+    ///
+    /// # Example
+    /// ```
+    /// #     use pegasus::api::*;
+    /// #     use pegasus::JobConf;
+    /// #     let mut conf = JobConf::new("band_join_example");
+    /// #     conf.set_workers(2);
+    ///     let mut results = pegasus::run(conf, || {
+    ///         let id = pegasus::get_current_worker().index;
+    ///         move |input, output| {
+    ///             let src1 = if id == 0 { input.input_from(vec![1, 5, 9])? } else { input.input_from(vec![])? };
+    ///             let (src1, src2) = src1.copied()?; // stream {1,5,9}
+    ///             let src2 = src2.flat_map(|x| Ok(vec![x + 1, x + 100].into_iter()))?;
+    ///             src1.band_join(src2, |l| *l as i64, |r| *r as i64, 1, Some(4))?
+    ///                 .collect::<Vec<(u32, u32)>>()?
+    ///                 .sink_into(output)
+    ///         }
+    ///     })
+    ///     .expect("run job failure;");
+    ///
+    ///     let mut expected = results.next().unwrap().unwrap();
+    ///     expected.sort();
+    ///     assert_eq!(expected, [(1, 2), (5, 6), (9, 10)]);
+    /// ```
+    fn band_join<FL, FR>(
+        self, other: Stream<R>, left_key: FL, right_key: FR, width: i64, partition: Option<i64>,
+    ) -> Result<Stream<(L, R)>, BuildJobError>
+    where
+        FL: Fn(&L) -> i64 + Send + 'static,
+        FR: Fn(&R) -> i64 + Send + 'static;
+}
@@ -14,5 +14,7 @@
 //! limitations under the License.
 
 pub use join::*;
+pub use join_by::*;
 
 mod join;
+mod join_by;
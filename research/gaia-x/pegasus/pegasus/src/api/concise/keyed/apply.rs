@@ -2,7 +2,45 @@ use crate::api::{Key, Pair};
 use crate::stream::{SingleItem, Stream};
 use crate::{BuildJobError, Data};
 
+/// `ScopeByKey` runs a sub-task over each group of data sharing the same key, like
+/// [`CorrelatedSubTask`] does for each single data.
+///
+/// [`CorrelatedSubTask`]: crate::api::CorrelatedSubTask
 pub trait ScopeByKey<K: Data + Key, V: Data> {
+    /// Partition the input by key, and run the sub-task built by `task` over all values of each key
+    /// in a scope of its own, producing a [`Pair`] of the key and the single result of its sub-task.
+    ///
+    /// Values are sent into the scope of their key as soon as they arrive, and the scope ends when the
+    /// input ends. If a sub-task stops early, e.g. by a `limit()`, the values of its key arriving later
+    /// are dropped before entering the sub-task. No more scopes than the scope capacity are live at
+    /// once on a worker: the values of keys beyond it are buffered until the input ends, and are then
+    /// forked into scopes as earlier ones finish. A key whose sub-task gives no result is not output.
+    ///
+    /// [`Pair`]: crate::api::keyed::Pair
+    ///
+    /// # Example
+    /// ```
+    ///   # use pegasus::{JobConf};
+    ///   # use pegasus::api::{Sink, Map, KeyBy, ScopeByKey, Count, Collect};
+    ///
+    ///   # let conf = JobConf::new("segment_apply_example");
+    ///     let mut results = pegasus::run(conf, || {
+    ///         move |input, output| {
+    ///             input
+    ///                 .input_from(0..10u32)?
+    ///                 .key_by(|d| Ok((d % 3, d)))?
+    ///                 .segment_apply(|sub| sub.count())?
+    ///                 .map(|pair| Ok(pair.take()))?
+    ///                 .collect::<Vec<(u32, u64)>>()?
+    ///                 .sink_into(output)
+    ///         }
+    ///     })
+    ///       .expect("build job failure");
+    ///
+    ///     let mut expected = results.next().unwrap().unwrap();
+    ///     expected.sort();
+    ///     assert_eq!(expected, [(0, 4), (1, 3), (2, 3)]);
+    /// ```
     fn segment_apply<F, T>(self, task: F) -> Result<Stream<Pair<K, T>>, BuildJobError>
     where
         T: Data,
@@ -16,16 +16,29 @@
 use std::collections::HashMap;
 
 use crate::api::function::FnResult;
-use crate::api::Key;
-use crate::stream::SingleItem;
+use crate::api::{Key, Pair};
+use crate::stream::{SingleItem, Stream};
 use crate::{BuildJobError, Data};
 
 pub trait ReduceByKey<K: Key + Data, V: Data> {
     /// Analogous to [`reduce()`] but reducing the data according to the key part of the input data.
+    /// The reduced values of all keys are gathered into a single map, use [`reduce_by_key_stream()`]
+    /// instead if they can't be held in memory.
     ///
     /// [`reduce()`]: crate::api::reduce::Reduce::reduce()
+    /// [`reduce_by_key_stream()`]: ReduceByKey::reduce_by_key_stream()
     fn reduce_by_key<B, F>(self, builder: B) -> Result<SingleItem<HashMap<K, V>>, BuildJobError>
     where
         F: FnMut(V, V) -> FnResult<V> + Send + 'static,
         B: Fn() -> F + Send + 'static;
+
+    /// Analogous to [`reduce_by_key()`] but giving a [`Pair`] of each key and its reduced value when the
+    /// scope ends. The keys beyond the spill threshold are spilled by their hash into partitions, which
+    /// are reduced and given one by one, so no more than a partition of keys is held in memory.
+    ///
+    /// [`reduce_by_key()`]: ReduceByKey::reduce_by_key()
+    fn reduce_by_key_stream<B, F>(self, builder: B) -> Result<Stream<Pair<K, V>>, BuildJobError>
+    where
+        F: FnMut(V, V) -> FnResult<V> + Send + 'static,
+        B: Fn() -> F + Send + 'static;
 }
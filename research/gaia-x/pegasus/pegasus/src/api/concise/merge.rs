@@ -2,7 +2,6 @@ use std::fmt::{Debug, Formatter};
 
 use bitflags::_core::cmp::Ordering;
 
-use crate::codec::{Decode, Encode, ReadExt, WriteExt};
 use crate::stream::Stream;
 use crate::{BuildJobError, Data};
 
@@ -47,7 +46,6 @@ pub trait Merge<D: Data> {
     ///
     /// # Example
     /// ```
-    /// /*
     ///   # use pegasus::{JobConf};
     ///   # use pegasus::api::{Sink, Map, Merge, Either};
     ///
@@ -69,7 +67,6 @@ pub trait Merge<D: Data> {
     ///
     ///     results.sort();
     ///     assert_eq!(results, [Either::A(1_u32), Either::A(3_u32), Either::B(0_u64), Either::B(2_u64)]);
-    /// */
     /// ```
     fn merge_isomer<T: Data>(self, isomer: Stream<T>) -> Result<Stream<Either<D, T>>, BuildJobError>;
 }
@@ -163,14 +160,10 @@ impl<A: Debug, B: Debug> Debug for Either<A, B> {
     }
 }
 
-impl<A: Encode, B: Encode> Encode for Either<A, B> {
-    fn write_to<W: WriteExt>(&self, _writer: &mut W) -> std::io::Result<()> {
-        todo!()
-    }
-}
-
-impl<A: Decode, B: Decode> Decode for Either<A, B> {
-    fn read_from<R: ReadExt>(_reader: &mut R) -> std::io::Result<Self> {
-        todo!()
+// encoded as a tag of `u8` followed by the data of either side;
+enum_codec! {
+    Either<A, B> {
+        0 => A(a),
+        1 => B(b),
     }
 }
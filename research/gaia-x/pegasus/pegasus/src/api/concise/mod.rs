@@ -25,6 +25,9 @@ pub use map::*;
 pub use merge::*;
 pub use reduce::*;
 pub use sort::*;
+pub use switch::*;
+pub use window::*;
+pub use zip::*;
 
 mod collect;
 mod correlate;
@@ -39,4 +42,5 @@ mod merge;
 mod reduce;
 mod sort;
 mod switch;
+mod window;
 mod zip;
@@ -2,17 +2,54 @@ use crate::macros::map::FnResult;
 use crate::stream::Stream;
 use crate::{BuildJobError, Data};
 
+/// `SwitchFn` is a [`Branch`] operator that splits the input stream into two streams by a predicate.
+///
+/// [`Branch`]: crate::api::primitive::branch::Branch
 pub trait SwitchFn<D: Data> {
+    /// Given a user-defined predicate `func`, the data `d` such that `func(d) = Ok(true)` is sent to
+    /// the first output stream as a `L`, and the others are sent to the second output stream as a `R`.
+    ///
+    /// # Example
+    /// ```
+    ///   # use pegasus::{JobConf};
+    ///   # use pegasus::api::{Sink, Map, Merge, SwitchFn, Collect};
+    ///
+    ///   # let conf = JobConf::new("switch_example");
+    ///     let mut results = pegasus::run(conf, || {
+    ///         move |input, output| {
+    ///             let (even, odd) = input
+    ///                 .input_from(1..7u32)?
+    ///                 .switch::<u32, u64, _>(|d| Ok(d % 2 == 0))?;
+    ///             even.map(|d| Ok(d as u64 * 10))?
+    ///                 .merge(odd)?
+    ///                 .collect::<Vec<u64>>()?
+    ///                 .sink_into(output)
+    ///         }
+    ///     })
+    ///       .expect("build job failure");
+    ///
+    ///     let mut expected = results.next().unwrap().unwrap();
+    ///     expected.sort();
+    ///     assert_eq!(expected, [1, 3, 5, 20, 40, 60]);
+    /// ```
     fn switch<L, R, F>(self, func: F) -> Result<(Stream<L>, Stream<R>), BuildJobError>
     where
         L: Data,
         R: Data,
+        D: Into<L> + Into<R>,
         F: Fn(&D) -> FnResult<bool> + Send + 'static;
 
+    /// Similar to `switch()`, but the predicate can be stateful. The `builder` creates a new
+    /// predicate for each scope, so the state is never shared among different scopes, e.g. among
+    /// different iterations of an [`iterate()`], or different inputs of an [`apply()`].
+    ///
+    /// [`iterate()`]: crate::api::Iteration::iterate
+    /// [`apply()`]: crate::api::CorrelatedSubTask::apply
     fn switch_mut<L, R, F, B>(self, builder: B) -> Result<(Stream<L>, Stream<R>), BuildJobError>
     where
         L: Data,
         R: Data,
+        D: Into<L> + Into<R>,
         F: FnMut(&D) -> FnResult<bool> + Send + 'static,
         B: Fn() -> F + Send + 'static;
 }
@@ -0,0 +1,80 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use crate::errors::BuildJobError;
+use crate::stream::Stream;
+use crate::Data;
+
+/// Group the items of a stream, which is usually unbounded, into windows by their event time, and
+/// aggregate the items of each window, e.g. counting the events of every minute.
+///
+/// Each window is a child scope of the scope the stream is in, so the operators following the window
+/// are applied to the items of each window separately, e.g. [`fold`] emits a result for each window,
+/// and the results are sent back to the parent scope by [`leave`].
+///
+/// A window is closed once the watermark passes its end, where the watermark is the max event time
+/// seen so far minus the `delay`, meaning an item may arrive at most `delay` later than the items
+/// happened after it. Items arrive after their windows closed are dropped. All windows are closed
+/// when the input ends.
+///
+/// The windows of a stream are assigned on one worker, so that the watermark is consistent, that is,
+/// all items of a stream in the root scope are sent to the first worker, and all items of a parent
+/// scope are sent to one worker picked by the scope. The throughput of assigning windows is bounded by
+/// one worker, so the items are better filtered and projected before windowing; the aggregations of
+/// different windows are still distributed among all workers. The event time should be relative to a
+/// start point close to the first event, as the windows are numbered by `u32`.
+///
+/// [`fold`]: crate::api::Fold::fold
+/// [`leave`]: crate::stream::Stream::leave
+pub trait Window<D: Data> {
+    /// Divide the stream into windows of `size` that don't overlap, i.e. the window `[k * size, (k + 1) * size)`
+    /// holds the items whose event time fall into it.
+    ///
+    /// # Example
+    /// ```
+    /// #     use pegasus::api::*;
+    /// #     use pegasus::JobConf;
+    /// #     let conf = JobConf::new("tumbling_window_example");
+    ///     let mut results = pegasus::run(conf, || {
+    ///         |input, output| {
+    ///             input
+    ///                 .input_from(0..10u64)?
+    ///                 .tumbling_window(4, 0, |t| *t)?
+    ///                 .count()?
+    ///                 .into_stream()?
+    ///                 .leave()?
+    ///                 .collect::<Vec<u64>>()?
+    ///                 .sink_into(output)
+    ///         }
+    ///     })
+    ///     .expect("run job failure;");
+    ///
+    ///     let mut counts = results.next().unwrap().unwrap();
+    ///     counts.sort();
+    ///     assert_eq!(counts, [2, 4, 4]);
+    /// ```
+    fn tumbling_window<F>(self, size: u64, delay: u64, event_time: F) -> Result<Stream<D>, BuildJobError>
+    where
+        F: Fn(&D) -> u64 + Send + 'static;
+
+    /// Divide the stream into windows of `size` starting every `slide`, i.e. the window
+    /// `[k * slide, k * slide + size)` holds the items whose event time fall into it. An item is
+    /// copied into each of the windows holding it if `slide` is less than `size`.
+    fn sliding_window<F>(
+        self, size: u64, slide: u64, delay: u64, event_time: F,
+    ) -> Result<Stream<D>, BuildJobError>
+    where
+        F: Fn(&D) -> u64 + Send + 'static;
+}
@@ -1,7 +1,41 @@
 use crate::stream::Stream;
 use crate::{BuildJobError, Data};
 
+/// `Zip` is a [`Binary`] operator that pairs up the data of two input streams one by one.
+///
+/// [`Binary`]: crate::api::primitive::binary::Binary
 pub trait Zip<D: Data> {
     /// 'Zips up' two streams into a single stream of pairs.
+    ///
+    /// In each scope, the i-th data arriving from `self` is paired with the i-th data arriving from
+    /// `other` on the same worker. If one stream has more data than the other in a scope, the data
+    /// left over is discarded once the shorter one ends, just like [`Iterator::zip`]. As data is
+    /// paired in the order it arrives, the pairs are only meaningful if both streams keep the order
+    /// of a common origin, e.g. both are derived from the same stream by [`copied`] without any
+    /// data exchange in between.
+    ///
+    /// [`Iterator::zip`]: std::iter::Iterator::zip
+    /// [`copied`]: crate::stream::Stream::copied
+    ///
+    /// # Example
+    /// ```
+    ///   # use pegasus::{JobConf};
+    ///   # use pegasus::api::{Sink, Map, Zip, Collect};
+    ///
+    ///   # let conf = JobConf::new("zip_example");
+    ///     let mut results = pegasus::run(conf, || {
+    ///         move |input, output| {
+    ///             let (src1, src2) = input.input_from(1..5u32)?.copied()?;
+    ///             src1.zip(src2.map(|d| Ok(d * 10))?)?
+    ///                 .collect::<Vec<(u32, u32)>>()?
+    ///                 .sink_into(output)
+    ///         }
+    ///     })
+    ///       .expect("build job failure");
+    ///
+    ///     let mut expected = results.next().unwrap().unwrap();
+    ///     expected.sort();
+    ///     assert_eq!(expected, [(1, 10), (2, 20), (3, 30), (4, 40)]);
+    /// ```
     fn zip<T: Data>(self, other: Stream<T>) -> Result<Stream<(D, T)>, BuildJobError>;
 }
@@ -13,8 +13,13 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::any::Any;
+use std::io;
+
 use crate::api::function::{FilterFunction, FnResult};
+use crate::codec::{Decode, Encode};
 use crate::errors::BuildJobError;
+use crate::resource::{get_resource, get_resource_mut};
 use crate::stream::Stream;
 use crate::Data;
 
@@ -27,6 +32,15 @@ pub trait Iteration<D: Data> {
     /// (or feedback) edge between the last operator and first operator of `func`, as the name
     /// cyclic dataflow has suggested.
     ///
+    /// If `JobConf::checkpoint_dir` is set, the data entering each iteration are written into
+    /// checkpoints there, and a job restarted with the same `job_id` and `JobConf::resume` set
+    /// skips the iterations before the latest checkpoint written by all workers. Only the iterations
+    /// not nested in other scopes are checkpointed. Besides the data entering and leaving the
+    /// iterations, the checkpoints hold the resources given by `IterCondition::checkpoint_resource`.
+    /// The state of operators is not checkpointed, so the job is rejected if `func` builds any operator
+    /// which may keep state across iterations, i.e. operators other than `map`, `filter`, `filter_map`,
+    /// `flat_map`, `fold`, `reduce` and `count`, and the `_partition` variants of them.
+    ///
     /// # Example
     /// ```
     ///   # use pegasus::{JobConf};
@@ -105,15 +119,32 @@ pub struct IterCondition<D> {
     pub max_iters: u32,
     /// The data-dependent termination condition
     until: Option<Box<dyn FilterFunction<D>>>,
+    /// The resources written into the checkpoints of the iteration;
+    pub(crate) resources: Vec<ResourceCheckpoint>,
 }
 
 impl<D: 'static> IterCondition<D> {
     pub fn new() -> Self {
-        IterCondition { max_iters: !0u32, until: None }
+        IterCondition { max_iters: !0u32, until: None, resources: vec![] }
     }
 
     pub fn max_iters(max_iters: u32) -> Self {
-        IterCondition { max_iters, until: None }
+        IterCondition { max_iters, until: None, resources: vec![] }
+    }
+
+    /// Write the resource of type `T` of each worker(see `pegasus::run_with_resources`) into the
+    /// checkpoints of the iteration, and restore it on resume, if the resource is changed by the body
+    /// of the iteration, e.g. the ranks of vertices updated in each iteration of page rank.
+    ///
+    /// The resource is snapshotted once an iteration has received all its input on this worker, so
+    /// the snapshot is consistent only if the changes of the body to the resource in an iteration are
+    /// done before the end of the iteration, e.g. the ranks are updated ahead of a `reduce` in the body;
+    pub fn checkpoint_resource<T: Encode + Decode + Any>(&mut self) {
+        self.resources.push(ResourceCheckpoint {
+            name: std::any::type_name::<T>(),
+            snapshot: snapshot_resource::<T>,
+            restore: restore_resource::<T>,
+        });
     }
 
     pub fn set_until(&mut self, until: Box<dyn FilterFunction<D>>) {
@@ -134,3 +165,33 @@ impl<D: 'static> IterCondition<D> {
         self.until.is_some()
     }
 }
+
+/// The functions writing a resource into checkpoints and restoring it;
+#[derive(Copy, Clone)]
+pub(crate) struct ResourceCheckpoint {
+    pub name: &'static str,
+    pub snapshot: fn(&mut Vec<u8>) -> io::Result<()>,
+    pub restore: fn(&[u8]) -> io::Result<()>,
+}
+
+fn snapshot_resource<T: Encode + Any>(buf: &mut Vec<u8>) -> io::Result<()> {
+    match get_resource::<T>() {
+        Some(res) => res.write_to(buf),
+        None => Err(resource_not_found::<T>()),
+    }
+}
+
+fn restore_resource<T: Decode + Any>(mut bytes: &[u8]) -> io::Result<()> {
+    match get_resource_mut::<T>() {
+        Some(mut res) => {
+            *res = T::read_from(&mut bytes)?;
+            Ok(())
+        }
+        None => Err(resource_not_found::<T>()),
+    }
+}
+
+fn resource_not_found<T>() -> io::Error {
+    let msg = format!("resource {} to checkpoint not found", std::any::type_name::<T>());
+    io::Error::new(io::ErrorKind::NotFound, msg)
+}
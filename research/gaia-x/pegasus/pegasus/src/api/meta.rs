@@ -15,6 +15,7 @@
 
 use std::sync::Arc;
 
+use crate::schedule::park::Parker;
 use crate::Tag;
 
 pub trait Priority: Send + Sync {
@@ -34,6 +35,8 @@ pub struct OperatorInfo {
     pub name: String,
     pub index: usize,
     pub scope_level: u32,
+    /// park the operator while it waits for an event out of the dataflow, see [`Parker`];
+    pub(crate) parker: Parker,
 }
 
 impl std::fmt::Debug for OperatorInfo {
@@ -44,6 +47,6 @@ impl std::fmt::Debug for OperatorInfo {
 
 impl OperatorInfo {
     pub fn new(name: &str, index: usize, scope_level: u32) -> Self {
-        OperatorInfo { name: name.to_owned(), index, scope_level }
+        OperatorInfo { name: name.to_owned(), index, scope_level, parker: Parker::default() }
     }
 }
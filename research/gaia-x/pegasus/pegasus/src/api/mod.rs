@@ -18,9 +18,11 @@ pub use iteration::{IterCondition, Iteration};
 pub use primitive::binary::Binary;
 pub use primitive::branch::Branch;
 pub use primitive::sink::{FromStream, Sink};
-pub use primitive::source::{IntoDataflow, Source};
+pub use primitive::source::{source_channel, IntoDataflow, Source, SourceReceiver, SourceSender};
 pub use primitive::unary::Unary;
 
+pub use crate::schedule::park::Parker;
+
 pub mod notification {
 
     use crate::progress::Weight;
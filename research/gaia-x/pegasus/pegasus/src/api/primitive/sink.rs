@@ -14,11 +14,30 @@
 //! limitations under the License.
 
 use crate::api::function::FnResult;
+use crate::schedule::park::Parker;
 use crate::BuildJobError;
 
 /// `FromStream` provides the capability to consume the data from the stream
 pub trait FromStream<D>: Send + 'static {
     fn on_next(&mut self, next: D) -> FnResult<()>;
+
+    /// Check if more data can be accepted, the stream is paused until it can if not, which applies
+    /// backpressure to the dataflow rather than buffering the data in the collector;
+    fn is_ready(&mut self) -> bool {
+        true
+    }
+
+    /// Unpark the stream paused for the collector not being ready, once it turns ready again. Return
+    /// `false` if the collector doesn't notify, the paused stream is then fired again as soon as
+    /// possible to check if it is ready;
+    fn notify_ready(&mut self, _parker: &Parker) -> bool {
+        false
+    }
+
+    /// Flush the data buffered by the collector, invoked each time the data in hand is consumed;
+    fn flush(&mut self) -> FnResult<()> {
+        Ok(())
+    }
 }
 
 /// `Sink` the final results for further processing.  
@@ -51,6 +70,10 @@ impl<D: Send + 'static> FromStream<D> for crossbeam_channel::Sender<D> {
         self.send(next)
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send>)
     }
+
+    fn is_ready(&mut self) -> bool {
+        !self.is_full()
+    }
 }
 
 impl<D: Send + 'static> FromStream<D> for std::sync::mpsc::Sender<D> {
@@ -13,9 +13,14 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::sync::{Arc, Mutex};
+
+use crossbeam_channel::{Receiver, SendError, Sender};
+
 use crate::communication::output::OutputBuilderImpl;
 use crate::dataflow::DataflowBuilder;
 use crate::errors::BuildJobError;
+use crate::schedule::park::Parker;
 use crate::stream::Stream;
 use crate::Data;
 
@@ -23,6 +28,80 @@ pub trait IntoDataflow<D: Data> {
     fn into_dataflow(self, entry: Stream<D>) -> Result<Stream<D>, BuildJobError>;
 }
 
+/// Create a channel which feeds an unbounded input of jobs, see [`Source::input_from_channel`].
+///
+/// The input ends after all senders of the channel are dropped.
+pub fn source_channel<D: Data>() -> (SourceSender<D>, SourceReceiver<D>) {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let parkers = Arc::new(Mutex::new(vec![]));
+    (SourceSender { tx: Some(tx), parkers: parkers.clone() }, SourceReceiver { rx, parkers })
+}
+
+/// The sending half of a [`source_channel`], it unparks the sources waiting on the channel after each
+/// item is sent, and after it is dropped;
+pub struct SourceSender<D> {
+    tx: Option<Sender<D>>,
+    parkers: Arc<Mutex<Vec<Parker>>>,
+}
+
+impl<D> SourceSender<D> {
+    pub fn send(&self, item: D) -> Result<(), SendError<D>> {
+        self.tx
+            .as_ref()
+            .expect("sender dropped;")
+            .send(item)?;
+        self.unpark();
+        Ok(())
+    }
+
+    fn unpark(&self) {
+        let parkers = self
+            .parkers
+            .lock()
+            .expect("lock poisoned;");
+        for p in parkers.iter() {
+            p.unpark();
+        }
+    }
+}
+
+impl<D> Clone for SourceSender<D> {
+    fn clone(&self) -> Self {
+        SourceSender { tx: self.tx.clone(), parkers: self.parkers.clone() }
+    }
+}
+
+impl<D> Drop for SourceSender<D> {
+    fn drop(&mut self) {
+        // drop the sender before unparking, so that the sources find the channel disconnected once
+        // the last sender is dropped;
+        self.tx.take();
+        self.unpark();
+    }
+}
+
+/// The receiving half of a [`source_channel`], it is cloned for each worker reading the channel;
+pub struct SourceReceiver<D> {
+    pub(crate) rx: Receiver<D>,
+    parkers: Arc<Mutex<Vec<Parker>>>,
+}
+
+impl<D> SourceReceiver<D> {
+    /// Register the parker of a source reading the channel, so that it is unparked by the senders;
+    pub(crate) fn register(&self, parker: Parker) {
+        self.parkers
+            .lock()
+            .expect("lock poisoned;")
+            .push(parker);
+    }
+}
+
+impl<D> Clone for SourceReceiver<D> {
+    fn clone(&self) -> Self {
+        SourceReceiver { rx: self.rx.clone(), parkers: self.parkers.clone() }
+    }
+}
+
 pub struct Source<D: Data> {
     output: OutputBuilderImpl<D>,
     dfb: DataflowBuilder,
@@ -43,4 +122,19 @@ impl<D: Data> Source<D> {
         let stream = Stream::create(output, &self.dfb);
         source.into_dataflow(stream)
     }
+
+    /// Read the input from a channel which is fed by the host, e.g. a stream of events, so the job keeps
+    /// running until all senders of the channel are dropped, and the items are sent into the dataflow
+    /// as soon as they arrive. The source is parked while the channel is empty, see [`source_channel`].
+    ///
+    /// The items of an unbounded input are usually grouped by windows before being aggregated,
+    /// see [`Window`].
+    ///
+    /// [`Window`]: crate::api::Window
+    pub fn input_from_channel(&mut self, rx: SourceReceiver<D>) -> Result<Stream<D>, BuildJobError> {
+        let output = self.output.copy_data();
+        let output = std::mem::replace(&mut self.output, output);
+        let stream = Stream::create(output, &self.dfb);
+        rx.into_dataflow(stream)
+    }
 }
@@ -42,6 +42,7 @@ pub struct Channel<T: Data> {
     batch_size: usize,
     batch_capacity: u32,
     scope_capacity: u32,
+    spill_threshold: Option<usize>,
     inbound_scope_level: u32,
     source: Port,
     scope_delta: MergedScopeDelta,
@@ -54,6 +55,7 @@ impl<T: Data> Clone for Channel<T> {
             batch_size: self.batch_size,
             batch_capacity: self.batch_capacity,
             scope_capacity: self.scope_capacity,
+            spill_threshold: self.spill_threshold,
             inbound_scope_level: self.inbound_scope_level,
             source: self.source,
             scope_delta: self.scope_delta.clone(),
@@ -68,6 +70,7 @@ impl<T: Data> Default for Channel<T> {
             batch_size: 1024,
             batch_capacity: 64,
             scope_capacity: 64,
+            spill_threshold: None,
             inbound_scope_level: 0,
             source: Port::new(0, 0),
             scope_delta: MergedScopeDelta::new(0),
@@ -96,6 +99,7 @@ impl<T: Data> Channel<T> {
             batch_capacity: port.get_batch_capacity(),
             inbound_scope_level: scope_level,
             scope_capacity: port.get_scope_capacity(),
+            spill_threshold: None,
             source: port.get_port(),
             scope_delta: MergedScopeDelta::new(scope_level as usize),
             kind: ChannelKind::Pipeline,
@@ -129,6 +133,16 @@ impl<T: Data> Channel<T> {
         self.scope_capacity
     }
 
+    /// Set the spill threshold of the blocking operator this channel flows into;
+    pub fn set_spill_threshold(&mut self, threshold: usize) -> &mut Self {
+        self.spill_threshold = Some(threshold);
+        self
+    }
+
+    pub fn get_spill_threshold(&self) -> Option<usize> {
+        self.spill_threshold
+    }
+
     pub fn set_channel_kind(&mut self, kind: ChannelKind<T>) -> &mut Self {
         self.kind = kind;
         self
@@ -205,6 +219,9 @@ mod rob {
             let mut pushes = Vec::with_capacity(raw.len());
             let source = dfb.worker_id.index;
             for (idx, p) in raw.into_iter().enumerate() {
+                if let Some(sent_bytes) = p.remote_sent_bytes() {
+                    dfb.add_remote_sent_bytes(id.index, sent_bytes);
+                }
                 let has_cycles = cyclic.clone();
                 let push = EventEmitPush::new(
                     ch_info,
@@ -362,6 +379,9 @@ mod rob {
             let mut pushes = Vec::with_capacity(raw.len());
             let source = dfb.worker_id.index;
             for (idx, p) in raw.into_iter().enumerate() {
+                if let Some(sent_bytes) = p.remote_sent_bytes() {
+                    dfb.add_remote_sent_bytes(id.index, sent_bytes);
+                }
                 let push = EventEmitPush::new(ch_info, source, idx as u32, p, dfb.event_emitter.clone());
                 pushes.push(push);
             }
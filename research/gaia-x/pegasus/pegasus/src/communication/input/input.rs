@@ -29,6 +29,7 @@ use crate::data_plane::{GeneralPull, Pull};
 use crate::errors::IOResult;
 use crate::event::emitter::EventEmitter;
 use crate::event::{Event, EventKind};
+use crate::profile::PortProfile;
 use crate::progress::EndSignal;
 use crate::tag::tools::map::TidyTagMap;
 use crate::{Data, Tag};
@@ -55,11 +56,15 @@ pub struct InputHandle<D: Data> {
     // scope skip manager:
     cancel: TidyTagMap<()>,
     parent_cancel: AHashSet<Tag>,
+    metrics: PortProfile,
+    // the records stashed now, which is only tracked if the job is profiled;
+    profile: bool,
+    buffered: u64,
 }
 
 impl<D: Data> InputHandle<D> {
     pub fn new(
-        ch_info: ChannelInfo, pull: GeneralPull<MicroBatch<D>>, event_emitter: EventEmitter,
+        ch_info: ChannelInfo, pull: GeneralPull<MicroBatch<D>>, event_emitter: EventEmitter, profile: bool,
     ) -> Self {
         let scope_level = ch_info.scope_level;
         InputHandle {
@@ -72,6 +77,9 @@ impl<D: Data> InputHandle<D> {
             event_emitter,
             cancel: TidyTagMap::new(scope_level),
             parent_cancel: AHashSet::new(),
+            metrics: PortProfile::default(),
+            profile,
+            buffered: 0,
         }
     }
 
@@ -102,6 +110,7 @@ impl<D: Data> InputHandle<D> {
                 if !stash.is_block() {
                     if let Some(dataset) = stash.pop_front() {
                         self.stash_index = stash_index;
+                        self.on_unstash(dataset.len());
                         return Ok(Some(dataset));
                     }
                 }
@@ -111,7 +120,8 @@ impl<D: Data> InputHandle<D> {
                 if let Some(stash) = self.stash_index.get_mut(&dataset.tag) {
                     if !stash.is_empty() || stash.is_block() {
                         //debug_worker!("stash data , len={}", dataset.len());
-                        stash.stash(dataset);
+                        let stashed = stash.stash(dataset);
+                        self.on_stash(stashed);
                         continue;
                     }
                 }
@@ -127,6 +137,7 @@ impl<D: Data> InputHandle<D> {
             if !stash.is_block() {
                 if let Some(data_set) = stash.pop_front() {
                     self.stash_index = stash_index;
+                    self.on_unstash(data_set.len());
                     return Ok(Some(data_set));
                 }
             } else {
@@ -168,18 +179,43 @@ impl<D: Data> InputHandle<D> {
     }
 
     fn stash_back(&mut self, dataset: MicroBatch<D>) {
-        if let Some(stash) = self.stash_index.get_mut(&dataset.tag) {
-            stash.stash(dataset);
+        let stashed = if let Some(stash) = self.stash_index.get_mut(&dataset.tag) {
+            stash.stash(dataset)
         } else {
             let mut stashes = StashedQueue::new();
             let tag = dataset.tag.clone();
+            let len = dataset.len();
             stashes.push_back(dataset);
             self.stash_index.insert(tag.clone(), stashes);
+            len
+        };
+        self.on_stash(stashed);
+    }
+
+    #[inline]
+    fn on_stash(&mut self, len: usize) {
+        if self.profile {
+            self.buffered += len as u64;
+            if self.buffered > self.metrics.peak_buffered {
+                self.metrics.peak_buffered = self.buffered;
+            }
         }
     }
 
+    #[inline]
+    fn on_unstash(&mut self, len: usize) {
+        if self.profile {
+            self.buffered = self.buffered.saturating_sub(len as u64);
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> PortProfile {
+        self.metrics.clone()
+    }
+
     pub(crate) fn stash_block_front(&mut self, dataset: MicroBatch<D>) -> InputBlockGuard {
         let tag = dataset.tag.clone();
+        self.on_stash(dataset.len());
         if let Some(queue) = self.stash_index.get_mut(&dataset.tag) {
             queue.push_front(dataset);
             if let Some(ref cnt) = queue.block_cnt {
@@ -202,7 +238,11 @@ impl<D: Data> InputHandle<D> {
     }
 
     pub(crate) fn end_on(&mut self, end: EndSignal) {
-        self.stash_index.remove(&end.tag);
+        if let Some(stash) = self.stash_index.remove(&end.tag) {
+            if self.profile {
+                self.on_unstash(stash.records());
+            }
+        }
         self.current_end.push_back(end);
     }
 
@@ -288,6 +328,10 @@ impl<D: Data> InputHandle<D> {
                                     );
                                 }
                             }
+                            if !batch.is_empty() {
+                                self.metrics.records += batch.len() as u64;
+                                self.metrics.batches += 1;
+                            }
                             return Ok(Some(batch));
                         }
                     }
@@ -317,12 +361,14 @@ impl<D: Data> InputHandle<D> {
                     tag
                 );
                 if let Some(stash) = self.stash_index.get_mut(tag) {
+                    let discarded = if self.profile { stash.records() } else { 0 };
                     stash.discard();
                     if !stash.is_exhaust() {
                         self.propagate_cancel(tag);
                     } else {
                         // upstream had finished producing data of the tag;
                     }
+                    self.on_unstash(discarded);
                 } else {
                     self.propagate_cancel(tag);
                 }
@@ -336,6 +382,9 @@ impl<D: Data> InputHandle<D> {
                     if tag.is_parent_of(&*child) {
                         trace_worker!("EARLY_STOP: channel[{}] cancel consume data of {:?} as it's parent scope {:?} been canceled;", self.ch_info.index(), child, tag);
                         self.cancel.insert((&*child).clone(), ());
+                        if self.profile {
+                            self.on_unstash(stash.records());
+                        }
                         stash.discard();
                         // todo: if need to propagate event of this child scope;
                     }
@@ -450,6 +499,10 @@ impl<D: Data> InputProxy for RefWrapInput<D> {
     fn cancel_scope(&self, tag: &Tag) {
         self.inbound.borrow_mut().cancel_scope(tag)
     }
+
+    fn metrics(&self) -> PortProfile {
+        self.inbound.borrow().metrics()
+    }
 }
 
 struct StashedQueue<D> {
@@ -480,7 +533,8 @@ impl<D> StashedQueue<D> {
             .unwrap_or(false)
     }
 
-    fn stash(&mut self, mut batch: MicroBatch<D>) {
+    /// Return the number of records stashed;
+    fn stash(&mut self, mut batch: MicroBatch<D>) -> usize {
         if batch.is_empty() {
             if let Some(end) = batch.take_end() {
                 if let Some(last) = self.queue.back_mut() {
@@ -490,13 +544,22 @@ impl<D> StashedQueue<D> {
                     self.queue.push_back(batch);
                 }
             }
+            0
         } else {
             if !self.skip {
-                self.queue.push_back(batch)
+                let len = batch.len();
+                self.queue.push_back(batch);
+                len
+            } else {
+                0
             }
         }
     }
 
+    fn records(&self) -> usize {
+        self.queue.iter().map(|b| b.len()).sum()
+    }
+
     fn discard(&mut self) {
         self.skip = true;
         let last = self.queue.pop_back();
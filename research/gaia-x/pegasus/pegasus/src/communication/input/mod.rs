@@ -20,6 +20,7 @@ use crate::data::MicroBatch;
 use crate::data_plane::GeneralPull;
 use crate::errors::IOResult;
 use crate::event::emitter::EventEmitter;
+use crate::profile::PortProfile;
 use crate::progress::EndSignal;
 use crate::{Data, Tag};
 
@@ -34,6 +35,9 @@ pub trait InputProxy: AsAny + Send {
     fn is_exhaust(&self) -> bool;
 
     fn cancel_scope(&self, tag: &Tag);
+
+    /// Records and batches received by this input so far;
+    fn metrics(&self) -> PortProfile;
 }
 
 mod input;
@@ -45,9 +49,9 @@ pub use session::InputSession;
 
 #[inline]
 pub(crate) fn new_input<D: Data>(
-    ch_info: ChannelInfo, pull: GeneralPull<MicroBatch<D>>, event_emitter: &EventEmitter,
+    ch_info: ChannelInfo, pull: GeneralPull<MicroBatch<D>>, event_emitter: &EventEmitter, profile: bool,
 ) -> Box<dyn InputProxy> {
-    let input = InputHandle::new(ch_info, pull, event_emitter.clone());
+    let input = InputHandle::new(ch_info, pull, event_emitter.clone(), profile);
     Box::new(RefWrapInput::wrap(input)) as Box<dyn InputProxy>
 }
 
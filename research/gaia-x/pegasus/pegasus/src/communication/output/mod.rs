@@ -26,6 +26,7 @@ use crate::communication::decorator::ScopeStreamPush;
 use crate::communication::output::output::OutputHandle;
 use crate::data::MicroBatch;
 use crate::errors::IOResult;
+use crate::profile::PortProfile;
 use crate::progress::EndSignal;
 use crate::schedule::state::outbound::OutputCancelState;
 use crate::{Data, Tag};
@@ -82,6 +83,9 @@ pub trait OutputProxy: AsAny + Send {
 
     /// Check if this output has been closed;
     fn is_closed(&self) -> bool;
+
+    /// Records and batches sent by this output so far;
+    fn metrics(&self) -> PortProfile;
 }
 
 pub trait OutputBuilder: AsAny {
@@ -182,6 +186,10 @@ impl<D: Data> OutputProxy for RefWrapOutput<D> {
     fn is_closed(&self) -> bool {
         self.output.borrow().is_closed()
     }
+
+    fn metrics(&self) -> PortProfile {
+        self.output.borrow().metrics()
+    }
 }
 
 #[inline(always)]
@@ -29,6 +29,7 @@ mod rob {
     use crate::data::MicroBatch;
     use crate::errors::IOResult;
     use crate::graph::Port;
+    use crate::profile::PortProfile;
     use crate::progress::EndSignal;
     use crate::tag::tools::map::TidyTagMap;
     use crate::{Data, Tag};
@@ -43,6 +44,7 @@ mod rob {
         is_closed: bool,
         current_canceled: TidyTagMap<()>,
         parent_canceled: AHashSet<Tag>,
+        metrics: PortProfile,
     }
 
     impl<D: Data> OutputHandle<D> {
@@ -60,11 +62,16 @@ mod rob {
                 is_closed: false,
                 current_canceled: TidyTagMap::new(scope_level),
                 parent_canceled: AHashSet::new(),
+                metrics: PortProfile::default(),
             }
         }
 
         pub fn push_batch(&mut self, dataset: MicroBatch<D>) -> IOResult<()> {
             if !self.is_canceled(&dataset.tag) {
+                if !dataset.is_empty() {
+                    self.metrics.records += dataset.len() as u64;
+                    self.metrics.batches += 1;
+                }
                 self.tee.forward(dataset)
             } else {
                 Ok(())
@@ -215,6 +222,10 @@ mod rob {
             false
         }
 
+        pub fn metrics(&self) -> PortProfile {
+            self.metrics.clone()
+        }
+
         #[inline]
         pub fn is_closed(&self) -> bool {
             self.is_closed
@@ -230,6 +241,7 @@ mod rob {
         #[inline]
         fn push(&mut self, tag: &Tag, msg: D) -> IOResult<()> {
             if !self.is_canceled(tag) {
+                self.metrics.records += 1;
                 self.tee.push(tag, msg)?;
             }
             Ok(())
@@ -237,13 +249,19 @@ mod rob {
 
         #[inline]
         fn push_last(&mut self, msg: D, end: EndSignal) -> IOResult<()> {
+            self.metrics.records += 1;
             self.tee.push_last(msg, end)
         }
 
         #[inline]
         fn try_push_iter<I: Iterator<Item = D>>(&mut self, tag: &Tag, iter: &mut I) -> IOResult<()> {
             if !self.is_canceled(tag) {
-                self.tee.try_push_iter(tag, iter)?;
+                let mut count = 0;
+                let result = self
+                    .tee
+                    .try_push_iter(tag, &mut iter.inspect(|_| count += 1));
+                self.metrics.records += count;
+                result?;
             }
             Ok(())
         }
@@ -325,6 +343,7 @@ mod rob {
     use crate::data_plane::Push;
     use crate::errors::IOError;
     use crate::graph::Port;
+    use crate::profile::PortProfile;
     use crate::progress::EndSignal;
     use crate::tag::tools::map::TidyTagMap;
     use crate::{Data, Tag};
@@ -347,6 +366,7 @@ mod rob {
         is_closed: bool,
         current_skips: TidyTagMap<()>,
         parent_skips: TidyTagMap<()>,
+        metrics: PortProfile,
     }
 
     impl<D: Data> OutputHandle<D> {
@@ -370,6 +390,7 @@ mod rob {
                 is_closed: false,
                 current_skips: TidyTagMap::new(scope_level),
                 parent_skips: TidyTagMap::new(parent_level),
+                metrics: PortProfile::default(),
             }
         }
 
@@ -409,6 +430,10 @@ mod rob {
             if self.is_skipped(&batch.tag) {
                 Ok(())
             } else {
+                if !batch.is_empty() {
+                    self.metrics.records += batch.len() as u64;
+                    self.metrics.batches += 1;
+                }
                 self.tee.push(batch)
             }
         }
@@ -423,6 +448,10 @@ mod rob {
             }
         }
 
+        pub fn metrics(&self) -> PortProfile {
+            self.metrics.clone()
+        }
+
         pub fn is_closed(&self) -> bool {
             self.is_closed
         }
@@ -606,6 +635,10 @@ mod rob {
                 *seq += 1;
             }
 
+            if !batch.is_empty() {
+                self.metrics.records += batch.len() as u64;
+                self.metrics.batches += 1;
+            }
             let tag = batch.tag();
             if batch.is_last() {
                 trace_worker!("output[{:?}] push last batch(len={}) of {:?} ;", self.port, batch.len(), tag)
@@ -72,8 +72,14 @@ lazy_static! {
     pub static ref LOOP_OPT: bool = configure_with_default!(bool, "LOOP_OPT", true);
     /// set `true` to enable immediately cleaning the data of ports received signals from all workers
     pub static ref BRANCH_OPT: bool = configure_with_default!(bool, "BRANCH_OPT", true);
+    /// the directory where the blocking operators spill their data to;
+    pub static ref SPILL_DIR: String = configure_with_default!(String, "SPILL_DIR", std::env::temp_dir().to_string_lossy().to_string());
 }
 
+/// The least records the blocking operators hold in memory for each scope before spilling, a smaller
+/// spill threshold is raised to it, or else the spill files of a scope would exhaust file descriptors;
+pub(crate) const MIN_SPILL_RUN: usize = 1024;
+
 #[derive(Debug, Clone)]
 pub enum ServerConf {
     Local,
@@ -99,6 +105,16 @@ impl ServerConf {
     }
 }
 
+/// The strategy to decide the order of firing operators in each schedule step;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleStrategy {
+    /// fire operators from the sources to the sinks, pushing data downstream as far as possible;
+    Waterfall,
+    /// fire operators from the sinks to the sources, an operator is fired only if none of its
+    /// followers is blocked by its outputs, which bounds the data in flight by the output capacities;
+    Volcano,
+}
+
 #[derive(Debug, Clone)]
 pub struct JobConf {
     /// unique identifier of the job;
@@ -115,7 +131,8 @@ pub struct JobConf {
     pub batch_capacity: u32,
     ///
     pub scope_capacity: u32,
-    /// the most memory(MB) this job can use in each server;
+    /// the most memory(MB) this job can use in each server, which is only supported with the feature
+    /// `mem`, a job setting it is rejected otherwise;
     pub memory_limit: u32,
     /// set to print runtime dataflow plan before running;
     pub plan_print: bool,
@@ -123,6 +140,26 @@ pub struct JobConf {
     servers: ServerConf,
     /// set enable trace job run progress;
     pub trace_enable: bool,
+    /// the strategy used to fire operators in each schedule step;
+    pub strategy: ScheduleStrategy,
+    /// set to collect execution metrics of operators, which are merged into a profile at job end;
+    pub profile: bool,
+    /// the most records a blocking operator(e.g. sort, join) holds in memory for each scope, the
+    /// records beyond it would be spilled into local files under `SPILL_DIR`. It is the default of all
+    /// operators in the job, which can be overridden by `Stream::set_spill_threshold`, and is never less
+    /// than `MIN_SPILL_RUN`;
+    pub spill_threshold: u64,
+    /// the directory where the iterations write checkpoints at the end of each iteration, no
+    /// checkpoint is written if unset. The checkpoints hold the data entering and leaving the
+    /// iterations and the resources given by `IterCondition::checkpoint_resource`, the state of
+    /// operators is not checkpointed, so the job is rejected if the body of any checkpointed iteration
+    /// has operators keeping state across iterations. Each worker writes its own checkpoints, the
+    /// directory is not required to be shared by the servers;
+    pub checkpoint_dir: Option<String>,
+    /// set to resume the iterations from the latest checkpoints under `checkpoint_dir` written by all
+    /// workers in a previous run of the job with the same `job_id`, which is agreed by the workers
+    /// before the input enters the iterations;
+    pub resume: bool,
     /// optimization factors of early-stop
     pub debug: bool,
 }
@@ -182,6 +219,11 @@ impl Default for JobConf {
             plan_print: false,
             servers: ServerConf::Local,
             trace_enable: false,
+            strategy: ScheduleStrategy::Waterfall,
+            profile: false,
+            spill_threshold: !0,
+            checkpoint_dir: None,
+            resume: false,
             debug: false,
         }
     }
@@ -13,6 +13,9 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
 use pegasus_network::{IPCReceiver, IPCSender};
 
 use crate::channel_id::ChannelId;
@@ -24,11 +27,15 @@ use crate::Data;
 pub struct RemotePush<T: Data> {
     pub id: ChannelId,
     push: IPCSender<T>,
+    sent_bytes: Arc<AtomicU64>,
 }
 
 impl<T: Data> Push<T> for RemotePush<T> {
     fn push(&mut self, msg: T) -> Result<(), IOError> {
-        Ok(self.push.send(&msg)?)
+        let size = self.push.send(&msg)?;
+        self.sent_bytes
+            .fetch_add(size as u64, Ordering::Relaxed);
+        Ok(())
     }
 
     fn check_failed(&mut self) -> Option<T> {
@@ -46,7 +53,12 @@ impl<T: Data> Push<T> for RemotePush<T> {
 
 impl<T: Data> RemotePush<T> {
     pub fn new(id: ChannelId, push: IPCSender<T>) -> Self {
-        RemotePush { id, push }
+        RemotePush { id, push, sent_bytes: Arc::new(AtomicU64::new(0)) }
+    }
+
+    /// The counter of bytes sent by this push to the remote server;
+    pub fn sent_bytes(&self) -> &Arc<AtomicU64> {
+        &self.sent_bytes
     }
 }
 
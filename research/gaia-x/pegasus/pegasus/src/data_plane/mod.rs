@@ -14,6 +14,8 @@
 //! limitations under the License.
 
 use std::collections::LinkedList;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
 
 use pegasus_common::channel::MPMCSender;
 use pegasus_network::{IPCReceiver, IPCSender};
@@ -122,6 +124,14 @@ impl<T: Data> GeneralPush<T> {
             _ => true,
         }
     }
+
+    /// The counter of bytes sent to the remote server, `None` if the push is local;
+    pub fn remote_sent_bytes(&self) -> Option<Arc<AtomicU64>> {
+        match self {
+            GeneralPush::InterProcesses(p) => Some(p.sent_bytes().clone()),
+            _ => None,
+        }
+    }
 }
 
 #[enum_dispatch(Pull<T>)]
@@ -430,7 +440,7 @@ mod test {
                 println!("start server 1");
                 pegasus_network::start_up(1, ConnectionParams::nonblocking(), "127.0.0.1:2334", servers)
                     .unwrap();
-                while !pegasus_network::check_ipc_ready(0, &vec![0, 1]) {
+                while !pegasus_network::check_ipc_ready(1, &vec![0, 1]) {
                     std::thread::sleep(std::time::Duration::from_secs(1));
                 }
                 let server_conf = ServerConf::Partial(vec![0, 1]);
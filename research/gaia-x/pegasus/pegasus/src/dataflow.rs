@@ -18,6 +18,7 @@ use std::collections::VecDeque;
 use std::fmt::Write;
 use std::fs::File;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use crate::api::meta::OperatorInfo;
@@ -28,7 +29,10 @@ use crate::data_plane::{GeneralPull, GeneralPush};
 use crate::errors::{BuildJobError, IOResult, JobExecError};
 use crate::event::emitter::EventEmitter;
 use crate::graph::{Dependency, DotGraph, Edge, Port};
-use crate::operator::{GeneralOperator, NotifiableOperator, Operator, OperatorBuilder, OperatorCore};
+use crate::operator::{
+    GeneralOperator, NotifiableOperator, Operator, OperatorBuilder, OperatorCore, OperatorState,
+};
+use crate::profile::{ExchangeProfile, JobProfile, OperatorProfile};
 use crate::schedule::Schedule;
 use crate::{Data, JobConf, Tag, WorkerId};
 
@@ -40,6 +44,7 @@ pub struct DataflowBuilder {
     operators: Rc<RefCell<Vec<OperatorBuilder>>>,
     edges: Rc<RefCell<Vec<Edge>>>,
     sinks: Rc<RefCell<Vec<usize>>>,
+    remote_sent_bytes: Rc<RefCell<Vec<(u32, Arc<AtomicU64>)>>>,
 }
 
 impl DataflowBuilder {
@@ -52,6 +57,7 @@ impl DataflowBuilder {
             event_emitter,
             ch_index: Rc::new(RefCell::new(1)),
             sinks: Rc::new(RefCell::new(vec![])),
+            remote_sent_bytes: Rc::new(RefCell::new(vec![])),
         }
     }
 
@@ -101,6 +107,20 @@ impl DataflowBuilder {
         OperatorRef::new(index, self.operators.clone(), self.config.clone())
     }
 
+    pub(crate) fn set_operator_state(&self, index: usize, state: OperatorState) {
+        self.operators.borrow_mut()[index - 1].set_state(state);
+    }
+
+    /// The operators added after the operator at `index` which may keep state across scopes;
+    pub(crate) fn stateful_operators_after(&self, index: usize) -> Vec<OperatorInfo> {
+        self.operators
+            .borrow()
+            .iter()
+            .filter(|op| op.index() > index && op.get_state() == OperatorState::Unknown)
+            .map(|op| op.info.clone())
+            .collect()
+    }
+
     pub(crate) fn add_edge(&self, edge: Edge) {
         self.edges.borrow_mut().push(edge);
     }
@@ -109,6 +129,16 @@ impl DataflowBuilder {
         self.sinks.borrow_mut().push(index);
     }
 
+    /// Register the counter of bytes sent to remote servers through the channel, which would be
+    /// reported in the job profile;
+    pub(crate) fn add_remote_sent_bytes(&self, ch_index: u32, sent_bytes: Arc<AtomicU64>) {
+        if self.config.profile {
+            self.remote_sent_bytes
+                .borrow_mut()
+                .push((ch_index, sent_bytes));
+        }
+    }
+
     pub(crate) fn build(self, sch: &mut Schedule) -> Result<Dataflow, BuildJobError> {
         let report = self.worker_id.index == 0 && (self.config.plan_print || self.config.trace_enable);
         let mut plan_desc = String::new();
@@ -167,11 +197,14 @@ impl DataflowBuilder {
             }
         }
 
+        let remote_sent_bytes = self.remote_sent_bytes.replace(vec![]);
         Ok(Dataflow {
             worker_id: self.worker_id,
             operators: RefCell::new(operators),
             conf: self.config,
             depends,
+            profiles: RefCell::new(vec![]),
+            remote_sent_bytes,
         })
     }
 }
@@ -186,6 +219,7 @@ impl Clone for DataflowBuilder {
             ch_index: self.ch_index.clone(),
             edges: self.edges.clone(),
             sinks: self.sinks.clone(),
+            remote_sent_bytes: self.remote_sent_bytes.clone(),
         }
     }
 }
@@ -215,7 +249,7 @@ impl OperatorRef {
         notify: Option<GeneralPush<MicroBatch<T>>>, event_emitter: &EventEmitter,
     ) {
         let mut b = self.borrow.borrow_mut();
-        b[self.index - 1].add_input(ch_info, pull, notify, event_emitter)
+        b[self.index - 1].add_input(ch_info, pull, notify, event_emitter, self.conf.profile)
     }
 
     pub fn new_output<D: Data>(&self) -> OutputBuilderImpl<D> {
@@ -232,6 +266,8 @@ pub struct Dataflow {
     pub worker_id: WorkerId,
     operators: RefCell<Vec<Option<Operator>>>,
     depends: Dependency,
+    profiles: RefCell<Vec<OperatorProfile>>,
+    remote_sent_bytes: Vec<(u32, Arc<AtomicU64>)>,
 }
 
 impl Dataflow {
@@ -251,6 +287,7 @@ impl Dataflow {
                     let result = op.fire();
                     if op.is_finished() {
                         op.close();
+                        self.record_profile(&op);
                         // debug_worker!("operator {:?} finished;", op.meta);
                     } else {
                         *op_opt = Some(op);
@@ -264,6 +301,17 @@ impl Dataflow {
         Ok(false)
     }
 
+    /// Check if the operator at `index` has output data blocked, the finished operators are never
+    /// blocked;
+    pub fn is_blocked(&self, index: usize) -> bool {
+        let operators = self.operators.borrow();
+        operators
+            .get(index)
+            .and_then(|op| op.as_ref())
+            .map(|op| op.has_blocked_outputs())
+            .unwrap_or(false)
+    }
+
     pub fn is_idle(&self) -> IOResult<bool> {
         let operators = self.operators.borrow();
         for op in operators.iter() {
@@ -282,6 +330,7 @@ impl Dataflow {
             if let Some(op) = op_opt.take() {
                 if op.is_finished() {
                     op.close();
+                    self.record_profile(&op);
                     // debug_worker!("operator {:?} finished;", op.meta);
                 } else {
                     debug_worker!("operator {:?} is unfinished;", op.info);
@@ -296,6 +345,36 @@ impl Dataflow {
         true
     }
 
+    #[inline]
+    fn record_profile(&self, op: &Operator) {
+        if self.conf.profile {
+            self.profiles.borrow_mut().push(op.profile());
+        }
+    }
+
+    /// Collect the profile of this worker, in which only the finished operators are present;
+    pub fn profile(&self, steps: u64) -> JobProfile {
+        let mut profile = JobProfile::new(self.conf.job_id);
+        profile.workers = 1;
+        profile.steps = steps;
+        let mut operators = self.profiles.replace(vec![]);
+        operators.sort_by_key(|op| op.index);
+        profile.operators = operators;
+        for (channel, sent_bytes) in self.remote_sent_bytes.iter() {
+            let bytes = sent_bytes.load(Ordering::Relaxed);
+            match profile
+                .exchanges
+                .binary_search_by_key(channel, |e| e.channel)
+            {
+                Ok(i) => profile.exchanges[i].bytes += bytes,
+                Err(i) => profile
+                    .exchanges
+                    .insert(i, ExchangeProfile { channel: *channel, bytes }),
+            }
+        }
+        profile
+    }
+
     pub fn try_cancel(
         &self, index: usize, discards: &mut VecDeque<(Port, Tag)>,
     ) -> Result<(), JobExecError> {
@@ -33,6 +33,10 @@ pub enum ErrorKind {
     Interrupted,
     IOError,
     IllegalScopeInput,
+    /// the job exceeds its memory or time limit, and is aborted on all workers;
+    ExceedLimit,
+    /// the job is canceled by the user, and is stopped on all workers;
+    Canceled,
     Others,
 }
 
@@ -43,6 +47,8 @@ impl Debug for ErrorKind {
             ErrorKind::Interrupted => write!(f, "Interrupted, retry later"),
             ErrorKind::IOError => write!(f, "IOError"),
             ErrorKind::IllegalScopeInput => write!(f, "IllegalScopeInput"),
+            ErrorKind::ExceedLimit => write!(f, "ExceedLimit"),
+            ErrorKind::Canceled => write!(f, "Canceled"),
             ErrorKind::Others => write!(f, "Unknown"),
         }
     }
@@ -64,6 +70,16 @@ impl JobExecError {
         JobExecError { kind: ErrorKind::Others, is_system: false, cause: err as Box<dyn Error + Send> }
     }
 
+    pub fn exceed_limit(msg: String) -> Self {
+        let err: Box<dyn Error + Send + Sync> = msg.into();
+        JobExecError { kind: ErrorKind::ExceedLimit, is_system: true, cause: err as Box<dyn Error + Send> }
+    }
+
+    pub fn canceled(msg: String) -> Self {
+        let err: Box<dyn Error + Send + Sync> = msg.into();
+        JobExecError { kind: ErrorKind::Canceled, is_system: true, cause: err as Box<dyn Error + Send> }
+    }
+
     pub(crate) fn from_box(err: Box<dyn Error + Send>) -> Self {
         if let Some(e) = err.downcast_ref::<JobExecError>() {
             JobExecError { kind: e.kind.clone(), is_system: e.is_system, cause: err }
@@ -26,6 +26,10 @@ pub enum EventKind {
     /// hint to cancel producing data of scope to channel;
     /// Cancel( (channel index,  scope tag) )
     Cancel((u32, Tag)),
+    /// abort the whole job on all workers, with the reason why it is aborted;
+    Abort(String),
+    /// cancel the whole job on all workers, as the user requested;
+    CancelJob,
 }
 
 #[derive(Debug, Clone)]
@@ -40,6 +44,10 @@ impl Event {
         Event { from_worker: worker, target_port: target, kind }
     }
 
+    pub fn kind(&self) -> &EventKind {
+        &self.kind
+    }
+
     pub fn take_kind(self) -> EventKind {
         self.kind
     }
@@ -60,6 +68,13 @@ impl Encode for Event {
                 writer.write_u32(*ch)?;
                 tag.write_to(writer)?;
             }
+            EventKind::Abort(reason) => {
+                writer.write_u8(2)?;
+                reason.write_to(writer)?;
+            }
+            EventKind::CancelJob => {
+                writer.write_u8(3)?;
+            }
         }
         Ok(())
     }
@@ -82,6 +97,11 @@ impl Decode for Event {
                 let tag = Tag::read_from(reader)?;
                 EventKind::Cancel((ch, tag))
             }
+            2 => {
+                let reason = String::read_from(reader)?;
+                EventKind::Abort(reason)
+            }
+            3 => EventKind::CancelJob,
             _ => unreachable!("unrecognized event;"),
         };
         Ok(Event { from_worker, target_port, kind })
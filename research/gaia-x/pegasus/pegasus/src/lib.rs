@@ -23,7 +23,6 @@ extern crate enum_dispatch;
 extern crate pegasus_common;
 
 use std::cell::Cell;
-use std::sync::atomic::AtomicUsize;
 use std::sync::{Arc, Mutex, RwLock};
 
 mod config;
@@ -43,6 +42,7 @@ mod data_plane;
 pub mod dataflow;
 mod event;
 mod operator;
+pub mod profile;
 pub(crate) mod progress;
 pub mod resource;
 pub mod result;
@@ -54,7 +54,7 @@ mod worker;
 use std::collections::HashSet;
 use std::fmt::Debug;
 
-pub use config::{read_from, Configuration, JobConf, ServerConf};
+pub use config::{read_from, Configuration, JobConf, ScheduleStrategy, ServerConf};
 pub use data::Data;
 pub use pegasus_common::codec;
 pub use pegasus_memory::alloc::check_current_task_memory;
@@ -151,7 +151,7 @@ pub fn startup(conf: Configuration) -> Result<(), StartupError> {
     if let Some(net_conf) = conf.network_config() {
         if let Some(peers) = net_conf.get_peers()? {
             let addr = net_conf.local_addr()?;
-            let conn_conf = net_conf.get_connection_param();
+            let conn_conf = net_conf.get_connection_param()?;
             for p in peers.iter() {
                 servers.insert(p.id);
             }
@@ -181,7 +181,7 @@ pub fn startup_with<D: ServerDetect + 'static>(
     }
     let res = if let Some(net_conf) = conf.network_config() {
         let addr = net_conf.local_addr()?;
-        let conn_conf = net_conf.get_connection_param();
+        let conn_conf = net_conf.get_connection_param()?;
         let addr = pegasus_network::start_up(server_id, conn_conf, addr, detect)?;
         info!("server {} start on {:?}", server_id, addr);
         Some(addr)
@@ -215,7 +215,8 @@ where
     let (tx, rx) = crossbeam_channel::unbounded();
     let sink = ResultSink::new(tx);
     let cancel_hook = sink.get_cancel_hook().clone();
-    let results = ResultStream::new(conf.job_id, cancel_hook, rx);
+    let mut results = ResultStream::new(conf.job_id, cancel_hook, rx);
+    results.set_profile_hook(sink.get_profile_hook().clone());
     run_opt(conf, sink, |worker| worker.dataflow(func()))?;
     Ok(results)
 }
@@ -233,7 +234,8 @@ where
     let (tx, rx) = crossbeam_channel::unbounded();
     let sink = ResultSink::new(tx);
     let cancel_hook = sink.get_cancel_hook().clone();
-    let results = ResultStream::new(conf.job_id, cancel_hook, rx);
+    let mut results = ResultStream::new(conf.job_id, cancel_hook, rx);
+    results.set_profile_hook(sink.get_profile_hook().clone());
     run_opt(conf, sink, |worker| {
         let index = worker.id.index as usize;
         if let Some(r) = resource.take_partition_of(index) {
@@ -251,7 +253,12 @@ where
     F: FnMut(&mut Worker<DI, DO>) -> Result<(), BuildJobError>,
 {
     init_singleton();
-    let peer_guard = Arc::new(AtomicUsize::new(0));
+    // the memory of jobs is only tracked with the feature `mem`, otherwise the limit can't be enforced;
+    if !cfg!(feature = "mem") && conf.memory_limit != !0u32 {
+        let msg = format!("memory limit of job {} requires the feature `mem`;", conf.job_id);
+        Err(BuildJobError::Unsupported(msg))?
+    }
+    let peer_guard = sink.get_running_hook().clone();
     let conf = Arc::new(conf);
     let workers = allocate_local_worker(&conf)?;
     if workers.is_none() {
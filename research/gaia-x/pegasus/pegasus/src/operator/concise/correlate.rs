@@ -182,11 +182,11 @@ impl<D: Data> CorrelatedSubTask<D> for Stream<D> {
 }
 
 #[cfg(not(feature = "rob"))]
-fn new_batch<D>(tag: Tag, worker: u32, buf: Buffer<D>) -> MicroBatch<D> {
+pub(super) fn new_batch<D>(tag: Tag, worker: u32, buf: Buffer<D>) -> MicroBatch<D> {
     MicroBatch::new(tag.clone(), worker, 0, buf)
 }
 
 #[cfg(feature = "rob")]
-fn new_batch<D>(tag: Tag, worker: u32, buf: Buffer<D>) -> MicroBatch<D> {
+pub(super) fn new_batch<D>(tag: Tag, worker: u32, buf: Buffer<D>) -> MicroBatch<D> {
     MicroBatch::new(tag.clone(), worker, buf.into_read_only())
 }
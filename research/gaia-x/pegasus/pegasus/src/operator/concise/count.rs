@@ -24,6 +24,7 @@ impl<D: Data> Count<D> for Stream<D> {
                     })
                 }
             })?
+            .scope_local()
             .aggregate()
             .unary("count_global", |info| {
                 let mut table = TidyTagMap::<u64>::new(info.scope_level);
@@ -59,6 +60,6 @@ impl<D: Data> Count<D> for Stream<D> {
                 }
             })?;
 
-        Ok(SingleItem::new(stream))
+        Ok(SingleItem::new(stream.scope_local()))
     }
 }
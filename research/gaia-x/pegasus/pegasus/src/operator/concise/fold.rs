@@ -1,9 +1,15 @@
 use std::fmt::Debug;
 
+use ahash::AHashMap;
+
 use crate::api::function::FnResult;
 use crate::api::{Fold, Unary};
+use crate::data::MicroBatch;
+use crate::errors::JobExecError;
+use crate::operator::concise::spill::SpillFile;
 use crate::stream::{Single, SingleItem, Stream};
 use crate::tag::tools::map::TidyTagMap;
+use crate::tag::Tag;
 use crate::{BuildJobError, Data};
 
 impl<D: Data> Fold<D> for Stream<D> {
@@ -13,30 +19,22 @@ impl<D: Data> Fold<D> for Stream<D> {
         F: FnMut(B, D) -> FnResult<B> + Send + 'static,
         C: Fn() -> F + Send + 'static,
     {
+        let threshold = self.spill_threshold();
         let s = self.unary("fold_partition", |info| {
-            let mut table = TidyTagMap::<(B, F)>::new(info.scope_level);
+            let name = format!("fold_partition_{}", info.index);
+            let mut table = FoldTable::<D, B, F>::new(info.scope_level, threshold, name);
             move |input, output| {
                 input.for_each_batch(|dataset| {
-                    let (mut accum, mut f) = table
-                        .remove(&dataset.tag)
-                        .unwrap_or((init.clone(), factory()));
-
-                    for d in dataset.drain() {
-                        accum = f(accum, d)?;
-                    }
-
-                    if dataset.is_last() {
+                    if let Some(accum) = table.fold(dataset, &init, &factory)? {
                         let mut session = output.new_session(&dataset.tag)?;
                         let end = dataset.take_end().expect("unreachable");
                         session.give_last(Single(accum), end)?;
-                    } else {
-                        table.insert(dataset.tag.clone(), (accum, f));
                     }
                     Ok(())
                 })
             }
         })?;
-        Ok(SingleItem::new(s))
+        Ok(SingleItem::new(s.scope_local()))
     }
 
     fn fold<B, F, C>(self, init: B, factory: C) -> Result<SingleItem<B>, BuildJobError>
@@ -45,37 +43,29 @@ impl<D: Data> Fold<D> for Stream<D> {
         F: FnMut(B, D) -> FnResult<B> + Send + 'static,
         C: Fn() -> F + Send + 'static,
     {
+        let threshold = self.spill_threshold();
         let s = self.aggregate().unary("fold", |info| {
-            let mut table = TidyTagMap::<(B, F)>::new(info.scope_level);
+            let name = format!("fold_{}", info.index);
+            let mut table = FoldTable::<D, B, F>::new(info.scope_level, threshold, name);
             let worker_id = crate::worker_id::get_current_worker();
             let index = worker_id.index;
             let peers = worker_id.total_peers();
             move |input, output| {
                 input.for_each_batch(|dataset| {
                     if !dataset.is_empty() {
-                        let (mut accum, mut f) = table
-                            .remove(&dataset.tag)
-                            .unwrap_or((init.clone(), factory()));
-
-                        for d in dataset.drain() {
-                            accum = f(accum, d)?;
-                        }
-
-                        if dataset.is_last() {
+                        if let Some(accum) = table.fold(dataset, &init, &factory)? {
                             let mut session = output.new_session(&dataset.tag)?;
                             let end = dataset.take_end().expect("unreachable");
                             trace_worker!("fold all data and emit result of {:?} ;", dataset.tag);
                             session.give_last(Single(accum), end)?;
-                        } else {
-                            table.insert(dataset.tag.clone(), (accum, f));
                         }
                     } else if dataset.is_last() {
                         // dataset empty and is last;
+                        let accum = table
+                            .fold(dataset, &init, &factory)?
+                            .expect("unreachable");
                         let mut session = output.new_session(&dataset.tag)?;
                         let end = dataset.take_end().expect("unreachable");
-                        let (accum, _) = table
-                            .remove(&dataset.tag)
-                            .unwrap_or((init.clone(), factory()));
                         let flag = if dataset.tag.len() == 0 {
                             index == 0
                         } else {
@@ -92,6 +82,122 @@ impl<D: Data> Fold<D> for Stream<D> {
                 })
             }
         })?;
-        Ok(SingleItem::new(s))
+        Ok(SingleItem::new(s.scope_local()))
+    }
+}
+
+/// The accumulators of the scopes being folded, one for each scope. At most `threshold` records are
+/// folded into the accumulators in memory, as an accumulator may hold all records it folds(e.g. by
+/// `collect`). Once the threshold is reached, the later records of a scope are spilled into a file of
+/// the scope, which is folded into its accumulator when the scope ends;
+struct FoldTable<D: Data, B, F> {
+    threshold: usize,
+    name: String,
+    /// the accumulator of each scope, with the number of records folded into it;
+    accums: TidyTagMap<(B, F, usize)>,
+    /// the number of records folded into all the accumulators;
+    folded: usize,
+    spilled: AHashMap<Tag, SpillFile<D>>,
+}
+
+impl<D: Data, B: Clone, F: FnMut(B, D) -> FnResult<B>> FoldTable<D, B, F> {
+    fn new(scope_level: u32, threshold: usize, name: String) -> Self {
+        FoldTable { threshold, name, accums: TidyTagMap::new(scope_level), folded: 0, spilled: AHashMap::new() }
+    }
+
+    /// Fold the records of a scope, return the accumulator of the scope if it is the last batch;
+    fn fold<C: Fn() -> F>(
+        &mut self, dataset: &mut MicroBatch<D>, init: &B, factory: &C,
+    ) -> Result<Option<B>, JobExecError> {
+        let tag = dataset.tag.clone();
+        let (mut accum, mut f, mut count) = self
+            .accums
+            .remove(&tag)
+            .unwrap_or_else(|| (init.clone(), factory(), 0));
+        // the last batch is folded in memory anyway, as the accumulator is given out right after;
+        if !dataset.is_last()
+            && !self.spilled.contains_key(&tag)
+            && self.folded + dataset.len() > self.threshold
+        {
+            trace_worker!("spill records of {:?} as {} records are folded;", tag, self.folded);
+            self.spilled
+                .insert(tag.clone(), SpillFile::create(&self.name)?);
+        }
+        if let Some(file) = self.spilled.get_mut(&tag) {
+            for d in dataset.drain() {
+                file.write(&d)?;
+            }
+        } else {
+            for d in dataset.drain() {
+                accum = f(accum, d)?;
+                count += 1;
+                self.folded += 1;
+            }
+        }
+        if dataset.is_last() {
+            self.folded -= count;
+            if let Some(file) = self.spilled.remove(&tag) {
+                for item in file.into_reader()? {
+                    accum = f(accum, item?)?;
+                }
+            }
+            Ok(Some(accum))
+        } else {
+            self.accums.insert(tag, (accum, f, count));
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "rob")))]
+mod test {
+    use pegasus_common::buffer::Batch;
+
+    use super::*;
+    use crate::progress::{EndSignal, Weight};
+
+    fn batch_of(scope: u32, data: Vec<u64>, is_last: bool) -> MicroBatch<u64> {
+        let tag = Tag::with(scope);
+        let mut buf = Batch::with_capacity(data.len());
+        for d in data {
+            buf.push(d);
+        }
+        let mut batch = MicroBatch::new(tag.clone(), 0, 0, buf);
+        if is_last {
+            batch.set_end(EndSignal::new(tag, Weight::single(0)));
+        }
+        batch
+    }
+
+    #[test]
+    fn fold_spill_test() {
+        let mut table = FoldTable::new(1, 150, "fold_test".to_owned());
+        let factory = || |a: u64, b: u64| Ok(a + b);
+        // 100 records of scope 0 are folded in memory, the records of scope 1 and 2 are spilled as
+        // they would exceed the threshold, and so are the later records of scope 0;
+        for scope in (0..3u32).chain(0..1) {
+            let mut batch = batch_of(scope, (0..100).collect(), false);
+            assert!(table
+                .fold(&mut batch, &0, &factory)
+                .unwrap()
+                .is_none());
+        }
+        assert_eq!(table.folded, 100);
+        assert_eq!(table.spilled.len(), 3);
+        for scope in (0..3u32).rev() {
+            let mut batch = batch_of(scope, vec![scope as u64], true);
+            let sum = table.fold(&mut batch, &0, &factory).unwrap();
+            let expected = if scope == 0 { 4950 * 2 } else { 4950 + scope as u64 };
+            assert_eq!(sum, Some(expected));
+        }
+        assert_eq!(table.folded, 0);
+        assert!(table.spilled.is_empty());
+        // the last batch of a scope is never spilled;
+        let mut batch = batch_of(5, (0..150).collect(), false);
+        table.fold(&mut batch, &0, &factory).unwrap();
+        let mut batch = batch_of(9, vec![1, 2, 3], true);
+        assert_eq!(table.fold(&mut batch, &0, &factory).unwrap(), Some(6));
+        assert!(table.spilled.is_empty());
+        assert_eq!(table.folded, 150);
     }
 }
@@ -19,6 +19,7 @@ use crate::api::{Binary, HasKey, Join};
 use crate::communication::output::OutputSession;
 use crate::communication::Output;
 use crate::errors::{BuildJobError, JobExecError};
+use crate::operator::concise::spill::{partition_of, SpillPartitions, SPILL_PARTITIONS};
 use crate::operator::TidyTagMap;
 use crate::stream::Stream;
 use crate::{Data, Tag};
@@ -46,22 +47,70 @@ struct MapEntry<C: Default> {
     indicator: bool,
 }
 
+/// The records of a scope spilled into local files, once the records of the scope held in memory
+/// exceed the spill threshold of the join;
+struct JoinSpill<L: Data, R: Data> {
+    /// Records arrived before the scope was spilled, the pairs among them have been output
+    old_left: SpillPartitions<L>,
+    old_right: SpillPartitions<R>,
+    /// Records arrived after the scope was spilled, which are joined when the scope completes
+    new_left: SpillPartitions<L>,
+    new_right: SpillPartitions<R>,
+}
+
+impl<L: Data, R: Data> JoinSpill<L, R> {
+    fn new(name: &str) -> Self {
+        JoinSpill {
+            old_left: SpillPartitions::new(format!("{}_old_left", name)),
+            old_right: SpillPartitions::new(format!("{}_old_right", name)),
+            new_left: SpillPartitions::new(format!("{}_new_left", name)),
+            new_right: SpillPartitions::new(format!("{}_new_right", name)),
+        }
+    }
+}
+
 struct Helper<L: Data + HasKey, R: Data + HasKey> {
     /// A map to maintain the data of the left stream based on the join key
     left_map: TaggedMap<L>,
     /// A map to maintain the data of the right stream based on the join key
     right_map: TaggedMap<R>,
+    /// The number of records held in the maps of each scope
+    sizes: TidyTagMap<usize>,
+    /// The scopes whose records have been spilled
+    spills: TidyTagMap<JoinSpill<L, R>>,
+    /// The most records held in memory for each scope
+    threshold: usize,
+    /// The name of spill files
+    name: String,
+    /// Whether only the keys of the right items are maintained, as in semi/anti join
+    semi: bool,
 }
 
 impl<L: Data + HasKey, R: Data + HasKey> Default for Helper<L, R> {
     fn default() -> Self {
-        Helper { left_map: TidyTagMap::default(), right_map: TidyTagMap::default() }
+        Helper {
+            left_map: TidyTagMap::default(),
+            right_map: TidyTagMap::default(),
+            sizes: TidyTagMap::default(),
+            spills: TidyTagMap::default(),
+            threshold: usize::MAX,
+            name: "join".to_owned(),
+            semi: false,
+        }
     }
 }
 
 impl<L: Data + HasKey, R: Data + HasKey> Helper<L, R> {
-    fn new(scope_level: u32) -> Self {
-        Helper { left_map: TidyTagMap::new(scope_level), right_map: TidyTagMap::new(scope_level) }
+    fn new(scope_level: u32, threshold: usize, name: String, semi: bool) -> Self {
+        Helper {
+            left_map: TidyTagMap::new(scope_level),
+            right_map: TidyTagMap::new(scope_level),
+            sizes: TidyTagMap::new(scope_level),
+            spills: TidyTagMap::new(scope_level),
+            threshold,
+            name,
+            semi,
+        }
     }
 
     fn get_maps_mut(&mut self, tag: &Tag) -> (&mut JoinMap<L>, &mut JoinMap<R>) {
@@ -83,6 +132,119 @@ impl<L: Data + HasKey, R: Data + HasKey> Helper<L, R> {
     fn set_right_end(&mut self, tag: &Tag) {
         self.right_map.get_mut_or_insert(tag).indicator = true;
     }
+
+    /// Remove the spilled records of the given scope (by `tag`), if any
+    fn remove_spill(&mut self, tag: &Tag) -> Option<JoinSpill<L, R>> {
+        self.sizes.remove(tag);
+        self.spills.remove(tag)
+    }
+
+    /// Count the records inserted into the maps of the given scope (by `tag`), and move all records
+    /// of the scope into spill files once the threshold is reached. The items of the right map are
+    /// kept in memory in semi/anti join, as only their keys are maintained.
+    fn try_spill(&mut self, tag: &Tag, count: usize) -> Result<(), JobExecError> {
+        if count == 0 || self.spills.contains_key(tag) {
+            return Ok(());
+        }
+        let size = self.sizes.get_mut_or_insert(tag);
+        *size += count;
+        if *size < self.threshold {
+            return Ok(());
+        }
+        let size = *size;
+        self.sizes.remove(tag);
+        let mut spill = JoinSpill::new(&self.name);
+        // The left items of semi/anti join are only output when the scope completes,
+        // so all of them are taken as new ones;
+        let left = if self.semi { &mut spill.new_left } else { &mut spill.old_left };
+        if let Some(entry) = self.left_map.get_mut(tag) {
+            for (k, entry) in entry.data.drain() {
+                let partition = partition_of(&k);
+                for item in entry.data {
+                    left.write(partition, &item)?;
+                }
+            }
+        }
+        if !self.semi {
+            if let Some(entry) = self.right_map.get_mut(tag) {
+                for (k, entry) in entry.data.drain() {
+                    let partition = partition_of(&k);
+                    for item in entry.data {
+                        spill.old_right.write(partition, &item)?;
+                    }
+                }
+            }
+        }
+        trace_worker!("{} spill {} records of scope {:?};", self.name, size, tag);
+        self.spills.insert(tag.clone(), spill);
+        Ok(())
+    }
+}
+
+impl<L: Data + HasKey, R: Data + HasKey<Target = L::Target>> Helper<L, R>
+where
+    L::Target: Clone + Send,
+{
+    /// Insert the left items of the given scope (by `tag`), and call `on_match` with each right
+    /// item they match; the items are spilled directly if the scope has been spilled.
+    fn join_left<I, F>(&mut self, tag: &Tag, data: I, mut on_match: F) -> Result<(), JobExecError>
+    where
+        I: Iterator<Item = L>,
+        F: FnMut(&L, &R) -> Result<(), JobExecError>,
+    {
+        if let Some(spill) = self.spills.get_mut(tag) {
+            for l in data {
+                spill
+                    .new_left
+                    .write(partition_of(l.get_key()), &l)?;
+            }
+            return Ok(());
+        }
+        let mut count = 0;
+        let (l_map, r_map) = self.get_maps_mut(tag);
+        for l in data {
+            if let Some(arr) = insert_and_query(l_map, r_map, &l, true) {
+                for r in arr {
+                    on_match(&l, r)?;
+                }
+            }
+            count += 1;
+        }
+        self.try_spill(tag, count)
+    }
+
+    /// The counterpart of `join_left`, except that in semi/anti join the right items are neither
+    /// stored nor spilled, but only their keys are maintained in memory.
+    fn join_right<I, F>(&mut self, tag: &Tag, data: I, mut on_match: F) -> Result<(), JobExecError>
+    where
+        I: Iterator<Item = R>,
+        F: FnMut(&L, &R) -> Result<(), JobExecError>,
+    {
+        if !self.semi {
+            if let Some(spill) = self.spills.get_mut(tag) {
+                for r in data {
+                    spill
+                        .new_right
+                        .write(partition_of(r.get_key()), &r)?;
+                }
+                return Ok(());
+            }
+        }
+        let need_insert = !self.semi;
+        let mut count = 0;
+        let (l_map, r_map) = self.get_maps_mut(tag);
+        for r in data {
+            if let Some(arr) = insert_and_query(r_map, l_map, &r, need_insert) {
+                for l in arr {
+                    on_match(l, &r)?;
+                }
+            }
+            if need_insert {
+                count += 1;
+            }
+        }
+        self.try_spill(tag, count)
+    }
 }
 
 // insert data into map1, query it in map2, and return the corresponding vector of items matching data in map2
@@ -111,10 +273,95 @@ where
     }
 }
 
-fn try_outer_join_output<L: Data + HasKey, R: Data + HasKey>(
+// Join the spilled records of a completed scope partition by partition, with the left items of one
+// partition loaded into memory at a time. The pairs of items that both arrived before the scope was
+// spilled have been output, and are skipped here.
+fn join_spilled<L: Data + HasKey, R: Data + HasKey<Target = L::Target>, F>(
+    mut spill: JoinSpill<L, R>, output_left: bool, output_right: bool, mut on_output: F,
+) -> Result<(), JobExecError>
+where
+    L::Target: Clone + Send,
+    F: FnMut(Option<&L>, Option<&R>) -> Result<(), JobExecError>,
+{
+    for partition in 0..SPILL_PARTITIONS {
+        // The items are marked whether arrived after the scope was spilled;
+        let mut lefts: AHashMap<L::Target, MapEntry<Vec<(L, bool)>>> = AHashMap::new();
+        let left_runs =
+            vec![(spill.old_left.take(partition)?, false), (spill.new_left.take(partition)?, true)];
+        for (l_is_new, reader) in left_runs
+            .into_iter()
+            .filter_map(|(reader, is_new)| reader.map(|r| (is_new, r)))
+        {
+            for l in reader {
+                let l = l?;
+                let k = l.get_key().clone();
+                lefts
+                    .entry(k)
+                    .or_insert_with(MapEntry::default)
+                    .data
+                    .push((l, l_is_new));
+            }
+        }
+        let right_runs =
+            vec![(spill.old_right.take(partition)?, false), (spill.new_right.take(partition)?, true)];
+        for (r_is_new, reader) in right_runs
+            .into_iter()
+            .filter_map(|(reader, is_new)| reader.map(|r| (is_new, r)))
+        {
+            for r in reader {
+                let r = r?;
+                if let Some(entry) = lefts.get_mut(r.get_key()) {
+                    entry.indicator = true;
+                    for (l, l_is_new) in entry.data.iter() {
+                        if r_is_new || *l_is_new {
+                            on_output(Some(l), Some(&r))?;
+                        }
+                    }
+                } else if output_right {
+                    on_output(None, Some(&r))?;
+                }
+            }
+        }
+        if output_left {
+            for entry in lefts.values().filter(|entry| !entry.indicator) {
+                for (l, _) in entry.data.iter() {
+                    on_output(Some(l), None)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn try_inner_join_output<L: Data + HasKey, R: Data + HasKey<Target = L::Target>>(
+    helper: &mut Helper<L, R>, mut session: OutputSession<(L, R)>, tag: &Tag,
+) -> Result<(), JobExecError>
+where
+    L::Target: Clone + Send,
+{
+    if !helper.is_end(tag) {
+        return Ok(());
+    }
+    helper.left_map.remove(tag);
+    helper.right_map.remove(tag);
+    if let Some(spill) = helper.remove_spill(tag) {
+        join_spilled(spill, false, false, |l, r| {
+            if let (Some(l), Some(r)) = (l, r) {
+                session.give((l.clone(), r.clone()))?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+fn try_outer_join_output<L: Data + HasKey, R: Data + HasKey<Target = L::Target>>(
     helper: &mut Helper<L, R>, mut session: OutputSession<(Option<L>, Option<R>)>, output_left: bool,
     outoutput_right: bool, tag: &Tag,
-) -> Result<(), JobExecError> {
+) -> Result<(), JobExecError>
+where
+    L::Target: Clone + Send,
+{
     if !helper.is_end(tag) {
         return Ok(());
     }
@@ -140,12 +387,21 @@ fn try_outer_join_output<L: Data + HasKey, R: Data + HasKey>(
             }
         }
     }
+    if let Some(spill) = helper.remove_spill(tag) {
+        join_spilled(spill, output_left, outoutput_right, |l, r| {
+            session.give((l.cloned(), r.cloned()))?;
+            Ok(())
+        })?;
+    }
     Ok(())
 }
 
-fn try_semi_join_output<L: Data + HasKey, R: Data + HasKey>(
+fn try_semi_join_output<L: Data + HasKey, R: Data + HasKey<Target = L::Target>>(
     helper: &mut Helper<L, R>, output: &Output<L>, is_anti: bool, tag: &Tag,
-) -> Result<(), JobExecError> {
+) -> Result<(), JobExecError>
+where
+    L::Target: Clone + Send,
+{
     if !helper.is_end(tag) {
         return Ok(());
     }
@@ -163,40 +419,66 @@ fn try_semi_join_output<L: Data + HasKey, R: Data + HasKey>(
             }
         }
     }
-    helper.right_map.remove(tag);
+    let right = helper.right_map.remove(tag);
+    if let Some(mut spill) = helper.remove_spill(tag) {
+        // All the spilled left items are matched against the keys of the right items;
+        let right = right
+            .map(|entry| entry.data)
+            .unwrap_or_default();
+        for partition in 0..SPILL_PARTITIONS {
+            if let Some(reader) = spill.new_left.take(partition)? {
+                for item in reader {
+                    let item = item?;
+                    if right.contains_key(item.get_key()) ^ is_anti {
+                        session.give(item)?;
+                    }
+                }
+            }
+        }
+    }
     Ok(())
 }
 
+/// The join holds the records of both inputs, the stricter spill threshold set on them is taken;
+#[inline]
+fn spill_threshold_of<L: Data, R: Data>(this: &Stream<L>, other: &Stream<R>) -> usize {
+    std::cmp::min(this.spill_threshold(), other.spill_threshold())
+}
+
 fn internal_inner_join<L: Data + HasKey, R: Data + HasKey<Target = L::Target>>(
     this: Stream<L>, other: Stream<R>,
 ) -> Result<Stream<(L, R)>, BuildJobError>
 where
     L::Target: Clone + Send,
 {
+    let threshold = spill_threshold_of(&this, &other);
     this.binary("inner_join", other, |info| {
-        let mut helper = Helper::<L, R>::new(info.scope_level);
+        let name = format!("inner_join_{}", info.index);
+        let mut helper = Helper::<L, R>::new(info.scope_level, threshold, name, false);
         move |left, right, output| {
             left.for_each_batch(|dataset| {
-                let mut session = output.new_session(&dataset.tag)?;
-                let (mut l_map, mut r_map) = helper.get_maps_mut(&dataset.tag);
-                for l in dataset.drain() {
-                    if let Some(arr) = insert_and_query(&mut l_map, &mut r_map, &l, true) {
-                        for r in arr {
-                            session.give((l.clone(), r.clone()))?;
-                        }
-                    }
+                let tag = dataset.tag.clone();
+                let mut session = output.new_session(&tag)?;
+                helper.join_left(&tag, dataset.drain(), |l, r| {
+                    session.give((l.clone(), r.clone()))?;
+                    Ok(())
+                })?;
+                if dataset.is_last() {
+                    helper.set_left_end(&tag);
+                    try_inner_join_output(&mut helper, session, &tag)?;
                 }
                 Ok(())
             })?;
             right.for_each_batch(|dataset| {
-                let mut session = output.new_session(&dataset.tag)?;
-                let (mut l_map, mut r_map) = helper.get_maps_mut(&dataset.tag);
-                for r in dataset.drain() {
-                    if let Some(arr) = insert_and_query(&mut r_map, &mut l_map, &r, true) {
-                        for l in arr {
-                            session.give((l.clone(), r.clone()))?;
-                        }
-                    }
+                let tag = dataset.tag.clone();
+                let mut session = output.new_session(&tag)?;
+                helper.join_right(&tag, dataset.drain(), |l, r| {
+                    session.give((l.clone(), r.clone()))?;
+                    Ok(())
+                })?;
+                if dataset.is_last() {
+                    helper.set_right_end(&tag);
+                    try_inner_join_output(&mut helper, session, &tag)?;
                 }
                 Ok(())
             })
@@ -216,38 +498,34 @@ where
         JoinType::FullOuter => (true, true),
         _ => return Err(BuildJobError::from("wrong join type".to_string())),
     };
+    let threshold = spill_threshold_of(&this, &other);
     this.binary(format!("{:?}", join_type).as_str(), other, |info| {
-        let mut helper = Helper::<L, R>::new(info.scope_level);
+        let name = format!("{:?}_{}", join_type, info.index);
+        let mut helper = Helper::<L, R>::new(info.scope_level, threshold, name, false);
         move |left, right, output| {
             left.for_each_batch(|dataset| {
-                let mut session = output.new_session(&dataset.tag)?;
-                let (mut l_map, mut r_map) = helper.get_maps_mut(&dataset.tag);
-                for l in dataset.drain() {
-                    if let Some(arr) = insert_and_query(&mut l_map, &mut r_map, &l, true) {
-                        for r in arr {
-                            session.give((Some(l.clone()), Some(r.clone())))?;
-                        }
-                    }
-                }
+                let tag = dataset.tag.clone();
+                let mut session = output.new_session(&tag)?;
+                helper.join_left(&tag, dataset.drain(), |l, r| {
+                    session.give((Some(l.clone()), Some(r.clone())))?;
+                    Ok(())
+                })?;
                 if dataset.is_last() {
-                    helper.set_left_end(&dataset.tag);
-                    try_outer_join_output(&mut helper, session, output_left, output_right, &dataset.tag)?;
+                    helper.set_left_end(&tag);
+                    try_outer_join_output(&mut helper, session, output_left, output_right, &tag)?;
                 }
                 Ok(())
             })?;
             right.for_each_batch(|dataset| {
-                let mut session = output.new_session(&dataset.tag)?;
-                let (mut l_map, mut r_map) = helper.get_maps_mut(&dataset.tag);
-                for r in dataset.drain() {
-                    if let Some(arr) = insert_and_query(&mut r_map, &mut l_map, &r, true) {
-                        for l in arr {
-                            session.give((Some(l.clone()), Some(r.clone())))?;
-                        }
-                    }
-                }
+                let tag = dataset.tag.clone();
+                let mut session = output.new_session(&tag)?;
+                helper.join_right(&tag, dataset.drain(), |l, r| {
+                    session.give((Some(l.clone()), Some(r.clone())))?;
+                    Ok(())
+                })?;
                 if dataset.is_last() {
-                    helper.set_right_end(&dataset.tag);
-                    try_outer_join_output(&mut helper, session, output_left, output_right, &dataset.tag)?;
+                    helper.set_right_end(&tag);
+                    try_outer_join_output(&mut helper, session, output_left, output_right, &tag)?;
                 }
                 Ok(())
             })?;
@@ -267,28 +545,26 @@ where
         JoinType::Anti => true,
         _ => return Err(BuildJobError::from("wrong join type".to_string())),
     };
+    let threshold = spill_threshold_of(&this, &other);
     this.binary(format!("{:?}", join_type).as_str(), other, |info| {
-        let mut helper = Helper::<L, R>::new(info.scope_level);
+        let name = format!("{:?}_{}", join_type, info.index);
+        let mut helper = Helper::<L, R>::new(info.scope_level, threshold, name, true);
         move |left, right, output| {
             left.for_each_batch(|dataset| {
-                let (mut l_map, mut r_map) = helper.get_maps_mut(&dataset.tag);
-                for l in dataset.drain() {
-                    insert_and_query(&mut l_map, &mut r_map, &l, true);
-                }
+                let tag = dataset.tag.clone();
+                helper.join_left(&tag, dataset.drain(), |_, _| Ok(()))?;
                 if dataset.is_last() {
-                    helper.set_left_end(&dataset.tag);
-                    try_semi_join_output(&mut helper, output, is_anti, &dataset.tag)?;
+                    helper.set_left_end(&tag);
+                    try_semi_join_output(&mut helper, output, is_anti, &tag)?;
                 }
                 Ok(())
             })?;
             right.for_each_batch(|dataset| {
-                let (mut l_map, mut r_map) = helper.get_maps_mut(&dataset.tag);
-                for r in dataset.drain() {
-                    insert_and_query(&mut r_map, &mut l_map, &r, false);
-                }
+                let tag = dataset.tag.clone();
+                helper.join_right(&tag, dataset.drain(), |_, _| Ok(()))?;
                 if dataset.is_last() {
-                    helper.set_right_end(&dataset.tag);
-                    try_semi_join_output(&mut helper, output, is_anti, &dataset.tag)?;
+                    helper.set_right_end(&tag);
+                    try_semi_join_output(&mut helper, output, is_anti, &tag)?;
                 }
                 Ok(())
             })?;
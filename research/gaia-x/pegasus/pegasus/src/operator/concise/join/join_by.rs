@@ -0,0 +1,201 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use crate::api::function::FnResult;
+use crate::api::{Binary, JoinBy, Map};
+use crate::errors::{BuildJobError, JobExecError};
+use crate::operator::TidyTagMap;
+use crate::stream::Stream;
+use crate::Data;
+
+/// The items of both participants of a join in a scope, and whether each participant has completed
+struct Sides<A, B> {
+    left: Vec<A>,
+    right: Vec<B>,
+    left_end: bool,
+    right_end: bool,
+}
+
+impl<A, B> Default for Sides<A, B> {
+    fn default() -> Self {
+        Sides { left: vec![], right: vec![], left_end: false, right_end: false }
+    }
+}
+
+/// An item of band join, with the range it is partitioned into, and its key
+type BandItem<T> = (i64, i64, T);
+
+// Sort the items of both sides by range and key, and output the pairs whose keys are within `width`
+// in the same range. As the left keys are ascending, the first right item in the band of a left
+// item never moves backward.
+fn band_merge<L, R, F>(
+    mut left: Vec<BandItem<L>>, mut right: Vec<BandItem<R>>, width: i64, mut on_pair: F,
+) -> Result<(), JobExecError>
+where
+    F: FnMut(&L, &R) -> Result<(), JobExecError>,
+{
+    left.sort_by_key(|(range, key, _)| (*range, *key));
+    right.sort_by_key(|(range, key, _)| (*range, *key));
+    let mut start = 0;
+    for (range, key, l) in left.iter() {
+        let lower = key.saturating_sub(width);
+        while start < right.len() && (right[start].0, right[start].1) < (*range, lower) {
+            start += 1;
+        }
+        let upper = key.saturating_add(width);
+        for (r_range, r_key, r) in right[start..].iter() {
+            if r_range != range || *r_key > upper {
+                break;
+            }
+            on_pair(l, r)?;
+        }
+    }
+    Ok(())
+}
+
+impl<L: Data, R: Data> JoinBy<L, R> for Stream<L> {
+    fn join_by<F>(self, other: Stream<R>, predicate: F) -> Result<Stream<(L, R)>, BuildJobError>
+    where
+        F: Fn(&L, &R) -> FnResult<bool> + Send + 'static,
+    {
+        self.binary("join_by", other.broadcast(), |info| {
+            let mut table = TidyTagMap::<Sides<L, R>>::new(info.scope_level);
+            move |left, right, output| {
+                left.for_each_batch(|dataset| {
+                    let mut session = output.new_session(&dataset.tag)?;
+                    let sides = table.get_mut_or_insert(&dataset.tag);
+                    for l in dataset.drain() {
+                        for r in sides.right.iter() {
+                            if predicate(&l, r)? {
+                                session.give((l.clone(), r.clone()))?;
+                            }
+                        }
+                        // no more right items would be compared with it;
+                        if !sides.right_end {
+                            sides.left.push(l);
+                        }
+                    }
+                    if dataset.is_last() {
+                        sides.left_end = true;
+                        if sides.right_end {
+                            table.remove(&dataset.tag);
+                        }
+                    }
+                    Ok(())
+                })?;
+                right.for_each_batch(|dataset| {
+                    let mut session = output.new_session(&dataset.tag)?;
+                    let sides = table.get_mut_or_insert(&dataset.tag);
+                    for r in dataset.drain() {
+                        for l in sides.left.iter() {
+                            if predicate(l, &r)? {
+                                session.give((l.clone(), r.clone()))?;
+                            }
+                        }
+                        if !sides.left_end {
+                            sides.right.push(r);
+                        }
+                    }
+                    if dataset.is_last() {
+                        sides.right_end = true;
+                        if sides.left_end {
+                            table.remove(&dataset.tag);
+                        }
+                    }
+                    Ok(())
+                })
+            }
+        })
+    }
+
+    fn band_join<FL, FR>(
+        self, other: Stream<R>, left_key: FL, right_key: FR, width: i64, partition: Option<i64>,
+    ) -> Result<Stream<(L, R)>, BuildJobError>
+    where
+        FL: Fn(&L) -> i64 + Send + 'static,
+        FR: Fn(&R) -> i64 + Send + 'static,
+    {
+        if width < 0 {
+            return Err(BuildJobError::from(format!("negative width {} of band join", width)));
+        }
+        let (left, right) = match partition {
+            Some(len) if len <= 0 => {
+                return Err(BuildJobError::from(format!("invalid partition {} of band join", len)));
+            }
+            Some(len) => {
+                let left = self
+                    .map(move |l| {
+                        let key = left_key(&l);
+                        Ok((key.div_euclid(len), key, l))
+                    })?
+                    .repartition(|(range, _, _)| Ok(*range as u64));
+                let right = other
+                    .flat_map(move |r| {
+                        // copy the item into all ranges overlapping [key - width, key + width];
+                        let key = right_key(&r);
+                        let first = key.saturating_sub(width).div_euclid(len);
+                        let last = key.saturating_add(width).div_euclid(len);
+                        Ok((first..=last).map(move |range| (range, key, r.clone())))
+                    })?
+                    .repartition(|(range, _, _)| Ok(*range as u64));
+                (left, right)
+            }
+            None => {
+                let left = self.map(move |l| Ok((0i64, left_key(&l), l)))?;
+                let right = other
+                    .map(move |r| Ok((0i64, right_key(&r), r)))?
+                    .broadcast();
+                (left, right)
+            }
+        };
+        left.binary("band_join", right, |info| {
+            let mut table = TidyTagMap::<Sides<BandItem<L>, BandItem<R>>>::new(info.scope_level);
+            move |left, right, output| {
+                left.for_each_batch(|dataset| {
+                    let sides = table.get_mut_or_insert(&dataset.tag);
+                    sides.left.extend(dataset.drain());
+                    if dataset.is_last() {
+                        sides.left_end = true;
+                        if sides.right_end {
+                            let sides = table.remove(&dataset.tag).expect("unreachable");
+                            let mut session = output.new_session(&dataset.tag)?;
+                            band_merge(sides.left, sides.right, width, |l, r| {
+                                session.give((l.clone(), r.clone()))?;
+                                Ok(())
+                            })?;
+                        }
+                    }
+                    Ok(())
+                })?;
+                right.for_each_batch(|dataset| {
+                    let sides = table.get_mut_or_insert(&dataset.tag);
+                    sides.right.extend(dataset.drain());
+                    if dataset.is_last() {
+                        sides.right_end = true;
+                        if sides.left_end {
+                            let sides = table.remove(&dataset.tag).expect("unreachable");
+                            let mut session = output.new_session(&dataset.tag)?;
+                            band_merge(sides.left, sides.right, width, |l, r| {
+                                session.give((l.clone(), r.clone()))?;
+                                Ok(())
+                            })?;
+                        }
+                    }
+                    Ok(())
+                })
+            }
+        })
+    }
+}
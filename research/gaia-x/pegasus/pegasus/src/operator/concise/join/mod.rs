@@ -14,3 +14,4 @@
 //! limitations under the License.
 
 mod join;
+mod join_by;
@@ -0,0 +1,252 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use ahash::AHashMap;
+use pegasus_common::buffer::{BufferPool, MemBufAlloc};
+
+use crate::api::{Binary, Branch, Key, Pair, PartitionByKey, ScopeByKey};
+use crate::errors::IOError;
+use crate::operator::concise::correlate::new_batch;
+use crate::operator::first_err;
+use crate::progress::{EndSignal, Weight};
+use crate::stream::{SingleItem, Stream};
+use crate::tag::tools::map::TidyTagMap;
+use crate::{BuildJobError, Data, Tag};
+
+/// The segments of a parent scope forked on one worker, each of which is a child scope holding
+/// all values of a key;
+struct Segments<K, V> {
+    /// sequence of the next child scope, which is `worker + n * peers` as in `apply`;
+    seq: u32,
+    /// keys whose scopes are open, the values of them are sent as soon as they arrive;
+    open: AHashMap<K, Tag>,
+    /// keys that can't open scopes as the scope capacity is exhausted, the values of them are
+    /// buffered and forked into scopes after the parent scope ends;
+    pending: Vec<(K, Vec<V>)>,
+    pending_index: AHashMap<K, usize>,
+}
+
+impl<K: Key, V> Segments<K, V> {
+    fn new(seq: u32) -> Self {
+        Segments { seq, open: AHashMap::new(), pending: vec![], pending_index: AHashMap::new() }
+    }
+
+    fn next_scope(&mut self, parent: &Tag, peers: u32) -> Tag {
+        let tag = Tag::inherit(parent, self.seq);
+        self.seq += peers;
+        tag
+    }
+
+    fn buffer(&mut self, key: K, value: V) {
+        if let Some(i) = self.pending_index.get(&key) {
+            self.pending[*i].1.push(value);
+        } else {
+            self.pending_index
+                .insert(key.clone(), self.pending.len());
+            self.pending.push((key, vec![value]));
+        }
+    }
+}
+
+/// The keys of the segments of a parent scope waiting for results of their sub-tasks;
+struct Joined<K> {
+    /// keys in the order their segments are forked, taken once the result of the segment is joined;
+    keys: Vec<Option<K>>,
+    end: Option<EndSignal>,
+    /// number of segments whose end has arrived;
+    finished: usize,
+}
+
+impl<K> Default for Joined<K> {
+    fn default() -> Self {
+        Joined { keys: vec![], end: None, finished: 0 }
+    }
+}
+
+impl<K: Data + Key, V: Data> ScopeByKey<K, V> for Stream<Pair<K, V>> {
+    fn segment_apply<F, T>(self, task: F) -> Result<Stream<Pair<K, T>>, BuildJobError>
+    where
+        T: Data,
+        F: FnOnce(Stream<V>) -> Result<SingleItem<T>, BuildJobError>,
+    {
+        let entered = self.partition_by_key().enter()?;
+        let scope_capacity = entered.get_scope_capacity() as usize;
+        // number of segments forked on this worker whose end hasn't arrived at `segment_join` yet;
+        let opened = Arc::new(AtomicUsize::new(0));
+        let closed = opened.clone();
+        let (sub, keys) = entered.branch("segment_fork", move |info| {
+            assert!(info.scope_level > 0);
+            let id = crate::worker_id::get_current_worker();
+            let worker = id.index;
+            let peers = id.total_peers();
+            let index = worker + peers;
+            let mut parents = TidyTagMap::new(info.scope_level - 1);
+            let mut buf_pool = BufferPool::new(1, scope_capacity, MemBufAlloc::new());
+            move |input, sub_output, key_output| {
+                input.for_each_batch(|dataset| {
+                    let p = dataset.tag.to_parent_uncheck();
+                    let segments = parents.get_mut_or_else(&p, || Segments::new(index));
+                    if !dataset.is_empty() {
+                        let mut keys = key_output.new_session(&dataset.tag)?;
+                        for item in dataset.drain() {
+                            let (key, value) = item.take();
+                            if let Some(tag) = segments.open.get(&key) {
+                                // dropped by the output if the sub-task of the key has stopped early;
+                                sub_output.new_session(tag)?.give(value)?;
+                            } else if segments.pending_index.contains_key(&key)
+                                || opened.load(Ordering::SeqCst) >= scope_capacity
+                            {
+                                segments.buffer(key, value);
+                            } else {
+                                let tag = segments.next_scope(&p, peers);
+                                trace_worker!("open scope {:?} of a new key from {:?};", tag, p);
+                                opened.fetch_add(1, Ordering::SeqCst);
+                                segments.open.insert(key.clone(), tag.clone());
+                                let mut res = sub_output.new_session(&tag)?.give(value);
+                                first_err(&mut res, keys.give(key));
+                                res?;
+                            }
+                        }
+                    }
+
+                    if dataset.is_last() {
+                        // the open scopes are still counted until their ends arrive at `segment_join`;
+                        let open = std::mem::replace(&mut segments.open, AHashMap::new());
+                        let mut res = Ok(());
+                        for (_, tag) in open {
+                            let end = EndSignal::new(tag.clone(), Weight::single(worker));
+                            first_err(&mut res, sub_output.new_session(&tag)?.notify_end(end));
+                        }
+                        res?;
+
+                        while !segments.pending.is_empty() {
+                            if opened.load(Ordering::SeqCst) >= scope_capacity {
+                                would_block!("scope capacity exhausted;")?
+                            }
+                            if let Some(mut buf) = buf_pool.fetch() {
+                                let (key, values) = segments.pending.pop().expect("pending lost;");
+                                let tag = segments.next_scope(&p, peers);
+                                trace_worker!("fork scope {:?} of a buffered key from {:?};", tag, p);
+                                opened.fetch_add(1, Ordering::SeqCst);
+                                for v in values {
+                                    buf.push(v);
+                                }
+                                let mut batch = new_batch(tag.clone(), worker, buf);
+                                batch.set_end(EndSignal::new(tag, Weight::single(worker)));
+                                let mut res = sub_output.push_batch(batch);
+                                first_err(&mut res, key_output.new_session(&dataset.tag)?.give(key));
+                                res?;
+                            } else {
+                                would_block!("no buffer available for new scope;")?
+                            }
+                        }
+
+                        parents.remove(&p);
+                        if let Some(end) = dataset.take_end() {
+                            key_output
+                                .new_session(&dataset.tag)?
+                                .notify_end(end)?;
+                        }
+                    }
+                    Ok(())
+                })
+            }
+        })?;
+
+        let SingleItem { inner } = task(sub)?;
+        keys.binary("segment_join", inner, move |info| {
+            let mut parents = TidyTagMap::new(info.scope_level - 1);
+            let id = crate::worker_id::get_current_worker();
+            let worker = id.index;
+            let peers = id.total_peers();
+            move |input_left, input_right, output| {
+                input_left.for_each_batch(|dataset| {
+                    let p = dataset.tag.to_parent_uncheck();
+                    let joined = parents.get_mut_or_else(&p, Joined::default);
+                    for key in dataset.drain() {
+                        joined.keys.push(Some(key));
+                    }
+                    if let Some(end) = dataset.take_end() {
+                        trace_worker!(
+                            "{} segments of {:?} waiting finish;",
+                            joined.keys.len() - joined.finished,
+                            p
+                        );
+                        if joined.finished == joined.keys.len() {
+                            parents.remove(&p);
+                            output
+                                .new_session(&dataset.tag)?
+                                .notify_end(end)?;
+                        } else {
+                            joined.end = Some(end);
+                        }
+                    }
+                    Ok(())
+                })?;
+
+                input_right.for_each_batch(|dataset| {
+                    let seq = dataset.tag.current_uncheck();
+                    if seq == 0 || seq % peers != worker {
+                        // not a segment forked on this worker, the sub-task may aggregate data into
+                        // scope 0, and the end signals of other workers' segments may be broadcast here;
+                        if !dataset.is_empty() {
+                            warn_worker!("data of scope {:?};", dataset.tag);
+                            dataset.clear();
+                        }
+                        dataset.take_end();
+                        return Ok(());
+                    }
+                    let p = dataset.tag.to_parent_uncheck();
+                    let offset = (seq / peers) as usize - 1;
+                    // segments are joined as soon as their keys arrive rather than the end of the
+                    // parent, as the buffered keys are only forked after earlier segments end;
+                    let joined = match parents.get_mut(&p) {
+                        Some(joined) if offset < joined.keys.len() => joined,
+                        _ => would_block!("segment waiting key;")?,
+                    };
+                    let tag = Tag::inherit(&p, 0);
+                    let mut session = output.new_session(&tag)?;
+                    let mut res = Ok(());
+                    if let Some(value) = dataset.next() {
+                        assert!(dataset.is_empty());
+                        let key = joined.keys[offset]
+                            .take()
+                            .expect("segment joined twice;");
+                        trace_worker!("join result of {}th segment {:?}", offset, dataset.tag);
+                        res = session.give(Pair { key, value: value.0 });
+                    }
+                    // a segment is finished by its end rather than its result, as the sub-task may
+                    // give nothing for the key;
+                    if dataset.take_end().is_some() {
+                        closed.fetch_sub(1, Ordering::SeqCst);
+                        joined.finished += 1;
+                        if joined.finished == joined.keys.len() {
+                            if let Some(end) = joined.end.take() {
+                                trace_worker!("all segments of {:?} joined;", p);
+                                parents.remove(&p);
+                                first_err(&mut res, session.notify_end(end));
+                            }
+                        }
+                    }
+                    Ok(res?)
+                })
+            }
+        })?
+        .leave()
+    }
+}
@@ -63,6 +63,7 @@ impl<D: Data + HasKey, H: BuildHasher + Send + 'static> RouteFunction<D> for Key
     }
 }
 
+mod apply;
 mod dedup;
 mod fold;
 mod reduce;
@@ -3,55 +3,141 @@ use std::collections::HashMap;
 use ahash::AHashMap;
 
 use crate::api::function::FnResult;
-use crate::api::{Fold, Key, Map, Pair, PartitionByKey, ReduceByKey, Unary};
-use crate::stream::{Single, SingleItem, Stream};
+use crate::api::{Fold, Key, Pair, PartitionByKey, ReduceByKey, Unary};
+use crate::communication::output::OutputSession;
+use crate::errors::{IOError, JobExecError};
+use crate::operator::concise::spill::{partition_of, SpillPartitions, SPILL_PARTITIONS};
+use crate::stream::{SingleItem, Stream};
 use crate::tag::tools::map::TidyTagMap;
 use crate::{BuildJobError, Data};
 
+type Groups<K, V, F> = AHashMap<K, (Option<V>, F)>;
+
+fn reduce_into<K: Data + Key, V: Data, F, B>(
+    groups: &mut Groups<K, V, F>, k: K, v: V, builder: &B,
+) -> FnResult<()>
+where
+    F: FnMut(V, V) -> FnResult<V> + Send + 'static,
+    B: Fn() -> F + Send + 'static,
+{
+    if let Some((r, f)) = groups.get_mut(&k) {
+        let detach = r.take().expect("reduce value lost;");
+        let x = (*f)(detach, v)?;
+        r.replace(x);
+    } else {
+        groups.insert(k, (Some(v), builder()));
+    }
+    Ok(())
+}
+
+fn give_groups<K: Data + Key, V: Data, F: Send + 'static>(
+    groups: Groups<K, V, F>, session: &mut OutputSession<Pair<K, V>>,
+) -> Result<(), JobExecError> {
+    let pairs = groups
+        .into_iter()
+        .filter_map(|(key, (value, _))| value.map(|value| Pair { key, value }));
+    session.give_iterator(pairs)?;
+    Ok(())
+}
+
+/// Groups of a scope, the groups are spilled into partitions by keys once their number reaches the
+/// threshold. At the end of the scope the partitions are reduced and given one by one, so that no more
+/// than a partition of groups is held in memory;
+struct SpillGroups<K: Data + Key, V: Data, F> {
+    groups: Groups<K, V, F>,
+    spilled: Option<SpillPartitions<Pair<K, V>>>,
+    /// the next partition to reduce after the scope ends;
+    next_partition: usize,
+}
+
+impl<K: Data + Key, V: Data, F> Default for SpillGroups<K, V, F> {
+    fn default() -> Self {
+        SpillGroups { groups: AHashMap::new(), spilled: None, next_partition: 0 }
+    }
+}
+
+impl<K: Data + Key, V: Data, F> SpillGroups<K, V, F> {
+    fn spill(&mut self, name: &str) -> std::io::Result<()> {
+        let spilled = self
+            .spilled
+            .get_or_insert_with(|| SpillPartitions::new(name.to_owned()));
+        for (key, (value, _)) in std::mem::replace(&mut self.groups, AHashMap::new()) {
+            if let Some(value) = value {
+                let p = partition_of(&key);
+                spilled.write(p, &Pair { key, value })?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<K: Data + Key, V: Data> ReduceByKey<K, V> for Stream<Pair<K, V>> {
     fn reduce_by_key<B, F>(self, builder: B) -> Result<SingleItem<HashMap<K, V>>, BuildJobError>
     where
         F: FnMut(V, V) -> FnResult<V> + Send + 'static,
         B: Fn() -> F + Send + 'static,
     {
+        self.reduce_by_key_stream(builder)?
+            .fold(HashMap::new(), || {
+                |mut map, pair| {
+                    map.insert(pair.key, pair.value);
+                    Ok(map)
+                }
+            })
+    }
+
+    fn reduce_by_key_stream<B, F>(self, builder: B) -> Result<Stream<Pair<K, V>>, BuildJobError>
+    where
+        F: FnMut(V, V) -> FnResult<V> + Send + 'static,
+        B: Fn() -> F + Send + 'static,
+    {
+        let threshold = self.spill_threshold();
         self.partition_by_key()
             .unary("reduce_by_key", |info| {
                 let mut ttm = TidyTagMap::new(info.scope_level);
+                let name = format!("reduce_by_key_{}", info.index);
                 move |input, output| {
                     input.for_each_batch(|dataset| {
-                        let groups = ttm.get_mut_or_else(&dataset.tag, AHashMap::<K, (Option<V>, F)>::new);
+                        let sg = ttm.get_mut_or_else(&dataset.tag, SpillGroups::<K, V, F>::default);
                         for item in dataset.drain() {
                             let (k, v) = item.take();
-                            if let Some((r, f)) = groups.get_mut(&k) {
-                                let detach = r.take().expect("reduce value lost;");
-                                let x = (*f)(detach, v)?;
-                                r.replace(x);
-                            } else {
-                                groups.insert(k, (Some(v), builder()));
+                            reduce_into(&mut sg.groups, k, v, &builder)?;
+                            if sg.groups.len() >= threshold {
+                                sg.spill(&name)?;
                             }
                         }
                         if dataset.is_last() {
-                            let groups = std::mem::replace(groups, Default::default());
-                            let mut map = HashMap::with_capacity(groups.len());
-                            for (k, v) in groups {
-                                if let Some(value) = v.0 {
-                                    map.insert(k, value);
+                            let mut session = output.new_session(&dataset.tag)?;
+                            if sg.spilled.is_none() {
+                                let sg = ttm.remove(&dataset.tag).expect("unreachable");
+                                give_groups(sg.groups, &mut session)?;
+                                return Ok(());
+                            }
+                            // the keys in memory may also be spilled before, reduce them together;
+                            sg.spill(&name)?;
+                            let spilled = sg.spilled.as_mut().expect("unreachable");
+                            while sg.next_partition < SPILL_PARTITIONS {
+                                let p = sg.next_partition;
+                                sg.next_partition += 1;
+                                if let Some(reader) = spilled.take(p)? {
+                                    let mut groups = AHashMap::new();
+                                    for item in reader {
+                                        let (k, v) = item?.take();
+                                        reduce_into(&mut groups, k, v, &builder)?;
+                                    }
+                                    give_groups(groups, &mut session)?;
+                                    if sg.next_partition < SPILL_PARTITIONS {
+                                        // yield before the next partition, so the groups given are
+                                        // consumed first;
+                                        interrupt!("reduce next spilled partition;")?;
+                                    }
                                 }
                             }
-                            output
-                                .new_session(&dataset.tag)?
-                                .give(Single(map))?;
+                            ttm.remove(&dataset.tag);
                         }
                         Ok(())
                     })
                 }
-            })?
-            .flat_map(|map| Ok(map.0.into_iter()))?
-            .fold(HashMap::new(), || {
-                |mut map, (k, v)| {
-                    map.insert(k, v);
-                    Ok(map)
-                }
             })
     }
 }
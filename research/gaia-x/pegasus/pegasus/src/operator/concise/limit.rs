@@ -13,15 +13,17 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
-use crate::api::{Limit, SortLimit, SortLimitBy, Unary};
-use crate::stream::Stream;
-use crate::tag::tools::map::TidyTagMap;
-use crate::{BuildJobError, Data};
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 use std::io;
 use std::rc::Rc;
 
+use crate::api::{Limit, SortLimit, SortLimitBy, Unary};
+use crate::operator::concise::spill::{ExternalSorter, SharedOrder};
+use crate::stream::Stream;
+use crate::tag::tools::map::TidyTagMap;
+use crate::{BuildJobError, Data};
+
 impl<D: Data> Limit<D> for Stream<D> {
     fn limit(self, size: u32) -> Result<Stream<D>, BuildJobError> {
         self.limit_partition(size)?
@@ -78,6 +80,13 @@ impl<D: Data> SortLimitBy<D> for Stream<D> {
         if size == 0 {
             return BuildJobError::unsupported("sort_limit n cannot equal to zero");
         }
+        let threshold = self.spill_threshold();
+        if size as usize > threshold {
+            // the top `size` records can't be held in memory, they are sorted externally;
+            let order = SharedOrder::new(cmp);
+            let local_sort = sort_limit_by_spill(self, size, threshold, order.clone())?;
+            return sort_limit_by_spill(local_sort.aggregate(), size, threshold, order);
+        }
         let share_cmp = ShadeCmp { cmp: Rc::new(cmp) };
         let cmp_clone = ShadeCmp { cmp: share_cmp.cmp.clone() };
         let local_sort = sort_limit_by_partition(self, size, cmp_clone)?;
@@ -85,6 +94,37 @@ impl<D: Data> SortLimitBy<D> for Stream<D> {
     }
 }
 
+fn sort_limit_by_spill<D: Data, F>(
+    stream: Stream<D>, size: u32, threshold: usize, order: SharedOrder<F>,
+) -> Result<Stream<D>, BuildJobError>
+where
+    F: Fn(&D, &D) -> Ordering + Send + 'static,
+{
+    stream.unary("sort_limit_by_spill", |info| {
+        let mut table = TidyTagMap::new(info.scope_level);
+        let name = format!("sort_limit_{}", info.index);
+        move |input, output| {
+            input.for_each_batch(|dataset| {
+                if !dataset.is_empty() {
+                    let sorter = table.get_mut_or_else(&dataset.tag, || {
+                        ExternalSorter::new(name.clone(), threshold, order.clone())
+                    });
+                    for d in dataset.drain() {
+                        sorter.push(d)?;
+                    }
+                }
+                if dataset.is_last() {
+                    let mut session = output.new_session(&dataset.tag)?;
+                    if let Some(sorter) = table.remove(&dataset.tag) {
+                        session.give_iterator(sorter.finish()?.take(size as usize))?;
+                    }
+                }
+                Ok(())
+            })
+        }
+    })
+}
+
 #[inline]
 fn sort_limit_by_partition<D: Data, F>(
     stream: Stream<D>, size: u32, share_cmp: ShadeCmp<F>,
@@ -56,6 +56,7 @@ impl<I: Data> Map<I> for Stream<I> {
                 })
             }
         })
+        .map(Stream::stateless)
     }
 
     fn filter_map<O, F>(self, func: F) -> Result<Stream<O>, BuildJobError>
@@ -100,6 +101,7 @@ impl<I: Data> Map<I> for Stream<I> {
                 })
             }
         })
+        .map(Stream::stateless)
     }
 
     fn flat_map<O, R, F>(self, func: F) -> Result<Stream<O>, BuildJobError>
@@ -128,5 +130,6 @@ impl<I: Data> Map<I> for Stream<I> {
                 })
             }
         })
+        .map(Stream::stateless)
     }
 }
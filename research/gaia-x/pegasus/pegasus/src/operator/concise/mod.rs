@@ -35,6 +35,10 @@ mod map;
 mod merge;
 mod reduce;
 mod sort;
+mod spill;
+mod switch;
+mod window;
+mod zip;
 
 #[inline]
 fn never_clone<T>(raw: T) -> NeverClone<T> {
@@ -63,5 +63,5 @@ where
             })
         }
     })?;
-    Ok(SingleItem::new(single))
+    Ok(SingleItem::new(single.scope_local()))
 }
@@ -16,28 +16,32 @@
 use std::cmp::Ordering;
 
 use crate::api::{Sort, SortBy, Unary};
+use crate::operator::concise::spill::{ExternalSorter, NaturalOrder, SharedOrder};
 use crate::stream::Stream;
 use crate::tag::tools::map::TidyTagMap;
 use crate::{BuildJobError, Data};
 
 impl<D: Data + Ord> Sort<D> for Stream<D> {
     fn sort(self) -> Result<Stream<D>, BuildJobError> {
+        let threshold = self.spill_threshold();
         self.aggregate().unary("sort", |info| {
             let mut map = TidyTagMap::new(info.scope_level);
+            let name = format!("sort_{}", info.index);
             move |input, output| {
                 input.for_each_batch(|dataset| {
                     if !dataset.is_empty() {
-                        let vec = map.get_mut_or_else(&dataset.tag, Vec::new);
+                        let sorter = map.get_mut_or_else(&dataset.tag, || {
+                            ExternalSorter::new(name.clone(), threshold, NaturalOrder)
+                        });
                         for d in dataset.drain() {
-                            vec.push(d);
+                            sorter.push(d)?;
                         }
                     }
 
                     if dataset.is_last() {
                         let mut session = output.new_session(&dataset.tag)?;
-                        if let Some(mut vec) = map.remove(&dataset.tag) {
-                            vec.sort();
-                            session.give_iterator(vec.into_iter())?;
+                        if let Some(sorter) = map.remove(&dataset.tag) {
+                            session.give_iterator(sorter.finish()?)?;
                         }
                     }
                     Ok(())
@@ -52,22 +56,26 @@ impl<D: Data> SortBy<D> for Stream<D> {
     where
         F: Fn(&D, &D) -> Ordering + Send + 'static,
     {
+        let threshold = self.spill_threshold();
         self.aggregate().unary("sort_by", |info| {
             let mut map = TidyTagMap::new(info.scope_level);
+            let name = format!("sort_by_{}", info.index);
+            let order = SharedOrder::new(cmp);
             move |input, output| {
                 input.for_each_batch(|dataset| {
                     if !dataset.is_empty() {
-                        let vec = map.get_mut_or_else(&dataset.tag, Vec::new);
+                        let sorter = map.get_mut_or_else(&dataset.tag, || {
+                            ExternalSorter::new(name.clone(), threshold, order.clone())
+                        });
                         for d in dataset.drain() {
-                            vec.push(d);
+                            sorter.push(d)?;
                         }
                     }
 
                     if dataset.is_last() {
                         let mut session = output.new_session(&dataset.tag)?;
-                        if let Some(mut vec) = map.remove(&dataset.tag) {
-                            vec.sort_by(|x, y| cmp(x, y));
-                            session.give_iterator(vec.into_iter())?;
+                        if let Some(sorter) = map.remove(&dataset.tag) {
+                            session.give_iterator(sorter.finish()?)?;
                         }
                     }
                     Ok(())
@@ -0,0 +1,496 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+//! Local files used by the blocking operators to hold the records exceeding their spill thresholds,
+//! the records are serialized with the codec of pegasus;
+
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+
+use crate::Data;
+
+/// Number of hash partitions the keyed operators spill their records into;
+pub(crate) const SPILL_PARTITIONS: usize = 16;
+
+/// The most sorted runs merged at once, which bounds the files a sorter keeps open;
+const MERGE_FAN_IN: usize = 16;
+
+static SPILL_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+#[inline]
+pub(crate) fn partition_of<K: Hash>(key: &K) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % SPILL_PARTITIONS as u64) as usize
+}
+
+/// The path of a spill file, the file is removed once the path is dropped;
+struct SpillPath(PathBuf);
+
+impl SpillPath {
+    fn new(name: &str) -> io::Result<Self> {
+        let owner = match crate::worker_id::get_current_worker_checked() {
+            Some(worker) => format!("{}_{}", worker.job_id, worker.index),
+            None => "local".to_owned(),
+        };
+        let mut path = PathBuf::from(&*crate::config::SPILL_DIR);
+        path.push("pegasus");
+        std::fs::create_dir_all(&path)?;
+        let seq = SPILL_SEQ.fetch_add(1, AtomicOrdering::SeqCst);
+        path.push(format!("{}_{}_{}_{}.spill", std::process::id(), owner, name, seq));
+        Ok(SpillPath(path))
+    }
+}
+
+impl Drop for SpillPath {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.0) {
+            warn_worker!("remove spill file {:?} failure: {}", self.0, e);
+        }
+    }
+}
+
+/// A file which records are appended to, and read back in the same order after all records are written;
+pub(crate) struct SpillFile<D: Data> {
+    writer: BufWriter<File>,
+    len: u64,
+    path: SpillPath,
+    _ph: std::marker::PhantomData<D>,
+}
+
+impl<D: Data> SpillFile<D> {
+    pub fn create(name: &str) -> io::Result<Self> {
+        let path = SpillPath::new(name)?;
+        let writer = BufWriter::new(File::create(&path.0)?);
+        trace_worker!("create spill file {:?};", path.0);
+        Ok(SpillFile { writer, len: 0, path, _ph: std::marker::PhantomData })
+    }
+
+    pub fn write(&mut self, item: &D) -> io::Result<()> {
+        item.write_to(&mut self.writer)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn into_reader(self) -> io::Result<SpillReader<D>> {
+        self.close()?.into_reader()
+    }
+
+    /// Close the file after all records are written, which can be read back later;
+    fn close(mut self) -> io::Result<ClosedSpillFile<D>> {
+        self.writer.flush()?;
+        Ok(ClosedSpillFile { len: self.len, path: self.path, _ph: std::marker::PhantomData })
+    }
+}
+
+/// A spill file holding no open file handle until it's read back;
+struct ClosedSpillFile<D: Data> {
+    len: u64,
+    path: SpillPath,
+    _ph: std::marker::PhantomData<D>,
+}
+
+impl<D: Data> ClosedSpillFile<D> {
+    fn into_reader(self) -> io::Result<SpillReader<D>> {
+        let reader = BufReader::new(File::open(&self.path.0)?);
+        Ok(SpillReader { reader, left: self.len, _path: self.path, _ph: std::marker::PhantomData })
+    }
+}
+
+pub(crate) struct SpillReader<D: Data> {
+    reader: BufReader<File>,
+    left: u64,
+    _path: SpillPath,
+    _ph: std::marker::PhantomData<D>,
+}
+
+impl<D: Data> Iterator for SpillReader<D> {
+    type Item = io::Result<D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.left == 0 {
+            None
+        } else {
+            self.left -= 1;
+            Some(D::read_from(&mut self.reader))
+        }
+    }
+}
+
+/// Records spilled into [`SPILL_PARTITIONS`] files by the hash of their keys, so that the records of
+/// the same key can be processed partition by partition;
+pub(crate) struct SpillPartitions<D: Data> {
+    name: String,
+    parts: Vec<Option<SpillFile<D>>>,
+}
+
+impl<D: Data> SpillPartitions<D> {
+    pub fn new(name: String) -> Self {
+        let mut parts = Vec::with_capacity(SPILL_PARTITIONS);
+        for _ in 0..SPILL_PARTITIONS {
+            parts.push(None);
+        }
+        SpillPartitions { name, parts }
+    }
+
+    pub fn write(&mut self, partition: usize, item: &D) -> io::Result<()> {
+        if self.parts[partition].is_none() {
+            self.parts[partition] = Some(SpillFile::create(&self.name)?);
+        }
+        self.parts[partition]
+            .as_mut()
+            .expect("unreachable")
+            .write(item)
+    }
+
+    /// Take the records spilled into the partition, which would be empty after taken;
+    pub fn take(&mut self, partition: usize) -> io::Result<Option<SpillReader<D>>> {
+        if let Some(file) = self.parts[partition].take() {
+            Ok(Some(file.into_reader()?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// The order of records sorted by [`ExternalSorter`];
+pub(crate) trait RunOrder<D>: Clone + Send + 'static {
+    fn sort(&self, items: &mut Vec<D>);
+
+    /// Call `func` with the comparator of the order, which is borrowed once for all the comparisons
+    /// made by `func`;
+    fn with_cmp<R, T: FnOnce(&dyn Fn(&D, &D) -> Ordering) -> R>(&self, func: T) -> R;
+}
+
+#[derive(Clone)]
+pub(crate) struct NaturalOrder;
+
+impl<D: Ord> RunOrder<D> for NaturalOrder {
+    fn sort(&self, items: &mut Vec<D>) {
+        items.sort();
+    }
+
+    fn with_cmp<R, T: FnOnce(&dyn Fn(&D, &D) -> Ordering) -> R>(&self, func: T) -> R {
+        func(&|a: &D, b: &D| a.cmp(b))
+    }
+}
+
+/// User defined order shared by the sorters of all scopes, and by the iterators merging the sorted runs,
+/// which is locked once for sorting a run or taking a merged record rather than for each comparison;
+pub(crate) struct SharedOrder<F> {
+    cmp: Arc<Mutex<F>>,
+}
+
+impl<F> SharedOrder<F> {
+    pub fn new(cmp: F) -> Self {
+        SharedOrder { cmp: Arc::new(Mutex::new(cmp)) }
+    }
+}
+
+impl<F> Clone for SharedOrder<F> {
+    fn clone(&self) -> Self {
+        SharedOrder { cmp: self.cmp.clone() }
+    }
+}
+
+impl<D, F: Fn(&D, &D) -> Ordering + Send + 'static> RunOrder<D> for SharedOrder<F> {
+    fn sort(&self, items: &mut Vec<D>) {
+        let cmp = self.cmp.lock().expect("lock poisoned");
+        items.sort_by(|a, b| (*cmp)(a, b));
+    }
+
+    fn with_cmp<R, T: FnOnce(&dyn Fn(&D, &D) -> Ordering) -> R>(&self, func: T) -> R {
+        let cmp = self.cmp.lock().expect("lock poisoned");
+        func(&*cmp)
+    }
+}
+
+/// Sort records in memory until the threshold is reached, after that the records are sorted and spilled
+/// into a run file, all runs are merged when all records have arrived;
+///
+/// The runs are closed once written, and merged in passes of at most [`MERGE_FAN_IN`] runs: whenever
+/// a level has [`MERGE_FAN_IN`] runs, they are merged into one run of the next level, so each record
+/// is merged a logarithmic number of times;
+pub(crate) struct ExternalSorter<D: Data, O: RunOrder<D>> {
+    name: String,
+    threshold: usize,
+    order: O,
+    buffer: Vec<D>,
+    /// the sorted runs by levels, a run of level `n` is merged from runs of level `n - 1`;
+    runs: Vec<Vec<ClosedSpillFile<D>>>,
+}
+
+impl<D: Data, O: RunOrder<D>> ExternalSorter<D, O> {
+    pub fn new(name: String, threshold: usize, order: O) -> Self {
+        ExternalSorter { name, threshold, order, buffer: vec![], runs: vec![] }
+    }
+
+    pub fn push(&mut self, item: D) -> io::Result<()> {
+        self.buffer.push(item);
+        if self.buffer.len() >= self.threshold {
+            self.order.sort(&mut self.buffer);
+            let mut run = SpillFile::create(&self.name)?;
+            for item in self.buffer.drain(..) {
+                run.write(&item)?;
+            }
+            trace_worker!("spill sorted run of {} records;", run.len);
+            self.add_run(run.close()?)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<Box<dyn Iterator<Item = D> + Send + 'static>> {
+        self.order.sort(&mut self.buffer);
+        // runs of higher levels hold earlier records, so the runs are merged from the highest level
+        // down, and the buffer last, which keeps equal records in their arriving order as the
+        // in-memory sort does;
+        let mut runs: Vec<ClosedSpillFile<D>> = self.runs.drain(..).rev().flatten().collect();
+        if runs.is_empty() {
+            return Ok(Box::new(self.buffer.into_iter()));
+        }
+        while runs.len() >= MERGE_FAN_IN {
+            let merged = self.merge(runs.drain(..MERGE_FAN_IN).collect())?;
+            runs.insert(0, merged);
+        }
+        let mut sources: Vec<Box<dyn Iterator<Item = D> + Send + 'static>> =
+            Vec::with_capacity(runs.len() + 1);
+        for run in runs {
+            sources.push(Box::new(SpilledIter(run.into_reader()?)));
+        }
+        sources.push(Box::new(self.buffer.into_iter()));
+        Ok(Box::new(MergeRuns::new(self.order, sources)))
+    }
+
+    fn add_run(&mut self, mut run: ClosedSpillFile<D>) -> io::Result<()> {
+        let mut level = 0;
+        loop {
+            if self.runs.len() <= level {
+                self.runs.push(vec![]);
+            }
+            self.runs[level].push(run);
+            if self.runs[level].len() < MERGE_FAN_IN {
+                return Ok(());
+            }
+            let runs = std::mem::replace(&mut self.runs[level], vec![]);
+            run = self.merge(runs)?;
+            level += 1;
+        }
+    }
+
+    /// Merge the runs into one run, the merged runs are removed after that;
+    fn merge(&self, runs: Vec<ClosedSpillFile<D>>) -> io::Result<ClosedSpillFile<D>> {
+        let mut sources: Vec<Box<dyn Iterator<Item = D> + Send + 'static>> = Vec::with_capacity(runs.len());
+        for run in runs {
+            sources.push(Box::new(SpilledIter(run.into_reader()?)));
+        }
+        let mut merged = SpillFile::create(&self.name)?;
+        for item in MergeRuns::new(self.order.clone(), sources) {
+            merged.write(&item)?;
+        }
+        trace_worker!("merge sorted runs into a run of {} records;", merged.len);
+        merged.close()
+    }
+}
+
+/// The records were written by this process just before, any failure on reading them back means the
+/// local disk is broken, which can't be recovered;
+struct SpilledIter<D: Data>(SpillReader<D>);
+
+impl<D: Data> Iterator for SpilledIter<D> {
+    type Item = D;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next()
+            .map(|r| r.unwrap_or_else(|e| panic!("read spilled records failure: {}", e)))
+    }
+}
+
+/// The head record of a run in the heap of [`MergeRuns`];
+struct RunHead<D> {
+    item: D,
+    source: usize,
+}
+
+/// Merge sorted runs with a binary heap of their head records, the least record is taken first, and
+/// the one of the earlier run on ties;
+struct MergeRuns<D, O> {
+    order: O,
+    heads: Vec<RunHead<D>>,
+    sources: Vec<Box<dyn Iterator<Item = D> + Send + 'static>>,
+}
+
+impl<D, O: RunOrder<D>> MergeRuns<D, O> {
+    fn new(order: O, mut sources: Vec<Box<dyn Iterator<Item = D> + Send + 'static>>) -> Self {
+        let mut heads = Vec::with_capacity(sources.len());
+        for (source, s) in sources.iter_mut().enumerate() {
+            if let Some(item) = s.next() {
+                heads.push(RunHead { item, source });
+            }
+        }
+        order.with_cmp(|cmp| {
+            for i in (0..heads.len() / 2).rev() {
+                sift_down(&mut heads, i, cmp);
+            }
+        });
+        MergeRuns { order, heads, sources }
+    }
+}
+
+impl<D, O: RunOrder<D>> Iterator for MergeRuns<D, O> {
+    type Item = D;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.heads.is_empty() {
+            return None;
+        }
+        let item = match self.sources[self.heads[0].source].next() {
+            Some(next) => std::mem::replace(&mut self.heads[0].item, next),
+            None => self.heads.swap_remove(0).item,
+        };
+        let heads = &mut self.heads;
+        self.order
+            .with_cmp(|cmp| sift_down(heads, 0, cmp));
+        Some(item)
+    }
+}
+
+#[inline]
+fn is_before<D>(a: &RunHead<D>, b: &RunHead<D>, cmp: &dyn Fn(&D, &D) -> Ordering) -> bool {
+    cmp(&a.item, &b.item)
+        .then_with(|| a.source.cmp(&b.source))
+        == Ordering::Less
+}
+
+fn sift_down<D>(heads: &mut [RunHead<D>], mut i: usize, cmp: &dyn Fn(&D, &D) -> Ordering) {
+    loop {
+        let mut first = i;
+        for child in [2 * i + 1, 2 * i + 2].iter() {
+            if *child < heads.len() && is_before(&heads[*child], &heads[first], cmp) {
+                first = *child;
+            }
+        }
+        if first == i {
+            return;
+        }
+        heads.swap(i, first);
+        i = first;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merge_runs_test() {
+        let mut sources: Vec<Box<dyn Iterator<Item = u32> + Send + 'static>> = vec![];
+        sources.push(Box::new(vec![1, 4, 7].into_iter()));
+        sources.push(Box::new(vec![2, 5, 8, 9].into_iter()));
+        sources.push(Box::new(vec![].into_iter()));
+        sources.push(Box::new(vec![0, 3, 6].into_iter()));
+        let merged = MergeRuns::new(NaturalOrder, sources).collect::<Vec<_>>();
+        assert_eq!(merged, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn external_sort_test() {
+        let mut sorter =
+            ExternalSorter::new("sort_test".to_owned(), 100, SharedOrder::new(|a: &u32, b: &u32| b.cmp(a)));
+        for i in 0..1000u32 {
+            sorter.push((i * 7919) % 1000).unwrap();
+        }
+        assert_eq!(sorter.runs.len(), 1);
+        assert_eq!(sorter.runs[0].len(), 10);
+        let sorted = sorter.finish().unwrap().collect::<Vec<_>>();
+        assert_eq!(sorted, (0..1000).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn external_sort_multi_pass_test() {
+        let mut sorter = ExternalSorter::new("multi_pass_test".to_owned(), 10, NaturalOrder);
+        for i in 0..10_000u32 {
+            sorter.push((i * 7919) % 10_000).unwrap();
+            // no more than `MERGE_FAN_IN` runs of each level are kept;
+            assert!(sorter
+                .runs
+                .iter()
+                .all(|runs| runs.len() < MERGE_FAN_IN));
+        }
+        // 1000 runs are merged into 3 levels: 1000 = 3 * 256 + 14 * 16 + 8;
+        let runs = sorter
+            .runs
+            .iter()
+            .map(|runs| runs.len())
+            .collect::<Vec<_>>();
+        assert_eq!(runs, vec![8, 14, 3]);
+        let sorted = sorter.finish().unwrap().collect::<Vec<_>>();
+        assert_eq!(sorted, (0..10_000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn merge_runs_stable_test() {
+        let mut sources: Vec<Box<dyn Iterator<Item = (u32, u32)> + Send + 'static>> = vec![];
+        sources.push(Box::new(vec![(1, 0), (2, 0)].into_iter()));
+        sources.push(Box::new(vec![(1, 1), (2, 1)].into_iter()));
+        // records of equal keys are merged in the order of runs;
+        let order = SharedOrder::new(|a: &(u32, u32), b: &(u32, u32)| a.0.cmp(&b.0));
+        let merged = MergeRuns::new(order, sources).collect::<Vec<_>>();
+        assert_eq!(merged, vec![(1, 0), (1, 1), (2, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn external_sort_stable_test() {
+        // 4 keys of 1000 records each, which are spilled into runs of 3 levels;
+        let order = SharedOrder::new(|a: &(u32, u32), b: &(u32, u32)| a.0.cmp(&b.0));
+        let mut sorter = ExternalSorter::new("stable_test".to_owned(), 5, order);
+        let records = (0..4000u32)
+            .map(|i| ((i * 7919) % 4, i))
+            .collect::<Vec<_>>();
+        for r in records.iter() {
+            sorter.push(*r).unwrap();
+        }
+        assert_eq!(sorter.runs.len(), 3);
+        let sorted = sorter.finish().unwrap().collect::<Vec<_>>();
+        let mut expected = records;
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn spill_partitions_test() {
+        let mut parts = SpillPartitions::new("partitions_test".to_owned());
+        for i in 0..1000u64 {
+            parts.write(partition_of(&i), &i).unwrap();
+        }
+        let mut count = 0;
+        for p in 0..SPILL_PARTITIONS {
+            if let Some(reader) = parts.take(p).unwrap() {
+                for item in reader {
+                    assert_eq!(partition_of(&item.unwrap()), p);
+                    count += 1;
+                }
+            }
+            assert!(parts.take(p).unwrap().is_none());
+        }
+        assert_eq!(count, 1000);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::api::{Branch, SwitchFn};
+use crate::macros::map::FnResult;
+use crate::stream::Stream;
+use crate::tag::tools::map::TidyTagMap;
+use crate::{BuildJobError, Data};
+
+impl<D: Data> SwitchFn<D> for Stream<D> {
+    fn switch<L, R, F>(self, func: F) -> Result<(Stream<L>, Stream<R>), BuildJobError>
+    where
+        L: Data,
+        R: Data,
+        D: Into<L> + Into<R>,
+        F: Fn(&D) -> FnResult<bool> + Send + 'static,
+    {
+        self.branch("switch", |_info| {
+            move |input, left, right| {
+                input.for_each_batch(|dataset| {
+                    if !dataset.is_empty() {
+                        let mut left_session = left.new_session(&dataset.tag)?;
+                        let mut right_session = right.new_session(&dataset.tag)?;
+                        for item in dataset.drain() {
+                            if func(&item)? {
+                                left_session.give(Into::<L>::into(item))?;
+                            } else {
+                                right_session.give(Into::<R>::into(item))?;
+                            }
+                        }
+                    }
+                    Ok(())
+                })
+            }
+        })
+    }
+
+    fn switch_mut<L, R, F, B>(self, builder: B) -> Result<(Stream<L>, Stream<R>), BuildJobError>
+    where
+        L: Data,
+        R: Data,
+        D: Into<L> + Into<R>,
+        F: FnMut(&D) -> FnResult<bool> + Send + 'static,
+        B: Fn() -> F + Send + 'static,
+    {
+        self.branch("switch_mut", |info| {
+            let mut table = TidyTagMap::<F>::new(info.scope_level);
+            move |input, left, right| {
+                input.for_each_batch(|dataset| {
+                    if !dataset.is_empty() {
+                        let func = table.get_mut_or_else(&dataset.tag, &builder);
+                        let mut left_session = left.new_session(&dataset.tag)?;
+                        let mut right_session = right.new_session(&dataset.tag)?;
+                        for item in dataset.drain() {
+                            if func(&item)? {
+                                left_session.give(Into::<L>::into(item))?;
+                            } else {
+                                right_session.give(Into::<R>::into(item))?;
+                            }
+                        }
+                    }
+                    if dataset.is_last() {
+                        table.remove(&dataset.tag);
+                    }
+                    Ok(())
+                })
+            }
+        })
+    }
+}
@@ -0,0 +1,138 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::collections::BTreeMap;
+
+use pegasus_common::buffer::{BufferPool, MemBufAlloc};
+
+use crate::api::{Unary, Window};
+use crate::errors::{BuildJobError, IOError, JobExecError};
+use crate::operator::concise::correlate::new_batch;
+use crate::progress::{EndSignal, Weight};
+use crate::stream::Stream;
+use crate::tag::tools::map::TidyTagMap;
+use crate::{Data, Tag};
+
+/// The windows of a parent scope which are not closed yet;
+struct Windows<D> {
+    /// the max event time seen, the watermark is `max_time - delay`;
+    max_time: u64,
+    /// items of the open windows, by the numbers of windows, so the windows are closed in order;
+    open: BTreeMap<u64, Vec<D>>,
+}
+
+impl<D> Default for Windows<D> {
+    fn default() -> Self {
+        Windows { max_time: 0, open: BTreeMap::new() }
+    }
+}
+
+impl<D: Data> Window<D> for Stream<D> {
+    fn tumbling_window<F>(self, size: u64, delay: u64, event_time: F) -> Result<Stream<D>, BuildJobError>
+    where
+        F: Fn(&D) -> u64 + Send + 'static,
+    {
+        self.sliding_window(size, size, delay, event_time)
+    }
+
+    fn sliding_window<F>(
+        self, size: u64, slide: u64, delay: u64, event_time: F,
+    ) -> Result<Stream<D>, BuildJobError>
+    where
+        F: Fn(&D) -> u64 + Send + 'static,
+    {
+        if size == 0 || slide == 0 {
+            return Err(BuildJobError::from(format!(
+                "invalid window of size {} and slide {}",
+                size, slide
+            )));
+        }
+        // the end of the k-th window, after which the window can be closed;
+        let end_of = move |k: u64| {
+            k.saturating_mul(slide)
+                .saturating_add(size)
+                .saturating_add(delay)
+        };
+        // all items of a parent scope go to one worker for a consistent watermark, see `Window`;
+        let entered = self.aggregate().enter()?;
+        let scope_capacity = entered.get_scope_capacity();
+        entered.unary("window", move |info| {
+            assert!(info.scope_level > 0);
+            let worker = crate::worker_id::get_current_worker().index;
+            let mut parents = TidyTagMap::<Windows<D>>::new(info.scope_level - 1);
+            let mut buf_pool = BufferPool::new(1, scope_capacity as usize, MemBufAlloc::new());
+            move |input, output| {
+                input.for_each_batch(|dataset| {
+                    let p = dataset.tag.to_parent_uncheck();
+                    let windows = parents.get_mut_or_insert(&p);
+                    let mut late = 0;
+                    for item in dataset.drain() {
+                        let time = event_time(&item);
+                        windows.max_time = windows.max_time.max(time);
+                        // the windows `[k * slide, k * slide + size)` holding the time, none if the
+                        // time falls into the gap between windows;
+                        let first = if time >= size { (time - size) / slide + 1 } else { 0 };
+                        for k in first..=time / slide {
+                            if end_of(k) <= windows.max_time {
+                                late += 1;
+                            } else {
+                                windows
+                                    .open
+                                    .entry(k)
+                                    .or_insert_with(Vec::new)
+                                    .push(item.clone());
+                            }
+                        }
+                    }
+                    if late > 0 {
+                        debug_worker!("drop {} late items of windows in {:?};", late, p);
+                    }
+
+                    // close all windows if the input ends, otherwise the windows the watermark passes;
+                    let all = dataset.is_last();
+                    while let Some(&k) = windows.open.keys().next() {
+                        if !all && end_of(k) > windows.max_time {
+                            break;
+                        }
+                        if k >= u32::MAX as u64 {
+                            let msg =
+                                format!("window {} out of range, the event time should be relative;", k);
+                            return Err(JobExecError::from(msg));
+                        }
+                        if let Some(mut buf) = buf_pool.fetch() {
+                            let tag = Tag::inherit(&p, k as u32 + 1);
+                            trace_worker!("close window {:?} from {:?};", tag, p);
+                            for item in windows.open.remove(&k).expect("unreachable") {
+                                buf.push(item);
+                            }
+                            let mut batch = new_batch(tag.clone(), worker, buf);
+                            batch.set_end(EndSignal::new(tag, Weight::single(worker)));
+                            output.push_batch(batch)?;
+                        } else {
+                            would_block!("no buffer available for new window;")?
+                        }
+                    }
+
+                    if all {
+                        parents.remove(&p);
+                        // the scope entered is not a window, its end is replaced by those of windows;
+                        dataset.take_end();
+                    }
+                    Ok(())
+                })
+            }
+        })
+    }
+}
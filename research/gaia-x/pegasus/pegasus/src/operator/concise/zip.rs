@@ -0,0 +1,74 @@
+use std::collections::VecDeque;
+
+use crate::api::{Binary, Zip};
+use crate::stream::Stream;
+use crate::tag::tools::map::TidyTagMap;
+use crate::{BuildJobError, Data};
+
+/// The data of a scope waiting to be paired, at most one side of which is non-empty.
+struct ZipBuffer<L, R> {
+    left: VecDeque<L>,
+    right: VecDeque<R>,
+    left_end: bool,
+    right_end: bool,
+}
+
+impl<L, R> Default for ZipBuffer<L, R> {
+    fn default() -> Self {
+        ZipBuffer { left: VecDeque::new(), right: VecDeque::new(), left_end: false, right_end: false }
+    }
+}
+
+impl<D: Data> Zip<D> for Stream<D> {
+    fn zip<T: Data>(self, other: Stream<T>) -> Result<Stream<(D, T)>, BuildJobError> {
+        self.binary("zip", other, |info| {
+            let mut table = TidyTagMap::<ZipBuffer<D, T>>::new(info.scope_level);
+            move |left, right, output| {
+                left.for_each_batch(|dataset| {
+                    let buf = table.get_mut_or_else(&dataset.tag, ZipBuffer::default);
+                    if !dataset.is_empty() {
+                        let mut session = output.new_session(&dataset.tag)?;
+                        for l in dataset.drain() {
+                            if let Some(r) = buf.right.pop_front() {
+                                session.give((l, r))?;
+                            } else if !buf.right_end {
+                                buf.left.push_back(l);
+                            }
+                        }
+                    }
+                    if dataset.is_last() {
+                        buf.left_end = true;
+                        // nothing will come to pair with the rest of the right side;
+                        buf.right.clear();
+                        if buf.right_end {
+                            table.remove(&dataset.tag);
+                        }
+                    }
+                    Ok(())
+                })?;
+                right.for_each_batch(|dataset| {
+                    let buf = table.get_mut_or_else(&dataset.tag, ZipBuffer::default);
+                    if !dataset.is_empty() {
+                        let mut session = output.new_session(&dataset.tag)?;
+                        for r in dataset.drain() {
+                            if let Some(l) = buf.left.pop_front() {
+                                session.give((l, r))?;
+                            } else if !buf.left_end {
+                                buf.right.push_back(r);
+                            }
+                        }
+                    }
+                    if dataset.is_last() {
+                        buf.right_end = true;
+                        // nothing will come to pair with the rest of the left side;
+                        buf.left.clear();
+                        if buf.left_end {
+                            table.remove(&dataset.tag);
+                        }
+                    }
+                    Ok(())
+                })
+            }
+        })
+    }
+}
@@ -0,0 +1,259 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use crate::api::iteration::ResourceCheckpoint;
+use crate::codec::{Decode, Encode};
+use crate::config::JobConf;
+use crate::tag::tools::map::TidyTagMap;
+use crate::{Data, Tag};
+
+const ENTER: u8 = 0;
+const LEAVE: u8 = 1;
+const FINISH: u8 = 2;
+const STATE: u8 = 3;
+
+/// The iteration to resume from, agreed by all workers before any data enters the iteration, and
+/// shared by the operators of the iteration on this worker;
+#[derive(Clone, Default)]
+pub(crate) struct ResumePoint {
+    /// the number of iterations skipped, i.e. the iteration resumed from plus one, or 0 if not resumed;
+    skipped: Arc<AtomicU32>,
+}
+
+impl ResumePoint {
+    pub fn set(&self, resume: Option<u32>) {
+        self.skipped
+            .store(resume.map(|k| k + 1).unwrap_or(0), Ordering::SeqCst);
+    }
+
+    pub fn get(&self) -> Option<u32> {
+        self.skipped.load(Ordering::SeqCst).checked_sub(1)
+    }
+}
+
+/// The checkpoint files of an iteration written by this worker, under `JobConf::checkpoint_dir`, named
+/// by the job id, the index of the first operator of the iteration, the iteration and the worker, e.g.
+/// `{checkpoint_dir}/{job_id}/iter_{op}_{n}_{worker}.ckpt`.
+///
+/// A worker only reads and writes its own checkpoints, so the directory is not required to be shared
+/// by the servers, as long as it is kept by each server between the runs of the job;
+#[derive(Clone)]
+pub(crate) struct CheckpointFiles {
+    dir: PathBuf,
+    op: usize,
+    worker: u32,
+}
+
+impl CheckpointFiles {
+    pub fn open(conf: &JobConf, op: usize) -> io::Result<Self> {
+        let mut dir = PathBuf::from(conf.checkpoint_dir.as_ref().expect("checkpoint dir not set;"));
+        dir.push(conf.job_id.to_string());
+        std::fs::create_dir_all(&dir)?;
+        let worker = crate::worker_id::get_current_worker().index;
+        Ok(CheckpointFiles { dir, op, worker })
+    }
+
+    /// The number of checkpoints this worker has written for the first iterations, without any
+    /// iteration missed, the iteration can be resumed from any of them on this worker;
+    pub fn local_view(&self) -> io::Result<u32> {
+        let written = self.list()?;
+        let mut n = 0;
+        while written.contains(&n) {
+            n += 1;
+        }
+        Ok(n)
+    }
+
+    /// Remove the checkpoints written after the one to resume from, or all if not resumed, as they
+    /// would be written again by this run;
+    pub fn remove_stale(&self, resume: Option<u32>) -> io::Result<()> {
+        for n in self.list()? {
+            if resume.map(|k| n > k).unwrap_or(true) {
+                std::fs::remove_file(self.path_of(n))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The iterations of the checkpoints written by this worker;
+    fn list(&self) -> io::Result<Vec<u32>> {
+        let prefix = format!("iter_{}_", self.op);
+        let suffix = format!("_{}.ckpt", self.worker);
+        let mut written = vec![];
+        for entry in std::fs::read_dir(&self.dir)? {
+            let name = entry?.file_name();
+            let name = name.to_string_lossy();
+            let n = name
+                .strip_prefix(&prefix)
+                .and_then(|s| s.strip_suffix(&suffix))
+                .and_then(|s| s.parse().ok());
+            if let Some(n) = n {
+                written.push(n);
+            }
+        }
+        Ok(written)
+    }
+
+    fn path_of(&self, n: u32) -> PathBuf {
+        self.dir
+            .join(format!("iter_{}_{}_{}.ckpt", self.op, n, self.worker))
+    }
+
+    fn tmp_path_of(&self, n: u32) -> PathBuf {
+        self.dir
+            .join(format!("iter_{}_{}_{}.tmp", self.op, n, self.worker))
+    }
+}
+
+/// The data switched into and out of an iteration, kept until the iteration ends on this worker;
+struct Snapshot<D: Data> {
+    tmp: PathBuf,
+    writer: BufWriter<File>,
+    _ph: std::marker::PhantomData<D>,
+}
+
+impl<D: Data> Snapshot<D> {
+    fn create(tmp: PathBuf) -> io::Result<Self> {
+        let writer = BufWriter::new(File::create(&tmp)?);
+        Ok(Snapshot { tmp, writer, _ph: std::marker::PhantomData })
+    }
+
+    fn write(&mut self, mark: u8, item: &D) -> io::Result<()> {
+        mark.write_to(&mut self.writer)?;
+        item.write_to(&mut self.writer)
+    }
+
+    fn write_state(&mut self, index: u32, state: Vec<u8>) -> io::Result<()> {
+        STATE.write_to(&mut self.writer)?;
+        index.write_to(&mut self.writer)?;
+        state.write_to(&mut self.writer)
+    }
+
+    /// Rename the snapshot to its final path, so that only complete snapshots are seen by the resume;
+    fn finish(mut self, path: &PathBuf) -> io::Result<()> {
+        FINISH.write_to(&mut self.writer)?;
+        self.writer.flush()?;
+        self.writer.get_ref().sync_all()?;
+        std::fs::rename(&self.tmp, path)
+    }
+}
+
+/// Checkpoints of an iteration on this worker, each of which snapshots the data entering the `n`th
+/// iteration, the data leaving the iteration before it, and the resources checkpointed by the
+/// iteration(see `IterCondition::checkpoint_resource`), written once the `n`th iteration has received
+/// all its input on this worker.
+///
+/// A job restarted with the same job id can skip the first `n` iterations by feeding the snapshots
+/// back if all workers have written their checkpoints of the first `n` iterations, which is agreed
+/// by the workers through the dataflow before the input enters the iteration, see `ResumePoint`.
+/// The state of operators is not snapshotted, so the body of the iteration is required to keep no
+/// state across iterations, see `Iteration::iterate_until`.
+///
+/// Only iterations entered from the root scope are checkpointed, as scopes of the parents can't be
+/// recovered.
+pub(crate) struct IterCheckpoint<D: Data> {
+    files: CheckpointFiles,
+    point: ResumePoint,
+    resources: Vec<ResourceCheckpoint>,
+    snapshots: TidyTagMap<Snapshot<D>>,
+}
+
+impl<D: Data> IterCheckpoint<D> {
+    pub fn new(
+        files: CheckpointFiles, point: ResumePoint, resources: Vec<ResourceCheckpoint>, scope_level: u32,
+    ) -> Self {
+        IterCheckpoint { files, point, resources, snapshots: TidyTagMap::new(scope_level) }
+    }
+
+    /// The iteration to resume from, the data entering it and all data left before it are restored;
+    pub fn resume_point(&self) -> Option<u32> {
+        self.point.get()
+    }
+
+    pub fn record_enter(&mut self, tag: &Tag, item: &D) -> io::Result<()> {
+        self.record(tag, ENTER, item)
+    }
+
+    pub fn record_leave(&mut self, tag: &Tag, item: &D) -> io::Result<()> {
+        self.record(tag, LEAVE, item)
+    }
+
+    /// Write the checkpoint of the iteration of the tag, as all data of the iteration has been switched;
+    pub fn finish(&mut self, tag: &Tag) -> io::Result<()> {
+        let n = tag.current_uncheck();
+        if self.resume_point().map(|k| n <= k).unwrap_or(false) {
+            return Ok(());
+        }
+        let mut snapshot = match self.snapshots.remove(tag) {
+            Some(snapshot) => snapshot,
+            None => Snapshot::create(self.files.tmp_path_of(n))?,
+        };
+        for (i, res) in self.resources.iter().enumerate() {
+            let mut state = vec![];
+            (res.snapshot)(&mut state)?;
+            snapshot.write_state(i as u32, state)?;
+        }
+        snapshot.finish(&self.files.path_of(n))?;
+        trace_worker!("write checkpoint of {:?};", tag);
+        Ok(())
+    }
+
+    /// Read the data entering the resumed iteration, and the data left the iteration before it, the
+    /// checkpointed resources are restored as they were when the resumed iteration began;
+    pub fn restore(&self) -> io::Result<(Vec<D>, Vec<D>)> {
+        let k = self
+            .resume_point()
+            .expect("no checkpoint to restore;");
+        let mut enter = vec![];
+        let mut leave = vec![];
+        for n in 0..=k {
+            let mut reader = BufReader::new(File::open(self.files.path_of(n))?);
+            loop {
+                match u8::read_from(&mut reader)? {
+                    ENTER => {
+                        let item = D::read_from(&mut reader)?;
+                        if n == k {
+                            enter.push(item);
+                        }
+                    }
+                    LEAVE => leave.push(D::read_from(&mut reader)?),
+                    STATE => {
+                        let index = u32::read_from(&mut reader)? as usize;
+                        let state = Vec::<u8>::read_from(&mut reader)?;
+                        if n == k {
+                            let res = self.resources.get(index).ok_or_else(|| {
+                                let msg = format!("unknown resource {} in checkpoint {}", index, n);
+                                io::Error::new(io::ErrorKind::InvalidData, msg)
+                            })?;
+                            trace_worker!("restore resource {} from checkpoint {};", res.name, n);
+                            (res.restore)(&state)?;
+                        }
+                    }
+                    FINISH => break,
+                    mark => {
+                        let msg = format!(
+                            "unknown mark {} in checkpoint {} of iteration {}",
+                            mark, n, self.files.op
+                        );
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+                    }
+                }
+            }
+        }
+        Ok((enter, leave))
+    }
+
+    fn record(&mut self, tag: &Tag, mark: u8, item: &D) -> io::Result<()> {
+        if !self.snapshots.contains_key(tag) {
+            let snapshot = Snapshot::create(self.files.tmp_path_of(tag.current_uncheck()))?;
+            self.snapshots.insert(tag.clone(), snapshot);
+        }
+        self.snapshots
+            .get_mut(tag)
+            .expect("unreachable")
+            .write(mark, item)
+    }
+}
@@ -2,6 +2,7 @@ use crate::api::notification::{CancelScope, EndScope};
 use crate::communication::input::{new_input_session, InputProxy};
 use crate::communication::output::{new_output, OutputProxy};
 use crate::errors::JobExecError;
+use crate::operator::iteration::checkpoint::ResumePoint;
 use crate::operator::{Notifiable, OperatorCore};
 use crate::progress::{EndSignal, Weight};
 use crate::tag::tools::map::TidyTagMap;
@@ -73,15 +74,18 @@ impl<D: Data> Notifiable for IterSyncOperator<D> {
 pub(crate) struct FeedbackOperator<D: Data> {
     pub _scope_level: u32,
     max_iters: u32,
+    /// the iteration resumed from, the first iteration is 0 if not resumed;
+    start: ResumePoint,
     observer: TidyTagMap<()>,
     _ph: std::marker::PhantomData<D>,
 }
 
 impl<D: Data> FeedbackOperator<D> {
-    pub fn new(_scope_level: u32, max_iters: u32) -> Self {
+    pub fn new(_scope_level: u32, max_iters: u32, start: ResumePoint) -> Self {
         FeedbackOperator {
             _scope_level,
             max_iters,
+            start,
             observer: TidyTagMap::new(_scope_level - 1),
             _ph: std::marker::PhantomData,
         }
@@ -104,14 +108,14 @@ impl<D: Data> OperatorCore for FeedbackOperator<D> {
                 assert!(end.tag.len() > 0);
                 let cur = end.tag.current_uncheck();
                 let p = end.tag.to_parent_uncheck();
-                if cur == 0 {
-                    debug_worker!("observe {:?} in iteration;", p);
-                    self.observer.insert(p, ());
-                    session.notify_end(end)?;
-                } else if cur == self.max_iters - 1 {
+                if cur == self.max_iters - 1 {
                     debug_worker!("observe {:?} out iteration;", p);
                     self.observer.remove(&p);
                     session.notify_end(end)?;
+                } else if cur == self.start.get().unwrap_or(0) {
+                    debug_worker!("observe {:?} in iteration;", p);
+                    self.observer.insert(p, ());
+                    session.notify_end(end)?;
                 } else if self.observer.contains_key(&p) {
                     session.notify_end(end)?;
                 } else {
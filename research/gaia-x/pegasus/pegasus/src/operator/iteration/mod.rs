@@ -1,10 +1,12 @@
-use crate::api::{IterCondition, Iteration};
+use crate::api::{Binary, IterCondition, Iteration, Unary};
 use crate::macros::filter::*;
 use crate::stream::Stream;
 use crate::{BuildJobError, Data};
 
+mod checkpoint;
 mod feedback;
 mod switch;
+use checkpoint::{CheckpointFiles, IterCheckpoint, ResumePoint};
 use feedback::{FeedbackOperator, IterSyncOperator};
 use switch::SwitchOperator;
 
@@ -32,23 +34,126 @@ impl<D: Data> Iteration<D> for Stream<D> {
         F: FnOnce(Stream<D>) -> Result<Stream<D>, BuildJobError>,
     {
         let max_iters = until.max_iters;
-        let (leave, enter) = self
+        let point = ResumePoint::default();
+        let (input, files) = if self.get_conf().checkpoint_dir.is_none() {
+            (self, None)
+        } else if self.get_scope_level() != 0 {
+            debug_worker!("checkpoint of nested iteration is not supported;");
+            (self, None)
+        } else {
+            let (input, files) = agree_resume_point(self, &point)?;
+            (input, Some(files))
+        };
+        let checkpointed = files.is_some();
+        let resume = point.clone();
+        let (leave, enter) = input
             .enter()?
-            .binary_branch_notify("switch", |info| SwitchOperator::<D>::new(info.scope_level, until))?;
+            .binary_branch_notify("switch", |info| {
+                let checkpoint = files.map(|files| {
+                    let resources = until.resources.clone();
+                    IterCheckpoint::new(files, resume, resources, info.scope_level)
+                });
+                SwitchOperator::<D>::new(info.scope_level, until, checkpoint)
+            })?;
 
         let index = enter.port().index;
         let after_iter = func(enter)?;
+        if checkpointed {
+            // the state of operators in the body is not checkpointed, it would be lost on resume;
+            let stateful = after_iter.stateful_operators_after(index);
+            if !stateful.is_empty() {
+                let msg = format!("can't checkpoint iteration with stateful operators {:?};", stateful);
+                return BuildJobError::unsupported(msg);
+            }
+        }
         let (pipeline, sync): (Stream<D>, Stream<D>) =
             after_iter.binary_branch_notify("sync", |info| IterSyncOperator::<D>::new(info.scope_level))?;
         let sync = sync.broadcast();
         let feedback: Stream<D> = pipeline.union_notify_transform("feedback", sync, move |info| {
-            FeedbackOperator::<D>::new(info.scope_level, max_iters)
+            FeedbackOperator::<D>::new(info.scope_level, max_iters, point)
         })?;
         feedback.feedback_to(index)?;
         leave.leave()
     }
 }
 
+/// Agree on the checkpoint to resume the iteration from with all workers, before the input enters the
+/// iteration. Each worker counts the checkpoints it has written for the first iterations, and
+/// broadcasts the count to all workers, so the iteration is resumed from the latest checkpoint that
+/// all workers have written. The input is held until the agreement is reached, then the checkpoints
+/// of this worker after the agreed one are removed, as they are written again by this run;
+fn agree_resume_point<D: Data>(
+    stream: Stream<D>, point: &ResumePoint,
+) -> Result<(Stream<D>, CheckpointFiles), BuildJobError> {
+    let conf = stream.get_conf();
+    let resume = conf.resume;
+    let mut files = None;
+    let mut open_err = None;
+    let (main, copy) = stream.copied()?;
+    let views = copy
+        .unary("resume_view", |info| {
+            let mut view_of = match CheckpointFiles::open(&conf, info.index) {
+                Ok(opened) => {
+                    files = Some(opened.clone());
+                    Some(opened)
+                }
+                Err(e) => {
+                    open_err = Some(e);
+                    None
+                }
+            };
+            move |input, output| {
+                input.for_each_batch(|dataset| {
+                    dataset.clear();
+                    if let Some(files) = view_of.take() {
+                        let view = if resume { files.local_view()? } else { 0 };
+                        trace_worker!("has written {} checkpoints of iteration;", view);
+                        output.new_session(&dataset.tag)?.give(view)?;
+                    }
+                    Ok(())
+                })
+            }
+        })?
+        .broadcast();
+    if let Some(e) = open_err {
+        return Err(BuildJobError::from(format!("open checkpoints of iteration failure: {}", e)));
+    }
+    let files = files.expect("checkpoints not opened;");
+
+    let stale = files.clone();
+    let point = point.clone();
+    let peers = crate::worker_id::get_current_worker().total_peers() as usize;
+    let input = main.binary("resume", views, |_info| {
+        let mut received = vec![];
+        let mut agreed = false;
+        move |input, views, output| {
+            views.for_each_batch(|dataset| {
+                received.extend(dataset.drain());
+                Ok(())
+            })?;
+            if !agreed {
+                if resume && received.len() < peers {
+                    // keep the input until all workers have told their checkpoints;
+                    return Ok(());
+                }
+                let skipped = if resume { received.iter().min().copied().unwrap_or(0) } else { 0 };
+                let k = skipped.checked_sub(1);
+                if resume {
+                    info_worker!("resume iteration from checkpoint {:?};", k);
+                }
+                stale.remove_stale(k)?;
+                point.set(k);
+                agreed = true;
+            }
+            input.for_each_batch(|dataset| {
+                output.push_batch_mut(dataset)?;
+                Ok(())
+            })
+        }
+    })?;
+    Ok((input, files))
+}
+
 impl<D: 'static + Send> IterCondition<D> {
     pub fn until<F>(&mut self, func: F)
     where
@@ -7,14 +7,16 @@ use crate::communication::output::{new_output, OutputProxy};
 use crate::communication::Output;
 use crate::data::{MarkedData, MicroBatch};
 use crate::errors::JobExecError;
-use crate::operator::{Notifiable, OperatorCore};
-use crate::progress::EndSignal;
+use crate::operator::iteration::checkpoint::IterCheckpoint;
+use crate::operator::{first_err, Notifiable, OperatorCore};
+use crate::progress::{EndSignal, Weight};
 use crate::tag::tools::map::TidyTagMap;
-use crate::Data;
+use crate::{Data, Tag};
 
-pub(crate) struct SwitchOperator<D> {
+pub(crate) struct SwitchOperator<D: Data> {
     scope_level: u32,
     cond: IterCondition<D>,
+    checkpoint: Option<IterCheckpoint<D>>,
     // record scopes in iteration;
     // e.g. if scope ( [0, 0], [1, 0] ) need iteration:
     // it records :
@@ -24,13 +26,44 @@ pub(crate) struct SwitchOperator<D> {
     iter_scope: TidyTagMap<Vec<EndGuard>>,
 }
 
-impl<D> SwitchOperator<D> {
-    pub fn new(scope_level: u32, cond: IterCondition<D>) -> Self {
+impl<D: Data> SwitchOperator<D> {
+    pub fn new(scope_level: u32, cond: IterCondition<D>, checkpoint: Option<IterCheckpoint<D>>) -> Self {
         assert!(scope_level > 0);
-        SwitchOperator { scope_level, cond, iter_scope: TidyTagMap::new(scope_level - 1) }
+        SwitchOperator { scope_level, cond, checkpoint, iter_scope: TidyTagMap::new(scope_level - 1) }
     }
 }
 
+/// Feed the data restored from the checkpoint into the resumed iteration, instead of the input of
+/// the iteration which had entered the iteration in the previous run, after the checkpointed
+/// resources are restored;
+fn resume<D: Data>(
+    checkpoint: &IterCheckpoint<D>, parent: &Tag, leave: &Output<D>, enter: &Output<D>,
+) -> Result<(), JobExecError> {
+    let k = checkpoint
+        .resume_point()
+        .expect("no checkpoint to resume;");
+    let (items, left) = checkpoint.restore()?;
+    let tag = Tag::inherit(parent, k);
+    info_worker!(
+        "resume {:?} with {} data, replay {} data out of iteration;",
+        tag,
+        items.len(),
+        left.len()
+    );
+    // the snapshots are restored only once, so keep pushing all of them even if the output is blocked;
+    let mut res = Ok(());
+    let mut leave_session = leave.new_session(&Tag::inherit(parent, 0))?;
+    for d in left {
+        first_err(&mut res, leave_session.give(d));
+    }
+    let mut enter_session = enter.new_session(&tag)?;
+    for d in items {
+        first_err(&mut res, enter_session.give(d));
+    }
+    first_err(&mut res, enter_session.notify_end(EndSignal::new(tag, Weight::all())));
+    Ok(res?)
+}
+
 impl<D: Data> OperatorCore for SwitchOperator<D> {
     fn on_receive(
         &mut self, inputs: &[Box<dyn InputProxy>], outputs: &[Box<dyn OutputProxy>],
@@ -40,12 +73,32 @@ impl<D: Data> OperatorCore for SwitchOperator<D> {
         let leave = new_output::<D>(&outputs[0]);
         let enter = new_output::<D>(&outputs[1]);
         main.for_each_batch(|dataset| {
-            if dataset.is_last() {
+            let last = dataset.is_last();
+            if last {
                 let tag = dataset.tag.to_parent_uncheck();
                 trace_worker!("{:?} into iteration at scope level {}", tag, self.scope_level);
                 self.iter_scope.insert(tag, vec![]);
             }
-            switch(dataset, &self.cond, leave, enter)
+            match self.checkpoint {
+                Some(ref checkpoint) if checkpoint.resume_point().is_some() => {
+                    // the input had been switched into the iteration before the checkpoint;
+                    dataset.clear();
+                    if dataset.take_end().is_some() {
+                        let p = dataset.tag.to_parent_uncheck();
+                        resume(checkpoint, &p, leave, enter)?;
+                    }
+                    Ok(())
+                }
+                _ => {
+                    switch(dataset, &self.cond, leave, enter, &mut self.checkpoint)?;
+                    if last {
+                        if let Some(checkpoint) = self.checkpoint.as_mut() {
+                            checkpoint.finish(&dataset.tag)?;
+                        }
+                    }
+                    Ok(())
+                }
+            }
         })?;
 
         let mut feedback = new_input_session::<D>(&inputs[1]);
@@ -91,11 +144,20 @@ impl<D: Data> OperatorCore for SwitchOperator<D> {
             } else {
                 // data not of last iteration;
                 if !dataset.is_empty() {
-                    switch(dataset, &self.cond, leave, enter)?;
+                    let last = dataset.is_last();
+                    switch(dataset, &self.cond, leave, enter, &mut self.checkpoint)?;
+                    if last {
+                        if let Some(checkpoint) = self.checkpoint.as_mut() {
+                            checkpoint.finish(&dataset.tag)?;
+                        }
+                    }
                 } else {
                     if let Some(end) = dataset.take_end() {
                         let p = end.tag.to_parent_uncheck();
                         if self.iter_scope.contains_key(&p) {
+                            if let Some(checkpoint) = self.checkpoint.as_mut() {
+                                checkpoint.finish(&end.tag)?;
+                            }
                             enter.notify_end(end)?;
                         } else {
                             //
@@ -122,15 +184,23 @@ impl<D: Data> OperatorCore for SwitchOperator<D> {
 
 fn switch<D: Data>(
     dataset: &mut MicroBatch<D>, cond: &IterCondition<D>, leave: &Output<D>, enter: &Output<D>,
+    checkpoint: &mut Option<IterCheckpoint<D>>,
 ) -> Result<(), JobExecError> {
     if !dataset.is_last() {
         // not last batch;
-        let mut leave_session = leave.new_session(&dataset.tag)?;
-        let mut enter_session = enter.new_session(&dataset.tag)?;
+        let tag = dataset.tag.clone();
+        let mut leave_session = leave.new_session(&tag)?;
+        let mut enter_session = enter.new_session(&tag)?;
         for d in dataset.drain() {
             if cond.is_converge(&d)? {
+                if let Some(checkpoint) = checkpoint.as_mut() {
+                    checkpoint.record_leave(&tag, &d)?;
+                }
                 leave_session.give(d)?;
             } else {
+                if let Some(checkpoint) = checkpoint.as_mut() {
+                    checkpoint.record_enter(&tag, &d)?;
+                }
                 enter_session.give(d)?;
             }
         }
@@ -145,17 +215,29 @@ fn switch<D: Data>(
                 match item {
                     MarkedData::Data(d) => {
                         if cond.is_converge(&d)? {
+                            if let Some(checkpoint) = checkpoint.as_mut() {
+                                checkpoint.record_leave(&tag, &d)?;
+                            }
                             leave_session.give(d)?;
                         } else {
+                            if let Some(checkpoint) = checkpoint.as_mut() {
+                                checkpoint.record_enter(&tag, &d)?;
+                            }
                             enter_session.give(d)?;
                         }
                     }
                     MarkedData::Marked(d, e) => {
                         if let Some(d) = d {
                             if cond.is_converge(&d)? {
+                                if let Some(checkpoint) = checkpoint.as_mut() {
+                                    checkpoint.record_leave(&tag, &d)?;
+                                }
                                 enter_session.notify_end(e)?;
                                 leave_session.give(d)?;
                             } else {
+                                if let Some(checkpoint) = checkpoint.as_mut() {
+                                    checkpoint.record_enter(&tag, &d)?;
+                                }
                                 enter_session.give_last(d, e)?;
                             }
                         } else {
@@ -26,9 +26,10 @@ use crate::communication::input::{new_input, InputProxy};
 use crate::communication::output::{OutputBuilder, OutputBuilderImpl, OutputProxy};
 use crate::data::MicroBatch;
 use crate::data_plane::{GeneralPull, GeneralPush};
-use crate::errors::{IOResult, JobExecError};
+use crate::errors::{IOError, IOResult, JobExecError};
 use crate::event::emitter::EventEmitter;
 use crate::graph::Port;
+use crate::profile::OperatorProfile;
 use crate::progress::EndSignal;
 use crate::schedule::state::inbound::InputEndNotify;
 use crate::schedule::state::outbound::OutputCancelState;
@@ -297,7 +298,15 @@ impl Operator {
             }
         }
 
-        Ok(!self.has_outstanding()?)
+        // the input kept pending by a parked operator can't make progress until it is unparked;
+        Ok(self.info.parker.is_parked() || !self.has_outstanding()?)
+    }
+
+    /// Check if any output of this operator has data blocked for lack of capacity;
+    pub fn has_blocked_outputs(&self) -> bool {
+        self.outputs
+            .iter()
+            .any(|output| !output.get_blocks().is_empty())
     }
 
     #[inline]
@@ -361,6 +370,26 @@ impl Operator {
         );
     }
 
+    /// Execution metrics of this operator so far;
+    pub fn profile(&self) -> OperatorProfile {
+        OperatorProfile {
+            index: self.info.index,
+            name: self.info.name.clone(),
+            fire_times: self.fire_times as u64,
+            fire_us: self.exec_st.get() as u64,
+            inputs: self
+                .inputs
+                .iter()
+                .map(|i| i.metrics())
+                .collect(),
+            outputs: self
+                .outputs
+                .iter()
+                .map(|o| o.metrics())
+                .collect(),
+        }
+    }
+
     fn fire_inner(&mut self) -> Result<(), JobExecError> {
         for output in self.outputs.iter() {
             if let Err(e) = output.try_unblock() {
@@ -391,29 +420,59 @@ impl Operator {
     }
 }
 
+/// What an operator keeps across the data it processes, which decides if the operator can be resumed
+/// from the checkpoints of iterations, see `Iteration::iterate_until`;
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum OperatorState {
+    /// nothing is kept, e.g. `map`;
+    Stateless,
+    /// the state of a scope is kept until the scope ends, and is not seen by other scopes, e.g. `fold`;
+    ScopeLocal,
+    /// the state may be kept across scopes, which is taken by default;
+    Unknown,
+}
+
 pub struct OperatorBuilder {
     pub info: OperatorInfo,
     inputs: Vec<Box<dyn InputProxy>>,
     inputs_notify: Vec<Option<Box<dyn InputEndNotify>>>,
     outputs: Vec<Box<dyn OutputBuilder>>,
     core: GeneralOperator,
+    state: OperatorState,
 }
 
 impl OperatorBuilder {
     pub fn new(meta: OperatorInfo, core: GeneralOperator) -> Self {
-        OperatorBuilder { info: meta, inputs: vec![], inputs_notify: vec![], outputs: vec![], core }
+        OperatorBuilder {
+            info: meta,
+            inputs: vec![],
+            inputs_notify: vec![],
+            outputs: vec![],
+            core,
+            state: OperatorState::Unknown,
+        }
     }
 
     pub fn index(&self) -> usize {
         self.info.index
     }
 
+    /// Set what the operator keeps across the data it processes, the operators are taken as keeping
+    /// state across scopes unless set;
+    pub(crate) fn set_state(&mut self, state: OperatorState) {
+        self.state = state;
+    }
+
+    pub(crate) fn get_state(&self) -> OperatorState {
+        self.state
+    }
+
     pub(crate) fn add_input<T: Data>(
         &mut self, ch_info: ChannelInfo, pull: GeneralPull<MicroBatch<T>>,
-        notify: Option<GeneralPush<MicroBatch<T>>>, event_emitter: &EventEmitter,
+        notify: Option<GeneralPush<MicroBatch<T>>>, event_emitter: &EventEmitter, profile: bool,
     ) {
         assert_eq!(ch_info.target_port.port, self.inputs.len());
-        let input = new_input(ch_info, pull, event_emitter);
+        let input = new_input(ch_info, pull, event_emitter, profile);
         self.inputs.push(input);
         let n = notify.map(|p| Box::new(p) as Box<dyn InputEndNotify>);
         self.inputs_notify.push(n);
@@ -493,6 +552,14 @@ impl Drop for Finally {
     }
 }
 
+/// Keep the first error, the data and end signals pushed before an error are not lost, the error
+/// only hints the caller to stop pushing more;
+pub(crate) fn first_err(res: &mut Result<(), IOError>, next: Result<(), IOError>) {
+    if res.is_ok() {
+        *res = next;
+    }
+}
+
 mod concise;
 mod iteration;
 mod primitives;
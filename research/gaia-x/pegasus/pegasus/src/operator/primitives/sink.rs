@@ -19,19 +19,35 @@ use crate::api::primitive::sink::Sink;
 use crate::api::FromStream;
 use crate::communication::input::{new_input_session, InputProxy};
 use crate::communication::output::OutputProxy;
-use crate::errors::{BuildJobError, JobExecError};
+use crate::errors::{BuildJobError, IOError, JobExecError};
 use crate::operator::OperatorCore;
+use crate::schedule::park::Parker;
 use crate::stream::{Single, SingleItem, Stream};
 use crate::Data;
 
+/// Park the sink until the collector which is not ready notifies that it turns ready, return `true` if
+/// it is ready after all, e.g. it drained before parking;
+fn park_until_ready<D, C: FromStream<D>>(collector: &mut C, parker: &Parker) -> bool {
+    if collector.notify_ready(parker) {
+        parker.park();
+        // check again after parking, as a notification before it is lost;
+        if collector.is_ready() {
+            parker.unpark();
+            return true;
+        }
+    }
+    false
+}
+
 struct SinkOperator<D, C> {
     collector: C,
+    parker: Parker,
     _ph: std::marker::PhantomData<D>,
 }
 
 impl<D, C> SinkOperator<D, C> {
-    fn new(collector: C) -> Self {
-        SinkOperator { collector, _ph: std::marker::PhantomData }
+    fn new(collector: C, parker: Parker) -> Self {
+        SinkOperator { collector, parker, _ph: std::marker::PhantomData }
     }
 }
 
@@ -44,23 +60,33 @@ where
         &mut self, inputs: &[Box<dyn InputProxy>], _: &[Box<dyn OutputProxy>],
     ) -> Result<(), JobExecError> {
         let mut input = new_input_session::<D>(&inputs[0]);
+        let collector = &mut self.collector;
+        let parker = &self.parker;
         input.for_each_batch(|dataset| {
-            for d in dataset.drain() {
-                self.collector.on_next(d)?;
+            while !dataset.is_empty() {
+                if !collector.is_ready() && !park_until_ready::<D, C>(collector, parker) {
+                    return Ok(interrupt!("collector is not ready;")?);
+                }
+                if let Some(d) = dataset.next() {
+                    collector.on_next(d)?;
+                }
             }
             Ok(())
-        })
+        })?;
+        self.collector.flush()?;
+        Ok(())
     }
 }
 
 struct SinkSingleOperator<D, C> {
     sender: C,
+    parker: Parker,
     _ph: std::marker::PhantomData<D>,
 }
 
 impl<D, C> SinkSingleOperator<D, C> {
-    fn new(sender: C) -> Self {
-        SinkSingleOperator { sender, _ph: std::marker::PhantomData }
+    fn new(sender: C, parker: Parker) -> Self {
+        SinkSingleOperator { sender, parker, _ph: std::marker::PhantomData }
     }
 }
 
@@ -73,24 +99,33 @@ where
         &mut self, inputs: &[Box<dyn InputProxy>], _: &[Box<dyn OutputProxy>],
     ) -> Result<(), JobExecError> {
         let mut input = new_input_session::<Single<D>>(&inputs[0]);
+        let sender = &mut self.sender;
+        let parker = &self.parker;
         input.for_each_batch(|dataset| {
-            for d in dataset.drain() {
-                self.sender.on_next(d.0)?;
+            while !dataset.is_empty() {
+                if !sender.is_ready() && !park_until_ready::<D, C>(sender, parker) {
+                    return Ok(interrupt!("collector is not ready;")?);
+                }
+                if let Some(d) = dataset.next() {
+                    sender.on_next(d.0)?;
+                }
             }
             Ok(())
-        })
+        })?;
+        self.sender.flush()?;
+        Ok(())
     }
 }
 
 impl<D: Data> Sink<D> for Stream<D> {
     fn sink_into<C: FromStream<D>>(self, collector: C) -> Result<(), BuildJobError> {
-        self.sink_by("sink_stream", |_| SinkOperator::new(collector))
+        self.sink_by("sink_stream", |info| SinkOperator::new(collector, info.parker.clone()))
     }
 }
 
 impl<D: Debug + Send + Sync + 'static> Sink<D> for SingleItem<D> {
     fn sink_into<C: FromStream<D>>(self, collector: C) -> Result<(), BuildJobError> {
         self.inner
-            .sink_by("sink_single", |_| SinkSingleOperator::new(collector))
+            .sink_by("sink_single", |info| SinkSingleOperator::new(collector, info.parker.clone()))
     }
 }
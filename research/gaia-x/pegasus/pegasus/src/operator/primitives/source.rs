@@ -13,8 +13,11 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use crossbeam_channel::TryRecvError;
+
+use crate::api::primitive::source::SourceReceiver;
 use crate::api::{IntoDataflow, Unary};
-use crate::errors::BuildJobError;
+use crate::errors::{BuildJobError, IOError};
 use crate::stream::Stream;
 use crate::Data;
 
@@ -38,3 +41,49 @@ where
         })
     }
 }
+
+impl<D: Data> IntoDataflow<D> for SourceReceiver<D> {
+    fn into_dataflow(self, entry: Stream<D>) -> Result<Stream<D>, BuildJobError> {
+        let conf = entry.get_conf();
+        // receive at most as many items as the output can hold in each schedule;
+        let limit = conf.batch_size as usize * conf.batch_capacity as usize;
+        entry.unary("channel_source", move |info| {
+            let parker = info.parker.clone();
+            self.register(parker.clone());
+            let rx = self.rx;
+            move |input, output| {
+                input.for_each_batch(|dataset| {
+                    let mut session = output.new_session(&dataset.tag)?;
+                    // never wait on the channel, as the worker thread is shared with other operators;
+                    for _ in 0..limit {
+                        let next = match rx.try_recv() {
+                            Err(TryRecvError::Empty) => {
+                                // park until an item is sent or the senders are dropped, and check the
+                                // channel again after parking, as an unpark before it is lost;
+                                parker.park();
+                                let next = rx.try_recv();
+                                if !matches!(next, Err(TryRecvError::Empty)) {
+                                    parker.unpark();
+                                }
+                                next
+                            }
+                            next => next,
+                        };
+                        match next {
+                            Ok(item) => session.give(item)?,
+                            Err(TryRecvError::Empty) => break,
+                            Err(TryRecvError::Disconnected) => {
+                                trace_worker!("all senders of channel source dropped;");
+                                session.flush()?;
+                                return Ok(());
+                            }
+                        }
+                    }
+                    session.flush()?;
+                    // keep the end of input pending, the source is fired again after it is unparked;
+                    Ok(interrupt!("channel source waiting for more items;")?)
+                })
+            }
+        })
+    }
+}
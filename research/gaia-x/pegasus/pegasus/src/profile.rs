@@ -0,0 +1,235 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+//! Execution metrics of a job, collected by each worker if `JobConf::profile` is set, and merged
+//! into one [`JobProfile`] when the job ends;
+//!
+//! The profile of a server is the merge of its local workers' profiles, the profiles of different
+//! servers can be encoded and merged the same way;
+
+use std::fmt::{Display, Formatter};
+
+use pegasus_common::codec::*;
+
+/// Metrics of an input or output port of an operator;
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PortProfile {
+    /// number of records received by an input port, or sent by an output port;
+    pub records: u64,
+    /// number of batches received by an input port, or forwarded as whole by an output port;
+    pub batches: u64,
+    /// the most records buffered in the port, i.e. stashed in an input port waiting to be consumed;
+    pub peak_buffered: u64,
+}
+
+impl PortProfile {
+    pub fn merge(&mut self, other: &PortProfile) {
+        self.records += other.records;
+        self.batches += other.batches;
+        self.peak_buffered = std::cmp::max(self.peak_buffered, other.peak_buffered);
+    }
+}
+
+/// Metrics of an operator, the time is measured in microseconds;
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperatorProfile {
+    pub index: usize,
+    pub name: String,
+    /// times the operator is fired by `Dataflow::try_fire`;
+    pub fire_times: u64,
+    /// total time spent in firing the operator;
+    pub fire_us: u64,
+    pub inputs: Vec<PortProfile>,
+    pub outputs: Vec<PortProfile>,
+}
+
+impl OperatorProfile {
+    pub fn merge(&mut self, other: &OperatorProfile) {
+        self.fire_times += other.fire_times;
+        self.fire_us += other.fire_us;
+        merge_ports(&mut self.inputs, &other.inputs);
+        merge_ports(&mut self.outputs, &other.outputs);
+    }
+}
+
+fn merge_ports(ports: &mut Vec<PortProfile>, others: &[PortProfile]) {
+    while ports.len() < others.len() {
+        ports.push(PortProfile::default());
+    }
+    for (p, o) in ports.iter_mut().zip(others.iter()) {
+        p.merge(o);
+    }
+}
+
+/// Bytes sent to the remote servers through an exchange channel;
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExchangeProfile {
+    /// index of the channel in the dataflow;
+    pub channel: u32,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JobProfile {
+    pub job_id: u64,
+    /// number of workers whose profiles are merged into this one;
+    pub workers: u32,
+    /// schedule steps of all workers;
+    pub steps: u64,
+    /// operators ordered by index;
+    pub operators: Vec<OperatorProfile>,
+    /// exchange channels ordered by index, only the ones sending data to remote are present;
+    pub exchanges: Vec<ExchangeProfile>,
+}
+
+impl JobProfile {
+    pub fn new(job_id: u64) -> Self {
+        JobProfile { job_id, ..Default::default() }
+    }
+
+    pub fn merge(&mut self, other: &JobProfile) {
+        self.workers += other.workers;
+        self.steps += other.steps;
+        for op in other.operators.iter() {
+            match self
+                .operators
+                .binary_search_by_key(&op.index, |o| o.index)
+            {
+                Ok(i) => self.operators[i].merge(op),
+                Err(i) => self.operators.insert(i, op.clone()),
+            }
+        }
+        for ex in other.exchanges.iter() {
+            match self
+                .exchanges
+                .binary_search_by_key(&ex.channel, |e| e.channel)
+            {
+                Ok(i) => self.exchanges[i].bytes += ex.bytes,
+                Err(i) => self.exchanges.insert(i, ex.clone()),
+            }
+        }
+    }
+}
+
+impl Display for JobProfile {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "============ Profile of job[{}] ==============", self.job_id)?;
+        writeln!(f, "Workers:\t{}\tSteps:\t{}", self.workers, self.steps)?;
+        writeln!(f, "Operators:")?;
+        for op in self.operators.iter() {
+            writeln!(
+                f,
+                "\t{}\t{}\tfired {} times, used {:.2}ms;",
+                op.index,
+                op.name,
+                op.fire_times,
+                op.fire_us as f64 / 1000.0
+            )?;
+            for (i, p) in op.inputs.iter().enumerate() {
+                writeln!(
+                    f,
+                    "\t\tinput[{}]: {} records in {} batches, peak buffered {} records;",
+                    i, p.records, p.batches, p.peak_buffered
+                )?;
+            }
+            for (i, p) in op.outputs.iter().enumerate() {
+                writeln!(f, "\t\toutput[{}]: {} records, {} batches;", i, p.records, p.batches)?;
+            }
+        }
+        if !self.exchanges.is_empty() {
+            writeln!(f, "Exchanges:")?;
+            for ex in self.exchanges.iter() {
+                writeln!(f, "\tchannel[{}]: {} bytes sent to remote;", ex.channel, ex.bytes)?;
+            }
+        }
+        write!(f, "==========================================")
+    }
+}
+
+impl Encode for PortProfile {
+    fn write_to<W: WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_u64(self.records)?;
+        writer.write_u64(self.batches)?;
+        writer.write_u64(self.peak_buffered)
+    }
+}
+
+impl Decode for PortProfile {
+    fn read_from<R: ReadExt>(reader: &mut R) -> std::io::Result<Self> {
+        let records = reader.read_u64()?;
+        let batches = reader.read_u64()?;
+        let peak_buffered = reader.read_u64()?;
+        Ok(PortProfile { records, batches, peak_buffered })
+    }
+}
+
+impl Encode for OperatorProfile {
+    fn write_to<W: WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_u64(self.index as u64)?;
+        self.name.write_to(writer)?;
+        writer.write_u64(self.fire_times)?;
+        writer.write_u64(self.fire_us)?;
+        self.inputs.write_to(writer)?;
+        self.outputs.write_to(writer)
+    }
+}
+
+impl Decode for OperatorProfile {
+    fn read_from<R: ReadExt>(reader: &mut R) -> std::io::Result<Self> {
+        let index = reader.read_u64()? as usize;
+        let name = String::read_from(reader)?;
+        let fire_times = reader.read_u64()?;
+        let fire_us = reader.read_u64()?;
+        let inputs = Vec::<PortProfile>::read_from(reader)?;
+        let outputs = Vec::<PortProfile>::read_from(reader)?;
+        Ok(OperatorProfile { index, name, fire_times, fire_us, inputs, outputs })
+    }
+}
+
+impl Encode for ExchangeProfile {
+    fn write_to<W: WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_u32(self.channel)?;
+        writer.write_u64(self.bytes)
+    }
+}
+
+impl Decode for ExchangeProfile {
+    fn read_from<R: ReadExt>(reader: &mut R) -> std::io::Result<Self> {
+        let channel = reader.read_u32()?;
+        let bytes = reader.read_u64()?;
+        Ok(ExchangeProfile { channel, bytes })
+    }
+}
+
+impl Encode for JobProfile {
+    fn write_to<W: WriteExt>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_u64(self.job_id)?;
+        writer.write_u32(self.workers)?;
+        writer.write_u64(self.steps)?;
+        self.operators.write_to(writer)?;
+        self.exchanges.write_to(writer)
+    }
+}
+
+impl Decode for JobProfile {
+    fn read_from<R: ReadExt>(reader: &mut R) -> std::io::Result<Self> {
+        let job_id = reader.read_u64()?;
+        let workers = reader.read_u32()?;
+        let steps = reader.read_u64()?;
+        let operators = Vec::<OperatorProfile>::read_from(reader)?;
+        let exchanges = Vec::<ExchangeProfile>::read_from(reader)?;
+        Ok(JobProfile { job_id, workers, steps, operators, exchanges })
+    }
+}
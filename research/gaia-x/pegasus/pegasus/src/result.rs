@@ -1,21 +1,31 @@
 use std::error::Error;
 use std::fmt::Debug;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
 use crossbeam_channel::{Receiver, Sender, TryRecvError};
 use dyn_clonable::*;
 
 use crate::api::function::FnResult;
-use crate::api::FromStream;
+use crate::api::{FromStream, Parker};
+use crate::profile::JobProfile;
 
 #[clonable]
 pub trait FromStreamExt<T>: FromStream<T> + Clone {
     fn on_error(&mut self, error: Box<dyn Error + Send>);
+
+    /// Invoked once with the profile merged from all local workers, if `JobConf::profile` is set. A job
+    /// running on many servers has a profile on each, which the sink reports to where the results of all
+    /// servers are gathered(e.g. the rpc client), and the profiles are merged there by `JobProfile::merge`;
+    fn on_profile(&mut self, _profile: &JobProfile) {}
 }
 
 pub struct ResultSink<T> {
     cancel: Arc<AtomicBool>,
+    /// if the cancellation or abort of the job has been notified to all workers;
+    notified: Arc<AtomicBool>,
+    running: Arc<AtomicUsize>,
+    profile: Arc<Mutex<Option<JobProfile>>>,
     kind: ResultSinkKind<T>,
 }
 
@@ -28,6 +38,9 @@ impl<T> ResultSink<T> {
     pub fn new(tx: Sender<Result<T, Box<dyn Error + Send>>>) -> Self {
         ResultSink {
             cancel: Arc::new(AtomicBool::new(false)),
+            notified: Arc::new(AtomicBool::new(false)),
+            running: Arc::new(AtomicUsize::new(0)),
+            profile: Arc::new(Mutex::new(None)),
             kind: ResultSinkKind::Default(DefaultResultSink::new(tx)),
         }
     }
@@ -38,6 +51,9 @@ impl<T> ResultSink<T> {
     {
         ResultSink {
             cancel: Arc::new(AtomicBool::new(false)),
+            notified: Arc::new(AtomicBool::new(false)),
+            running: Arc::new(AtomicUsize::new(0)),
+            profile: Arc::new(Mutex::new(None)),
             kind: ResultSinkKind::Customized(Box::new(sink)),
         }
     }
@@ -46,6 +62,38 @@ impl<T> ResultSink<T> {
         &self.cancel
     }
 
+    pub fn get_profile_hook(&self) -> &Arc<Mutex<Option<JobProfile>>> {
+        &self.profile
+    }
+
+    /// Get the number of local workers of the job which are not finished yet;
+    pub fn get_running_hook(&self) -> &Arc<AtomicUsize> {
+        &self.running
+    }
+
+    /// Mark that the cancellation or abort of the job has been notified to all workers, return `false`
+    /// if it has been marked by another local worker;
+    pub(crate) fn mark_notified(&self) -> bool {
+        !self.notified.swap(true, Ordering::SeqCst)
+    }
+
+    /// Merge the profile of a local worker, the merged profile is reported after all the `workers`
+    /// local workers have reported;
+    pub(crate) fn on_profile(&mut self, profile: JobProfile, workers: u32) {
+        let mut lock = self
+            .profile
+            .lock()
+            .expect("profile lock poisoned");
+        let merged = lock.get_or_insert_with(|| JobProfile::new(profile.job_id));
+        merged.merge(&profile);
+        if merged.workers == workers {
+            info!("{}", merged);
+            if let ResultSinkKind::Customized(tx) = &mut self.kind {
+                tx.on_profile(merged);
+            }
+        }
+    }
+
     pub fn on_error<E: std::error::Error + Send + 'static>(&mut self, error: E) {
         match &mut self.kind {
             ResultSinkKind::Default(tx) => {
@@ -65,6 +113,27 @@ impl<T: Send + Debug + 'static> FromStream<T> for ResultSink<T> {
             ResultSinkKind::Customized(tx) => tx.on_next(next),
         }
     }
+
+    fn is_ready(&mut self) -> bool {
+        match &mut self.kind {
+            ResultSinkKind::Default(_) => true,
+            ResultSinkKind::Customized(tx) => tx.is_ready(),
+        }
+    }
+
+    fn notify_ready(&mut self, parker: &Parker) -> bool {
+        match &mut self.kind {
+            ResultSinkKind::Default(_) => false,
+            ResultSinkKind::Customized(tx) => tx.notify_ready(parker),
+        }
+    }
+
+    fn flush(&mut self) -> FnResult<()> {
+        match &mut self.kind {
+            ResultSinkKind::Default(_) => Ok(()),
+            ResultSinkKind::Customized(tx) => tx.flush(),
+        }
+    }
 }
 
 impl<T> Clone for ResultSink<T> {
@@ -73,7 +142,13 @@ impl<T> Clone for ResultSink<T> {
             ResultSinkKind::Default(tx) => ResultSinkKind::Default(tx.clone()),
             ResultSinkKind::Customized(tx) => ResultSinkKind::Customized(tx.clone()),
         };
-        ResultSink { cancel: self.cancel.clone(), kind }
+        ResultSink {
+            cancel: self.cancel.clone(),
+            notified: self.notified.clone(),
+            running: self.running.clone(),
+            profile: self.profile.clone(),
+            kind,
+        }
     }
 }
 
@@ -86,6 +161,7 @@ pub struct ResultStream<T> {
     is_exhaust: AtomicBool,
     is_poison: AtomicBool,
     cancel_hook: Arc<AtomicBool>,
+    profile_hook: Arc<Mutex<Option<JobProfile>>>,
     rx: Receiver<Result<T, Box<dyn Error + Send>>>,
 }
 
@@ -117,10 +193,29 @@ impl<T> ResultStream<T> {
             is_exhaust: AtomicBool::new(false),
             is_poison: AtomicBool::new(false),
             cancel_hook,
+            profile_hook: Arc::new(Mutex::new(None)),
             rx,
         }
     }
 
+    pub fn set_profile_hook(&mut self, profile_hook: Arc<Mutex<Option<JobProfile>>>) {
+        self.profile_hook = profile_hook;
+    }
+
+    /// Get the profile of the job if `JobConf::profile` is set, which is available after all results
+    /// are consumed, and includes only the local workers finished normally. The profiles of other
+    /// servers are reported through their own result sinks, see `FromStreamExt::on_profile`;
+    pub fn profile(&self) -> Option<JobProfile> {
+        if self.is_exhaust() {
+            self.profile_hook
+                .lock()
+                .expect("profile lock poisoned")
+                .clone()
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn is_exhaust(&self) -> bool {
         self.is_exhaust.load(Ordering::SeqCst)
@@ -181,6 +276,7 @@ impl<T> ResultStream<T> {
         }
     }
 
+    /// Cancel the job, the local workers stop once they see it, and notify the workers on other servers;
     pub fn cancel(&self) {
         self.cancel_hook.store(true, Ordering::SeqCst)
     }
@@ -19,12 +19,14 @@ use crate::data_plane::GeneralPull;
 use crate::dataflow::Dataflow;
 use crate::errors::{IOResult, JobExecError};
 use crate::event::emitter::{EventCollector, EventEmitter};
-use crate::event::Event;
+use crate::event::{Event, EventKind};
+use crate::graph::Port;
 use crate::schedule::operator::OperatorScheduler;
 use crate::schedule::state::inbound::InputEndNotify;
 use crate::schedule::state::outbound::OutputCancelState;
 
 pub(crate) mod operator;
+pub(crate) mod park;
 pub(crate) mod state;
 pub mod strategies;
 
@@ -52,7 +54,6 @@ impl Schedule {
         }
     }
 
-    #[allow(dead_code)]
     #[inline]
     pub fn reset_step_strategy<S: StepStrategy>(&mut self, strategy: S) {
         self.strategy = Box::new(strategy);
@@ -71,6 +72,17 @@ impl Schedule {
         let updates = self.event_collector.get_updates();
         if !updates.is_empty() {
             for event in updates.drain(..) {
+                match event.kind() {
+                    EventKind::Abort(reason) => {
+                        let msg = format!("job aborted by worker {}: {}", event.from_worker, reason);
+                        return Err(JobExecError::exceed_limit(msg));
+                    }
+                    EventKind::CancelJob => {
+                        let msg = format!("job canceled by worker {}", event.from_worker);
+                        return Err(JobExecError::canceled(msg));
+                    }
+                    _ => (),
+                }
                 let index = event.target_port.index;
                 assert!(index < self.sch_ops.len());
                 self.sch_ops[index].accept(event)?;
@@ -100,6 +112,25 @@ impl Schedule {
         Ok(())
     }
 
+    /// Notify all the `peers` workers of this job, including the remote ones, to abort the job;
+    pub fn abort(&mut self, reason: &str, peers: u32) -> IOResult<()> {
+        self.broadcast(EventKind::Abort(reason.to_owned()), peers)
+    }
+
+    /// Notify all the `peers` workers of this job, including the remote ones, that the job is canceled;
+    pub fn cancel_job(&mut self, peers: u32) -> IOResult<()> {
+        self.broadcast(EventKind::CancelJob, peers)
+    }
+
+    fn broadcast(&mut self, kind: EventKind, peers: u32) -> IOResult<()> {
+        let source = crate::worker_id::get_current_worker().index;
+        for target in 0..peers {
+            let event = Event::new(source, Port::new(0, 0), kind.clone());
+            self.event_emitter.send(target, event)?;
+        }
+        self.event_emitter.flush()
+    }
+
     pub fn close(&mut self) -> IOResult<()> {
         self.event_emitter.close()
     }
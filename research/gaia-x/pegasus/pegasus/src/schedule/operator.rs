@@ -68,6 +68,9 @@ impl OperatorScheduler {
                     warn_worker!("unrecognized cancel event of port {:?}; form worker {}", port, src)
                 }
             }
+            EventKind::Abort(_) | EventKind::CancelJob => {
+                warn_worker!("unexpected abort event to port {:?} from worker {};", port, src)
+            }
         }
         Ok(())
     }
@@ -0,0 +1,46 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Park state of an operator which waits for an event out of the dataflow, e.g. items sent into a
+/// channel by the host.
+///
+/// A parked operator is taken as idle even if it keeps input pending, so it is not fired, and the
+/// worker is suspended by the executor if nothing else can make progress. The operator is unparked by
+/// whoever produces the event, and the scheduler fires it again once it observes that when checking
+/// if the worker is ready.
+#[derive(Clone, Default)]
+pub struct Parker {
+    parked: Arc<AtomicBool>,
+}
+
+impl Parker {
+    /// Park the operator, it should check again if the event has happened after parking, as an
+    /// unpark before it is lost;
+    pub fn park(&self) {
+        self.parked.store(true, Ordering::SeqCst);
+    }
+
+    pub fn unpark(&self) {
+        self.parked.store(false, Ordering::SeqCst);
+    }
+
+    #[inline]
+    pub fn is_parked(&self) -> bool {
+        self.parked.load(Ordering::SeqCst)
+    }
+}
@@ -74,11 +74,108 @@ impl StepStrategy for WaterfallStrategy {
     }
 }
 
-#[allow(dead_code)]
-pub struct VolcanoStepStrategy {}
+/// Fire operators from the sinks to the sources, so the data already produced is consumed by the
+/// downstream operators before their upstream ones produce more;
+///
+/// An operator is pulled, i.e. fired, only if none of its followers still has output data blocked
+/// after being fired in the step, that is, an upstream operator only produces data when the downstream
+/// ones have room for it, so the data in flight of explosive expansions like k-hop is bounded by the
+/// output capacities(`JobConf::batch_capacity`). The followers, including the ones fed back by loops,
+/// are fired right after the operator to consume its output in the same step, and an operator blocked
+/// by its output is fired again once its followers take some of the output;
+#[derive(Default)]
+pub struct VolcanoStepStrategy {
+    /// operators blocked, or not fired because of blocked followers, in the current step;
+    blocked: Vec<bool>,
+    /// operators being pulled, which are not pulled again by the followers fed back by loops;
+    pulling: Vec<bool>,
+}
+
+impl VolcanoStepStrategy {
+    fn followers_of(index: usize, task: &Dataflow) -> Vec<usize> {
+        let mut followers = vec![];
+        if let Some(children) = task.dependency().get_children_of(index) {
+            for f in children.iter() {
+                for (ff, _, _) in f.iter() {
+                    followers.push(*ff);
+                }
+            }
+        }
+        followers
+    }
+
+    fn has_blocked_follower(&self, index: usize, task: &Dataflow) -> bool {
+        // followers with smaller indexes are fed back by loops, they never hold this operator back,
+        // or else the loop would never be fired;
+        Self::followers_of(index, task)
+            .into_iter()
+            .any(|f| f > index && self.blocked[f])
+    }
+
+    /// Fire the operator at `index`, return false if it is idle;
+    fn fire(&self, task: &Dataflow, index: usize) -> Result<bool, JobExecError> {
+        loop {
+            match task.try_fire(index) {
+                Ok(fired) => return Ok(fired),
+                Err(e) => match &e.kind {
+                    // only a scope is blocked, fire again to go on with the other scopes;
+                    ErrorKind::WouldBlock(Some(tag)) => {
+                        debug_worker!("scope {:?} blocked in operator {}", tag, index);
+                    }
+                    ErrorKind::WouldBlock(None) | ErrorKind::Interrupted => return Ok(true),
+                    _ => return Err(e),
+                },
+            }
+        }
+    }
+
+    /// Fire the operator at `index` if its followers have room for its output, and fire the followers
+    /// to consume the output, return false if the operator is idle or not fired;
+    fn pull(&mut self, task: &Dataflow, index: usize) -> Result<bool, JobExecError> {
+        if self.pulling[index] {
+            return Ok(false);
+        }
+        if self.has_blocked_follower(index, task) {
+            trace_worker!("operator {} not fired as its followers are blocked;", index);
+            self.blocked[index] = true;
+            return Ok(false);
+        }
+        self.pulling[index] = true;
+        let fired = self.fire(task, index)?;
+        // fire again if the output is blocked but the followers take some of it, only once, as the
+        // followers may be fired without consuming anything, e.g. waiting for other workers;
+        if fired && self.push(task, index)? && task.is_blocked(index) {
+            self.fire(task, index)?;
+            self.push(task, index)?;
+        }
+        self.pulling[index] = false;
+        self.blocked[index] = task.is_blocked(index);
+        Ok(fired)
+    }
+
+    /// Fire the followers of the operator at `index` to consume its output, return true if any of
+    /// them is fired;
+    fn push(&mut self, task: &Dataflow, index: usize) -> Result<bool, JobExecError> {
+        let mut fired = false;
+        for f in Self::followers_of(index, task) {
+            if !self.blocked[f] {
+                fired |= self.pull(task, f)?;
+            }
+        }
+        Ok(fired)
+    }
+}
 
 impl StepStrategy for VolcanoStepStrategy {
-    fn make_step(&mut self, _task: &Dataflow) -> Result<(), JobExecError> {
-        todo!()
+    fn make_step(&mut self, task: &Dataflow) -> Result<(), JobExecError> {
+        let len = task.operator_length();
+        self.blocked.clear();
+        self.blocked.resize(len, false);
+        self.pulling.clear();
+        self.pulling.resize(len, false);
+        for index in (0..len).rev() {
+            self.pull(task, index)?;
+        }
+        Ok(())
     }
 }
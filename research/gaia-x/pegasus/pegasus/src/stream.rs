@@ -27,11 +27,12 @@ use crate::api::{Map, Unary};
 use crate::communication::channel::ChannelKind;
 use crate::communication::output::OutputBuilderImpl;
 use crate::communication::Channel;
+use crate::config::MIN_SPILL_RUN;
 use crate::dataflow::{DataflowBuilder, OperatorRef};
 use crate::errors::BuildJobError;
 use crate::graph::{Edge, Port};
 use crate::macros::route::*;
-use crate::operator::{NotifiableOperator, OperatorCore};
+use crate::operator::{NotifiableOperator, OperatorCore, OperatorState};
 use crate::{Data, JobConf};
 
 #[must_use = "this `Stream` may be consumed"]
@@ -70,6 +71,25 @@ impl<D: Data> Stream<D> {
         self.port.get_port()
     }
 
+    /// Mark the operator producing this stream as stateless, see `OperatorState`;
+    pub(crate) fn stateless(self) -> Self {
+        self.dfb
+            .set_operator_state(self.port.get_port().index, OperatorState::Stateless);
+        self
+    }
+
+    /// Mark the operator producing this stream as keeping the state of each scope only until the
+    /// scope ends, see `OperatorState`;
+    pub(crate) fn scope_local(self) -> Self {
+        self.dfb
+            .set_operator_state(self.port.get_port().index, OperatorState::ScopeLocal);
+        self
+    }
+
+    pub(crate) fn stateful_operators_after(&self, index: usize) -> Vec<OperatorInfo> {
+        self.dfb.stateful_operators_after(index)
+    }
+
     pub fn copied(self) -> Result<(Stream<D>, Stream<D>), BuildJobError> {
         if self.ch.is_local() {
             let copy = Stream {
@@ -100,7 +120,8 @@ impl<D: Data> Stream<D> {
     }
 
     pub fn aggregate(mut self) -> Stream<D> {
-        if self.port.get_scope_level() == 0 {
+        // the scope level of the data in the channel, which differs from the port's after `leave()`;
+        if self.ch.get_scope_level() == 0 {
             self.ch
                 .set_channel_kind(ChannelKind::Aggregate(0));
         } else {
@@ -128,6 +149,22 @@ impl<D: Data> Stream<D> {
         self.ch.set_batch_size(batch_size);
     }
 
+    /// Set the most records the next blocking operator(e.g. sort, join) on this stream holds in memory
+    /// for each scope, which overrides `JobConf::spill_threshold` for this operator only;
+    pub fn set_spill_threshold(&mut self, threshold: usize) {
+        self.ch.set_spill_threshold(threshold);
+    }
+
+    /// The spill threshold of the next blocking operator on this stream, which is never less than
+    /// [`MIN_SPILL_RUN`], so that a tiny threshold doesn't create a spill file for every few records;
+    pub(crate) fn spill_threshold(&self) -> usize {
+        let threshold = self
+            .ch
+            .get_spill_threshold()
+            .unwrap_or(self.dfb.config.spill_threshold as usize);
+        std::cmp::max(threshold, MIN_SPILL_RUN)
+    }
+
     pub fn transform<F, O, T>(mut self, name: &str, op_builder: F) -> Result<Stream<O>, BuildJobError>
     where
         O: Data,
@@ -25,15 +25,17 @@ use crate::api::primitive::source::Source;
 use crate::channel_id::ChannelId;
 use crate::communication::output::{OutputBuilder, OutputBuilderImpl};
 use crate::dataflow::{Dataflow, DataflowBuilder};
-use crate::errors::{BuildJobError, JobExecError};
+use crate::errors::{BuildJobError, ErrorKind, JobExecError};
 use crate::event::emitter::EventEmitter;
 use crate::event::Event;
 use crate::graph::Port;
+use crate::profile::JobProfile;
 use crate::progress::{EndSignal, Weight};
 use crate::resource::{KeyedResources, ResourceMap};
 use crate::result::ResultSink;
+use crate::schedule::strategies::VolcanoStepStrategy;
 use crate::schedule::Schedule;
-use crate::{Data, JobConf, Tag, WorkerId};
+use crate::{Data, JobConf, ScheduleStrategy, Tag, WorkerId};
 
 pub struct Worker<D: Data, T: Debug + Send + 'static> {
     pub conf: Arc<JobConf>,
@@ -90,6 +92,9 @@ impl<D: Data, T: Debug + Send + 'static> Worker<D, T> {
         let output = self.sink.clone();
         func(&mut input, output)?;
         let mut sch = Schedule::new(event_emitter, rx);
+        if self.conf.strategy == ScheduleStrategy::Volcano {
+            sch.reset_step_strategy(VolcanoStepStrategy::default());
+        }
         let df = dfb.build(&mut sch)?;
         self.task = WorkerTask::Dataflow(df, sch);
         let root = Box::new(root_builder)
@@ -113,14 +118,101 @@ impl<D: Data, T: Debug + Send + 'static> Worker<D, T> {
     }
 
     fn check_cancel(&self) -> bool {
-        // TODO: check cancel impl;
-        false
+        self.sink
+            .get_cancel_hook()
+            .load(Ordering::SeqCst)
+    }
+
+    /// Check if the job exceeds its time limit or memory limit, return the reason if so;
+    fn check_limits(&self) -> Option<String> {
+        let elapsed = self.start.elapsed();
+        if elapsed.as_millis() > self.conf.time_limit as u128 {
+            return Some(format!("time limit {}ms exceeded, used {:?};", self.conf.time_limit, elapsed));
+        }
+
+        if self.conf.memory_limit != !0u32 {
+            let limit = (self.conf.memory_limit as usize) << 20;
+            if let Some(used) = pegasus_memory::alloc::check_task_memory(self.conf.job_id as usize) {
+                if used > limit {
+                    return Some(format!(
+                        "memory limit {}MB exceeded, used {} bytes;",
+                        self.conf.memory_limit, used
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    /// Abort the job on all workers, the local workers are stopped by the cancel hook of the sink, and
+    /// the others are notified by the abort events;
+    /// Stop the job exceeding its limits, the first local worker seeing it notifies all workers and
+    /// reports the error, so the client gets a single error;
+    fn abort(&mut self, reason: String) -> TaskState {
+        self.sink
+            .get_cancel_hook()
+            .store(true, Ordering::SeqCst);
+        if self.sink.mark_notified() {
+            error_worker!("job({}) '{}' aborted: {}", self.id.job_id, self.conf.job_name, reason);
+            if let WorkerTask::Dataflow(_, sch) = &mut self.task {
+                if let Err(e) = sch.abort(&reason, self.id.total_peers()) {
+                    error_worker!("fail to notify other workers to abort: {}", e);
+                }
+            }
+            self.sink
+                .on_error(JobExecError::exceed_limit(reason));
+        }
+        TaskState::Finished
+    }
+
+    /// Stop the canceled job, the first local worker seeing the cancellation notifies all workers;
+    fn cancel(&mut self) -> TaskState {
+        if self.sink.mark_notified() {
+            info_worker!("job({}) '{}' canceled;", self.id.job_id, self.conf.job_name);
+            if let WorkerTask::Dataflow(_, sch) = &mut self.task {
+                if let Err(e) = sch.cancel_job(self.id.total_peers()) {
+                    error_worker!("fail to notify other workers to cancel: {}", e);
+                }
+            }
+            self.sink
+                .on_error(JobExecError::canceled("job canceled".to_owned()));
+        }
+        TaskState::Finished
+    }
+
+    fn on_error(sink: &mut ResultSink<T>, e: JobExecError) -> TaskState {
+        if e.kind == ErrorKind::ExceedLimit || e.kind == ErrorKind::Canceled {
+            // aborted or canceled by other workers, stop the local ones, and only the first of them
+            // reports the error;
+            sink.get_cancel_hook()
+                .store(true, Ordering::SeqCst);
+            if !sink.mark_notified() {
+                return TaskState::Finished;
+            }
+        }
+        sink.on_error(e);
+        TaskState::Finished
+    }
+
+    fn report_profile(&mut self) {
+        if self.conf.profile {
+            if let Some(profile) = self.task.profile() {
+                self.sink.on_profile(profile, self.conf.workers);
+            }
+        }
     }
 
     #[cfg(not(feature = "mem"))]
     fn release(&mut self) {
         self.peer_guard.fetch_sub(1, Ordering::SeqCst);
     }
+
+    #[cfg(feature = "mem")]
+    fn release(&mut self) {
+        if self.peer_guard.fetch_sub(1, Ordering::SeqCst) == 1 {
+            pegasus_memory::alloc::remove_task(self.conf.job_id as usize);
+        }
+    }
 }
 
 enum WorkerTask {
@@ -146,6 +238,13 @@ impl WorkerTask {
         }
     }
 
+    fn profile(&self) -> Option<JobProfile> {
+        match self {
+            WorkerTask::Empty => None,
+            WorkerTask::Dataflow(df, sch) => Some(df.profile(sch.step_count as u64)),
+        }
+    }
+
     pub fn check_ready(&mut self) -> Result<TaskState, JobExecError> {
         match self {
             WorkerTask::Empty => Ok(TaskState::Finished),
@@ -206,12 +305,25 @@ impl<D: Data, T: Debug + Send + 'static> Task for Worker<D, T> {
     fn execute(&mut self) -> TaskState {
         let _g = crate::worker_id::guard(self.id);
         if self.check_cancel() {
-            return TaskState::Finished;
+            return self.cancel();
         }
 
-        let _ctx = WorkerContext::new(&mut self.resources, &mut self.keyed_resources);
+        if let Some(reason) = self.check_limits() {
+            return self.abort(reason);
+        }
+
+        let result = {
+            // the context must be dropped before the profile is reported, as it borrows the resources;
+            let _ctx = WorkerContext::new(&mut self.resources, &mut self.keyed_resources);
+            #[cfg(feature = "mem")]
+            let _mem_trace = {
+                pegasus_memory::alloc::reset_current_task(Some(self.conf.job_id as usize));
+                pegasus_memory::alloc::trace_memory_alloc()
+            };
+            self.task.execute()
+        };
 
-        match self.task.execute() {
+        match result {
             Ok(state) => {
                 if TaskState::Finished == state {
                     info_worker!(
@@ -219,21 +331,23 @@ impl<D: Data, T: Debug + Send + 'static> Task for Worker<D, T> {
                         self.id.job_id,
                         self.conf.job_name,
                         self.start.elapsed()
-                    )
+                    );
+                    self.report_profile();
                 }
                 state
             }
-            Err(e) => {
-                self.sink.on_error(e);
-                TaskState::Finished
-            }
+            Err(e) => Self::on_error(&mut self.sink, e),
         }
     }
 
     fn check_ready(&mut self) -> TaskState {
         let _g = crate::worker_id::guard(self.id);
         if self.check_cancel() {
-            return TaskState::Finished;
+            return self.cancel();
+        }
+
+        if let Some(reason) = self.check_limits() {
+            return self.abort(reason);
         }
 
         match self.task.check_ready() {
@@ -248,10 +362,7 @@ impl<D: Data, T: Debug + Send + 'static> Task for Worker<D, T> {
                 }
                 state
             }
-            Err(e) => {
-                self.sink.on_error(e);
-                TaskState::Finished
-            }
+            Err(e) => Self::on_error(&mut self.sink, e),
         }
     }
 }
@@ -0,0 +1,64 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::time::Duration;
+
+use pegasus::api::{Map, Sink};
+use pegasus::errors::{ErrorKind, JobExecError};
+use pegasus::JobConf;
+
+#[test]
+fn cancel_job_test() {
+    let mut conf = JobConf::new("cancel_job_test");
+    conf.set_workers(2);
+    conf.batch_size = 16;
+    let mut result = pegasus::run(conf, || {
+        |input, output| {
+            input
+                .input_from(0..100_000u32)?
+                .repartition(|x| Ok(*x as u64))
+                .map(|x| {
+                    std::thread::sleep(Duration::from_millis(1));
+                    Ok(x)
+                })?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut count = 0;
+    let mut canceled = false;
+    while let Some(next) = result.next() {
+        match next {
+            Ok(_) => {
+                count += 1;
+                if count == 100 {
+                    result.cancel();
+                }
+            }
+            Err(e) => {
+                let e = e
+                    .downcast_ref::<JobExecError>()
+                    .expect("unknown error");
+                assert_eq!(e.kind, ErrorKind::Canceled);
+                canceled = true;
+                break;
+            }
+        }
+    }
+    assert!(canceled);
+    // only the results in flight are received after it is canceled, far fewer than the 200_000 inputs;
+    assert!(count < 5_000, "job isn't stopped in time after canceled, count = {}", count);
+}
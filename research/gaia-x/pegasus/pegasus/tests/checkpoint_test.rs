@@ -0,0 +1,302 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+use pegasus::api::{Collect, Fold, IterCondition, Iteration, Map, Reduce, Sink, Unary};
+use pegasus::codec::{Decode, Encode, ReadExt, WriteExt};
+use pegasus::errors::{BuildJobError, JobSubmitError};
+use pegasus::resource::{get_resource, get_resource_mut, PartitionedResource};
+use pegasus::JobConf;
+
+fn checkpoint_conf(name: &str, dir: &PathBuf, resume: bool) -> JobConf {
+    let mut conf = JobConf::new(name);
+    conf.set_workers(2);
+    conf.checkpoint_dir = Some(dir.to_string_lossy().to_string());
+    conf.resume = resume;
+    conf
+}
+
+fn checkpoint_dir(name: &str) -> PathBuf {
+    let mut dir = std::env::temp_dir();
+    dir.push(format!("{}_{}", name, std::process::id()));
+    dir
+}
+
+/// Remove the checkpoints of the worker since the `n`th iteration;
+fn lose_checkpoints(dir: &PathBuf, name: &str, worker: u32, n: u32) {
+    let job_dir = dir.join(JobConf::new(name).job_id.to_string());
+    for entry in std::fs::read_dir(&job_dir).unwrap() {
+        let path = entry.unwrap().path();
+        let stem = path
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let parts = stem.split('_').collect::<Vec<_>>();
+        let nth: u32 = parts[2].parse().unwrap();
+        if nth >= n && parts[3] == worker.to_string() {
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+}
+
+// the input is given only in the first run, so the results of the resumed run are right only if
+// the data in iteration are restored from the checkpoints;
+fn add_ten_times(conf: JobConf, with_input: bool) -> Vec<u32> {
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src = if with_input {
+                input.input_from(index * 50..(index + 1) * 50)?
+            } else {
+                input.input_from(vec![])?
+            };
+            src.iterate(10, |start| {
+                start
+                    .repartition(|d| Ok(*d as u64))
+                    .map(|d| Ok(d + 1))
+            })?
+            .collect::<Vec<u32>>()?
+            .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut result = result.next().unwrap().unwrap();
+    result.sort();
+    result
+}
+
+#[test]
+fn resume_iterate_test() {
+    let dir = checkpoint_dir("resume_iterate_test");
+    let result = add_ten_times(checkpoint_conf("resume_iterate_test", &dir, false), true);
+    assert_eq!(result, (10..110u32).collect::<Vec<_>>());
+
+    // lose the checkpoints of worker 1 since the 4th iteration, as if it crashed then;
+    lose_checkpoints(&dir, "resume_iterate_test", 1, 3);
+
+    let result = add_ten_times(checkpoint_conf("resume_iterate_test", &dir, true), false);
+    assert_eq!(result, (10..110u32).collect::<Vec<_>>());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn resume_iterate_until_test() {
+    let dir = checkpoint_dir("resume_iterate_until_test");
+    let run = |resume: bool| {
+        let conf = checkpoint_conf("resume_iterate_until_test", &dir, resume);
+        let mut result = pegasus::run(conf, || {
+            let index = pegasus::get_current_worker().index;
+            move |input, output| {
+                let src = if resume {
+                    input.input_from(vec![])?
+                } else {
+                    input.input_from(index * 50..(index + 1) * 50)?
+                };
+                let mut until = IterCondition::max_iters(10);
+                until.until(|d| Ok(*d % 7 == 0));
+                src.iterate_until(until, |start| start.map(|d| Ok(d + 1)))?
+                    .collect::<Vec<u32>>()?
+                    .sink_into(output)
+            }
+        })
+        .expect("run job failure;");
+        let mut result = result.next().unwrap().unwrap();
+        result.sort();
+        result
+    };
+
+    // the data left the iteration before the checkpoint are replayed on resume;
+    let mut expected = (0..100u32)
+        .map(|d| (d + 6) / 7 * 7)
+        .collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(run(false), expected);
+    assert_eq!(run(true), expected);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn checkpoint_stateful_iterate_test() {
+    let dir = checkpoint_dir("checkpoint_stateful_iterate_test");
+    let run = |checkpoint: bool| {
+        let mut conf = checkpoint_conf("checkpoint_stateful_iterate_test", &dir, false);
+        if !checkpoint {
+            conf.checkpoint_dir = None;
+        }
+        pegasus::run(conf, || {
+            move |input, output| {
+                input
+                    .input_from(0..10u32)?
+                    .iterate(3, |start| {
+                        // the number of items seen so far, which would be lost if resumed;
+                        start.unary("seen", |_info| {
+                            let mut seen = 0;
+                            move |input, output| {
+                                input.for_each_batch(|dataset| {
+                                    let mut session = output.new_session(&dataset.tag)?;
+                                    for _ in dataset.drain() {
+                                        seen += 1;
+                                        session.give(seen)?;
+                                    }
+                                    Ok(())
+                                })
+                            }
+                        })
+                    })?
+                    .collect::<Vec<u32>>()?
+                    .sink_into(output)
+            }
+        })
+    };
+
+    let mut result = run(false).expect("run job failure;");
+    assert_eq!(result.next().unwrap().unwrap().len(), 20);
+    match run(true) {
+        Err(JobSubmitError::Build(BuildJobError::Unsupported(msg))) => {
+            assert!(msg.contains("stateful operators"), "unexpected error: {}", msg)
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("checkpoint of stateful iteration isn't rejected"),
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+const PAGES: u64 = 16;
+
+/// The ranks of the pages on a worker, scaled to integers, so that the ranks of runs are compared exactly;
+struct Ranks {
+    ranks: HashMap<u64, u64>,
+}
+
+impl Ranks {
+    fn new(index: u64, peers: u64) -> Self {
+        let ranks = (0..PAGES)
+            .filter(|p| p % peers == index)
+            .map(|p| (p, 1_000_000))
+            .collect();
+        Ranks { ranks }
+    }
+
+    fn scatter(&self) -> Vec<(u64, u64)> {
+        let mut updates = vec![];
+        for (p, r) in self.ranks.iter() {
+            for q in [(p + 1) % PAGES, (p * 3 + 1) % PAGES].iter() {
+                updates.push((*q, r * 85 / 100 / 2));
+            }
+        }
+        updates
+    }
+
+    fn apply(&mut self, sums: HashMap<u64, u64>) -> u64 {
+        let mut change = 0;
+        for (p, sum) in sums {
+            let rank = sum + 150_000 / PAGES;
+            let old = self.ranks.insert(p, rank).unwrap_or(0);
+            change += if old > rank { old - rank } else { rank - old };
+        }
+        change
+    }
+}
+
+impl Encode for Ranks {
+    fn write_to<W: WriteExt>(&self, writer: &mut W) -> io::Result<()> {
+        let ranks = self
+            .ranks
+            .iter()
+            .map(|(p, r)| (*p, *r))
+            .collect::<Vec<_>>();
+        ranks.write_to(writer)
+    }
+}
+
+impl Decode for Ranks {
+    fn read_from<R: ReadExt>(reader: &mut R) -> io::Result<Self> {
+        let ranks = Vec::<(u64, u64)>::read_from(reader)?;
+        Ok(Ranks { ranks: ranks.into_iter().collect() })
+    }
+}
+
+// the ranks are updated in place in the resources, which are initial in each run, so the ranks of
+// the resumed run are right only if the ranks are restored from the checkpoints;
+fn page_rank(conf: JobConf) -> Vec<(u64, u64)> {
+    let peers = conf.workers as u64;
+    let ranks = (0..peers)
+        .map(|i| Ranks::new(i, peers))
+        .collect();
+    let resources = PartitionedResource::new(&conf, ranks)
+        .ok()
+        .unwrap();
+    let mut result = pegasus::run_with_resources(conf, resources, || {
+        |input, output| {
+            let mut until = IterCondition::max_iters(10);
+            until.checkpoint_resource::<Ranks>();
+            input
+                .input_from(Some(0u64))?
+                .iterate_until(until, |start| {
+                    start
+                        .flat_map(|_| Ok(get_resource::<Ranks>().unwrap().scatter().into_iter()))?
+                        .repartition(|x| Ok(x.0))
+                        .fold_partition(HashMap::new(), || {
+                            |mut sums: HashMap<u64, u64>, (p, r)| {
+                                *sums.entry(p).or_insert(0) += r;
+                                Ok(sums)
+                            }
+                        })?
+                        .unfold(|sums| {
+                            let change = get_resource_mut::<Ranks>().unwrap().apply(sums);
+                            Ok(Some(change).into_iter())
+                        })?
+                        .broadcast()
+                        .reduce_partition(|| |a, b| Ok(a + b))?
+                        .into_stream()
+                })?
+                .flat_map(|_| {
+                    let ranks = get_resource::<Ranks>().unwrap();
+                    let ranks = ranks
+                        .ranks
+                        .iter()
+                        .map(|(p, r)| (*p, *r))
+                        .collect::<Vec<_>>();
+                    Ok(ranks.into_iter())
+                })?
+                .collect::<Vec<(u64, u64)>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut ranks = result.next().unwrap().unwrap();
+    ranks.sort();
+    ranks
+}
+
+#[test]
+fn resume_page_rank_test() {
+    let dir = checkpoint_dir("resume_page_rank_test");
+    let mut conf = JobConf::new("resume_page_rank_test");
+    conf.set_workers(2);
+    let expected = page_rank(conf);
+    assert_eq!(expected.len(), PAGES as usize);
+
+    assert_eq!(page_rank(checkpoint_conf("resume_page_rank_test", &dir, false)), expected);
+    lose_checkpoints(&dir, "resume_page_rank_test", 1, 3);
+    assert_eq!(page_rank(checkpoint_conf("resume_page_rank_test", &dir, true)), expected);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -181,3 +181,39 @@ fn fold_by_key_test() {
     let cnt_3 = groups.get(&3).unwrap();
     assert_eq!(*cnt_3, (0..num * 2).filter(|x| x % 4 == 3).count() as u32);
 }
+
+#[test]
+fn aggregate_after_leave_test() {
+    let mut conf = JobConf::new("aggregate_after_leave_test");
+    conf.set_workers(2);
+    let num = 100u32;
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        let src = index * num..(index + 1) * num;
+        move |input, output| {
+            input
+                .input_from(src)?
+                .enter()?
+                .map(|x| Ok(x + 1))?
+                .leave()?
+                .aggregate()
+                .map(|x| Ok((pegasus::get_current_worker().index, x)))?
+                .collect::<Vec<_>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("submit job failure:");
+
+    let mut data = vec![];
+    while let Some(Ok(res)) = result.next() {
+        data.extend(res);
+    }
+    // data left the child scope are aggregated on the root scope, to the first worker;
+    assert!(data.iter().all(|(index, _)| *index == 0));
+    let mut data = data
+        .into_iter()
+        .map(|(_, x)| x)
+        .collect::<Vec<_>>();
+    data.sort();
+    assert_eq!(data, (1..num * 2 + 1).collect::<Vec<_>>());
+}
@@ -0,0 +1,116 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use pegasus::api::{Collect, JoinBy, Sink};
+use pegasus::JobConf;
+
+// the expected pairs of a band join of the items 0..100 on both sides;
+fn band_expected(width: i64) -> Vec<(u32, u32)> {
+    let mut expected = vec![];
+    for l in 0..100u32 {
+        for r in 0..100u32 {
+            if (l as i64 - r as i64).abs() <= width {
+                expected.push((l, r));
+            }
+        }
+    }
+    expected
+}
+
+#[test]
+fn join_by_test() {
+    let mut conf = JobConf::new("join_by_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let (src1, src2) = input
+                .input_from(index * 50..(index + 1) * 50)?
+                .copied()?;
+            src1.join_by(src2, |l, r| Ok(l % 10 == 0 && l + 1 < *r && *r < l + 4))?
+                .collect::<Vec<(u32, u32)>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut result = result.next().unwrap().unwrap();
+    result.sort();
+    let mut expected = vec![];
+    for l in (0..100u32).filter(|l| l % 10 == 0) {
+        expected.push((l, l + 2));
+        expected.push((l, l + 3));
+    }
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn band_join_broadcast_test() {
+    let mut conf = JobConf::new("band_join_broadcast_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let (src1, src2) = input
+                .input_from(index * 50..(index + 1) * 50)?
+                .copied()?;
+            src1.band_join(src2, |l| *l as i64, |r| *r as i64, 3, None)?
+                .collect::<Vec<(u32, u32)>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut result = result.next().unwrap().unwrap();
+    result.sort();
+    assert_eq!(result, band_expected(3));
+}
+
+#[test]
+fn band_join_partition_test() {
+    let mut conf = JobConf::new("band_join_partition_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let (src1, src2) = input
+                .input_from(index * 50..(index + 1) * 50)?
+                .copied()?;
+            // the ranges narrower than the band, so that the right items are copied into several ranges;
+            src1.band_join(src2, |l| *l as i64, |r| *r as i64, 5, Some(4))?
+                .collect::<Vec<(u32, u32)>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut result = result.next().unwrap().unwrap();
+    result.sort();
+    assert_eq!(result, band_expected(5));
+}
+
+#[test]
+fn band_join_invalid_test() {
+    let conf = JobConf::new("band_join_invalid_test");
+    let result = pegasus::run(conf, || {
+        |input, output| {
+            let (src1, src2) = input.input_from(0..10u32)?.copied()?;
+            src1.band_join(src2, |l| *l as i64, |r| *r as i64, 1, Some(0))?
+                .collect::<Vec<(u32, u32)>>()?
+                .sink_into(output)
+        }
+    });
+    assert!(result.is_err());
+}
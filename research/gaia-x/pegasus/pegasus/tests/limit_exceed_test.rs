@@ -0,0 +1,129 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "mem")]
+use pegasus::api::Collect;
+use pegasus::api::{Map, Sink};
+#[cfg(not(feature = "mem"))]
+use pegasus::errors::{BuildJobError, JobSubmitError};
+use pegasus::errors::{ErrorKind, JobExecError};
+use pegasus::JobConf;
+
+#[test]
+fn time_limit_exceed_test() {
+    let mut conf = JobConf::new("time_limit_exceed_test");
+    conf.set_workers(2);
+    conf.time_limit = 100;
+    conf.batch_size = 16;
+    let mut result = pegasus::run(conf, || {
+        |input, output| {
+            input
+                .input_from(0..100_000u32)?
+                .repartition(|x| Ok(*x as u64))
+                .map(|x| {
+                    std::thread::sleep(Duration::from_millis(1));
+                    Ok(x)
+                })?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let start = Instant::now();
+    let mut count = 0;
+    let mut elapsed = None;
+    let mut errors = vec![];
+    while let Some(next) = result.next() {
+        match next {
+            Ok(_) => count += 1,
+            Err(e) => {
+                elapsed.get_or_insert_with(|| start.elapsed());
+                errors.push(e);
+            }
+        }
+    }
+    let elapsed = elapsed.expect("job isn't aborted");
+    // all the workers exceed the limit, but only one of them reports it;
+    assert_eq!(errors.len(), 1, "the abort is reported {} times", errors.len());
+    let error = errors.pop().unwrap();
+    let e = error
+        .downcast_ref::<JobExecError>()
+        .expect("unknown error");
+    assert_eq!(e.kind, ErrorKind::ExceedLimit);
+    assert!(
+        e.to_string()
+            .contains("time limit 100ms exceeded"),
+        "unexpected error: {}",
+        e
+    );
+    // the limits are checked between schedule steps, so the job is aborted in the step the limit is
+    // exceeded, which handles at most a few thousands of the 200_000 records taking 1ms each;
+    assert!(count < 5_000, "job isn't aborted at the time limit, count = {}", count);
+    assert!(elapsed < Duration::from_secs(5), "job isn't aborted at the time limit, used {:?}", elapsed);
+}
+
+#[cfg(feature = "mem")]
+#[test]
+fn memory_limit_exceed_test() {
+    let mut conf = JobConf::new("memory_limit_exceed_test");
+    conf.set_workers(2);
+    conf.memory_limit = 8;
+    let mut result = pegasus::run(conf, || {
+        |input, output| {
+            // about 80MB are held by the collect if the job isn't aborted;
+            input
+                .input_from(0..20_000u32)?
+                .map(|x| Ok(vec![x as u8; 4096]))?
+                .collect::<Vec<Vec<u8>>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    match result.next() {
+        Some(Err(error)) => {
+            let e = error
+                .downcast_ref::<JobExecError>()
+                .expect("unknown error");
+            assert_eq!(e.kind, ErrorKind::ExceedLimit);
+            assert!(
+                e.to_string()
+                    .contains("memory limit 8MB exceeded"),
+                "unexpected error: {}",
+                e
+            );
+        }
+        Some(Ok(collected)) => panic!("job isn't aborted, {} records collected", collected.len()),
+        None => panic!("job isn't aborted"),
+    }
+}
+
+#[cfg(not(feature = "mem"))]
+#[test]
+fn memory_limit_unsupported_test() {
+    let mut conf = JobConf::new("memory_limit_unsupported_test");
+    conf.memory_limit = 8;
+    let result = pegasus::run(conf, || |input, output| input.input_from(0..10u32)?.sink_into(output));
+    // the memory isn't tracked without the feature `mem`, so the limit isn't silently ignored;
+    match result {
+        Err(JobSubmitError::Build(BuildJobError::Unsupported(msg))) => {
+            assert!(msg.contains("requires the feature `mem`"), "unexpected error: {}", msg)
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("job with memory limit isn't rejected"),
+    }
+}
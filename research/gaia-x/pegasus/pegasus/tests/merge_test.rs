@@ -1,4 +1,5 @@
-use pegasus::api::{Count, Merge, Sink};
+use pegasus::api::{Collect, Count, Either, Map, Merge, Sink};
+use pegasus::codec::{Decode, Encode};
 use pegasus::JobConf;
 
 #[test]
@@ -17,3 +18,80 @@ fn merge_test() {
         assert_eq!(count, 2000);
     }
 }
+
+#[test]
+fn merge_isomer_exchange_test() {
+    let mut conf = JobConf::new("merge_isomer_exchange_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src = input.input_from(index * 100..(index + 1) * 100)?;
+            let (numbers, names) = src.copied()?;
+            let names = names.map(|x| Ok(format!("name_{}", x)))?;
+            // route both sides to the other worker, so that every value is exchanged between workers;
+            numbers
+                .merge_isomer(names)?
+                .repartition(move |_| Ok(index as u64 + 1))
+                .map(move |x| {
+                    let worker = pegasus::get_current_worker().index;
+                    Ok((x, worker))
+                })?
+                .collect::<Vec<(Either<u32, String>, u32)>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("submit job failure");
+
+    let results = result.next().unwrap().unwrap();
+    assert_eq!(results.len(), 400);
+    let mut numbers = vec![];
+    let mut names = vec![];
+    for (x, worker) in results {
+        // the value `n` is produced by the worker `n / 100`;
+        let n = match x {
+            Either::A(n) => {
+                numbers.push(n);
+                n
+            }
+            Either::B(name) => {
+                let n = name
+                    .trim_start_matches("name_")
+                    .parse::<u32>()
+                    .unwrap();
+                names.push(n);
+                n
+            }
+        };
+        assert_eq!(worker, 1 - n / 100, "{} isn't exchanged to the other worker", n);
+    }
+    numbers.sort();
+    names.sort();
+    assert_eq!(numbers, (0..200).collect::<Vec<_>>());
+    assert_eq!(names, (0..200).collect::<Vec<_>>());
+}
+
+#[test]
+fn either_codec_test() {
+    let items: Vec<Either<u32, String>> = vec![Either::A(7), Either::B("name_7".to_owned()), Either::A(0)];
+    let mut bytes = vec![];
+    for item in items.iter() {
+        item.write_to(&mut bytes).unwrap();
+    }
+    // a tag of `u8` followed by the data of either side;
+    assert_eq!(&bytes[0..5], &[0u8, 7, 0, 0, 0]);
+    assert_eq!(bytes[5], 1u8);
+    let mut reader = &bytes[0..];
+    for item in items.iter() {
+        let decoded = Either::<u32, String>::read_from(&mut reader).unwrap();
+        assert_eq!(&decoded, item);
+    }
+    assert!(reader.is_empty());
+
+    // a tag other than 0 and 1 is invalid;
+    let mut reader = &[2u8, 7, 0, 0, 0][0..];
+    let err = Either::<u32, String>::read_from(&mut reader)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,88 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use pegasus::api::{Map, Sink};
+use pegasus::codec::{Decode, Encode};
+use pegasus::profile::JobProfile;
+use pegasus::JobConf;
+
+#[test]
+fn profile_map_test() {
+    let mut conf = JobConf::new("profile_map_test");
+    conf.set_workers(2);
+    conf.profile = true;
+    conf.batch_size = 16;
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(index * 100..(index + 1) * 100)?
+                .repartition(|x| Ok(*x as u64))
+                .map(|x| Ok(x + 1))?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    assert!(result.profile().is_none());
+    let mut count = 0;
+    while let Some(Ok(_)) = result.next() {
+        count += 1;
+    }
+    assert_eq!(count, 200);
+
+    let profile = result.profile().expect("profile not found");
+    assert_eq!(profile.workers, 2);
+    assert!(profile.steps > 0);
+    let map = profile
+        .operators
+        .iter()
+        .find(|op| op.name == "map")
+        .expect("map operator not found");
+    assert!(map.fire_times > 0);
+    assert_eq!(map.inputs.len(), 1);
+    assert_eq!(map.inputs[0].records, 200);
+    assert_eq!(map.outputs[0].records, 200);
+    // no remote servers;
+    assert!(profile.exchanges.is_empty());
+
+    let mut bytes = vec![];
+    profile.write_to(&mut bytes).unwrap();
+    let decoded = JobProfile::read_from(&mut bytes.as_slice()).unwrap();
+    assert_eq!(decoded, profile);
+
+    let mut merged = decoded.clone();
+    merged.merge(&profile);
+    assert_eq!(merged.workers, 4);
+    assert_eq!(merged.operators.len(), profile.operators.len());
+}
+
+#[test]
+fn profile_disabled_test() {
+    let mut conf = JobConf::new("profile_disabled_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        |input, output| {
+            input
+                .input_from(0..10u32)?
+                .map(|x| Ok(x + 1))?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    while let Some(Ok(_)) = result.next() {}
+    assert!(result.profile().is_none());
+}
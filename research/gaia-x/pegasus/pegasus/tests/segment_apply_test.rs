@@ -0,0 +1,149 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use pegasus::api::{Collect, Count, KeyBy, Limit, Map, ScopeByKey, Sink};
+use pegasus::JobConf;
+
+fn segment_count_test(name: &str, workers: u32, keys: u32, scope_capacity: u32) {
+    let mut conf = JobConf::new(name);
+    conf.set_workers(workers);
+    conf.scope_capacity = scope_capacity;
+    let num = 100u32;
+    let mut result = pegasus::run(conf, move || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(index * num..(index + 1) * num)?
+                .key_by(move |d| Ok((d % keys, d)))?
+                .segment_apply(|sub| sub.count())?
+                .map(|pair| Ok(pair.take()))?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut counts = vec![];
+    while let Some(Ok(d)) = result.next() {
+        counts.push(d);
+    }
+    counts.sort();
+    let expected = (0..keys)
+        .map(|k| (k, (num * workers / keys) as u64))
+        .collect::<Vec<_>>();
+    assert_eq!(counts, expected);
+}
+
+#[test]
+fn segment_apply_count_test() {
+    segment_count_test("segment_apply_count_test", 2, 10, 64)
+}
+
+#[test]
+fn segment_apply_over_scope_capacity_test() {
+    segment_count_test("segment_apply_over_scope_capacity_test", 2, 50, 4)
+}
+
+#[test]
+fn segment_apply_early_stop_test() {
+    let mut conf = JobConf::new("segment_apply_early_stop_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(index * 1000..(index + 1) * 1000)?
+                .key_by(|d| Ok((d % 5, d)))?
+                .segment_apply(|sub| sub.limit(3)?.collect::<Vec<u32>>())?
+                .map(|pair| Ok((pair.key, pair.value.len() as u64)))?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut results = vec![];
+    while let Some(Ok(d)) = result.next() {
+        results.push(d);
+    }
+    results.sort();
+    assert_eq!(results, [(0, 3), (1, 3), (2, 3), (3, 3), (4, 3)]);
+}
+
+#[test]
+fn segment_apply_empty_test() {
+    let mut conf = JobConf::new("segment_apply_empty_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        |input, output| {
+            input
+                .input_from(Vec::<u32>::new())?
+                .key_by(|d| Ok((d % 5, d)))?
+                .segment_apply(|sub| sub.count())?
+                .map(|pair| Ok(pair.take()))?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    assert!(result.next().is_none());
+}
+
+fn segment_apply_exchange_test(workers: u32) {
+    let name = format!("segment_apply_exchange_test_{}", workers);
+    let mut conf = JobConf::new(name);
+    conf.set_workers(workers);
+    let num = 100u32;
+    let mut result = pegasus::run(conf, move || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(index * num..(index + 1) * num)?
+                .key_by(|d| Ok((d % 7, d)))?
+                .segment_apply(|sub| {
+                    sub.repartition(|d| Ok(*d as u64))
+                        .flat_map(|d| Ok(0..d % 3))?
+                        .count()
+                })?
+                .map(|pair| Ok(pair.take()))?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut counts = vec![];
+    while let Some(Ok(d)) = result.next() {
+        counts.push(d);
+    }
+    counts.sort();
+    let expected = (0..7)
+        .map(|k| {
+            let cnt = (0..num * workers)
+                .filter(|d| d % 7 == k)
+                .map(|d| (d % 3) as u64)
+                .sum::<u64>();
+            (k, cnt)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(counts, expected);
+}
+
+#[test]
+fn segment_apply_exchange_test_2_workers() {
+    segment_apply_exchange_test(2)
+}
+
+#[test]
+fn segment_apply_exchange_test_3_workers() {
+    segment_apply_exchange_test(3)
+}
@@ -0,0 +1,51 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::time::Duration;
+
+use pegasus::api::Sink;
+use pegasus::result::ResultStream;
+use pegasus::JobConf;
+
+#[test]
+fn sink_into_bounded_channel_test() {
+    let (tx, rx) = crossbeam_channel::bounded(4);
+    let mut conf = JobConf::new("sink_into_bounded_channel_test");
+    conf.set_workers(2);
+    conf.batch_size = 16;
+    let mut result: ResultStream<()> = pegasus::run(conf, || {
+        let tx = tx.clone();
+        move |input, _output| {
+            let index = pegasus::get_current_worker().index;
+            input
+                .input_from(index * 500..(index + 1) * 500)?
+                .sink_into(tx)
+        }
+    })
+    .expect("run job failure;");
+    drop(tx);
+
+    // the sink pauses while the channel is full, rather than blocking the workers;
+    let mut received = vec![];
+    for d in rx.iter() {
+        if received.len() % 100 == 0 {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        received.push(d);
+    }
+    assert!(result.next().is_none());
+    received.sort();
+    assert_eq!(received, (0..1000u32).collect::<Vec<_>>());
+}
@@ -0,0 +1,291 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use pegasus::api::{Collect, Join, KeyBy, Map, PartitionByKey, ReduceByKey, Sink, Sort, SortBy, SortLimit};
+use pegasus::JobConf;
+
+fn spill_conf(name: &str) -> JobConf {
+    let mut conf = JobConf::new(name);
+    conf.set_workers(2);
+    conf.spill_threshold = 1024;
+    conf
+}
+
+// shuffle 0..10000 of each worker, so that the sorted runs spilled are not in order;
+fn shuffled(index: u32) -> Vec<u32> {
+    (0..10000u32)
+        .map(|i| (i * 7919) % 10000 + index * 10000)
+        .collect()
+}
+
+#[test]
+fn sort_spill_test() {
+    let conf = spill_conf("sort_spill_test");
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(shuffled(index))?
+                .sort()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut sorted = vec![];
+    while let Some(Ok(d)) = result.next() {
+        sorted.push(d);
+    }
+    assert_eq!(sorted, (0..20000u32).collect::<Vec<_>>());
+}
+
+#[test]
+fn sort_by_spill_test() {
+    let conf = spill_conf("sort_by_spill_test");
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(shuffled(index))?
+                .sort_by(|a, b| b.cmp(a))?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut sorted = vec![];
+    while let Some(Ok(d)) = result.next() {
+        sorted.push(d);
+    }
+    assert_eq!(sorted, (0..20000u32).rev().collect::<Vec<_>>());
+}
+
+#[test]
+fn reduce_by_key_spill_test() {
+    let conf = spill_conf("reduce_by_key_spill_test");
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(shuffled(index))?
+                .key_by(|x| Ok((x % 5000, 1u32)))?
+                .reduce_by_key(|| |a, b| Ok(a + b))?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let groups = result.next().unwrap().unwrap();
+    assert_eq!(groups.len(), 5000);
+    for (k, cnt) in groups {
+        assert!(k < 5000);
+        assert_eq!(cnt, 4);
+    }
+}
+
+#[test]
+fn reduce_by_key_stream_spill_test() {
+    let conf = spill_conf("reduce_by_key_stream_spill_test");
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(shuffled(index))?
+                .key_by(|x| Ok((x % 5000, 1u32)))?
+                .reduce_by_key_stream(|| |a, b| Ok(a + b))?
+                .map(|pair| Ok(pair.take()))?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut groups = vec![];
+    while let Some(Ok(d)) = result.next() {
+        groups.push(d);
+    }
+    groups.sort();
+    assert_eq!(groups, (0..5000u32).map(|k| (k, 4)).collect::<Vec<_>>());
+}
+
+#[test]
+fn collect_spill_test() {
+    let conf = spill_conf("collect_spill_test");
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            // all records are folded in a single scope, most of them are spilled;
+            input
+                .input_from(shuffled(index))?
+                .collect::<Vec<u32>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut collected = result.next().unwrap().unwrap();
+    collected.sort();
+    assert_eq!(collected, (0..20000u32).collect::<Vec<_>>());
+}
+
+#[test]
+fn inner_join_spill_test() {
+    let conf = spill_conf("inner_join_spill_test");
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src = input.input_from(shuffled(index))?;
+            let (left, right) = src.copied()?;
+            let left = left
+                .key_by(|x| Ok((x % 10000, x)))?
+                .partition_by_key();
+            let right = right
+                .filter_map(|x| Ok(if x % 2 == 0 { Some(x) } else { None }))?
+                .key_by(|x| Ok((x % 10000, x)))?
+                .partition_by_key();
+            left.inner_join(right)?
+                .map(|(l, r)| Ok((l.value, r.value)))?
+                .collect::<Vec<(u32, u32)>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut result = result.next().unwrap().unwrap();
+    result.sort();
+    let mut expected = vec![];
+    for l in 0..20000u32 {
+        if l % 2 == 0 {
+            expected.push((l, l % 10000));
+            expected.push((l, l % 10000 + 10000));
+        }
+    }
+    expected.sort();
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn outer_join_spill_test() {
+    let conf = spill_conf("outer_join_spill_test");
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src = input.input_from(shuffled(index))?;
+            let (left, right) = src.copied()?;
+            let left = left
+                .filter_map(|x| Ok(if x % 3 == 0 { Some(x) } else { None }))?
+                .key_by(|x| Ok((x, x)))?
+                .partition_by_key();
+            let right = right
+                .filter_map(|x| Ok(if x % 2 == 0 { Some(x) } else { None }))?
+                .key_by(|x| Ok((x, x)))?
+                .partition_by_key();
+            left.full_outer_join(right)?
+                .map(|(l, r)| Ok((l.map(|l| l.value), r.map(|r| r.value))))?
+                .collect::<Vec<(Option<u32>, Option<u32>)>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut result = result.next().unwrap().unwrap();
+    result.sort();
+    let mut expected = vec![];
+    for x in 0..20000u32 {
+        match (x % 3 == 0, x % 2 == 0) {
+            (true, true) => expected.push((Some(x), Some(x))),
+            (true, false) => expected.push((Some(x), None)),
+            (false, true) => expected.push((None, Some(x))),
+            (false, false) => (),
+        }
+    }
+    expected.sort();
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn semi_join_spill_test() {
+    let conf = spill_conf("semi_join_spill_test");
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src = input.input_from(shuffled(index))?;
+            let (left, right) = src.copied()?;
+            let left = left.key_by(|x| Ok((x, x)))?.partition_by_key();
+            let right = right
+                .filter_map(|x| Ok(if x % 2 == 0 { Some(x) } else { None }))?
+                .key_by(|x| Ok((x, x)))?
+                .partition_by_key();
+            left.anti_join(right)?
+                .map(|l| Ok(l.value))?
+                .collect::<Vec<u32>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut result = result.next().unwrap().unwrap();
+    result.sort();
+    assert_eq!(
+        result,
+        (0..20000u32)
+            .filter(|x| x % 2 == 1)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn sort_limit_spill_test() {
+    let mut conf = JobConf::new("sort_limit_spill_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let mut stream = input.input_from(shuffled(index))?;
+            // spill by the threshold of the operator only, the job spills nothing;
+            stream.set_spill_threshold(1024);
+            stream.sort_limit(5000)?.sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut sorted = vec![];
+    while let Some(Ok(d)) = result.next() {
+        sorted.push(d);
+    }
+    assert_eq!(sorted, (0..5000u32).collect::<Vec<_>>());
+}
+
+#[test]
+fn zero_spill_threshold_test() {
+    let mut conf = spill_conf("zero_spill_threshold_test");
+    // raised to the least run size, rather than a spill file for each record;
+    conf.spill_threshold = 0;
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(shuffled(index))?
+                .sort()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut sorted = vec![];
+    while let Some(Ok(d)) = result.next() {
+        sorted.push(d);
+    }
+    assert_eq!(sorted, (0..20000u32).collect::<Vec<_>>());
+}
@@ -0,0 +1,159 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use pegasus::api::{CorrelatedSubTask, Count, Iteration, Map, Sink};
+use pegasus::{JobConf, ScheduleStrategy};
+
+fn volcano_conf(name: &str, workers: u32) -> JobConf {
+    let mut conf = JobConf::new(name);
+    conf.set_workers(workers);
+    conf.strategy = ScheduleStrategy::Volcano;
+    // small capacity to make the expansions blocked frequently;
+    conf.batch_size = 64;
+    conf.batch_capacity = 4;
+    conf
+}
+
+#[test]
+fn volcano_flat_map_test() {
+    let conf = volcano_conf("volcano_flat_map_test", 2);
+    let num = 1000u32;
+    let mut result = pegasus::run(conf, move || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(index * num..(index + 1) * num)?
+                .repartition(|x| Ok(*x as u64))
+                .flat_map(|i| Ok(0..i % 100))?
+                .repartition(|x| Ok(*x as u64))
+                .flat_map(|i| Ok(0..i))?
+                .count()?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let expected = (0..2 * num)
+        .map(|i| (0..i % 100).map(|j| j as u64).sum::<u64>())
+        .sum::<u64>();
+    let mut count = 0;
+    while let Some(Ok(d)) = result.next() {
+        count += d;
+    }
+    assert_eq!(count, expected);
+}
+
+#[test]
+fn volcano_iterate_test() {
+    let conf = volcano_conf("volcano_iterate_test", 2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(index * 10..(index + 1) * 10)?
+                .iterate(3, |start| {
+                    start
+                        .repartition(|x| Ok(*x as u64))
+                        .flat_map(|i| Ok((0..4).map(move |j| i * 4 + j)))
+                })?
+                .count()?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut count = 0;
+    while let Some(Ok(d)) = result.next() {
+        count += d;
+    }
+    assert_eq!(count, 20 * 4 * 4 * 4);
+}
+
+#[test]
+fn volcano_subtask_test() {
+    let conf = volcano_conf("volcano_subtask_test", 2);
+    let num = 100u32;
+    let mut result = pegasus::run(conf, move || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            input
+                .input_from(index * num..(index + 1) * num)?
+                .apply(|sub| {
+                    sub.repartition(|x| Ok(*x as u64))
+                        .flat_map(|i| Ok(0..i))?
+                        .count()
+                })?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut count = 0;
+    while let Some(Ok((d, cnt))) = result.next() {
+        assert_eq!(d as u64, cnt);
+        count += 1;
+    }
+    assert_eq!(count, num * 2);
+}
+
+#[test]
+fn volcano_memory_bound_test() {
+    let conf = volcano_conf("volcano_memory_bound_test", 2);
+    let bound = 8 * (conf.batch_size * conf.batch_capacity) as i64;
+    // records expanded by the first flat_map but not yet consumed by the second one;
+    let in_flight = Arc::new(AtomicI64::new(0));
+    let peak = Arc::new(AtomicI64::new(0));
+    let (in_flight_c, peak_c) = (in_flight.clone(), peak.clone());
+    let mut result = pegasus::run(conf, move || {
+        let index = pegasus::get_current_worker().index;
+        let (produced, consumed, peak) = (in_flight_c.clone(), in_flight_c.clone(), peak_c.clone());
+        move |input, output| {
+            let (produced, consumed, peak) = (produced.clone(), consumed.clone(), peak.clone());
+            input
+                .input_from(index * 100..(index + 1) * 100)?
+                .repartition(|x| Ok(*x as u64))
+                .flat_map(move |_| {
+                    let (produced, peak) = (produced.clone(), peak.clone());
+                    Ok((0..1000u32).map(move |x| {
+                        let current = produced.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(current, Ordering::SeqCst);
+                        x
+                    }))
+                })?
+                .repartition(|x| Ok(*x as u64))
+                .map(move |x| {
+                    consumed.fetch_sub(1, Ordering::SeqCst);
+                    Ok(x)
+                })?
+                .flat_map(|_| Ok(0..10u32))?
+                .count()?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut count = 0;
+    while let Some(Ok(d)) = result.next() {
+        count += d;
+    }
+    assert_eq!(count, 200 * 1000 * 10);
+    assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+    // the first flat_map is held back once the outputs are full, though it expands 200_000 records;
+    let peak = peak.load(Ordering::SeqCst);
+    assert!(peak <= bound, "{} records in flight exceed the bound {}", peak, bound);
+}
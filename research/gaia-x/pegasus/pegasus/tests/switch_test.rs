@@ -0,0 +1,123 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use pegasus::api::{Collect, CorrelatedSubTask, Count, Iteration, Map, Merge, Sink, SwitchFn};
+use pegasus::JobConf;
+
+#[test]
+fn switch_test() {
+    let mut conf = JobConf::new("switch_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src =
+                if index == 0 { input.input_from(0..1000u32) } else { input.input_from(1000..2000u32) }?;
+            let (left, right) = src
+                .repartition(|x| Ok(*x as u64))
+                .switch::<u32, u64, _>(|x| Ok(*x >= 555))?;
+            left.map(|x| Ok((0u32, x as u64)))?
+                .merge(right.map(|x| Ok((1u32, x)))?)?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut count = vec![0, 0];
+    while let Some(Ok((flag, data))) = result.next() {
+        if flag == 0 {
+            assert!(data >= 555);
+        } else {
+            assert!(data < 555);
+        }
+        count[flag as usize] += 1;
+    }
+    assert_eq!(count, [2000 - 555, 555]);
+}
+
+#[test]
+fn switch_mut_in_apply_test() {
+    let mut conf = JobConf::new("switch_mut_in_apply_test");
+    conf.set_workers(2);
+    let num = 100u32;
+    let mut result = pegasus::run(conf, move || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src = if index == 0 { input.input_from(0..num) } else { input.input_from(num..2 * num) }?;
+            src.apply(|sub| {
+                // take the first 3 data of each sub-task, which needs a predicate of each sub-task;
+                let (first, rest) = sub
+                    .flat_map(|i| Ok(0..i))?
+                    .switch_mut::<u32, u32, _, _>(|| {
+                        let mut count = 0;
+                        move |_| {
+                            count += 1;
+                            Ok(count <= 3)
+                        }
+                    })?;
+                first
+                    .map(|x| Ok(Some(x)))?
+                    .merge(rest.map(|_| Ok(None))?)?
+                    .filter_map(Ok)?
+                    .count()
+            })?
+            .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut count = 0;
+    while let Some(Ok((i, cnt))) = result.next() {
+        assert_eq!(cnt, std::cmp::min(i, 3) as u64, "wrong count of {}", i);
+        count += 1;
+    }
+    assert_eq!(count, num * 2);
+}
+
+#[test]
+fn switch_in_iterate_test() {
+    let mut conf = JobConf::new("switch_in_iterate_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src =
+                if index == 0 { input.input_from(0..500u32) } else { input.input_from(500..1000u32) }?;
+            src.iterate(5, |start| {
+                let (even, odd) = start
+                    .repartition(|x| Ok(*x as u64))
+                    .switch::<u32, u32, _>(|x| Ok(x % 2 == 0))?;
+                even.map(|x| Ok(x + 1))?
+                    .merge(odd.map(|x| Ok(x + 3))?)
+            })?
+            .collect::<Vec<u32>>()?
+            .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut result = result.next().unwrap().unwrap();
+    result.sort();
+    let mut expected = (0..1000u32)
+        .map(|mut x| {
+            for _ in 0..5 {
+                x += if x % 2 == 0 { 1 } else { 3 };
+            }
+            x
+        })
+        .collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(result, expected);
+}
@@ -0,0 +1,217 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::time::{Duration, Instant};
+
+use pegasus::api::{source_channel, Collect, Count, Fold, Sink, Window};
+use pegasus::result::ResultStream;
+use pegasus::JobConf;
+
+#[test]
+fn tumbling_window_test() {
+    let mut conf = JobConf::new("tumbling_window_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index as u64;
+        move |input, output| {
+            // the items of two workers arrive out of order, the delay keeps them from being dropped;
+            input
+                .input_from(index * 50..(index + 1) * 50)?
+                .tumbling_window(10, 100, |t| *t)?
+                .count()?
+                .into_stream()?
+                .leave()?
+                .collect::<Vec<u64>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let counts = result.next().unwrap().unwrap();
+    assert_eq!(counts, vec![10; 10]);
+}
+
+#[test]
+fn sliding_window_test() {
+    let mut conf = JobConf::new("sliding_window_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src = if index == 0 { input.input_from(0..20u64)? } else { input.input_from(vec![])? };
+            src.sliding_window(10, 5, 0, |t| *t)?
+                .fold(0u64, || |sum, t| Ok(sum + t))?
+                .into_stream()?
+                .leave()?
+                .collect::<Vec<u64>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut sums = result.next().unwrap().unwrap();
+    sums.sort();
+    // sums of [0, 10), [5, 15), [10, 20) and [15, 20);
+    assert_eq!(sums, vec![45, 85, 95, 145]);
+}
+
+#[test]
+fn late_items_dropped_test() {
+    let conf = JobConf::new("late_items_dropped_test");
+    let mut result = pegasus::run(conf, || {
+        |input, output| {
+            // 3 arrives after the watermark passes the end of window [0, 10), so it is dropped;
+            input
+                .input_from(vec![0, 1, 2, 15, 3, 16, 25u64])?
+                .tumbling_window(10, 0, |t| *t)?
+                .count()?
+                .into_stream()?
+                .leave()?
+                .collect::<Vec<u64>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    let mut counts = result.next().unwrap().unwrap();
+    counts.sort();
+    assert_eq!(counts, vec![1, 2, 3]);
+}
+
+#[test]
+fn channel_source_test() {
+    let (tx, rx) = source_channel();
+    let mut conf = JobConf::new("channel_source_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let rx = rx.clone();
+        move |input, output| {
+            input
+                .input_from_channel(rx)?
+                .tumbling_window(10, 1000, |t| *t)?
+                .count()?
+                .into_stream()?
+                .leave()?
+                .collect::<Vec<u64>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+
+    // the job keeps running until the sender is dropped;
+    std::thread::spawn(move || {
+        for t in 0..100u64 {
+            tx.send(t).unwrap();
+            if t % 10 == 0 {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        }
+    });
+    drop(rx);
+
+    let counts = result.next().unwrap().unwrap();
+    assert_eq!(counts, vec![10; 10]);
+}
+
+// wait for the next result of a running job, and fail instead of hanging if it isn't emitted;
+fn next_in_time(result: &ResultStream<u64>) -> u64 {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(10) {
+        match result.try_next() {
+            Some(Ok(Some(next))) => return next,
+            Some(Ok(None)) => std::thread::sleep(Duration::from_millis(1)),
+            Some(Err(e)) => panic!("job failure: {}", e),
+            None => panic!("job finished without result"),
+        }
+    }
+    panic!("no result emitted in time");
+}
+
+#[test]
+fn window_emit_by_watermark_test() {
+    let (tx, rx) = source_channel();
+    let mut conf = JobConf::new("window_emit_by_watermark_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        // only one worker reads the channel, so that the items arrive in order and none is late;
+        let rx = if index == 0 { rx.clone() } else { source_channel().1 };
+        move |input, output| {
+            input
+                .input_from_channel(rx)?
+                .tumbling_window(10, 0, |t| *t)?
+                .count()?
+                .into_stream()?
+                .leave()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+    drop(rx);
+
+    // a window is emitted once the watermark passes its end, while the sender is still alive;
+    for t in 0..=10u64 {
+        tx.send(t).unwrap();
+    }
+    assert_eq!(next_in_time(&result), 10);
+    for t in 11..25u64 {
+        tx.send(t).unwrap();
+    }
+    assert_eq!(next_in_time(&result), 10);
+
+    // the last window is closed once the input ends;
+    drop(tx);
+    let mut rest = vec![];
+    while let Some(Ok(cnt)) = result.next() {
+        rest.push(cnt);
+    }
+    assert_eq!(rest, vec![5]);
+}
+
+#[test]
+fn channel_source_parked_test() {
+    let (tx, rx) = source_channel();
+    let mut conf = JobConf::new("channel_source_parked_test");
+    conf.profile = true;
+    let mut result = pegasus::run(conf, || {
+        let rx = rx.clone();
+        move |input, output| {
+            input
+                .input_from_channel(rx)?
+                .collect::<Vec<u64>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("run job failure;");
+    drop(rx);
+
+    // the source is parked while the channel is empty, instead of being fired again and again;
+    std::thread::sleep(Duration::from_millis(200));
+    for t in 0..3u64 {
+        tx.send(t).unwrap();
+    }
+    drop(tx);
+    let items = result.next().unwrap().unwrap();
+    assert_eq!(items, vec![0, 1, 2]);
+    assert!(result.next().is_none());
+
+    let profile = result.profile().expect("profile not found");
+    let source = profile
+        .operators
+        .iter()
+        .find(|op| op.name == "channel_source")
+        .expect("channel source not found");
+    assert!(source.fire_times < 10, "channel source fired {} times", source.fire_times);
+}
@@ -0,0 +1,160 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use pegasus::api::{Collect, CorrelatedSubTask, Count, Filter, Iteration, Map, Sink, Zip};
+use pegasus::JobConf;
+
+#[test]
+fn zip_test() {
+    let mut conf = JobConf::new("zip_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src =
+                if index == 0 { input.input_from(0..500u32) } else { input.input_from(500..1000u32) }?;
+            let (src1, src2) = src.copied()?;
+            src1.zip(src2.map(|x| Ok(x as u64 * 2))?)?
+                .collect::<Vec<(u32, u64)>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut result = result.next().unwrap().unwrap();
+    result.sort();
+    let expected = (0..1000u32)
+        .map(|x| (x, x as u64 * 2))
+        .collect::<Vec<_>>();
+    assert_eq!(result, expected);
+}
+
+fn zip_different_length_test(left_shorter: bool) {
+    let mut conf = JobConf::new(format!("zip_different_length_test_{}", left_shorter));
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, move || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src = if index == 0 { input.input_from(0..10u32) } else { input.input_from(10..20u32) }?;
+            let (src1, src2) = src.copied()?;
+            let stream = if left_shorter {
+                src1.filter(|x| Ok(x % 2 == 0))?
+                    .zip(src2)?
+                    .map(|(x, y)| Ok((y, x)))?
+            } else {
+                src1.zip(src2.filter(|x| Ok(x % 2 == 0))?)?
+            };
+            stream
+                .collect::<Vec<(u32, u32)>>()?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut result = result.next().unwrap().unwrap();
+    result.sort();
+    // the longer one is truncated, no matter which side it is on;
+    let mut expected = vec![];
+    for start in [0, 10] {
+        for i in 0..5 {
+            expected.push((start + i, start + 2 * i));
+        }
+    }
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn zip_left_shorter_test() {
+    zip_different_length_test(true)
+}
+
+#[test]
+fn zip_right_shorter_test() {
+    zip_different_length_test(false)
+}
+
+#[test]
+fn zip_empty_stream_test() {
+    let mut conf = JobConf::new("zip_empty_stream_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src = if index == 0 { input.input_from(0..10u32) } else { input.input_from(10..20u32) }?;
+            let (src1, src2) = src.copied()?;
+            src1.zip(src2.filter(|_| Ok(false))?)?
+                .count()?
+                .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    assert_eq!(result.next().unwrap().unwrap(), 0);
+}
+
+#[test]
+fn zip_in_apply_test() {
+    let mut conf = JobConf::new("zip_in_apply_test");
+    conf.set_workers(2);
+    let num = 100u32;
+    let mut result = pegasus::run(conf, move || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src = if index == 0 { input.input_from(0..num) } else { input.input_from(num..2 * num) }?;
+            src.apply(|sub| {
+                let (sub1, sub2) = sub.flat_map(|i| Ok(0..i))?.copied()?;
+                sub1.zip(sub2.filter(|x| Ok(x % 3 == 0))?)?
+                    .filter(|(x, y)| Ok(*y == 3 * *x))?
+                    .count()
+            })?
+            .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut count = 0;
+    while let Some(Ok((i, cnt))) = result.next() {
+        // each sub-task zips its own data only, so both sides are in step;
+        let expected = (0..i).filter(|x| x % 3 == 0).count() as u64;
+        assert_eq!(cnt, expected, "wrong count of {}", i);
+        count += 1;
+    }
+    assert_eq!(count, num * 2);
+}
+
+#[test]
+fn zip_in_iterate_test() {
+    let mut conf = JobConf::new("zip_in_iterate_test");
+    conf.set_workers(2);
+    let mut result = pegasus::run(conf, || {
+        let index = pegasus::get_current_worker().index;
+        move |input, output| {
+            let src =
+                if index == 0 { input.input_from(1..500u64) } else { input.input_from(500..1000u64) }?;
+            src.iterate(3, |start| {
+                let (left, right) = start.copied()?;
+                left.zip(right)?.map(|(x, y)| Ok(x + y))
+            })?
+            .collect::<Vec<u64>>()?
+            .sink_into(output)
+        }
+    })
+    .expect("build job failure");
+
+    let mut result = result.next().unwrap().unwrap();
+    result.sort();
+    let expected = (1..1000u64).map(|x| x * 8).collect::<Vec<_>>();
+    assert_eq!(result, expected);
+}
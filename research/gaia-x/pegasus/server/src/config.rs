@@ -20,7 +20,7 @@ use std::fmt::Debug;
 use std::path::Path;
 
 use pegasus::{Configuration, StartupError};
-use pegasus_network::config::{NetworkConfig, PeerConfig};
+use pegasus_network::config::{Compression, NetworkConfig, PeerConfig, TlsConfig};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -39,7 +39,7 @@ impl HostsConfig {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct CommonConfig {
     pub max_pool_size: Option<u32>,
     pub nonblocking: Option<bool>,
@@ -49,6 +49,14 @@ pub struct CommonConfig {
     pub no_delay: Option<bool>,
     pub send_buffer: Option<u32>,
     pub heartbeat_sec: Option<u32>,
+    pub compression: Option<Compression>,
+    pub compress_threshold: Option<u32>,
+    /// certificates of the mutual TLS between servers, which is disabled if not set;
+    pub tls: Option<TlsConfig>,
+    /// number of responses of a job buffered for a slow rpc client;
+    pub result_capacity: Option<u32>,
+    /// number of results coalesced into one rpc response;
+    pub result_batch: Option<u32>,
 }
 
 impl CommonConfig {
@@ -78,6 +86,9 @@ pub fn combine_config(
                 .with_no_delay(common_config.no_delay)
                 .with_send_buffer(common_config.send_buffer)
                 .with_heartbeat_sec(common_config.heartbeat_sec)
+                .with_compression(common_config.compression)
+                .with_compress_threshold(common_config.compress_threshold)
+                .with_tls(common_config.tls.clone())
                 .with_peers(Some(host_config.peers));
             Configuration { network: Some(network_config), max_pool_size: common_config.max_pool_size }
         } else {
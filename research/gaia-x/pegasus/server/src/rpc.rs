@@ -13,49 +13,186 @@
 //! See the License for the specific language governing permissions and
 //! limitations under the License.
 
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
 use std::fmt::Debug;
 use std::net::SocketAddr;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 use pegasus::api::function::FnResult;
-use pegasus::api::FromStream;
+use pegasus::api::{FromStream, Parker};
+use pegasus::errors::{ErrorKind, JobExecError};
+use pegasus::profile::JobProfile;
 use pegasus::result::{FromStreamExt, ResultSink};
 use pegasus::{Data, JobConf, ServerConf};
+use pegasus_common::codec::{Decode, Encode};
+use pegasus_network::config::TlsConfig;
 use prost::Message;
-use tokio::sync::mpsc::UnboundedSender;
-use tokio_stream::wrappers::UnboundedReceiverStream;
-use tonic::transport::Server;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 use tonic::{Code, Request, Response, Status};
 
 use crate::generated::protocol as pb;
 use crate::service::{JobParser, Service};
+use crate::CommonConfig;
 use tokio::net::TcpListener;
 
 pub struct RpcSink {
     pub job_id: u64,
     had_error: Arc<AtomicBool>,
+    dropped: Arc<AtomicBool>,
     peers: Arc<AtomicUsize>,
-    tx: UnboundedSender<Result<pb::JobResponse, Status>>,
+    jobs: JobRegistry,
+    batch_size: usize,
+    buffer: Vec<Vec<u8>>,
+    send_timeout: Duration,
+    /// the sinks paused for the channel being full, which are unparked once the client receives;
+    waiters: Arc<Mutex<Vec<Parker>>>,
+    tx: Sender<Result<pb::JobResponse, Status>>,
 }
 
 impl RpcSink {
-    pub fn new(job_id: u64, tx: UnboundedSender<Result<pb::JobResponse, Status>>) -> Self {
+    pub fn new(job_id: u64, tx: Sender<Result<pb::JobResponse, Status>>) -> Self {
+        RpcSink::with_registry(job_id, JobRegistry::default(), tx)
+    }
+
+    /// Create the sink of a job registered in the registry, whose state is updated once the job stops;
+    pub fn with_registry(
+        job_id: u64, jobs: JobRegistry, tx: Sender<Result<pb::JobResponse, Status>>,
+    ) -> Self {
         RpcSink {
             tx,
             had_error: Arc::new(AtomicBool::new(false)),
+            dropped: Arc::new(AtomicBool::new(false)),
             peers: Arc::new(AtomicUsize::new(1)),
+            jobs,
+            batch_size: 1,
+            buffer: vec![],
+            send_timeout: DEFAULT_SEND_TIMEOUT,
+            waiters: Arc::new(Mutex::new(vec![])),
             job_id,
         }
     }
+
+    /// Wrap the receiver of the channel into the result stream sent to the client, which unparks the
+    /// sinks of the job paused for the channel being full once the client receives;
+    pub fn result_stream(&self, rx: Receiver<Result<pb::JobResponse, Status>>) -> RpcResultStream {
+        RpcResultStream { inner: ReceiverStream::new(rx), waiters: self.waiters.clone() }
+    }
+
+    /// Coalesce every `batch_size` results into one response, the results are sent one by one in
+    /// the `data` of responses if it is 1;
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = std::cmp::max(batch_size, 1);
+        self
+    }
+
+    /// Set how long to wait for the client to receive the last responses once the job stops, the
+    /// result stream is dropped if the client doesn't receive in time;
+    pub fn with_send_timeout(mut self, timeout: Duration) -> Self {
+        self.send_timeout = timeout;
+        self
+    }
+
+    fn take_response(&mut self) -> pb::JobResponse {
+        let batch = std::mem::replace(&mut self.buffer, Vec::with_capacity(self.batch_size));
+        if self.batch_size == 1 && batch.len() == 1 {
+            let data = batch.into_iter().next().expect("unreachable");
+            pb::JobResponse { job_id: self.job_id, data, batch: vec![], profile: vec![] }
+        } else {
+            pb::JobResponse { job_id: self.job_id, data: vec![], batch, profile: vec![] }
+        }
+    }
+
+    /// Try to send the buffered results, return `false` if the channel is full;
+    fn try_flush(&mut self) -> bool {
+        if self.buffer.is_empty() {
+            return true;
+        }
+        if self.dropped.load(Ordering::SeqCst) {
+            // nobody receives the results of a dropped stream;
+            self.buffer.clear();
+            return true;
+        }
+        let res = self.take_response();
+        match self.tx.try_send(Ok(res)) {
+            Ok(()) => true,
+            Err(TrySendError::Full(Ok(mut res))) => {
+                self.buffer = if res.batch.is_empty() {
+                    vec![std::mem::replace(&mut res.data, vec![])]
+                } else {
+                    res.batch
+                };
+                false
+            }
+            // the client has gone, the results are discarded;
+            Err(_) => true,
+        }
+    }
+
+    /// Send even if the channel is full, as it happens only when the job stops. It runs on the
+    /// executor threads shared by all jobs, so it waits at most `send_timeout` for a client that
+    /// doesn't receive, and drops the result stream after that;
+    fn send_or_drop(&self, mut res: Result<pb::JobResponse, Status>) {
+        let start = Instant::now();
+        while !self.dropped.load(Ordering::SeqCst) {
+            match self.tx.try_send(res) {
+                Err(TrySendError::Full(r)) => {
+                    if start.elapsed() >= self.send_timeout {
+                        warn!(
+                            "client of job {} doesn't receive in {:?}, drop the result stream;",
+                            self.job_id, self.send_timeout
+                        );
+                        self.dropped.store(true, Ordering::SeqCst);
+                        return;
+                    }
+                    res = r;
+                    std::thread::sleep(SEND_RETRY_INTERVAL);
+                }
+                // sent, or the client has gone;
+                _ => return,
+            }
+        }
+    }
+
+    fn flush_or_drop(&mut self) {
+        if !self.buffer.is_empty() {
+            let res = self.take_response();
+            self.send_or_drop(Ok(res));
+        }
+    }
 }
 
 impl<T: Message> FromStream<T> for RpcSink {
     fn on_next(&mut self, next: T) -> FnResult<()> {
-        let bytes = next.encode_to_vec();
-        let res = pb::JobResponse { job_id: self.job_id, data: bytes };
-        self.tx.send(Ok(res)).ok();
+        self.buffer.push(next.encode_to_vec());
+        if self.buffer.len() >= self.batch_size {
+            self.try_flush();
+        }
+        Ok(())
+    }
+
+    fn is_ready(&mut self) -> bool {
+        self.buffer.len() < self.batch_size || self.try_flush()
+    }
+
+    fn notify_ready(&mut self, parker: &Parker) -> bool {
+        self.waiters
+            .lock()
+            .expect("lock poisoned")
+            .push(parker.clone());
+        true
+    }
+
+    fn flush(&mut self) -> FnResult<()> {
+        self.try_flush();
         Ok(())
     }
 }
@@ -66,7 +203,13 @@ impl Clone for RpcSink {
         RpcSink {
             job_id: self.job_id,
             had_error: self.had_error.clone(),
+            dropped: self.dropped.clone(),
             peers: self.peers.clone(),
+            jobs: self.jobs.clone(),
+            batch_size: self.batch_size,
+            buffer: Vec::with_capacity(self.batch_size),
+            send_timeout: self.send_timeout,
+            waiters: self.waiters.clone(),
             tx: self.tx.clone(),
         }
     }
@@ -75,31 +218,296 @@ impl Clone for RpcSink {
 impl<T: Message> FromStreamExt<T> for RpcSink {
     fn on_error(&mut self, error: Box<dyn Error + Send>) {
         self.had_error.store(true, Ordering::SeqCst);
-        let status = Status::unknown(format!("execution_error: {}", error));
-        self.tx.send(Err(status)).ok();
+        let canceled = error
+            .downcast_ref::<JobExecError>()
+            .map(|e| e.kind == ErrorKind::Canceled)
+            .unwrap_or(false);
+        let status = if canceled {
+            self.jobs
+                .stop(self.job_id, pb::JobState::Canceled, error.to_string());
+            Status::cancelled(format!("job canceled: {}", error))
+        } else {
+            self.jobs
+                .stop(self.job_id, pb::JobState::Failed, error.to_string());
+            Status::unknown(format!("execution_error: {}", error))
+        };
+        self.flush_or_drop();
+        self.send_or_drop(Err(status));
+    }
+
+    fn on_profile(&mut self, profile: &JobProfile) {
+        // the results of this worker are sent before, so the profile follows all results of the server;
+        self.flush_or_drop();
+        let mut bytes = vec![];
+        if let Err(e) = profile.write_to(&mut bytes) {
+            warn!("encode profile of job {} failure: {}", self.job_id, e);
+            return;
+        }
+        let res = pb::JobResponse { job_id: self.job_id, data: vec![], batch: vec![], profile: bytes };
+        self.send_or_drop(Ok(res));
     }
 }
 
+/// Merge the profile of a server carried by a response of the result stream into `merged`, return
+/// `false` if the response carries no profile. The profile of the job is merged from all servers once
+/// their result streams are exhausted;
+pub fn merge_profile(merged: &mut Option<JobProfile>, res: &pb::JobResponse) -> std::io::Result<bool> {
+    if res.profile.is_empty() {
+        return Ok(false);
+    }
+    let profile = JobProfile::read_from(&mut res.profile.as_slice())?;
+    merged
+        .get_or_insert_with(|| JobProfile::new(profile.job_id))
+        .merge(&profile);
+    Ok(true)
+}
+
 impl Drop for RpcSink {
     fn drop(&mut self) {
+        if !self.had_error.load(Ordering::SeqCst) {
+            self.flush_or_drop();
+        }
         let before_sub = self.peers.fetch_sub(1, Ordering::SeqCst);
         if before_sub == 1 {
             if !self.had_error.load(Ordering::SeqCst) {
-                self.tx.send(Err(Status::ok("ok"))).ok();
+                self.jobs
+                    .stop(self.job_id, pb::JobState::Finished, String::new());
+                self.send_or_drop(Err(Status::ok("ok")));
             }
         }
     }
 }
 
+/// The stream of responses of a job sent to the client, see [`RpcSink::result_stream`];
+pub struct RpcResultStream {
+    inner: ReceiverStream<Result<pb::JobResponse, Status>>,
+    waiters: Arc<Mutex<Vec<Parker>>>,
+}
+
+impl RpcResultStream {
+    fn wake_all(&self) {
+        let waiters = std::mem::replace(&mut *self.waiters.lock().expect("lock poisoned"), vec![]);
+        for w in waiters {
+            w.unpark();
+        }
+    }
+}
+
+impl Stream for RpcResultStream {
+    type Item = Result<pb::JobResponse, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let next = Pin::new(&mut self.inner).poll_next(cx);
+        if let Poll::Ready(Some(_)) = next {
+            // a slot of the channel is released, the paused sinks can send again;
+            self.wake_all();
+        }
+        next
+    }
+}
+
+impl Drop for RpcResultStream {
+    fn drop(&mut self) {
+        // the client has gone, the paused sinks discard their results then;
+        self.wake_all();
+    }
+}
+
+/// Default number of responses buffered for a job before the dataflow is paused;
+const DEFAULT_RESULT_CAPACITY: usize = 64;
+
+/// Default time waiting for the client to receive the last responses of a job;
+const DEFAULT_SEND_TIMEOUT: Duration = Duration::from_secs(30);
+
+const SEND_RETRY_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Number of stopped jobs whose status are kept, the earliest stopped ones are removed beyond it;
+const MAX_STOPPED_JOBS: usize = 1024;
+
+struct JobEntry {
+    job_name: String,
+    state: pb::JobState,
+    start: Instant,
+    elapsed: Option<Duration>,
+    workers: u32,
+    running: Arc<AtomicUsize>,
+    cancel: Arc<AtomicBool>,
+    error: String,
+}
+
+impl JobEntry {
+    fn status(&self, job_id: u64) -> pb::JobStatus {
+        let elapsed = self
+            .elapsed
+            .unwrap_or_else(|| self.start.elapsed());
+        pb::JobStatus {
+            job_id,
+            job_name: self.job_name.clone(),
+            state: self.state as i32,
+            elapsed_ms: elapsed.as_millis() as u64,
+            workers: self.workers,
+            running_workers: self.running.load(Ordering::SeqCst) as u32,
+            error: self.error.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct JobTable {
+    jobs: HashMap<u64, JobEntry>,
+    stopped: VecDeque<u64>,
+}
+
+/// The jobs submitted to this server, with the hooks to cancel the running ones;
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    table: Arc<Mutex<JobTable>>,
+}
+
+impl JobRegistry {
+    fn register<O>(&self, conf: &JobConf, sink: &ResultSink<O>) -> Result<(), Status> {
+        let mut table = self
+            .table
+            .lock()
+            .expect("job registry poisoned");
+        if let Some(entry) = table.jobs.get(&conf.job_id) {
+            if entry.state == pb::JobState::Running {
+                return Err(Status::already_exists(format!("job {} is running", conf.job_id)));
+            }
+        }
+        table.stopped.retain(|id| *id != conf.job_id);
+        let entry = JobEntry {
+            job_name: conf.job_name.clone(),
+            state: pb::JobState::Running,
+            start: Instant::now(),
+            elapsed: None,
+            workers: conf.workers,
+            running: sink.get_running_hook().clone(),
+            cancel: sink.get_cancel_hook().clone(),
+            error: String::new(),
+        };
+        table.jobs.insert(conf.job_id, entry);
+        Ok(())
+    }
+
+    fn remove(&self, job_id: u64) {
+        let mut table = self
+            .table
+            .lock()
+            .expect("job registry poisoned");
+        table.jobs.remove(&job_id);
+    }
+
+    /// Record the state of a stopped job, the first state recorded is kept;
+    fn stop(&self, job_id: u64, state: pb::JobState, error: String) {
+        let mut table = self
+            .table
+            .lock()
+            .expect("job registry poisoned");
+        match table.jobs.get_mut(&job_id) {
+            Some(entry) if entry.state == pb::JobState::Running => {
+                entry.state = state;
+                entry.elapsed = Some(entry.start.elapsed());
+                entry.error = error;
+            }
+            _ => return,
+        }
+        table.stopped.push_back(job_id);
+        while table.stopped.len() > MAX_STOPPED_JOBS {
+            if let Some(id) = table.stopped.pop_front() {
+                table.jobs.remove(&id);
+            }
+        }
+    }
+
+    /// Cancel a running job, return `None` if the job is not found;
+    pub fn cancel(&self, job_id: u64) -> Option<bool> {
+        let table = self
+            .table
+            .lock()
+            .expect("job registry poisoned");
+        table.jobs.get(&job_id).map(|entry| {
+            if entry.state == pb::JobState::Running {
+                entry.cancel.store(true, Ordering::SeqCst);
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    pub fn status(&self, job_id: u64) -> Option<pb::JobStatus> {
+        let table = self
+            .table
+            .lock()
+            .expect("job registry poisoned");
+        table
+            .jobs
+            .get(&job_id)
+            .map(|entry| entry.status(job_id))
+    }
+
+    pub fn list(&self) -> Vec<pb::JobStatus> {
+        let table = self
+            .table
+            .lock()
+            .expect("job registry poisoned");
+        let mut jobs: Vec<_> = table
+            .jobs
+            .iter()
+            .map(|(id, entry)| entry.status(*id))
+            .collect();
+        jobs.sort_by_key(|status| status.job_id);
+        jobs
+    }
+}
+
 #[derive(Clone)]
 pub struct RpcService<I: Data, O, P> {
     inner: Service<I, O, P>,
+    jobs: JobRegistry,
+    result_capacity: usize,
+    result_batch: usize,
     report: bool,
 }
 
 impl<I: Data, O, P> RpcService<I, O, P> {
     pub fn new(service: Service<I, O, P>, report: bool) -> RpcService<I, O, P> {
-        RpcService { inner: service, report }
+        RpcService {
+            inner: service,
+            jobs: JobRegistry::default(),
+            result_capacity: DEFAULT_RESULT_CAPACITY,
+            result_batch: 1,
+            report,
+        }
+    }
+
+    /// Set the number of responses of a job buffered for a slow client, the job is paused once
+    /// they are full until the client receives;
+    pub fn with_result_capacity(mut self, capacity: usize) -> Self {
+        self.result_capacity = std::cmp::max(capacity, 1);
+        self
+    }
+
+    /// Set the number of results coalesced into one response;
+    pub fn with_result_batch(mut self, batch: usize) -> Self {
+        self.result_batch = std::cmp::max(batch, 1);
+        self
+    }
+
+    /// Apply the settings of result streaming in the config, the defaults are kept for those unset;
+    pub fn with_config(mut self, config: &CommonConfig) -> Self {
+        if let Some(capacity) = config.result_capacity {
+            self = self.with_result_capacity(capacity as usize);
+        }
+        if let Some(batch) = config.result_batch {
+            self = self.with_result_batch(batch as usize);
+        }
+        self
+    }
+
+    pub fn get_jobs(&self) -> &JobRegistry {
+        &self.jobs
     }
 }
 
@@ -110,7 +518,7 @@ where
     O: Send + Debug + Message + 'static,
     P: JobParser<I, O>,
 {
-    type SubmitStream = UnboundedReceiverStream<Result<pb::JobResponse, Status>>;
+    type SubmitStream = RpcResultStream;
 
     async fn submit(&self, req: Request<pb::JobRequest>) -> Result<Response<Self::SubmitStream>, Status> {
         let mut job_req = req.into_inner();
@@ -120,24 +528,59 @@ where
 
         let conf_req = job_req.conf.take().unwrap();
         let conf = parse_conf_req(conf_req);
-        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-        let rpc_sink = RpcSink::new(conf.job_id, tx);
+        let job_id = conf.job_id;
+        let (tx, rx) = tokio::sync::mpsc::channel(self.result_capacity);
+        let rpc_sink =
+            RpcSink::with_registry(job_id, self.jobs.clone(), tx).with_batch_size(self.result_batch);
+        let stream = rpc_sink.result_stream(rx);
         let sink = ResultSink::<O>::with(rpc_sink);
+        self.jobs.register(&conf, &sink)?;
         let service = self.inner.clone();
         let submitted =
             pegasus::run_opt(conf, sink, move |worker| worker.dataflow(service.accept(&job_req)));
 
         if let Err(e) = submitted {
+            self.jobs.remove(job_id);
             return Err(Status::invalid_argument(format!("submit job error {}", e)));
         }
 
-        Ok(Response::new(UnboundedReceiverStream::new(rx)))
+        Ok(Response::new(stream))
+    }
+
+    async fn cancel(
+        &self, req: Request<pb::CancelRequest>,
+    ) -> Result<Response<pb::CancelResponse>, Status> {
+        let job_id = req.into_inner().job_id;
+        match self.jobs.cancel(job_id) {
+            Some(canceled) => {
+                info!("cancel job {}: {}", job_id, canceled);
+                Ok(Response::new(pb::CancelResponse { canceled }))
+            }
+            None => Err(Status::not_found(format!("job {} not found", job_id))),
+        }
+    }
+
+    async fn get_job_status(
+        &self, req: Request<pb::JobStatusRequest>,
+    ) -> Result<Response<pb::JobStatus>, Status> {
+        let job_id = req.into_inner().job_id;
+        match self.jobs.status(job_id) {
+            Some(status) => Ok(Response::new(status)),
+            None => Err(Status::not_found(format!("job {} not found", job_id))),
+        }
+    }
+
+    async fn list_jobs(
+        &self, _req: Request<pb::ListJobsRequest>,
+    ) -> Result<Response<pb::ListJobsResponse>, Status> {
+        Ok(Response::new(pb::ListJobsResponse { jobs: self.jobs.list() }))
     }
 }
 
 pub struct RpcServer<S: pb::job_service_server::JobService> {
     service: S,
     addr: SocketAddr,
+    tls: Option<TlsConfig>,
 }
 
 pub async fn start_rpc_server<I, O, P>(
@@ -148,22 +591,50 @@ where
     O: Send + Debug + Message + 'static,
     P: JobParser<I, O>,
 {
-    let server = RpcServer::new(addr, service);
+    start_rpc_server_with_tls(addr, service, None, blocking).await
+}
+
+/// Start the rpc server, which serves over mutual TLS if `tls` is given;
+pub async fn start_rpc_server_with_tls<I, O, P>(
+    addr: SocketAddr, service: RpcService<I, O, P>, tls: Option<TlsConfig>, blocking: bool,
+) -> Result<SocketAddr, Box<dyn std::error::Error>>
+where
+    I: Data,
+    O: Send + Debug + Message + 'static,
+    P: JobParser<I, O>,
+{
+    let server = RpcServer::new(addr, service).with_tls(tls);
     let local_addr = server.run(blocking).await?;
     Ok(local_addr)
 }
 
 impl<S: pb::job_service_server::JobService> RpcServer<S> {
     pub fn new(addr: SocketAddr, service: S) -> Self {
-        RpcServer { service, addr }
+        RpcServer { service, addr, tls: None }
+    }
+
+    /// Serve over mutual TLS, clients are rejected unless they present certificates signed by the CA;
+    pub fn with_tls(mut self, tls: Option<TlsConfig>) -> Self {
+        self.tls = tls;
+        self
     }
 
     pub async fn run(self, blocking: bool) -> Result<SocketAddr, Box<dyn std::error::Error>> {
-        let RpcServer { service, addr } = self;
+        let RpcServer { service, addr, tls } = self;
+        let mut builder = Server::builder();
+        if let Some(tls) = tls {
+            let cert = std::fs::read(&tls.cert)?;
+            let key = std::fs::read(&tls.key)?;
+            let ca = std::fs::read(&tls.ca)?;
+            let tls_config = ServerTlsConfig::new()
+                .identity(Identity::from_pem(cert, key))
+                .client_ca_root(Certificate::from_pem(ca));
+            builder = builder.tls_config(tls_config)?;
+        }
         let listener = TcpListener::bind(addr).await?;
         let local_addr = listener.local_addr()?;
         info!("Rpc server started on {}", local_addr);
-        let serve = Server::builder()
+        let serve = builder
             .add_service(pb::job_service_server::JobServiceServer::new(service))
             .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener));
         if blocking {
@@ -192,8 +663,262 @@ fn parse_conf_req(conf: pb::JobConfig) -> JobConf {
         job_conf.memory_limit = conf.memory_limit;
     }
     job_conf.plan_print = conf.plan_print;
+    job_conf.profile = conf.profile;
     if !conf.servers.is_empty() {
         job_conf.reset_servers(ServerConf::Partial(conf.servers.clone()));
     }
     job_conf
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decode_response(res: pb::JobResponse) -> Vec<u64> {
+        let frames = if res.batch.is_empty() { vec![res.data] } else { res.batch };
+        frames
+            .iter()
+            .map(|bytes| {
+                pb::CancelRequest::decode(bytes.as_slice())
+                    .expect("decode failure")
+                    .job_id
+            })
+            .collect()
+    }
+
+    fn push(sink: &mut RpcSink, range: std::ops::Range<u64>) {
+        for job_id in range {
+            sink.on_next(pb::CancelRequest { job_id })
+                .unwrap();
+        }
+    }
+
+    fn is_ready(sink: &mut RpcSink) -> bool {
+        FromStream::<pb::CancelRequest>::is_ready(sink)
+    }
+
+    #[test]
+    fn rpc_sink_backpressure_test() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let mut sink = RpcSink::new(1, tx).with_batch_size(4);
+        push(&mut sink, 0..4);
+        assert!(is_ready(&mut sink));
+        // the channel is full of the first batch, the sink is paused once the second batch is full;
+        push(&mut sink, 4..7);
+        assert!(is_ready(&mut sink));
+        push(&mut sink, 7..8);
+        assert!(!is_ready(&mut sink));
+        assert!(!is_ready(&mut sink));
+
+        let res = rx.blocking_recv().unwrap().unwrap();
+        assert_eq!(decode_response(res), vec![0, 1, 2, 3]);
+        assert!(is_ready(&mut sink));
+        push(&mut sink, 8..10);
+        // the sink waits for the client to receive the rest on drop;
+        let guard = std::thread::spawn(move || std::mem::drop(sink));
+
+        let res = rx.blocking_recv().unwrap().unwrap();
+        assert_eq!(decode_response(res), vec![4, 5, 6, 7]);
+        let res = rx.blocking_recv().unwrap().unwrap();
+        assert_eq!(decode_response(res), vec![8, 9]);
+        let status = rx.blocking_recv().unwrap().unwrap_err();
+        assert_eq!(status.code(), Code::Ok);
+        assert!(rx.blocking_recv().is_none());
+        guard.join().unwrap();
+    }
+
+    #[test]
+    fn rpc_sink_notify_ready_test() {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let mut sink = RpcSink::new(1, tx);
+        let mut stream = sink.result_stream(rx);
+        push(&mut sink, 0..2);
+        assert!(!is_ready(&mut sink));
+        let parker = Parker::default();
+        assert!(FromStream::<pb::CancelRequest>::notify_ready(&mut sink, &parker));
+        parker.park();
+
+        // the paused sink is unparked once the client receives;
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let res = rt
+            .block_on(tokio_stream::StreamExt::next(&mut stream))
+            .unwrap()
+            .unwrap();
+        assert_eq!(decode_response(res), vec![0]);
+        assert!(!parker.is_parked());
+        assert!(is_ready(&mut sink));
+
+        // and once the client has gone;
+        push(&mut sink, 2..3);
+        assert!(FromStream::<pb::CancelRequest>::notify_ready(&mut sink, &parker));
+        parker.park();
+        std::mem::drop(stream);
+        assert!(!parker.is_parked());
+        assert!(is_ready(&mut sink));
+    }
+
+    #[test]
+    fn rpc_sink_single_result_test() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        let mut sink = RpcSink::new(1, tx);
+        push(&mut sink, 0..2);
+        std::mem::drop(sink);
+        for job_id in 0..2 {
+            let res = rx.blocking_recv().unwrap().unwrap();
+            assert!(res.batch.is_empty());
+            assert_eq!(decode_response(res), vec![job_id]);
+        }
+        let status = rx.blocking_recv().unwrap().unwrap_err();
+        assert_eq!(status.code(), Code::Ok);
+    }
+
+    fn server_profile(workers: u32, steps: u64) -> JobProfile {
+        let mut profile = JobProfile::new(1);
+        profile.workers = workers;
+        profile.steps = steps;
+        profile
+    }
+
+    #[test]
+    fn rpc_sink_profile_test() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        // the sinks of the job on two servers, sharing one channel as the client gathers their results;
+        let mut sinks = vec![RpcSink::new(1, tx.clone()).with_batch_size(4), RpcSink::new(1, tx)];
+        for (i, sink) in sinks.iter_mut().enumerate() {
+            push(sink, 0..2);
+            let profile = server_profile(2, 10 * (i as u64 + 1));
+            FromStreamExt::<pb::CancelRequest>::on_profile(sink, &profile);
+        }
+        std::mem::drop(sinks);
+
+        let mut results = vec![];
+        let mut merged = None;
+        while let Some(Ok(res)) = rx.blocking_recv() {
+            if !merge_profile(&mut merged, &res).unwrap() {
+                results.extend(decode_response(res));
+            } else {
+                // the profile of a server follows all its results;
+                assert_eq!(results.len() % 2, 0);
+            }
+        }
+        assert_eq!(results, vec![0, 1, 0, 1]);
+        let merged = merged.unwrap();
+        assert_eq!(merged.job_id, 1);
+        assert_eq!(merged.workers, 4);
+        assert_eq!(merged.steps, 30);
+    }
+
+    #[test]
+    fn rpc_sink_drop_stream_test() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let mut sink = RpcSink::new(1, tx).with_send_timeout(Duration::from_millis(100));
+        push(&mut sink, 0..2);
+        let start = Instant::now();
+        // the client never receives, the last responses are dropped after the timeout;
+        std::mem::drop(sink);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let res = rx.blocking_recv().unwrap().unwrap();
+        assert_eq!(decode_response(res), vec![0]);
+        assert!(rx.blocking_recv().is_none());
+    }
+
+    struct EmptyParser;
+
+    impl JobParser<u64, pb::CancelRequest> for EmptyParser {
+        fn parse(
+            &self, _plan: &pb::JobRequest, _input: &mut pegasus::api::Source<u64>,
+            _output: ResultSink<pb::CancelRequest>,
+        ) -> Result<(), pegasus::BuildJobError> {
+            Ok(())
+        }
+    }
+
+    fn register(jobs: &JobRegistry, job_id: u64) -> Result<ResultSink<pb::CancelRequest>, Status> {
+        let mut conf = JobConf::new(format!("job_{}", job_id));
+        conf.job_id = job_id;
+        conf.set_workers(2);
+        let (tx, _rx) = tokio::sync::mpsc::channel(1);
+        let sink = ResultSink::with(RpcSink::new(job_id, tx));
+        jobs.register(&conf, &sink)?;
+        Ok(sink)
+    }
+
+    fn state_of(jobs: &JobRegistry, job_id: u64) -> Option<i32> {
+        jobs.status(job_id).map(|status| status.state)
+    }
+
+    #[test]
+    fn job_registry_test() {
+        let jobs = JobRegistry::default();
+        let sink = register(&jobs, 1).unwrap();
+        let status = jobs.status(1).unwrap();
+        assert_eq!(status.job_name, "job_1");
+        assert_eq!(status.state, pb::JobState::Running as i32);
+        assert_eq!(status.workers, 2);
+        // a running job can't be submitted again;
+        let err = register(&jobs, 1).err().unwrap();
+        assert_eq!(err.code(), Code::AlreadyExists);
+
+        assert_eq!(jobs.cancel(1), Some(true));
+        assert!(sink.get_cancel_hook().load(Ordering::SeqCst));
+        jobs.stop(1, pb::JobState::Canceled, "canceled".to_owned());
+        // the first state recorded is kept;
+        jobs.stop(1, pb::JobState::Finished, String::new());
+        let status = jobs.status(1).unwrap();
+        assert_eq!(status.state, pb::JobState::Canceled as i32);
+        assert_eq!(status.error, "canceled");
+        assert_eq!(jobs.cancel(1), Some(false));
+
+        register(&jobs, 2).unwrap();
+        let ids: Vec<u64> = jobs.list().iter().map(|s| s.job_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        jobs.remove(2);
+        assert_eq!(jobs.status(2), None);
+        assert_eq!(jobs.cancel(2), None);
+
+        // a stopped job can be submitted again;
+        register(&jobs, 1).unwrap();
+        assert_eq!(state_of(&jobs, 1), Some(pb::JobState::Running as i32));
+    }
+
+    #[test]
+    fn job_registry_evict_test() {
+        let jobs = JobRegistry::default();
+        let running = MAX_STOPPED_JOBS as u64;
+        register(&jobs, running).unwrap();
+        for job_id in 0..MAX_STOPPED_JOBS as u64 {
+            register(&jobs, job_id).unwrap();
+            jobs.stop(job_id, pb::JobState::Finished, String::new());
+        }
+        assert_eq!(jobs.list().len(), MAX_STOPPED_JOBS + 1);
+        jobs.stop(running, pb::JobState::Failed, "failed".to_owned());
+        // the earliest stopped job is evicted;
+        assert_eq!(jobs.list().len(), MAX_STOPPED_JOBS);
+        assert_eq!(jobs.status(0), None);
+        assert_eq!(state_of(&jobs, 1), Some(pb::JobState::Finished as i32));
+        assert_eq!(state_of(&jobs, running), Some(pb::JobState::Failed as i32));
+    }
+
+    #[tokio::test]
+    async fn job_service_not_found_test() {
+        use pb::job_service_server::JobService;
+
+        let service = RpcService::new(Service::new(EmptyParser), false);
+        let err = service
+            .cancel(Request::new(pb::CancelRequest { job_id: 1 }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+        let err = service
+            .get_job_status(Request::new(pb::JobStatusRequest { job_id: 1 }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+        let res = service
+            .list_jobs(Request::new(pb::ListJobsRequest {}))
+            .await
+            .unwrap();
+        assert!(res.into_inner().jobs.is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+//
+//! Copyright 2020 Alibaba Group Holding Limited.
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! http://www.apache.org/licenses/LICENSE-2.0
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use pegasus::api::Source;
+use pegasus::result::ResultSink;
+use pegasus::BuildJobError;
+use pegasus_network::config::TlsConfig;
+use pegasus_server::pb;
+use pegasus_server::pb::job_service_client::JobServiceClient;
+use pegasus_server::rpc::{start_rpc_server_with_tls, RpcService};
+use pegasus_server::service::{JobParser, Service};
+use rcgen::{BasicConstraints, Certificate, CertificateParams, ExtendedKeyUsagePurpose, IsCa};
+use tonic::transport::{Certificate as TlsCertificate, ClientTlsConfig, Endpoint, Identity};
+
+struct EmptyParser;
+
+impl JobParser<u64, pb::CancelRequest> for EmptyParser {
+    fn parse(
+        &self, _plan: &pb::JobRequest, _input: &mut Source<u64>, _output: ResultSink<pb::CancelRequest>,
+    ) -> Result<(), BuildJobError> {
+        Ok(())
+    }
+}
+
+fn new_ca() -> Certificate {
+    let mut params = CertificateParams::new(vec![]);
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    Certificate::from_params(params).unwrap()
+}
+
+/// Generate a certificate for `localhost` signed by the CA, and write the PEM files under a temp directory;
+fn gen_tls_config(name: &str, ca: &Certificate) -> TlsConfig {
+    let mut dir = std::env::temp_dir();
+    dir.push(format!("pegasus_rpc_tls_test_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut params = CertificateParams::new(vec!["localhost".to_owned()]);
+    params.extended_key_usages =
+        vec![ExtendedKeyUsagePurpose::ServerAuth, ExtendedKeyUsagePurpose::ClientAuth];
+    let cert = Certificate::from_params(params).unwrap();
+    let write = |file: &str, content: String| -> String {
+        let path: PathBuf = dir.join(file);
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    };
+    TlsConfig {
+        cert: write("cert.pem", cert.serialize_pem_with_signer(ca).unwrap()),
+        key: write("cert.key", cert.serialize_private_key_pem()),
+        ca: write("ca.pem", ca.serialize_pem().unwrap()),
+        domain: None,
+    }
+}
+
+/// List the jobs on the server, the client presents the certificate of `identity` if given;
+async fn list_jobs(
+    addr: SocketAddr, ca: &TlsConfig, identity: Option<&TlsConfig>,
+) -> Result<pb::ListJobsResponse, Box<dyn std::error::Error>> {
+    let mut tls = ClientTlsConfig::new()
+        .ca_certificate(TlsCertificate::from_pem(std::fs::read(&ca.ca)?))
+        .domain_name("localhost");
+    if let Some(conf) = identity {
+        tls = tls.identity(Identity::from_pem(std::fs::read(&conf.cert)?, std::fs::read(&conf.key)?));
+    }
+    let channel = Endpoint::from_shared(format!("https://{}", addr))?
+        .tls_config(tls)?
+        .connect()
+        .await?;
+    let mut client = JobServiceClient::new(channel);
+    let res = client
+        .list_jobs(pb::ListJobsRequest {})
+        .await?;
+    Ok(res.into_inner())
+}
+
+#[tokio::test]
+async fn rpc_server_mutual_tls_test() {
+    let ca = new_ca();
+    let server_conf = gen_tls_config("server", &ca);
+    let client_conf = gen_tls_config("client", &ca);
+    // a client with a certificate signed by another CA;
+    let untrusted = gen_tls_config("untrusted", &new_ca());
+
+    let service = RpcService::new(Service::new(EmptyParser), false);
+    let addr = start_rpc_server_with_tls(
+        "127.0.0.1:0".parse().unwrap(),
+        service,
+        Some(server_conf.clone()),
+        false,
+    )
+    .await
+    .unwrap();
+
+    // clients are rejected unless they present certificates signed by the CA;
+    assert!(list_jobs(addr, &server_conf, None).await.is_err());
+    assert!(list_jobs(addr, &server_conf, Some(&untrusted))
+        .await
+        .is_err());
+    let res = list_jobs(addr, &server_conf, Some(&client_conf))
+        .await
+        .unwrap();
+    assert!(res.jobs.is_empty());
+}